@@ -0,0 +1,137 @@
+//! Spawns many viewports across several windows and tabs, and logs
+//! diagnostics, to see how the app scales with the number of viewports.
+
+use {
+    bevy::{
+        diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+        prelude::*,
+    },
+    bevy_gtk::{
+        GtkDefaultPlugins, GtkViewportDiagnosticsPlugin, GtkViewports, GtkWindowContent,
+        gtk::{self, prelude::*},
+    },
+};
+
+#[derive(Debug, Clone, Copy, Resource, clap::Parser)]
+struct Args {
+    /// Number of windows to open.
+    #[arg(long, default_value_t = 3)]
+    windows: u32,
+    /// Number of tabs in each window.
+    #[arg(long, default_value_t = 3)]
+    tabs: u32,
+    /// Number of rows and columns of viewports in each tab.
+    #[arg(long, default_value_t = 2)]
+    grid: u32,
+}
+
+const APP_ID: &str = "io.github.aecsocket.BevyGtk";
+
+fn main() -> AppExit {
+    let args = <Args as clap::Parser>::parse();
+    App::new()
+        .add_plugins((
+            GtkDefaultPlugins::new(APP_ID).with_window_plugin(WindowPlugin {
+                primary_window: None,
+                ..default()
+            }),
+            FrameTimeDiagnosticsPlugin::default(),
+            GtkViewportDiagnosticsPlugin::default(),
+            LogDiagnosticsPlugin::default(),
+        ))
+        .insert_resource(args)
+        .add_systems(Startup, (setup_scene, setup_windows))
+        .add_systems(Update, rotate_cube)
+        .run()
+}
+
+#[derive(Debug, Component)]
+struct Rotating;
+
+fn setup_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // circular base
+    commands.spawn((
+        Mesh3d(meshes.add(Circle::new(4.0))),
+        MeshMaterial3d(materials.add(Color::WHITE)),
+        Transform::from_rotation(Quat::from_rotation_x(-core::f32::consts::FRAC_PI_2)),
+    ));
+    // cube
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
+        MeshMaterial3d(materials.add(Color::srgb_u8(124, 144, 255))),
+        Transform::from_xyz(0.0, 0.5, 0.0),
+        Rotating,
+    ));
+    // light
+    commands.spawn((
+        PointLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0),
+    ));
+}
+
+fn setup_windows(args: Res<Args>, mut viewports: GtkViewports, mut commands: Commands) {
+    let total = args.windows * args.tabs * args.grid * args.grid;
+    info!("Spawning {total} viewports");
+
+    let mut index = 0;
+    for window_index in 0..args.windows {
+        let mut tabs = Vec::new();
+        for _ in 0..args.tabs {
+            let mut widget_factories = Vec::new();
+            for _ in 0..(args.grid * args.grid) {
+                let (viewport, widget_factory) = viewports.create();
+                // look at the scene from a different angle in each viewport
+                #[expect(clippy::cast_precision_loss, reason = "there are few viewports")]
+                let angle = index as f32 / total as f32 * core::f32::consts::TAU;
+                commands.spawn((
+                    Camera3d::default(),
+                    viewport,
+                    Transform::from_xyz(9.0 * angle.cos(), 4.5, 9.0 * angle.sin())
+                        .looking_at(Vec3::ZERO, Vec3::Y),
+                ));
+                widget_factories.push(widget_factory);
+                index += 1;
+            }
+            tabs.push(widget_factories);
+        }
+
+        let grid_size = args.grid;
+        commands.spawn((
+            Window {
+                title: format!("Stress test {}", window_index + 1),
+                ..default()
+            },
+            GtkWindowContent::from(move || {
+                let notebook = gtk::Notebook::new();
+                for (tab_index, widget_factories) in tabs.into_iter().enumerate() {
+                    let grid = gtk::Grid::builder()
+                        .row_homogeneous(true)
+                        .column_homogeneous(true)
+                        .build();
+                    for (cell, widget_factory) in (0..).zip(widget_factories) {
+                        let (row, column) = (cell / grid_size, cell % grid_size);
+                        #[expect(clippy::cast_possible_wrap, reason = "the grid is small")]
+                        grid.attach(&widget_factory.make(), column as i32, row as i32, 1, 1);
+                    }
+                    let label = gtk::Label::new(Some(&format!("Tab {}", tab_index + 1)));
+                    notebook.append_page(&grid, Some(&label));
+                }
+                notebook
+            }),
+        ));
+    }
+}
+
+fn rotate_cube(time: Res<Time>, mut query: Query<&mut Transform, With<Rotating>>) {
+    for mut transform in &mut query {
+        transform.rotate_x(0.9 * time.delta_secs());
+        transform.rotate_y(0.7 * time.delta_secs());
+    }
+}
@@ -0,0 +1,70 @@
+use {
+    bevy::{prelude::*, window::PrimaryWindow, winit::WinitPlugin},
+    bevy_gtk::{GtkInitPlugin, GtkPlugin, GtkTemplate, GtkViewports, GtkWindowContent},
+};
+
+const APP_ID: &str = "io.github.aecsocket.BevyGtk";
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins((
+            GtkInitPlugin,
+            DefaultPlugins.build().disable::<WinitPlugin>(),
+            GtkPlugin::new(APP_ID),
+        ))
+        .add_systems(Startup, (setup_scene, setup_cameras))
+        .run()
+}
+
+fn setup_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // circular base
+    commands.spawn((
+        Mesh3d(meshes.add(Circle::new(4.0))),
+        MeshMaterial3d(materials.add(Color::WHITE)),
+        Transform::from_rotation(Quat::from_rotation_x(-core::f32::consts::FRAC_PI_2)),
+    ));
+    // cube
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
+        MeshMaterial3d(materials.add(Color::srgb_u8(124, 144, 255))),
+        Transform::from_xyz(0.0, 0.5, 0.0),
+    ));
+    // light
+    commands.spawn((
+        PointLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0),
+    ));
+}
+
+fn setup_cameras(
+    mut commands: Commands,
+    window: Single<Entity, With<PrimaryWindow>>,
+    mut viewports: GtkViewports,
+) {
+    let (left_viewport, left_widget_factory) = viewports.create();
+    let (right_viewport, right_widget_factory) = viewports.create();
+
+    commands.spawn((
+        Camera3d::default(),
+        left_viewport,
+        Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+    commands.spawn((
+        Camera3d::default(),
+        right_viewport,
+        Transform::from_xyz(0.5, 4.5, 2.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.entity(*window).insert(GtkWindowContent::from(
+        GtkTemplate::from_string(include_str!("template.ui"), "root")
+            .with_slot("game_view_left", left_widget_factory)
+            .with_slot("game_view_right", right_widget_factory),
+    ));
+}
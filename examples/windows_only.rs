@@ -0,0 +1,59 @@
+//! Uses Bevy for app logic and GTK for the UI, without any Bevy rendering.
+
+use {
+    bevy::{prelude::*, time::common_conditions::on_timer},
+    bevy_gtk::{GtkCommands, GtkContext, GtkInitPlugin, GtkPlugin, GtkWindowContent, gtk},
+    core::time::Duration,
+};
+
+const APP_ID: &str = "io.github.aecsocket.BevyGtk";
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins((
+            GtkInitPlugin,
+            MinimalPlugins,
+            WindowPlugin {
+                primary_window: None,
+                ..default()
+            },
+            GtkPlugin::new(APP_ID),
+        ))
+        .init_resource::<Ticks>()
+        .add_systems(Startup, setup_window)
+        .add_systems(Update, tick.run_if(on_timer(Duration::from_secs(1))))
+        .run()
+}
+
+#[derive(Debug, Default, Resource)]
+struct Ticks(u32);
+
+thread_local! {
+    // GTK widgets can only be used on the GTK thread,
+    // so we can't store this in the Bevy world
+    static LABEL: glib::WeakRef<gtk::Label> = glib::WeakRef::new();
+}
+
+fn setup_window(mut commands: Commands) {
+    commands.spawn((
+        Window {
+            title: "Windows only".into(),
+            ..default()
+        },
+        GtkWindowContent::from(|| {
+            let label = gtk::Label::new(Some("Waiting for the first tick..."));
+            LABEL.with(|weak| weak.set(Some(&label)));
+            label
+        }),
+    ));
+}
+
+fn tick(mut ticks: ResMut<Ticks>, mut gtk_commands: GtkCommands) {
+    ticks.0 += 1;
+    let text = format!("Bevy has ticked {} times", ticks.0);
+    gtk_commands.queue(move |_: &mut GtkContext| {
+        if let Some(label) = LABEL.with(glib::WeakRef::upgrade) {
+            label.set_text(&text);
+        }
+    });
+}
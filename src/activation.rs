@@ -0,0 +1,73 @@
+use {crate::GtkWindows, bevy_app::prelude::*, bevy_ecs::prelude::*, gtk::prelude::*, log::debug};
+
+pub(super) fn plugin(app: &mut App) {
+    let (tx, rx) = async_channel::unbounded();
+    app.add_event::<GtkRemoteActivation>()
+        .add_event::<GtkOpenFiles>()
+        .insert_resource(ActivationChannel { tx, rx })
+        .add_systems(PreUpdate, forward_activations);
+}
+
+/// Sent when the app is launched again while it's already running.
+///
+/// Unless the application has [`gio::ApplicationFlags::NON_UNIQUE`], only one
+/// instance of it runs at a time. Launching it again activates the running
+/// instance instead, and the new process exits. When this happens, the
+/// [`PrimaryWindow`](bevy_window::PrimaryWindow) is presented to the user,
+/// and this event is sent.
+///
+/// If the new instance was launched with files to open, [`GtkOpenFiles`] is
+/// sent instead.
+#[derive(Debug, Clone, Event)]
+pub struct GtkRemoteActivation;
+
+/// Sent when the application is asked to open files.
+///
+/// This requires [`gio::ApplicationFlags::HANDLES_OPEN`]. Files are opened
+/// when the app is launched with [`GtkPlugin::open_files`], either by this
+/// instance, or by the running instance if another one is already running.
+///
+/// [`GtkPlugin::open_files`]: crate::GtkPlugin::open_files
+#[derive(Debug, Clone, Event)]
+pub struct GtkOpenFiles {
+    /// URIs of the files to open.
+    pub uris: Vec<String>,
+    /// Hint of how to open the files, or an empty string.
+    pub hint: String,
+}
+
+/// Activation of the GTK application after it was first activated.
+#[derive(Debug)]
+pub(crate) enum Activation {
+    Remote,
+    Open { uris: Vec<String>, hint: String },
+}
+
+#[derive(Debug, Resource)]
+pub(crate) struct ActivationChannel {
+    pub tx: async_channel::Sender<Activation>,
+    rx: async_channel::Receiver<Activation>,
+}
+
+fn forward_activations(
+    channel: Res<ActivationChannel>,
+    gtk_windows: NonSend<GtkWindows>,
+    mut remote_activations: EventWriter<GtkRemoteActivation>,
+    mut open_files: EventWriter<GtkOpenFiles>,
+) {
+    while let Ok(activation) = channel.rx.try_recv() {
+        match activation {
+            Activation::Remote => {
+                debug!("App was activated by another instance");
+                if let Some(proxy) = gtk_windows.primary() {
+                    proxy.gtk_window.present();
+                }
+                remote_activations.write(GtkRemoteActivation);
+            }
+            Activation::Open { uris, hint } => {
+                debug!("Opening files {uris:?} with hint {hint:?}");
+                open_files.write(GtkOpenFiles { uris, hint });
+            }
+        }
+    }
+}
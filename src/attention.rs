@@ -0,0 +1,108 @@
+use {
+    crate::{GtkApplication, GtkSystems, GtkWindows},
+    alloc::rc::Rc,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    core::cell::Cell,
+    glib::clone,
+    gtk::prelude::*,
+    log::debug,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<GtkRequestAttention>()
+        .add_systems(Last, request_attention.after(GtkSystems::SyncWindows));
+}
+
+/// Send this event to ask the desktop to draw the user's attention to a
+/// window, e.g. when a long task finishes while the window is unfocused.
+///
+/// Bevy's `WinitPlugin` does this with `Window::request_user_attention`, which
+/// this backend can't support, since GTK 4 has no urgency hint. Instead, the
+/// window is presented without an activation token, which desktops treat as a
+/// request for attention: GNOME shows a "ready" notification, and KDE
+/// highlights the window in the task bar. The window is not focused.
+///
+/// If [`GtkRequestAttention::notification`] is set, a desktop notification is
+/// also sent, through the notification portal if the app is sandboxed. It's
+/// withdrawn again once the window is focused.
+///
+/// Requests for a window which is already focused are ignored.
+#[derive(Debug, Clone, Event)]
+pub struct GtkRequestAttention {
+    /// Window to draw attention to.
+    pub window: Entity,
+    /// Body of a desktop notification to send along with the request, like
+    /// "Export finished".
+    pub notification: Option<String>,
+}
+
+impl GtkRequestAttention {
+    /// Creates a request for attention on `window`, without a notification.
+    #[must_use]
+    pub const fn new(window: Entity) -> Self {
+        Self {
+            window,
+            notification: None,
+        }
+    }
+
+    /// Sets [`GtkRequestAttention::notification`].
+    #[must_use]
+    pub fn with_notification(self, body: impl Into<String>) -> Self {
+        Self {
+            notification: Some(body.into()),
+            ..self
+        }
+    }
+}
+
+fn request_attention(
+    mut requests: EventReader<GtkRequestAttention>,
+    gtk_app: NonSend<GtkApplication>,
+    gtk_windows: NonSend<GtkWindows>,
+) {
+    for request in requests.read() {
+        let Some(proxy) = gtk_windows.get(request.window) else {
+            continue;
+        };
+        let gtk_window = &proxy.gtk_window;
+        if gtk_window.is_active() {
+            continue;
+        }
+
+        debug!("Requesting attention for {}", request.window);
+        gtk_window.present();
+
+        let Some(body) = &request.notification else {
+            continue;
+        };
+        let id = format!("bevy-gtk-attention-{}", request.window);
+        let title = gtk_window
+            .title()
+            .or_else(glib::application_name)
+            .unwrap_or_default();
+        let notification = gio::Notification::new(&title);
+        notification.set_body(Some(body));
+        gtk_app.send_notification(Some(&id), &notification);
+
+        // withdraw it once the user has seen the window
+        let gtk_app = gtk_app.0.clone();
+        let handler = Rc::new(Cell::new(None));
+        handler.set(Some(gtk_window.connect_is_active_notify(clone!(
+            #[weak]
+            gtk_app,
+            #[strong]
+            handler,
+            move |gtk_window| {
+                if !gtk_window.is_active() {
+                    return;
+                }
+                gtk_app.withdraw_notification(&id);
+                if let Some(handler) = handler.take() {
+                    gtk_window.disconnect(handler);
+                }
+            }
+        ))));
+    }
+}
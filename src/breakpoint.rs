@@ -0,0 +1,223 @@
+use {
+    crate::{GtkSystems, GtkWindows},
+    adw::prelude::*,
+    alloc::rc::Rc,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_platform::collections::HashMap,
+    core::cell::RefCell,
+    glib::clone,
+    log::{debug, warn},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let (tx_changed, rx_changed) = async_channel::unbounded();
+    app.add_event::<GtkBreakpointChanged>()
+        .insert_resource(BreakpointChannel {
+            tx_changed,
+            rx_changed,
+        })
+        .insert_non_send_resource(WindowBreakpoints::default())
+        .add_systems(PreUpdate, forward_breakpoint_changes)
+        .add_systems(Last, sync_breakpoints.after(GtkSystems::SyncWindows));
+}
+
+/// Adaptive layout breakpoints of a window, which Adwaita applies as the window
+/// is resized.
+///
+/// Insert this into a [`Window`] entity to add an [`adw::Breakpoint`] to its
+/// [`adw::ApplicationWindow`] for each [`GtkBreakpoint`]. These are evaluated
+/// the same way as breakpoints in your own GTK layout, so a game's HUD can
+/// switch arrangements at exactly the same window size as the GTK widgets
+/// around it.
+///
+/// Only one breakpoint is applied at a time; if several match, the last one
+/// wins. The applied breakpoint is stored in [`GtkActiveBreakpoint`], and a
+/// [`GtkBreakpointChanged`] event is sent when it changes.
+///
+/// This requires [`GtkPlugin::use_adw`](crate::GtkPlugin::use_adw).
+///
+/// # Examples
+///
+/// ```ignore
+/// commands.entity(window).insert(GtkBreakpoints(vec![
+///     GtkBreakpoint::new("narrow", "max-width: 600sp"),
+/// ]));
+///
+/// fn update_hud(windows: Query<&GtkActiveBreakpoint, Changed<GtkActiveBreakpoint>>) {
+///     for active in &windows {
+///         let narrow = active.is("narrow");
+///         // ..
+///     }
+/// }
+/// ```
+///
+/// [`Window`]: bevy_window::Window
+#[derive(Debug, Clone, Default, PartialEq, Eq, Component)]
+#[require(GtkActiveBreakpoint)]
+pub struct GtkBreakpoints(pub Vec<GtkBreakpoint>);
+
+/// Single breakpoint of a [`GtkBreakpoints`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GtkBreakpoint {
+    /// Name which identifies this breakpoint in [`GtkActiveBreakpoint`].
+    pub name: String,
+    /// Condition for when this breakpoint is applied, in the syntax of
+    /// [`adw::BreakpointCondition::parse`], like `max-width: 600sp`.
+    pub condition: String,
+}
+
+impl GtkBreakpoint {
+    /// Creates a breakpoint.
+    #[must_use]
+    pub fn new(name: impl Into<String>, condition: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            condition: condition.into(),
+        }
+    }
+}
+
+/// Name of the [`GtkBreakpoint`] which is currently applied to a window, or
+/// [`None`] if no breakpoint is applied.
+///
+/// This is inserted alongside [`GtkBreakpoints`], and kept up to date.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Component)]
+pub struct GtkActiveBreakpoint(pub Option<String>);
+
+impl GtkActiveBreakpoint {
+    /// Whether the breakpoint with this name is applied.
+    #[must_use]
+    pub fn is(&self, name: &str) -> bool {
+        self.0.as_deref() == Some(name)
+    }
+}
+
+/// Emitted when the [`GtkActiveBreakpoint`] of a window changes.
+#[derive(Debug, Clone, Event)]
+pub struct GtkBreakpointChanged {
+    /// Window which the breakpoint was applied to.
+    pub window: Entity,
+    /// Name of the breakpoint which is now applied, if any.
+    pub breakpoint: Option<String>,
+}
+
+#[derive(Debug, Resource)]
+struct BreakpointChannel {
+    tx_changed: async_channel::Sender<(Entity, Option<String>)>,
+    rx_changed: async_channel::Receiver<(Entity, Option<String>)>,
+}
+
+/// Breakpoints which we've added to each GTK window, and the names of them.
+///
+/// Adwaita can't remove breakpoints from a window, so when [`GtkBreakpoints`]
+/// changes, we reuse the breakpoints we already added, and clear the condition
+/// of any left over, which means they're never applied.
+#[derive(Debug, Default)]
+struct WindowBreakpoints(HashMap<Entity, Rc<RefCell<Vec<(adw::Breakpoint, String)>>>>);
+
+fn sync_breakpoints(
+    windows: Query<(Entity, &GtkBreakpoints), Changed<GtkBreakpoints>>,
+    gtk_windows: NonSend<GtkWindows>,
+    mut window_breakpoints: NonSendMut<WindowBreakpoints>,
+    channel: Res<BreakpointChannel>,
+    mut removed: RemovedComponents<GtkBreakpoints>,
+    mut warned: Local<bool>,
+) {
+    for entity in removed.read() {
+        if gtk_windows.get(entity).is_none() {
+            window_breakpoints.0.remove(&entity);
+        } else if let Some(added) = window_breakpoints.0.get(&entity) {
+            for (breakpoint, _) in added.borrow().iter() {
+                breakpoint.set_condition(None);
+            }
+        }
+    }
+
+    for (entity, breakpoints) in &windows {
+        let Some(proxy) = gtk_windows.get(entity) else {
+            continue;
+        };
+        let Some(adw_window) = proxy.gtk_window.downcast_ref::<adw::ApplicationWindow>() else {
+            if !*warned {
+                warn!("`GtkBreakpoints` requires `GtkPlugin::use_adw`");
+                *warned = true;
+            }
+            continue;
+        };
+
+        let added = window_breakpoints
+            .0
+            .entry(entity)
+            .or_insert_with(|| {
+                let added = Rc::<RefCell<Vec<(adw::Breakpoint, String)>>>::default();
+                let tx_changed = channel.tx_changed.clone();
+                adw_window.connect_current_breakpoint_notify(clone!(
+                    #[strong]
+                    added,
+                    move |adw_window| send_current(adw_window, entity, &added, &tx_changed)
+                ));
+                added
+            })
+            .clone();
+
+        debug!("Setting {} breakpoints of {entity}", breakpoints.0.len());
+        // Adwaita may apply a breakpoint while we're changing them,
+        // so we don't hold a borrow of `added` while doing that
+        let mut new_added = added.borrow().clone();
+        for (index, breakpoint) in breakpoints.0.iter().enumerate() {
+            let condition = adw::BreakpointCondition::parse(&breakpoint.condition)
+                .inspect_err(|err| {
+                    warn!(
+                        "Invalid condition {:?} for breakpoint {:?} of {entity}: {err}",
+                        breakpoint.condition, breakpoint.name
+                    );
+                })
+                .ok();
+            if index == new_added.len() {
+                let new = glib::Object::new::<adw::Breakpoint>();
+                adw_window.add_breakpoint(new.clone());
+                new_added.push((new, String::new()));
+            }
+            let (existing, name) = &mut new_added[index];
+            existing.set_condition(condition.as_ref());
+            name.clone_from(&breakpoint.name);
+        }
+        for (unused, _) in new_added.iter().skip(breakpoints.0.len()) {
+            unused.set_condition(None);
+        }
+        added.replace(new_added);
+        send_current(adw_window, entity, &added, &channel.tx_changed);
+    }
+}
+
+fn send_current(
+    adw_window: &adw::ApplicationWindow,
+    entity: Entity,
+    added: &RefCell<Vec<(adw::Breakpoint, String)>>,
+    tx_changed: &async_channel::Sender<(Entity, Option<String>)>,
+) {
+    let name = adw_window.current_breakpoint().and_then(|current| {
+        added
+            .borrow()
+            .iter()
+            .find(|(breakpoint, _)| *breakpoint == current)
+            .map(|(_, name)| name.clone())
+    });
+    _ = tx_changed.try_send((entity, name));
+}
+
+fn forward_breakpoint_changes(
+    channel: Res<BreakpointChannel>,
+    mut windows: Query<&mut GtkActiveBreakpoint>,
+    mut events: EventWriter<GtkBreakpointChanged>,
+) {
+    while let Ok((window, breakpoint)) = channel.rx_changed.try_recv() {
+        let Ok(mut active) = windows.get_mut(window) else {
+            continue;
+        };
+        if active.set_if_neq(GtkActiveBreakpoint(breakpoint.clone())) {
+            events.write(GtkBreakpointChanged { window, breakpoint });
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use {
+    crate::GtkApplication,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    gdk::prelude::*,
+    log::debug,
+    std::{env, path::Path},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(PreStartup, probe_capabilities)
+        .add_systems(PreUpdate, forward_portals);
+}
+
+/// What the environment that the app is running in allows, probed at startup.
+///
+/// Apps shipped through Flatpak run in a sandbox, which behaves differently to
+/// running on the host: GPU access may be restricted, and most desktop
+/// integration has to go through [XDG desktop portals]. Use this to adapt to
+/// what's available, e.g. by hiding features which need a portal that doesn't
+/// exist.
+///
+/// Viewports consult this to pick how they present frames to GTK. If GTK can't
+/// import dmabufs, frames are copied through the CPU instead.
+///
+/// This is inserted as a resource at startup. [`GtkCapabilities::portals`] is
+/// filled in shortly after, since probing it needs a D-Bus round trip.
+///
+/// [XDG desktop portals]: https://flatpak.github.io/xdg-desktop-portal/
+#[derive(Debug, Clone, Default, PartialEq, Eq, Resource)]
+pub struct GtkCapabilities {
+    /// Sandbox that the app is running in, if any.
+    pub sandbox: Option<GtkSandbox>,
+    /// Whether GTK can import dmabufs on the current display.
+    pub dmabuf_import: bool,
+    /// D-Bus interface names of the XDG desktop portals which are available,
+    /// like `org.freedesktop.portal.FileChooser`.
+    ///
+    /// This is empty if the portal service isn't running, or hasn't responded
+    /// yet.
+    pub portals: Vec<String>,
+}
+
+impl GtkCapabilities {
+    /// Whether the app is running in a sandbox.
+    #[must_use]
+    pub fn is_sandboxed(&self) -> bool {
+        self.sandbox.is_some()
+    }
+
+    /// Whether the portal with this D-Bus interface name is available.
+    ///
+    /// The `org.freedesktop.portal.` prefix may be omitted, so
+    /// `has_portal("FileChooser")` also works.
+    #[must_use]
+    pub fn has_portal(&self, name: &str) -> bool {
+        self.portals.iter().any(|portal| {
+            portal == name || portal.strip_prefix(PORTAL_INTERFACE_PREFIX) == Some(name)
+        })
+    }
+}
+
+/// Sandbox which an app can run in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GtkSandbox {
+    /// [Flatpak](https://flatpak.org/).
+    Flatpak,
+    /// [Snap](https://snapcraft.io/).
+    Snap,
+}
+
+#[cfg(feature = "viewport")]
+impl bevy_render::extract_resource::ExtractResource for GtkCapabilities {
+    type Source = Self;
+
+    fn extract_resource(source: &Self::Source) -> Self {
+        source.clone()
+    }
+}
+
+const PORTAL_BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+const PORTAL_INTERFACE_PREFIX: &str = "org.freedesktop.portal.";
+/// How long we wait for the portal service to respond, in milliseconds.
+const PORTAL_TIMEOUT_MS: i32 = 1000;
+
+#[derive(Debug, Resource)]
+struct RxPortals(async_channel::Receiver<Vec<String>>);
+
+// `NonSend` keeps this on the GTK thread
+fn probe_capabilities(_: NonSend<GtkApplication>, mut commands: Commands) {
+    let sandbox = if Path::new("/.flatpak-info").exists() {
+        Some(GtkSandbox::Flatpak)
+    } else if env::var_os("SNAP").is_some() {
+        Some(GtkSandbox::Snap)
+    } else {
+        None
+    };
+    let dmabuf_import =
+        gdk::Display::default().is_some_and(|display| display.dmabuf_formats().n_formats() > 0);
+    let capabilities = GtkCapabilities {
+        sandbox,
+        dmabuf_import,
+        portals: Vec::new(),
+    };
+    debug!("Probed capabilities: {capabilities:?}");
+    commands.insert_resource(capabilities);
+
+    let (tx_portals, rx_portals) = async_channel::bounded(1);
+    commands.insert_resource(RxPortals(rx_portals));
+    glib::spawn_future_local(async move {
+        match available_portals().await {
+            Ok(portals) => _ = tx_portals.send(portals).await,
+            Err(err) => debug!("Failed to probe XDG desktop portals: {err}"),
+        }
+    });
+}
+
+async fn available_portals() -> Result<Vec<String>, glib::Error> {
+    let connection = gio::bus_get_future(gio::BusType::Session).await?;
+    let reply = connection
+        .call_future(
+            Some(PORTAL_BUS_NAME),
+            PORTAL_OBJECT_PATH,
+            "org.freedesktop.DBus.Introspectable",
+            "Introspect",
+            None,
+            Some(glib::VariantTy::new("(s)").expect("should be a valid variant type")),
+            gio::DBusCallFlags::NONE,
+            PORTAL_TIMEOUT_MS,
+        )
+        .await?;
+    let xml = reply
+        .child_value(0)
+        .str()
+        .map(ToOwned::to_owned)
+        .unwrap_or_default();
+
+    // GIO can parse this, but doesn't expose interface names
+    let portals = xml
+        .split("<interface name=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('"'))
+        .map(|(name, _)| name)
+        .filter(|name| name.starts_with(PORTAL_INTERFACE_PREFIX))
+        .map(ToOwned::to_owned)
+        .collect();
+    Ok(portals)
+}
+
+fn forward_portals(rx_portals: Res<RxPortals>, mut capabilities: ResMut<GtkCapabilities>) {
+    if let Ok(portals) = rx_portals.0.try_recv() {
+        debug!("Available portals: {portals:?}");
+        capabilities.portals = portals;
+    }
+}
@@ -0,0 +1,107 @@
+use {
+    crate::{GtkApplication, GtkSystems, GtkWindows},
+    bevy_app::prelude::*,
+    bevy_ecs::{prelude::*, system::SystemParam},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let (tx, rx) = async_channel::unbounded();
+    app.insert_resource(GtkCommandQueue { tx, rx })
+        .add_systems(Last, apply_commands.in_set(GtkSystems::ApplyCommands));
+}
+
+/// Queues [`GtkCommand`]s to be run on the GTK thread.
+///
+/// GTK types are `!Send`, so most systems can't touch GTK objects directly.
+/// Instead, you can queue a closure here, and it will be run on the GTK thread
+/// during [`GtkSystems::ApplyCommands`], with access to the [`GtkContext`].
+///
+/// # Examples
+///
+/// ```ignore
+/// fn set_subtitle(mut gtk: GtkCommands, window: Single<Entity, With<PrimaryWindow>>) {
+///     let window = *window;
+///     gtk.queue(move |ctx: &mut GtkContext| {
+///         if let Some(proxy) = ctx.windows.get(window) {
+///             proxy.gtk_window.set_title(Some("Hello from Bevy"));
+///         }
+///     });
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct GtkCommands<'w> {
+    queue: Res<'w, GtkCommandQueue>,
+}
+
+impl GtkCommands<'_> {
+    /// Queues a command to be run on the GTK thread at the next
+    /// [`GtkSystems::ApplyCommands`].
+    pub fn queue(&mut self, command: impl GtkCommand) {
+        self.queue.push(command);
+    }
+}
+
+/// Channel which [`GtkCommands`] sends commands into.
+///
+/// Usually you should use [`GtkCommands`] instead, but you can clone the
+/// [`GtkCommandQueue::sender`] to queue commands from outside of a system, e.g.
+/// from an async task.
+#[derive(Debug, Resource)]
+pub struct GtkCommandQueue {
+    tx: async_channel::Sender<Box<dyn GtkCommand>>,
+    rx: async_channel::Receiver<Box<dyn GtkCommand>>,
+}
+
+impl GtkCommandQueue {
+    /// Queues a command to be run on the GTK thread.
+    pub fn push(&self, command: impl GtkCommand) {
+        _ = self.tx.try_send(Box::new(command));
+    }
+
+    /// Gets a sender for queueing commands.
+    #[must_use]
+    pub fn sender(&self) -> async_channel::Sender<Box<dyn GtkCommand>> {
+        self.tx.clone()
+    }
+}
+
+/// Code which runs on the GTK thread, with access to the [`GtkContext`].
+///
+/// This is automatically implemented for closures accepting a
+/// [`&mut GtkContext`](GtkContext).
+pub trait GtkCommand: Send + 'static {
+    /// Runs this command.
+    fn apply(self: Box<Self>, ctx: &mut GtkContext);
+}
+
+impl<F> GtkCommand for F
+where
+    F: FnOnce(&mut GtkContext) + Send + 'static,
+{
+    fn apply(self: Box<Self>, ctx: &mut GtkContext) {
+        (self)(ctx);
+    }
+}
+
+/// GTK-side state which a [`GtkCommand`] has access to.
+#[derive(Debug)]
+pub struct GtkContext<'a> {
+    /// Application that this app is running under.
+    pub app: &'a gtk::Application,
+    /// Windows which are managed by Bevy.
+    pub windows: &'a mut GtkWindows,
+}
+
+fn apply_commands(
+    queue: Res<GtkCommandQueue>,
+    gtk_app: NonSend<GtkApplication>,
+    mut gtk_windows: NonSendMut<GtkWindows>,
+) {
+    let mut ctx = GtkContext {
+        app: &gtk_app,
+        windows: &mut gtk_windows,
+    };
+    while let Ok(command) = queue.rx.try_recv() {
+        command.apply(&mut ctx);
+    }
+}
@@ -0,0 +1,207 @@
+use {
+    crate::{GtkSystems, GtkWindows},
+    alloc::rc::Rc,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_input::mouse::MouseMotion,
+    bevy_math::Vec2,
+    bevy_platform::collections::{HashMap, HashSet},
+    bevy_window::{CursorGrabMode, CursorOptions, Window},
+    core::cell::Cell,
+    gtk::prelude::*,
+    log::{debug, warn},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let (tx_motion, rx_motion) = async_channel::unbounded();
+    app.add_event::<GtkCursorWarpFailed>()
+        .add_event::<MouseMotion>()
+        .insert_resource(MotionChannel {
+            tx: tx_motion,
+            rx: rx_motion,
+        })
+        .init_non_send_resource::<CursorStates>()
+        .add_systems(PreUpdate, forward_motion)
+        .add_systems(
+            Last,
+            (warp_cursors, sync_cursor_options)
+                .after(crate::window::sync_window_config)
+                .in_set(GtkSystems::SyncWindows),
+        );
+}
+
+/// Sent when [`Window::set_cursor_position`] asked to move the pointer, but
+/// the pointer could not be moved.
+///
+/// Games which capture the mouse often recenter the pointer every frame. GTK 4
+/// has no API to move the pointer on any backend: X11 pointer warping was
+/// removed along with `gdk_device_warp`, and on Wayland the compositor only
+/// accepts a position hint for a locked pointer, which GTK doesn't expose. So
+/// every request to set the cursor position fails, and this event is sent
+/// instead of the request being silently dropped.
+///
+/// To keep relative mouse input working, read mouse motion deltas instead of
+/// the absolute cursor position, and hide the cursor while it's captured.
+#[derive(Debug, Clone, Event)]
+pub struct GtkCursorWarpFailed {
+    /// Window whose cursor position was set.
+    pub window: Entity,
+}
+
+fn warp_cursors(
+    windows: Query<(Entity, &Window), Changed<Window>>,
+    gtk_windows: NonSend<GtkWindows>,
+    mut last_positions: Local<HashMap<Entity, [f32; 2]>>,
+    mut warned: Local<HashSet<Entity>>,
+    mut failed: EventWriter<GtkCursorWarpFailed>,
+) {
+    last_positions.retain(|entity, _| gtk_windows.get(*entity).is_some());
+    warned.retain(|entity| gtk_windows.get(*entity).is_some());
+
+    for (entity, window) in &windows {
+        let Some(proxy) = gtk_windows.get(entity) else {
+            continue;
+        };
+        // we never write the cursor position ourselves, so any change to it
+        // comes from the app
+        let Some(position) = window.physical_cursor_position() else {
+            last_positions.remove(&entity);
+            continue;
+        };
+        let position = position.to_array();
+        if last_positions.insert(entity, position) == Some(position) {
+            continue;
+        }
+
+        if warned.insert(entity) {
+            // `display` is ambiguous between `WidgetExt` and `RootExt`
+            let backend = WidgetExt::display(&proxy.gtk_window).type_().name();
+            warn!(
+                "Cannot set the cursor position of {entity}: GTK 4 can't move the pointer on \
+                 {backend}"
+            );
+        }
+        failed.write(GtkCursorWarpFailed { window: entity });
+    }
+}
+
+#[derive(Debug, Resource)]
+struct MotionChannel {
+    tx: async_channel::Sender<Vec2>,
+    rx: async_channel::Receiver<Vec2>,
+}
+
+/// GTK-side cursor state of each window.
+#[derive(Debug, Default)]
+struct CursorStates {
+    windows: HashMap<Entity, CursorState>,
+}
+
+#[derive(Debug)]
+struct CursorState {
+    _motion: gtk::EventControllerMotion,
+    /// Visibility and grab mode which we've applied to the window.
+    cache: Option<(bool, CursorGrabMode)>,
+    shortcuts_inhibited: bool,
+}
+
+/// Keeps the GTK windows in sync with their [`CursorOptions`], and feeds
+/// pointer motion over them into [`MouseMotion`].
+///
+/// Wayland has the `zwp_relative_pointer_v1` and `zwp_pointer_constraints_v1`
+/// protocols for unaccelerated relative motion and pointer locking, but GTK 4
+/// doesn't expose either of them. The closest we can get is:
+/// - deriving [`MouseMotion`] deltas from the absolute pointer position, in
+///   physical pixels, which stop when the pointer reaches the edge of the
+///   window or screen
+/// - hiding the cursor when [`CursorOptions::visible`] is `false`
+/// - inhibiting system shortcuts while the cursor is grabbed, so that keyboard
+///   input goes to the game instead of the compositor
+///
+/// The pointer itself can't be confined or locked to the window.
+fn sync_cursor_options(
+    windows: Query<(Entity, Option<&CursorOptions>), With<Window>>,
+    gtk_windows: NonSend<GtkWindows>,
+    channel: Res<MotionChannel>,
+    mut states: NonSendMut<CursorStates>,
+) {
+    states
+        .windows
+        .retain(|entity, _| gtk_windows.get(*entity).is_some());
+
+    for (entity, options) in &windows {
+        let Some(proxy) = gtk_windows.get(entity) else {
+            continue;
+        };
+        let gtk_window = &proxy.gtk_window;
+        let state = states.windows.entry(entity).or_insert_with(|| CursorState {
+            _motion: track_motion(gtk_window, channel.tx.clone()),
+            cache: None,
+            shortcuts_inhibited: false,
+        });
+
+        let (visible, grab_mode) = options.map_or((true, CursorGrabMode::None), |options| {
+            (options.visible, options.grab_mode)
+        });
+        if state.cache == Some((visible, grab_mode)) {
+            continue;
+        }
+
+        gtk_window.set_cursor_from_name(if visible { None } else { Some("none") });
+
+        let grabbed = grab_mode != CursorGrabMode::None;
+        if grabbed != state.shortcuts_inhibited {
+            let toplevel = gtk_window
+                .surface()
+                .and_then(|surface| surface.dynamic_cast::<gdk::Toplevel>().ok());
+            // the window isn't mapped yet, so try again next time
+            let Some(toplevel) = toplevel else {
+                continue;
+            };
+            debug!("Cursor of {entity} grabbed: {grabbed}");
+            if grabbed {
+                toplevel.inhibit_system_shortcuts(None::<gdk::Event>);
+            } else {
+                toplevel.restore_system_shortcuts();
+            }
+            state.shortcuts_inhibited = grabbed;
+        }
+
+        state.cache = Some((visible, grab_mode));
+    }
+}
+
+fn track_motion(
+    gtk_window: &gtk::ApplicationWindow,
+    tx_motion: async_channel::Sender<Vec2>,
+) -> gtk::EventControllerMotion {
+    let controller = gtk::EventControllerMotion::new();
+    let last_position = Rc::new(Cell::new(None::<(f64, f64)>));
+    controller.connect_motion({
+        let last_position = last_position.clone();
+        move |controller, x, y| {
+            let scale = controller
+                .widget()
+                .and_then(|widget| widget.native())
+                .and_then(|native| native.surface())
+                .map_or(1.0, |surface| surface.scale());
+            if let Some((last_x, last_y)) = last_position.replace(Some((x, y))) {
+                #[expect(clippy::cast_possible_truncation, reason = "deltas are small")]
+                let delta = Vec2::new(((x - last_x) * scale) as f32, ((y - last_y) * scale) as f32);
+                if delta != Vec2::ZERO {
+                    _ = tx_motion.try_send(delta);
+                }
+            }
+        }
+    });
+    // the pointer may come back in somewhere else entirely
+    controller.connect_leave(move |_| last_position.set(None));
+    gtk_window.add_controller(controller.clone());
+    controller
+}
+
+fn forward_motion(channel: Res<MotionChannel>, mut motion: EventWriter<MouseMotion>) {
+    while let Ok(delta) = channel.rx.try_recv() {
+        motion.write(MouseMotion { delta });
+    }
+}
@@ -0,0 +1,228 @@
+#[cfg(feature = "viewport")]
+use {crate::GtkAdapterSelection, bevy_render::RenderPlugin, bevy_utils::default};
+use {
+    crate::{GtkInitPlugin, GtkPlugin},
+    bevy_app::{PluginGroupBuilder, prelude::*},
+    bevy_internal::DefaultPlugins,
+    bevy_window::WindowPlugin,
+    bevy_winit::WinitPlugin,
+    gtk::prelude::*,
+    std::path::PathBuf,
+};
+
+/// Bevy's `DefaultPlugins`, set up to run under GTK.
+///
+/// This adds, in order:
+/// - [`GtkInitPlugin`]
+/// - `DefaultPlugins`, without `WinitPlugin`, and with
+///   [`GtkDefaultPlugins::window`] as the [`WindowPlugin`]
+///   - with the `viewport` feature, its `RenderPlugin` renders on the GPU
+///     picked by [`GtkDefaultPlugins::adapter`]
+/// - [`GtkDefaultPlugins::gtk`] as the [`GtkPlugin`]
+///
+/// which is the order that these plugins must be added in. The builder methods
+/// mirror the ones on [`GtkPlugin`]. Like any plugin group, you can still
+/// [`set`](PluginGroupBuilder::set) or
+/// [`disable`](PluginGroupBuilder::disable) individual plugins after calling
+/// [`PluginGroup::build`].
+///
+/// Which plugins `DefaultPlugins` contains depends on the features that you
+/// enable on `bevy`, as usual.
+///
+/// # Examples
+///
+/// ```ignore
+/// App::new()
+///     .add_plugins(GtkDefaultPlugins::new("org.bevy.DemoApp").with_panic_dialog())
+///     .run();
+/// ```
+#[derive(Default)]
+pub struct GtkDefaultPlugins {
+    /// Plugin which runs the app under GTK.
+    pub gtk: GtkPlugin,
+    /// Plugin which manages Bevy windows.
+    pub window: WindowPlugin,
+    /// Which GPU Bevy renders on.
+    #[cfg(feature = "viewport")]
+    pub adapter: GtkAdapterSelection,
+}
+
+impl PluginGroup for GtkDefaultPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let mut group = PluginGroupBuilder::start::<Self>()
+            .add(GtkInitPlugin)
+            .add_group(DefaultPlugins);
+        if group.contains::<WinitPlugin>() {
+            group = group.disable::<WinitPlugin>();
+        }
+        let group = match group.try_set(self.window) {
+            Ok(group) => group,
+            Err((group, window)) => group.add(window),
+        };
+        #[cfg(feature = "viewport")]
+        let group = if group.contains::<RenderPlugin>() {
+            group.set(RenderPlugin {
+                render_creation: self.adapter.wgpu_settings().into(),
+                ..default()
+            })
+        } else {
+            group
+        };
+        group.add(self.gtk)
+    }
+}
+
+impl GtkDefaultPlugins {
+    /// Creates a new plugin group with the given application ID.
+    ///
+    /// See [`GtkPlugin::new`].
+    #[must_use]
+    pub fn new(app_id: impl Into<String>) -> Self {
+        Self {
+            gtk: GtkPlugin::new(app_id),
+            ..Self::default()
+        }
+    }
+
+    /// Sets [`GtkDefaultPlugins::window`].
+    #[must_use]
+    pub fn with_window_plugin(self, window: WindowPlugin) -> Self {
+        Self { window, ..self }
+    }
+
+    /// Sets [`GtkDefaultPlugins::adapter`].
+    #[cfg(feature = "viewport")]
+    #[must_use]
+    pub fn with_adapter(self, adapter: GtkAdapterSelection) -> Self {
+        Self { adapter, ..self }
+    }
+
+    /// See [`GtkPlugin::with_app_flags`].
+    #[must_use]
+    pub fn with_app_flags(self, app_flags: gio::ApplicationFlags) -> Self {
+        Self {
+            gtk: self.gtk.with_app_flags(app_flags),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::non_unique`].
+    #[must_use]
+    pub fn non_unique(self) -> Self {
+        Self {
+            gtk: self.gtk.non_unique(),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::with_open_files`].
+    #[must_use]
+    pub fn with_open_files(self, files: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        Self {
+            gtk: self.gtk.with_open_files(files),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::with_app_name`].
+    #[must_use]
+    pub fn with_app_name(self, app_name: impl Into<String>) -> Self {
+        Self {
+            gtk: self.gtk.with_app_name(app_name),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::with_adw`].
+    #[must_use]
+    pub fn with_adw(self) -> Self {
+        Self {
+            gtk: self.gtk.with_adw(),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::without_adw`].
+    #[must_use]
+    pub fn without_adw(self) -> Self {
+        Self {
+            gtk: self.gtk.without_adw(),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::with_application`].
+    #[must_use]
+    pub fn with_application<A: IsA<gtk::Application>>(
+        self,
+        make_application: impl Fn() -> A + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            gtk: self.gtk.with_application(make_application),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::with_panic_dialog`].
+    #[must_use]
+    pub fn with_panic_dialog(self) -> Self {
+        Self {
+            gtk: self.gtk.with_panic_dialog(),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::with_frame_clock_time`].
+    #[must_use]
+    pub fn with_frame_clock_time(self) -> Self {
+        Self {
+            gtk: self.gtk.with_frame_clock_time(),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::with_fixed_timestep_from_monitor`].
+    #[must_use]
+    pub fn with_fixed_timestep_from_monitor(self) -> Self {
+        Self {
+            gtk: self.gtk.with_fixed_timestep_from_monitor(),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::with_resources`].
+    #[must_use]
+    pub fn with_resources(self, data: &'static [u8]) -> Self {
+        Self {
+            gtk: self.gtk.with_resources(data),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::with_resource_base_path`].
+    #[must_use]
+    pub fn with_resource_base_path(self, path: impl Into<String>) -> Self {
+        Self {
+            gtk: self.gtk.with_resource_base_path(path),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::with_icon_resource_path`].
+    #[must_use]
+    pub fn with_icon_resource_path(self, path: impl Into<String>) -> Self {
+        Self {
+            gtk: self.gtk.with_icon_resource_path(path),
+            ..self
+        }
+    }
+
+    /// See [`GtkPlugin::with_icon_search_path`].
+    #[must_use]
+    pub fn with_icon_search_path(self, path: impl Into<PathBuf>) -> Self {
+        Self {
+            gtk: self.gtk.with_icon_search_path(path),
+            ..self
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use {
+    crate::{GtkSystems, GtkWindows},
+    adw::prelude::*,
+    alloc::rc::Rc,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_platform::collections::HashMap,
+    core::cell::RefCell,
+    glib::clone,
+    log::debug,
+};
+
+/// Shows errors reported by this crate to the user, through Adwaita widgets.
+///
+/// Errors are collected as [`GtkErrorReport`] events, which you can also send
+/// yourself to report your own errors the same way. Crate errors which are
+/// reported:
+/// - [`ViewportError`](crate::ViewportError), when the `viewport` feature is
+///   enabled
+///
+/// Reports are shown according to [`GtkErrorReportingPlugin::display`], with
+/// a button to copy the details of the error to the clipboard, so that users
+/// can paste them into a bug report.
+///
+/// This requires [`GtkPlugin::use_adw`](crate::GtkPlugin::use_adw).
+#[derive(Debug, Clone, Default)]
+pub struct GtkErrorReportingPlugin {
+    /// How errors are shown.
+    pub display: GtkErrorDisplay,
+}
+
+impl Plugin for GtkErrorReportingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GtkErrorReport>()
+            .insert_resource(ErrorDisplay(self.display))
+            .init_non_send_resource::<ErrorBanners>()
+            .add_systems(Last, show_errors.after(GtkSystems::SyncWindows));
+
+        #[cfg(feature = "viewport")]
+        app.add_systems(
+            Last,
+            report_viewport_errors
+                .before(show_errors)
+                .after(GtkSystems::SyncWindows),
+        );
+    }
+}
+
+/// How a [`GtkErrorReportingPlugin`] shows errors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GtkErrorDisplay {
+    /// Shows an [`adw::AlertDialog`] over the
+    /// [`PrimaryWindow`](bevy_window::PrimaryWindow).
+    #[default]
+    Dialog,
+    /// Shows an [`adw::Banner`] at the top of the
+    /// [`PrimaryWindow`](bevy_window::PrimaryWindow), below its header bar.
+    ///
+    /// This is less intrusive than a dialog, but is only possible if the
+    /// window has an opaque title bar. Otherwise, a dialog is shown instead.
+    Banner,
+    /// Only sends [`GtkErrorReport`] events, without showing anything.
+    None,
+}
+
+/// Error to show to the user through a [`GtkErrorReportingPlugin`].
+#[derive(Debug, Clone, Event)]
+pub struct GtkErrorReport {
+    /// Short, user-facing description of what failed.
+    pub summary: String,
+    /// Technical details of the error, which the user can copy.
+    pub details: String,
+}
+
+#[derive(Debug, Resource)]
+struct ErrorDisplay(GtkErrorDisplay);
+
+/// Banners which we've added to each window.
+#[derive(Debug, Default)]
+struct ErrorBanners(HashMap<Entity, ErrorBanner>);
+
+#[derive(Debug, Clone)]
+struct ErrorBanner {
+    banner: adw::Banner,
+    /// Details of the error which the banner currently shows.
+    details: Rc<RefCell<String>>,
+}
+
+#[cfg(feature = "viewport")]
+fn report_viewport_errors(
+    mut viewport_errors: EventReader<crate::ViewportError>,
+    mut reports: EventWriter<GtkErrorReport>,
+) {
+    for error in viewport_errors.read() {
+        reports.write(GtkErrorReport {
+            summary: "A viewport failed to render".into(),
+            details: format!(
+                "Viewport {} failed ({:?}): {}",
+                error.viewport, error.kind, error.message
+            ),
+        });
+    }
+}
+
+fn show_errors(
+    mut reports: EventReader<GtkErrorReport>,
+    display: Res<ErrorDisplay>,
+    gtk_windows: NonSend<GtkWindows>,
+    mut banners: NonSendMut<ErrorBanners>,
+) {
+    let reports = reports.read().collect::<Vec<_>>();
+    let Some(first) = reports.first() else {
+        return;
+    };
+    let Some((window, proxy)) = gtk_windows.primary_entity().zip(gtk_windows.primary()) else {
+        return;
+    };
+    banners
+        .0
+        .retain(|entity, _| gtk_windows.get(*entity).is_some());
+
+    // several errors in the same frame are usually caused by the same thing,
+    // so we show them together
+    let summary = if reports.len() > 1 {
+        format!("{} (and {} more errors)", first.summary, reports.len() - 1)
+    } else {
+        first.summary.clone()
+    };
+    let details = reports
+        .iter()
+        .map(|report| report.details.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    debug!("Showing error to the user: {summary}");
+
+    if display.0 == GtkErrorDisplay::Banner {
+        if let Some(banner) = show_banner(&mut banners, window, &proxy.gtk_window) {
+            banner.banner.set_title(&summary);
+            *banner.details.borrow_mut() = details;
+            banner.banner.set_revealed(true);
+            return;
+        }
+    }
+    if display.0 != GtkErrorDisplay::None {
+        show_dialog(&proxy.gtk_window, &summary, &details);
+    }
+}
+
+/// Gets the banner of `window`, adding it below the header bar if it isn't in
+/// the window already.
+///
+/// Returns [`None`] if the window has no header bar to add it below.
+fn show_banner(
+    banners: &mut ErrorBanners,
+    window: Entity,
+    gtk_window: &gtk::ApplicationWindow,
+) -> Option<ErrorBanner> {
+    let banner = banners
+        .0
+        .entry(window)
+        .or_insert_with(|| {
+            let banner = adw::Banner::builder().button_label("Copy Details").build();
+            let details = Rc::new(RefCell::new(String::new()));
+            banner.connect_button_clicked(clone!(
+                #[strong]
+                details,
+                move |banner| {
+                    banner.clipboard().set_text(&details.borrow());
+                    banner.set_revealed(false);
+                }
+            ));
+            ErrorBanner { banner, details }
+        })
+        .clone();
+
+    // the window's widgets are rebuilt when its title bar settings change,
+    // so the banner may have been removed
+    if banner.banner.parent().is_none() {
+        let toolbar = find_toolbar(gtk_window.upcast_ref())?;
+        toolbar.add_top_bar(&banner.banner);
+    }
+    Some(banner)
+}
+
+fn find_toolbar(widget: &gtk::Widget) -> Option<adw::ToolbarView> {
+    if let Some(toolbar) = widget.downcast_ref::<adw::ToolbarView>() {
+        return Some(toolbar.clone());
+    }
+    let mut child = widget.first_child();
+    while let Some(widget) = child {
+        if let Some(toolbar) = find_toolbar(&widget) {
+            return Some(toolbar);
+        }
+        child = widget.next_sibling();
+    }
+    None
+}
+
+fn show_dialog(gtk_window: &gtk::ApplicationWindow, summary: &str, details: &str) {
+    let dialog = adw::AlertDialog::new(Some(summary), Some(details));
+    dialog.add_responses(&[("copy", "Copy Details"), ("close", "Close")]);
+    dialog.set_default_response(Some("close"));
+    dialog.set_close_response("close");
+    let details = details.to_owned();
+    dialog.connect_response(Some("copy"), move |dialog, _| {
+        dialog.clipboard().set_text(&details);
+    });
+    dialog.present(Some(gtk_window));
+}
@@ -0,0 +1,179 @@
+use {
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_platform::collections::HashMap,
+    gio::prelude::*,
+    log::{debug, warn},
+    std::path::PathBuf,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let (tx_change, rx_change) = async_channel::unbounded();
+    app.add_event::<GtkFileChanged>()
+        .insert_resource(FileChangeChannel {
+            tx_change,
+            rx_change,
+        })
+        .init_non_send_resource::<FileMonitors>()
+        .add_systems(PreUpdate, (sync_monitors, forward_changes).chain());
+}
+
+/// Watches a file or directory for changes using [`gio::FileMonitor`], and
+/// sends a [`GtkFileChanged`] event for each change.
+///
+/// The monitor runs on the GLib main loop which the app is already running
+/// under, so unlike `notify`-based watchers, this doesn't need its own thread.
+/// This is useful for e.g. hot-reloading assets in an editor.
+///
+/// Spawn an entity with this component to start watching, and despawn it (or
+/// remove the component) to stop watching. Changing the component restarts the
+/// watch.
+///
+/// Watching a directory reports changes to its direct children, but not to
+/// anything deeper.
+#[derive(Debug, Clone, Component)]
+pub struct GtkFileWatcher {
+    /// Path of the file or directory to watch.
+    pub path: PathBuf,
+    /// Minimum time between [`GtkFileChangeKind::Changed`] events for the same
+    /// file, in milliseconds.
+    ///
+    /// By default, this is 800ms, which is GIO's own default.
+    pub rate_limit_ms: u32,
+}
+
+impl GtkFileWatcher {
+    /// Creates a watcher for the given path.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            rate_limit_ms: 800,
+        }
+    }
+}
+
+/// Emitted when a file watched by a [`GtkFileWatcher`] changes.
+#[derive(Debug, Clone, Event)]
+pub struct GtkFileChanged {
+    /// Entity of the [`GtkFileWatcher`].
+    pub watcher: Entity,
+    /// What kind of change happened.
+    pub kind: GtkFileChangeKind,
+    /// Path of the file which changed.
+    pub path: Option<PathBuf>,
+    /// For [`GtkFileChangeKind::Renamed`], [`GtkFileChangeKind::MovedIn`] and
+    /// [`GtkFileChangeKind::MovedOut`], the path of the file on the other side
+    /// of the move.
+    pub other_path: Option<PathBuf>,
+}
+
+/// Kind of change reported in [`GtkFileChanged`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GtkFileChangeKind {
+    /// The file's contents changed.
+    ///
+    /// While a file is being written, this may be sent several times, followed
+    /// by [`GtkFileChangeKind::ChangesDone`].
+    Changed,
+    /// A series of [`GtkFileChangeKind::Changed`] events has finished.
+    ChangesDone,
+    /// The file was created.
+    Created,
+    /// The file was deleted.
+    Deleted,
+    /// The file's metadata, like its permissions, changed.
+    AttributeChanged,
+    /// The file was renamed within the watched directory.
+    Renamed,
+    /// The file was moved into the watched directory.
+    MovedIn,
+    /// The file was moved out of the watched directory.
+    MovedOut,
+    /// The file system containing the file was unmounted.
+    Unmounted,
+}
+
+impl GtkFileChangeKind {
+    fn from_gio(event: gio::FileMonitorEvent) -> Option<Self> {
+        match event {
+            gio::FileMonitorEvent::Changed => Some(Self::Changed),
+            gio::FileMonitorEvent::ChangesDoneHint => Some(Self::ChangesDone),
+            gio::FileMonitorEvent::Created => Some(Self::Created),
+            gio::FileMonitorEvent::Deleted => Some(Self::Deleted),
+            gio::FileMonitorEvent::AttributeChanged => Some(Self::AttributeChanged),
+            gio::FileMonitorEvent::Renamed => Some(Self::Renamed),
+            gio::FileMonitorEvent::MovedIn => Some(Self::MovedIn),
+            gio::FileMonitorEvent::MovedOut => Some(Self::MovedOut),
+            gio::FileMonitorEvent::Unmounted => Some(Self::Unmounted),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Resource)]
+struct FileChangeChannel {
+    tx_change: async_channel::Sender<GtkFileChanged>,
+    rx_change: async_channel::Receiver<GtkFileChanged>,
+}
+
+#[derive(Debug, Default)]
+struct FileMonitors(HashMap<Entity, gio::FileMonitor>);
+
+fn sync_monitors(
+    changed: Query<(Entity, &GtkFileWatcher), Changed<GtkFileWatcher>>,
+    mut removed: RemovedComponents<GtkFileWatcher>,
+    mut monitors: NonSendMut<FileMonitors>,
+    channel: Res<FileChangeChannel>,
+) {
+    for watcher in removed.read() {
+        if let Some(monitor) = monitors.0.remove(&watcher) {
+            debug!("Stopped file watcher {watcher}");
+            monitor.cancel();
+        }
+    }
+
+    for (watcher, config) in &changed {
+        if let Some(old) = monitors.0.remove(&watcher) {
+            old.cancel();
+        }
+
+        let file = gio::File::for_path(&config.path);
+        let monitor = match file.monitor(
+            gio::FileMonitorFlags::WATCH_MOVES,
+            None::<&gio::Cancellable>,
+        ) {
+            Ok(monitor) => monitor,
+            Err(err) => {
+                warn!("Failed to watch {}: {err}", config.path.display());
+                continue;
+            }
+        };
+        monitor.set_rate_limit(i32::try_from(config.rate_limit_ms).unwrap_or(i32::MAX));
+
+        let tx_change = channel.tx_change.clone();
+        monitor.connect_changed(move |_, file, other_file, event| {
+            let Some(kind) = GtkFileChangeKind::from_gio(event) else {
+                return;
+            };
+            _ = tx_change.try_send(GtkFileChanged {
+                watcher,
+                kind,
+                path: file.path(),
+                other_path: other_file.and_then(FileExt::path),
+            });
+        });
+
+        debug!(
+            "Started file watcher {watcher} on {}",
+            config.path.display()
+        );
+        monitors.0.insert(watcher, monitor);
+    }
+}
+
+fn forward_changes(channel: Res<FileChangeChannel>, mut changes: EventWriter<GtkFileChanged>) {
+    while let Ok(change) = channel.rx_change.try_recv() {
+        changes.write(change);
+    }
+}
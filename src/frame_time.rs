@@ -0,0 +1,99 @@
+use {
+    crate::GtkWindows,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_time::{TimeSystems, TimeUpdateStrategy},
+    core::time::Duration,
+    gtk::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GtkFrameTime>()
+        .add_systems(First, update_frame_time.before(TimeSystems));
+}
+
+/// Timing information from the GDK frame clock of the [`PrimaryWindow`].
+///
+/// GTK schedules its frames against the compositor, and knows roughly when
+/// the frame currently being drawn will be shown on screen. Animating against
+/// these times instead of the wall clock reduces judder, since the time that
+/// Bevy renders a frame is not necessarily the time that it's presented.
+///
+/// This is updated at the start of every Bevy update, in [`First`]. To drive
+/// Bevy's [`Time`](bevy_time::Time) from this as well, enable
+/// [`GtkPlugin::frame_clock_time`](crate::GtkPlugin::frame_clock_time).
+///
+/// [`PrimaryWindow`]: bevy_window::PrimaryWindow
+#[derive(Debug, Clone, Default, Resource)]
+pub struct GtkFrameTime {
+    /// Time of the frame clock's current frame.
+    ///
+    /// This is a monotonic timestamp, only useful for comparing against other
+    /// timestamps from the frame clock.
+    pub frame_time: Duration,
+    /// When the compositor predicts that the current frame will be presented,
+    /// if known.
+    pub predicted_presentation_time: Option<Duration>,
+    /// Refresh interval of the monitor that the window is on, if known.
+    pub refresh_interval: Option<Duration>,
+    /// Time between the presentation of the previous frame and the current
+    /// frame, or between their frame times if presentation times are unknown.
+    ///
+    /// This is zero if the frame clock hasn't advanced since the last Bevy
+    /// update.
+    pub delta: Duration,
+}
+
+/// Whether [`update_frame_time`] drives Bevy's [`Time`](bevy_time::Time).
+#[derive(Debug, Resource)]
+pub(crate) struct DriveTimeFromFrameClock(pub bool);
+
+#[expect(
+    clippy::cast_sign_loss,
+    reason = "frame clock timestamps are non-negative"
+)]
+fn micros(value: i64) -> Option<Duration> {
+    (value > 0).then(|| Duration::from_micros(value as u64))
+}
+
+fn update_frame_time(
+    gtk_windows: NonSend<GtkWindows>,
+    drive_time: Option<Res<DriveTimeFromFrameClock>>,
+    mut frame_time: ResMut<GtkFrameTime>,
+    mut time_update_strategy: Option<ResMut<TimeUpdateStrategy>>,
+    mut last_present_time: Local<Option<Duration>>,
+) {
+    let Some(frame_clock) = gtk_windows
+        .primary()
+        .and_then(|proxy| proxy.gtk_window.frame_clock())
+    else {
+        return;
+    };
+
+    let timings = frame_clock.current_timings();
+    let predicted_presentation_time = timings
+        .as_ref()
+        .and_then(|timings| micros(timings.predicted_presentation_time()));
+    let refresh_interval = timings
+        .as_ref()
+        .and_then(|timings| micros(timings.refresh_interval()));
+    let clock_frame_time = micros(frame_clock.frame_time()).unwrap_or_default();
+
+    let present_time = predicted_presentation_time.unwrap_or(clock_frame_time);
+    let delta = last_present_time
+        .replace(present_time)
+        .map_or(Duration::ZERO, |last| present_time.saturating_sub(last));
+
+    *frame_time = GtkFrameTime {
+        frame_time: clock_frame_time,
+        predicted_presentation_time,
+        refresh_interval,
+        delta,
+    };
+
+    if drive_time.is_some_and(|drive_time| drive_time.0) {
+        if let Some(strategy) = &mut time_update_strategy {
+            **strategy = TimeUpdateStrategy::ManualDuration(delta);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use {bevy_app::prelude::*, bevy_gilrs::GilrsPlugin};
+
+/// Provides gamepad input to an app running under [`GtkPlugin`], using
+/// [`gilrs`](bevy_gilrs).
+///
+/// Gamepad handling in Bevy doesn't depend on `WinitPlugin`, but it's easy to
+/// lose track of it when assembling plugins manually for a GTK app. This
+/// plugin adds [`GilrsPlugin`] if it isn't already added (e.g. by
+/// `DefaultPlugins`).
+///
+/// Gamepads are polled in [`PreUpdate`], which the GTK runner runs once per
+/// iteration of the GLib main loop, so gamepad events are handled at the same
+/// rate as under the winit runner.
+///
+/// [`GtkPlugin`]: crate::GtkPlugin
+pub struct GtkGilrsPlugin;
+
+impl Plugin for GtkGilrsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<GilrsPlugin>() {
+            app.add_plugins(GilrsPlugin);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use {bevy_app::prelude::*, bevy_ecs::prelude::*};
+
+/// When a [`GtkRunnerHooks`] hook runs, relative to the Bevy app update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GtkRunnerStage {
+    /// Runs on every iteration of the GTK runner, before the Bevy app is
+    /// updated.
+    BeforeUpdate,
+    /// Runs on every iteration of the GTK runner, after the Bevy app is
+    /// updated, and before control is returned to the GLib main loop.
+    ///
+    /// This is the equivalent of winit's `about_to_wait`.
+    AboutToWait,
+}
+
+/// Code which runs on the GTK thread on every iteration of the GTK runner.
+///
+/// This allows third-party integrations which would normally hook into winit's
+/// event loop to run code under [`GtkPlugin`] as well.
+///
+/// This is a non-send resource, so hooks may capture GTK objects. Use
+/// [`GtkAppExt::add_gtk_runner_hook`] to register a hook.
+///
+/// [`GtkPlugin`]: crate::GtkPlugin
+#[derive(Default)]
+pub struct GtkRunnerHooks {
+    hooks: Vec<RunnerHook>,
+}
+
+struct RunnerHook {
+    stage: GtkRunnerStage,
+    order: i32,
+    run: Box<dyn FnMut(&mut World)>,
+}
+
+impl GtkRunnerHooks {
+    /// Registers a hook which runs at `stage`.
+    ///
+    /// Hooks in the same stage run in ascending `order`. Hooks with the same
+    /// `order` run in the order they were added.
+    pub fn add(
+        &mut self,
+        stage: GtkRunnerStage,
+        order: i32,
+        hook: impl FnMut(&mut World) + 'static,
+    ) {
+        self.insert(RunnerHook {
+            stage,
+            order,
+            run: Box::new(hook),
+        });
+    }
+
+    fn insert(&mut self, hook: RunnerHook) {
+        let index = self
+            .hooks
+            .partition_point(|other| other.order <= hook.order);
+        self.hooks.insert(index, hook);
+    }
+
+    pub(crate) fn run(&mut self, stage: GtkRunnerStage, world: &mut World) {
+        for hook in &mut self.hooks {
+            if hook.stage == stage {
+                (hook.run)(world);
+            }
+        }
+    }
+}
+
+impl core::fmt::Debug for GtkRunnerHooks {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GtkRunnerHooks")
+            .field("len", &self.hooks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Code which runs on the GTK thread whenever a GTK window is created for a
+/// Bevy [`Window`](bevy_window::Window).
+///
+/// Hooks run right after the window is constructed, before it's configured
+/// from the [`Window`](bevy_window::Window) and presented, and receive the
+/// window entity and the GTK window. Use them for customizations which the
+/// [`Window`](bevy_window::Window) component can't express, e.g. adding event
+/// controllers or CSS classes. This includes windows created through
+/// [`GtkAdoptedWindow`](crate::GtkAdoptedWindow).
+///
+/// This is a non-send resource, so hooks may capture GTK objects. Use
+/// [`GtkAppExt::add_gtk_window_hook`] to register a hook.
+#[derive(Default)]
+pub struct GtkWindowHooks {
+    hooks: Vec<Box<dyn FnMut(Entity, &gtk::ApplicationWindow)>>,
+}
+
+impl GtkWindowHooks {
+    /// Registers a hook, which runs after all previously registered hooks.
+    pub fn add(&mut self, hook: impl FnMut(Entity, &gtk::ApplicationWindow) + 'static) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    pub(crate) fn run(&mut self, entity: Entity, gtk_window: &gtk::ApplicationWindow) {
+        for hook in &mut self.hooks {
+            hook(entity, gtk_window);
+        }
+    }
+}
+
+impl core::fmt::Debug for GtkWindowHooks {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GtkWindowHooks")
+            .field("len", &self.hooks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Extension trait for registering GTK-specific app logic.
+pub trait GtkAppExt {
+    /// Registers a [`GtkRunnerHooks`] hook.
+    fn add_gtk_runner_hook(
+        &mut self,
+        stage: GtkRunnerStage,
+        order: i32,
+        hook: impl FnMut(&mut World) + 'static,
+    ) -> &mut Self;
+
+    /// Registers a [`GtkWindowHooks`] hook.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// app.add_gtk_window_hook(|_, gtk_window| {
+    ///     gtk_window.add_css_class("devel");
+    /// });
+    /// ```
+    fn add_gtk_window_hook(
+        &mut self,
+        hook: impl FnMut(Entity, &gtk::ApplicationWindow) + 'static,
+    ) -> &mut Self;
+}
+
+impl GtkAppExt for App {
+    fn add_gtk_runner_hook(
+        &mut self,
+        stage: GtkRunnerStage,
+        order: i32,
+        hook: impl FnMut(&mut World) + 'static,
+    ) -> &mut Self {
+        let world = self.world_mut();
+        world.init_non_send_resource::<GtkRunnerHooks>();
+        world
+            .non_send_resource_mut::<GtkRunnerHooks>()
+            .add(stage, order, hook);
+        self
+    }
+
+    fn add_gtk_window_hook(
+        &mut self,
+        hook: impl FnMut(Entity, &gtk::ApplicationWindow) + 'static,
+    ) -> &mut Self {
+        let world = self.world_mut();
+        world.init_non_send_resource::<GtkWindowHooks>();
+        world.non_send_resource_mut::<GtkWindowHooks>().add(hook);
+        self
+    }
+}
+
+pub(crate) fn run_hooks(stage: GtkRunnerStage, world: &mut World) {
+    // temporarily take the hooks out, so that they can access the world
+    let Some(mut hooks) = world.remove_non_send_resource::<GtkRunnerHooks>() else {
+        return;
+    };
+    hooks.run(stage, world);
+    // hooks may have registered more hooks while they were running
+    if let Some(added) = world.remove_non_send_resource::<GtkRunnerHooks>() {
+        for hook in added.hooks {
+            hooks.insert(hook);
+        }
+    }
+    world.insert_non_send_resource(hooks);
+}
@@ -0,0 +1,162 @@
+use {
+    crate::{GtkApplication, GtkSystems, GtkWindows},
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_platform::collections::HashMap,
+    bevy_window::WindowClosed,
+    gtk::prelude::*,
+    log::{debug, warn},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GtkInhibitor>()
+        .add_systems(Last, apply_inhibitions.after(GtkSystems::SyncWindows));
+}
+
+/// Prevents the session from suspending, the screen from blanking, or the
+/// user from logging out, e.g. during gameplay or a long render.
+///
+/// This uses [`gtk::Application::inhibit`], so the desktop decides how to
+/// honour the request, and may show the reason to the user. Inhibitions tied
+/// to a window are released automatically when that window closes.
+///
+/// # Examples
+///
+/// ```ignore
+/// fn start_render(mut inhibitor: ResMut<GtkInhibitor>, window: Single<Entity, With<PrimaryWindow>>) {
+///     let inhibition = inhibitor.inhibit(
+///         Some(*window),
+///         gtk::ApplicationInhibitFlags::SUSPEND | gtk::ApplicationInhibitFlags::IDLE,
+///         "Rendering",
+///     );
+///     // later..
+///     inhibitor.uninhibit(inhibition);
+/// }
+/// ```
+#[derive(Debug, Default, Resource)]
+pub struct GtkInhibitor {
+    next_id: u64,
+    active: HashMap<InhibitionId, Inhibition>,
+    pending: Vec<InhibitRequest>,
+}
+
+/// Identifies an inhibition made with [`GtkInhibitor::inhibit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InhibitionId(u64);
+
+/// Inhibition made with [`GtkInhibitor::inhibit`].
+#[derive(Debug, Clone)]
+pub struct Inhibition {
+    /// Window which the inhibition is tied to.
+    pub window: Option<Entity>,
+    /// What is being inhibited.
+    pub flags: gtk::ApplicationInhibitFlags,
+    /// User-facing reason for the inhibition.
+    pub reason: String,
+}
+
+#[derive(Debug)]
+enum InhibitRequest {
+    Inhibit(InhibitionId),
+    Uninhibit(InhibitionId),
+}
+
+impl GtkInhibitor {
+    /// Starts inhibiting `flags`, until [`GtkInhibitor::uninhibit`] is called,
+    /// or `window` is closed.
+    ///
+    /// `reason` is a short, user-facing description, like "Playing a game".
+    pub fn inhibit(
+        &mut self,
+        window: Option<Entity>,
+        flags: gtk::ApplicationInhibitFlags,
+        reason: impl Into<String>,
+    ) -> InhibitionId {
+        let id = InhibitionId(self.next_id);
+        self.next_id += 1;
+        self.active.insert(
+            id,
+            Inhibition {
+                window,
+                flags,
+                reason: reason.into(),
+            },
+        );
+        self.pending.push(InhibitRequest::Inhibit(id));
+        id
+    }
+
+    /// Stops an inhibition.
+    ///
+    /// Does nothing if the inhibition has already been released.
+    pub fn uninhibit(&mut self, id: InhibitionId) {
+        if self.active.remove(&id).is_some() {
+            self.pending.push(InhibitRequest::Uninhibit(id));
+        }
+    }
+
+    /// Gets an inhibition, if it hasn't been released yet.
+    #[must_use]
+    pub fn get(&self, id: InhibitionId) -> Option<&Inhibition> {
+        self.active.get(&id)
+    }
+
+    /// Iterates over all inhibitions which haven't been released yet.
+    pub fn iter(&self) -> impl Iterator<Item = (InhibitionId, &Inhibition)> {
+        self.active.iter().map(|(id, inhibition)| (*id, inhibition))
+    }
+}
+
+fn apply_inhibitions(
+    mut inhibitor: ResMut<GtkInhibitor>,
+    mut closed: EventReader<WindowClosed>,
+    gtk_app: NonSend<GtkApplication>,
+    gtk_windows: NonSend<GtkWindows>,
+    mut cookies: Local<HashMap<InhibitionId, u32>>,
+) {
+    let inhibitor = &mut *inhibitor;
+    for event in closed.read() {
+        let released = inhibitor
+            .active
+            .iter()
+            .filter(|(_, inhibition)| inhibition.window == Some(event.window))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in released {
+            debug!("Releasing inhibition {id:?} since its window was closed");
+            inhibitor.uninhibit(id);
+        }
+    }
+
+    for request in inhibitor.pending.drain(..) {
+        match request {
+            InhibitRequest::Inhibit(id) => {
+                // this may have been released already in the same frame
+                let Some(inhibition) = inhibitor.active.get(&id) else {
+                    continue;
+                };
+                let gtk_window = inhibition
+                    .window
+                    .and_then(|window| gtk_windows.get(window))
+                    .map(|proxy| &proxy.gtk_window);
+                let cookie =
+                    gtk_app.inhibit(gtk_window, inhibition.flags, Some(&inhibition.reason));
+                if cookie == 0 {
+                    warn!(
+                        "Failed to inhibit {:?}: {}",
+                        inhibition.flags, inhibition.reason
+                    );
+                    inhibitor.active.remove(&id);
+                } else {
+                    debug!("Inhibited {:?}: {}", inhibition.flags, inhibition.reason);
+                    cookies.insert(id, cookie);
+                }
+            }
+            InhibitRequest::Uninhibit(id) => {
+                if let Some(cookie) = cookies.remove(&id) {
+                    gtk_app.uninhibit(cookie);
+                }
+            }
+        }
+    }
+}
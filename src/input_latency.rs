@@ -0,0 +1,196 @@
+use {
+    crate::{GtkSystems, GtkWindows},
+    bevy_app::prelude::*,
+    bevy_diagnostic::{
+        DEFAULT_MAX_HISTORY_LENGTH, Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic,
+    },
+    bevy_ecs::prelude::*,
+    bevy_window::Window,
+    core::time::Duration,
+    gtk::prelude::*,
+};
+
+/// Adds a diagnostic measuring how long it takes from the user pressing a key
+/// or button, to the first frame drawn after Bevy handled that input being
+/// shown on screen.
+///
+/// This is the latency that users feel when e.g. clicking in a viewport, and
+/// includes time spent waiting for GTK to dispatch the event, for Bevy to
+/// update and render, and for GTK and the compositor to present the frame. Use
+/// `LogDiagnosticsPlugin` to print it, or read it from
+/// [`DiagnosticsStore`](bevy_diagnostic::DiagnosticsStore) under
+/// [`GtkInputLatencyDiagnosticsPlugin::INPUT_LATENCY`].
+///
+/// Inputs are timestamped with their GDK event time, and the response with
+/// the presentation time reported by the window's frame clock, or the time
+/// that GTK drew the frame if the compositor doesn't report presentation
+/// times. While a sample is being measured, further inputs are ignored.
+///
+/// GDK event times come from the display server. On Wayland, these use the
+/// same monotonic clock as the frame clock, but on X11 they may not, in which
+/// case samples which are implausibly large are discarded.
+#[derive(Debug, Clone)]
+pub struct GtkInputLatencyDiagnosticsPlugin {
+    /// Number of samples to keep for averaging.
+    pub max_history_length: usize,
+}
+
+impl Default for GtkInputLatencyDiagnosticsPlugin {
+    fn default() -> Self {
+        Self {
+            max_history_length: DEFAULT_MAX_HISTORY_LENGTH,
+        }
+    }
+}
+
+impl GtkInputLatencyDiagnosticsPlugin {
+    /// Time from input to the response being presented, in milliseconds.
+    pub const INPUT_LATENCY: DiagnosticPath = DiagnosticPath::const_new("gtk/input_latency");
+
+    /// Samples longer than this are assumed to compare timestamps from
+    /// different clocks, and are discarded.
+    pub const MAX_PLAUSIBLE_LATENCY: Duration = Duration::from_secs(10);
+}
+
+impl Plugin for GtkInputLatencyDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        let (tx, rx) = async_channel::unbounded();
+        app.register_diagnostic(
+            Diagnostic::new(Self::INPUT_LATENCY)
+                .with_suffix("ms")
+                .with_max_history_length(self.max_history_length),
+        )
+        .insert_resource(InputTimes { tx, rx })
+        .add_systems(PreUpdate, measure_latency)
+        .add_systems(Last, watch_input.after(GtkSystems::SyncWindows));
+    }
+}
+
+#[derive(Debug, Resource)]
+struct InputTimes {
+    tx: async_channel::Sender<InputTime>,
+    rx: async_channel::Receiver<InputTime>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct InputTime {
+    window: Entity,
+    /// GDK event time, in milliseconds.
+    time: u32,
+}
+
+/// Input which Bevy has received, waiting for the frame which responds to it.
+#[derive(Debug, Clone, Copy)]
+struct PendingInput {
+    input: InputTime,
+    /// Frame clock counter of the window when Bevy received the input.
+    frame_counter: i64,
+}
+
+fn watch_input(
+    new_windows: Query<Entity, Added<Window>>,
+    gtk_windows: NonSend<GtkWindows>,
+    input_times: Res<InputTimes>,
+) {
+    for window in &new_windows {
+        let Some(proxy) = gtk_windows.get(window) else {
+            continue;
+        };
+
+        // sees every event before any widget in the window handles it
+        let controller = gtk::EventControllerLegacy::new();
+        controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+        let tx = input_times.tx.clone();
+        controller.connect_event(move |_, event| {
+            if matches!(
+                event.event_type(),
+                gdk::EventType::ButtonPress
+                    | gdk::EventType::KeyPress
+                    | gdk::EventType::TouchBegin
+                    | gdk::EventType::Scroll
+            ) {
+                _ = tx.try_send(InputTime {
+                    window,
+                    time: event.time(),
+                });
+            }
+            glib::Propagation::Proceed
+        });
+        proxy.gtk_window.add_controller(controller);
+    }
+}
+
+fn measure_latency(
+    gtk_windows: NonSend<GtkWindows>,
+    input_times: Res<InputTimes>,
+    mut pending: Local<Option<PendingInput>>,
+    mut diagnostics: Diagnostics,
+) {
+    if let Some(PendingInput {
+        input,
+        frame_counter,
+    }) = *pending
+    {
+        let frame_clock = gtk_windows
+            .get(input.window)
+            .and_then(|proxy| proxy.gtk_window.frame_clock());
+        match frame_clock {
+            Some(frame_clock) => {
+                // the frame which Bevy rendered in response is picked up by
+                // GTK in the frame after the one in which Bevy got the input
+                let response_frame = frame_counter + 1;
+                if let Some(timings) = frame_clock.timings(response_frame) {
+                    if timings.is_complete() {
+                        let shown_at = if timings.presentation_time() > 0 {
+                            timings.presentation_time()
+                        } else {
+                            timings.frame_time()
+                        };
+                        if let Some(latency) = latency(input.time, shown_at) {
+                            diagnostics.add_measurement(
+                                &GtkInputLatencyDiagnosticsPlugin::INPUT_LATENCY,
+                                || latency.as_secs_f64() * 1000.0,
+                            );
+                        }
+                        *pending = None;
+                    }
+                } else if frame_clock.history_start() > response_frame {
+                    // the timings have already been dropped from the history
+                    *pending = None;
+                }
+            }
+            None => *pending = None,
+        }
+    }
+
+    while let Ok(input) = input_times.rx.try_recv() {
+        if pending.is_some() {
+            continue;
+        }
+        let Some(frame_clock) = gtk_windows
+            .get(input.window)
+            .and_then(|proxy| proxy.gtk_window.frame_clock())
+        else {
+            continue;
+        };
+        *pending = Some(PendingInput {
+            input,
+            frame_counter: frame_clock.frame_counter(),
+        });
+    }
+}
+
+/// Time from an event at `event_time` (in milliseconds, wrapping) until
+/// `shown_at` (in microseconds of the monotonic clock).
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "event times are the monotonic clock's milliseconds, truncated to `u32`"
+)]
+fn latency(event_time: u32, shown_at: i64) -> Option<Duration> {
+    let shown_at_millis = (shown_at / 1000) as u32;
+    let millis = shown_at_millis.wrapping_sub(event_time);
+    let latency =
+        Duration::from_millis(u64::from(millis)) + Duration::from_micros((shown_at % 1000) as u64);
+    (latency <= GtkInputLatencyDiagnosticsPlugin::MAX_PLAUSIBLE_LATENCY).then_some(latency)
+}
@@ -26,17 +26,102 @@ macro_rules! if_adw {
 use {
     alloc::rc::Rc,
     bevy_app::{PluginsState, prelude::*},
-    core::cell::{Cell, RefCell},
+    bevy_ecs::{prelude::*, schedule::ScheduleLabel},
+    core::{
+        any::Any,
+        cell::{Cell, RefCell},
+        panic::AssertUnwindSafe,
+    },
     derive_more::Deref,
     glib::clone,
     gtk::prelude::*,
-    log::debug,
+    log::{debug, error, warn},
+    std::{panic::catch_unwind, path::PathBuf},
 };
 
+mod activation;
+mod attention;
+mod capabilities;
+mod commands;
+mod cursor;
+mod file_watcher;
+mod frame_time;
+mod hooks;
+mod inhibit;
+mod input_latency;
+mod lifecycle;
+mod monitor;
+mod owned;
+mod progress;
+mod style;
+mod template;
+mod theme;
+mod widgets;
 mod window;
 #[cfg(feature = "adwaita")]
 pub use adw;
-pub use {gdk, gio, gtk, window::*};
+pub use {
+    activation::{GtkOpenFiles, GtkRemoteActivation},
+    attention::*,
+    capabilities::*,
+    commands::*,
+    cursor::*,
+    file_watcher::*,
+    frame_time::GtkFrameTime,
+    gdk, gio, gtk,
+    hooks::*,
+    inhibit::*,
+    input_latency::*,
+    lifecycle::GtkLifecycle,
+    monitor::GtkMonitorRefreshRate,
+    owned::GtkOwned,
+    progress::*,
+    style::*,
+    template::*,
+    theme::*,
+    widgets::*,
+    window::*,
+};
+
+#[cfg(feature = "adwaita")]
+mod breakpoint;
+#[cfg(feature = "adwaita")]
+pub use breakpoint::*;
+
+#[cfg(feature = "adwaita")]
+mod error_reporting;
+#[cfg(feature = "adwaita")]
+pub use error_reporting::*;
+
+#[cfg(feature = "adwaita")]
+mod sidebar;
+#[cfg(feature = "adwaita")]
+pub use sidebar::*;
+
+#[cfg(feature = "default-plugins")]
+mod default_plugins;
+#[cfg(feature = "default-plugins")]
+pub use default_plugins::*;
+
+#[cfg(feature = "gilrs")]
+mod gilrs;
+#[cfg(feature = "gilrs")]
+pub use gilrs::*;
+
+#[cfg(feature = "navigation")]
+mod navigation;
+#[cfg(feature = "navigation")]
+pub use navigation::*;
+
+#[cfg(feature = "portal")]
+mod portal;
+#[cfg(feature = "portal")]
+pub use portal::*;
+
+#[cfg(feature = "test-utils")]
+mod test_app;
+#[cfg(feature = "test-utils")]
+pub use test_app::*;
 
 #[cfg(feature = "viewport")]
 pub mod viewport;
@@ -50,6 +135,15 @@ pub use viewport::*;
 /// - **[`GtkInitPlugin`]**
 /// - `DefaultPlugins.build().disable::<WinitPlugin>()`
 /// - [`GtkPlugin`]
+///
+/// With the `default-plugins` feature, `GtkDefaultPlugins` adds all of these
+/// in the right order.
+///
+/// With the `viewport` feature, this also sets up how Bevy creates its render
+/// device, since the renderer is created as soon as `DefaultPlugins` is added.
+/// See [`GtkVulkanValidation`] to enable Vulkan validation. Which GPU Bevy
+/// renders on is set on the `RenderPlugin` instead, with
+/// [`GtkAdapterSelection`].
 pub struct GtkInitPlugin;
 
 impl Plugin for GtkInitPlugin {
@@ -65,11 +159,23 @@ impl Plugin for GtkInitPlugin {
 /// This replaces the [app runner](App::set_runner) and windowing backend, so
 /// make sure to disable `WinitPlugin` when adding this plugin.
 ///
+/// Gamepad input does not come from the windowing backend; enable the `gilrs`
+/// feature and add `GtkGilrsPlugin` to make sure it is handled.
+///
+/// Bevy's renderer is not required. With only `MinimalPlugins` and
+/// `WindowPlugin`, this plugin still manages GTK windows, so you can use Bevy
+/// for app logic and GTK for the UI. With the `viewport` feature, viewports
+/// are only set up if the app has a `RenderApp`; otherwise `GtkViewports`
+/// can't be used. See the `windows_only` example.
+///
 /// # Plugin ordering
 ///
 /// - [`GtkInitPlugin`]
 /// - `DefaultPlugins.build().disable::<WinitPlugin>()`
 /// - **[`GtkPlugin`]**
+///
+/// With the `default-plugins` feature, `GtkDefaultPlugins` adds all of these
+/// in the right order.
 #[derive(Default)]
 pub struct GtkPlugin {
     /// If the `adwaita` feature is enabled, determines whether [Adwaita](adw)
@@ -89,8 +195,87 @@ pub struct GtkPlugin {
     /// - `org.gnome.TextEditor`
     /// - `org.bevy.DemoApp`
     pub app_id: Option<String>,
+    /// Human-readable name of the application, passed into
+    /// [`glib::set_application_name`].
+    ///
+    /// Desktop environments and screen readers show this to users, e.g. in
+    /// the window switcher. If [`None`], GLib falls back to the program name.
+    pub app_name: Option<String>,
     /// Application flags, passed into [`gtk::Application::new`].
+    ///
+    /// By default, only one instance of the application runs at a time, and
+    /// launching it again activates the running instance instead (see
+    /// [`GtkRemoteActivation`]). To run several instances side by side, e.g.
+    /// two editors while developing, add
+    /// [`gio::ApplicationFlags::NON_UNIQUE`]. To open files, add
+    /// [`gio::ApplicationFlags::HANDLES_OPEN`] and see
+    /// [`GtkPlugin::open_files`].
     pub app_flags: gio::ApplicationFlags,
+    /// Files to open when the application starts, e.g. from your own command
+    /// line parsing.
+    ///
+    /// This requires [`gio::ApplicationFlags::HANDLES_OPEN`]. The files are
+    /// sent as a [`GtkOpenFiles`] event to this instance, or to the running
+    /// instance if one is already running, in which case this process exits.
+    pub open_files: Vec<PathBuf>,
+    /// Creates the application to run under, instead of creating one from
+    /// [`GtkPlugin::app_id`] and [`GtkPlugin::app_flags`].
+    ///
+    /// Use this if you already have GTK bootstrap code, e.g. an application
+    /// subclass or `startup` handlers. This is called once, on the main
+    /// thread, while the plugin is built. The application is then registered
+    /// and activated by the app runner as normal, and its `activate` handlers
+    /// run before [`GtkStartupSystems`].
+    ///
+    /// If the `adwaita` feature is enabled, Adwaita windows are created if
+    /// the application is an [`adw::Application`], regardless of
+    /// [`GtkPlugin::use_adw`].
+    ///
+    /// GTK only supports one application per process, so only one Bevy app
+    /// may use [`GtkPlugin`] at a time.
+    pub make_application: Option<Box<dyn Fn() -> gtk::Application + Send + Sync>>,
+    /// If the Bevy app panics while updating, whether to show an error dialog
+    /// with the panic message before quitting the application.
+    ///
+    /// Regardless of this setting, a panic will destroy all GTK windows, quit
+    /// the GTK application, and make the app exit with [`AppExit::error`].
+    pub show_panic_dialog: bool,
+    /// Whether Bevy's [`Time`](bevy_time::Time) is advanced by the GDK frame
+    /// clock's predicted presentation times, instead of the wall clock.
+    ///
+    /// This makes animations shown through viewports smoother, but time only
+    /// advances while the [`PrimaryWindow`](bevy_window::PrimaryWindow) is
+    /// being redrawn.
+    ///
+    /// See [`GtkFrameTime`].
+    pub frame_clock_time: bool,
+    /// Whether Bevy's fixed timestep is set to the refresh rate of the
+    /// monitor which the [`PrimaryWindow`](bevy_window::PrimaryWindow) is on,
+    /// and updated when the window moves to another monitor.
+    ///
+    /// See [`GtkMonitorRefreshRate`].
+    pub fixed_timestep_from_monitor: bool,
+    /// Compiled [`gio::Resource`] bundles to register before the GTK
+    /// application starts, e.g. from
+    /// `include_bytes!(concat!(env!("OUT_DIR"), "/app.gresource"))`.
+    ///
+    /// Once registered, [`GtkTemplate::from_resource`] and other resource
+    /// paths can load anything inside of these bundles.
+    pub resources: Vec<&'static [u8]>,
+    /// Base path that the GTK application loads its resources from, passed
+    /// into [`gio::Application::set_resource_base_path`].
+    ///
+    /// If [`None`], GIO derives this from [`GtkPlugin::app_id`], e.g.
+    /// `/org/bevy/DemoApp` for `org.bevy.DemoApp`. GTK automatically adds
+    /// `<base path>/icons` to the icon theme, and loads things like
+    /// `<base path>/gtk/help-overlay.ui` from here.
+    pub resource_base_path: Option<String>,
+    /// Extra [`gio::Resource`] paths that the icon theme looks for icons in,
+    /// passed into [`gtk::IconTheme::add_resource_path`].
+    pub icon_resource_paths: Vec<String>,
+    /// Extra directories that the icon theme looks for icons in, passed into
+    /// [`gtk::IconTheme::add_search_path`].
+    pub icon_search_paths: Vec<PathBuf>,
 }
 
 impl GtkPlugin {
@@ -103,7 +288,52 @@ impl GtkPlugin {
         Self {
             use_adw: if_adw!(true, false),
             app_id: Some(app_id.into()),
+            app_name: None,
             app_flags: gio::ApplicationFlags::empty(),
+            open_files: Vec::new(),
+            make_application: None,
+            show_panic_dialog: false,
+            frame_clock_time: false,
+            fixed_timestep_from_monitor: false,
+            resources: Vec::new(),
+            resource_base_path: None,
+            icon_resource_paths: Vec::new(),
+            icon_search_paths: Vec::new(),
+        }
+    }
+
+    /// Adds flags to [`GtkPlugin::app_flags`].
+    #[must_use]
+    pub fn with_app_flags(self, app_flags: gio::ApplicationFlags) -> Self {
+        Self {
+            app_flags: self.app_flags | app_flags,
+            ..self
+        }
+    }
+
+    /// Adds [`gio::ApplicationFlags::NON_UNIQUE`] to [`GtkPlugin::app_flags`],
+    /// so that several instances of the application can run at once.
+    #[must_use]
+    pub fn non_unique(self) -> Self {
+        self.with_app_flags(gio::ApplicationFlags::NON_UNIQUE)
+    }
+
+    /// Adds [`gio::ApplicationFlags::HANDLES_OPEN`] to
+    /// [`GtkPlugin::app_flags`], and sets [`GtkPlugin::open_files`].
+    #[must_use]
+    pub fn with_open_files(self, files: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        Self {
+            open_files: files.into_iter().map(Into::into).collect(),
+            ..self.with_app_flags(gio::ApplicationFlags::HANDLES_OPEN)
+        }
+    }
+
+    /// Sets [`GtkPlugin::app_name`].
+    #[must_use]
+    pub fn with_app_name(self, app_name: impl Into<String>) -> Self {
+        Self {
+            app_name: Some(app_name.into()),
+            ..self
         }
     }
 
@@ -124,62 +354,271 @@ impl GtkPlugin {
             ..self
         }
     }
+
+    /// Sets [`GtkPlugin::make_application`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// GtkPlugin::default().with_application(|| {
+    ///     let app = gtk::Application::new(Some("org.bevy.DemoApp"), default());
+    ///     app.connect_startup(|_| load_css());
+    ///     app
+    /// })
+    /// ```
+    #[must_use]
+    pub fn with_application<A: IsA<gtk::Application>>(
+        self,
+        make_application: impl Fn() -> A + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            make_application: Some(Box::new(move || make_application().upcast())),
+            ..self
+        }
+    }
+
+    /// Enables [`GtkPlugin::show_panic_dialog`].
+    #[must_use]
+    pub fn with_panic_dialog(self) -> Self {
+        Self {
+            show_panic_dialog: true,
+            ..self
+        }
+    }
+
+    /// Enables [`GtkPlugin::frame_clock_time`].
+    #[must_use]
+    pub fn with_frame_clock_time(self) -> Self {
+        Self {
+            frame_clock_time: true,
+            ..self
+        }
+    }
+
+    /// Enables [`GtkPlugin::fixed_timestep_from_monitor`].
+    #[must_use]
+    pub fn with_fixed_timestep_from_monitor(self) -> Self {
+        Self {
+            fixed_timestep_from_monitor: true,
+            ..self
+        }
+    }
+
+    /// Adds a compiled resource bundle to [`GtkPlugin::resources`].
+    #[must_use]
+    pub fn with_resources(mut self, data: &'static [u8]) -> Self {
+        self.resources.push(data);
+        self
+    }
+
+    /// Sets [`GtkPlugin::resource_base_path`].
+    #[must_use]
+    pub fn with_resource_base_path(self, path: impl Into<String>) -> Self {
+        Self {
+            resource_base_path: Some(path.into()),
+            ..self
+        }
+    }
+
+    /// Adds a path to [`GtkPlugin::icon_resource_paths`].
+    #[must_use]
+    pub fn with_icon_resource_path(mut self, path: impl Into<String>) -> Self {
+        self.icon_resource_paths.push(path.into());
+        self
+    }
+
+    /// Adds a directory to [`GtkPlugin::icon_search_paths`].
+    #[must_use]
+    pub fn with_icon_search_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.icon_search_paths.push(path.into());
+        self
+    }
+}
+
+/// System sets for systems added by [`GtkPlugin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub enum GtkSystems {
+    /// Creates, updates, and destroys GTK windows to match Bevy
+    /// [`Window`](bevy_window::Window)s.
+    ///
+    /// Runs in [`Last`].
+    SyncWindows,
+    /// Runs [`GtkCommand`]s queued via [`GtkCommands`].
+    ///
+    /// Runs in [`Last`], after [`GtkSystems::SyncWindows`].
+    ApplyCommands,
+}
+
+/// Schedule which runs once the GTK application has been activated, before
+/// the first Bevy update.
+///
+/// [`GtkPlugin`] doesn't start the GTK application while it's being built.
+/// Instead, the app runner registers and activates it, and only starts
+/// updating the Bevy app once GTK is ready. Systems which need GTK to be
+/// initialized before [`Startup`], e.g. to load CSS for the default display,
+/// can go in this schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ScheduleLabel)]
+pub struct GtkStartupSystems;
+
+/// Keeps the GTK application running while Bevy is managing its windows.
+///
+/// Releasing the hold must happen on the GTK thread, so this is a
+/// [`GtkOwned`] handle.
+#[derive(Debug, Resource)]
+struct AppHold {
+    _guard: GtkOwned<gio::ApplicationHoldGuard>,
+}
+
+#[derive(Debug, Resource)]
+struct IconPaths {
+    resource_paths: Vec<String>,
+    search_paths: Vec<PathBuf>,
+}
+
+fn add_icon_paths(paths: Res<IconPaths>) {
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+    let icon_theme = gtk::IconTheme::for_display(&display);
+    for path in &paths.resource_paths {
+        icon_theme.add_resource_path(path);
+    }
+    for path in &paths.search_paths {
+        icon_theme.add_search_path(path);
+    }
 }
 
 /// Stores a reference to the [`gtk::Application`] this app is running under.
 ///
 /// If [`GtkPlugin`] uses Adwaita, this will be an [`adw::Application`].
+///
+/// This is a non-send resource, so systems which take it run on the GTK
+/// thread. The app runner removes it, and destroys the remaining GTK windows,
+/// before the Bevy app is dropped, so that the world never outlives GTK
+/// objects which depend on the application.
 #[derive(Debug, Clone, Deref)]
 pub struct GtkApplication(pub gtk::Application);
 
 impl Plugin for GtkPlugin {
     fn build(&self, app: &mut App) {
-        assert!(
-            app.is_plugin_added::<GtkInitPlugin>(),
-            "add `GtkInitPlugin` before `GtkPlugin`"
-        );
+        if !app.is_plugin_added::<GtkInitPlugin>() {
+            error!(
+                "`GtkInitPlugin` was not added before `GtkPlugin`. Without it, Bevy's renderer is \
+                 created without the GPU selection and Vulkan extensions that viewports need, so \
+                 viewports will copy every frame through the CPU. It can't be added now, since \
+                 the renderer may already exist. Add plugins in this order:\n1. \
+                 `GtkInitPlugin`\n2. `DefaultPlugins.build().disable::<WinitPlugin>()`\n3. \
+                 `GtkPlugin`"
+            );
+        }
+
+        if !app.is_plugin_added::<bevy_window::WindowPlugin>() {
+            error!(
+                "`WindowPlugin` was not added before `GtkPlugin`, so no windows can be created. \
+                 Add it through `DefaultPlugins`, or alongside `MinimalPlugins` if you don't need \
+                 rendering."
+            );
+        }
 
         #[cfg(feature = "viewport")]
         viewport::plugin(app);
+        #[cfg(feature = "portal")]
+        portal::plugin(app);
+        #[cfg(feature = "adwaita")]
+        breakpoint::plugin(app);
+        #[cfg(feature = "adwaita")]
+        sidebar::plugin(app);
+
+        let (gtk_app, use_adw) = if let Some(make_application) = &self.make_application {
+            let gtk_app = make_application();
+            let use_adw = if_adw!(gtk_app.is::<adw::Application>(), false);
+            (gtk_app, use_adw)
+        } else {
+            let gtk_app = if_adw!(
+                self.use_adw,
+                adw::Application::new(self.app_id.as_deref(), self.app_flags)
+                    .upcast::<gtk::Application>(),
+                gtk::Application::new(self.app_id.as_deref(), self.app_flags),
+            );
+            (gtk_app, self.use_adw)
+        };
+        // on X11, window managers match windows by `WM_CLASS`, which GTK takes
+        // from the program name; use the application ID there too, so that
+        // windows are matched the same way as on Wayland
+        if glib::prgname().is_none() {
+            if let Some(app_id) = gtk_app.application_id() {
+                glib::set_prgname(Some(app_id));
+            }
+        }
+        if let Some(app_name) = &self.app_name {
+            glib::set_application_name(app_name);
+        }
 
-        let gtk_app = if_adw!(
-            self.use_adw,
-            adw::Application::new(self.app_id.as_deref(), self.app_flags)
-                .upcast::<gtk::Application>(),
-            gtk::Application::new(self.app_id.as_deref(), self.app_flags),
-        );
         // prevent app closing when there are no windows;
         // this becomes `bevy_window`'s responsibility
         let app_hold = gtk_app.hold();
 
-        let (tx_activated, rx_activated) = oneshot::channel::<()>();
-        let tx_activated = RefCell::new(Some(tx_activated));
-        gtk_app.connect_activate(move |_| {
-            if let Some(tx) = tx_activated.take() {
-                _ = tx.send(());
+        // resources must be registered before the app starts up,
+        // so that GTK can find the icons and UI files in them
+        for data in &self.resources {
+            match gio::Resource::from_data(&glib::Bytes::from_static(data)) {
+                Ok(resource) => gio::resources_register(&resource),
+                Err(err) => error!("Failed to load resource bundle: {err}"),
             }
-        });
+        }
+        if let Some(path) = &self.resource_base_path {
+            gtk_app.set_resource_base_path(Some(path.as_str()));
+        }
 
-        debug!("Registering GTK app");
-        gtk_app
-            .register(None::<&gio::Cancellable>)
-            .expect("failed to register GTK app");
-        debug!("Activating GTK app");
-        gtk_app.activate();
-        rx_activated
-            .recv()
-            .expect("channel dropped while activating GTK app");
-        debug!("App activated");
-
-        app.add_plugins(window::plugin)
-            .insert_non_send_resource(app_hold)
-            .insert_non_send_resource(GtkApplication(gtk_app.clone()))
-            .insert_non_send_resource(GtkWindows::new(self.use_adw))
-            .set_runner(|bevy_app| gtk_runner(bevy_app, gtk_app));
+        app.configure_sets(
+            Last,
+            (GtkSystems::SyncWindows, GtkSystems::ApplyCommands).chain(),
+        )
+        .add_plugins((
+            window::plugin,
+            commands::plugin,
+            attention::plugin,
+            cursor::plugin,
+            capabilities::plugin,
+            theme::plugin,
+            frame_time::plugin,
+            progress::plugin,
+            inhibit::plugin,
+            file_watcher::plugin,
+            lifecycle::plugin,
+            activation::plugin,
+            style::plugin,
+            monitor::plugin,
+        ))
+        .init_schedule(GtkStartupSystems)
+        .add_systems(GtkStartupSystems, add_icon_paths)
+        .insert_resource(IconPaths {
+            resource_paths: self.icon_resource_paths.clone(),
+            search_paths: self.icon_search_paths.clone(),
+        })
+        .insert_resource(frame_time::DriveTimeFromFrameClock(self.frame_clock_time))
+        .insert_resource(monitor::FixedTimestepFromMonitor(
+            self.fixed_timestep_from_monitor,
+        ))
+        .insert_resource(AppHold {
+            _guard: GtkOwned::new(app_hold),
+        })
+        .insert_non_send_resource(GtkApplication(gtk_app.clone()))
+        .insert_non_send_resource(GtkWindows::new(use_adw))
+        .set_runner({
+            let show_panic_dialog = self.show_panic_dialog;
+            let open_files = self.open_files.clone();
+            move |bevy_app| gtk_runner(bevy_app, gtk_app, show_panic_dialog, open_files)
+        });
     }
 }
 
-fn gtk_runner(mut bevy_app: App, gtk_app: gtk::Application) -> AppExit {
+fn gtk_runner(
+    mut bevy_app: App,
+    gtk_app: gtk::Application,
+    show_panic_dialog: bool,
+    open_files: Vec<PathBuf>,
+) -> AppExit {
     if bevy_app.plugins_state() == PluginsState::Ready {
         bevy_app.finish();
         bevy_app.cleanup();
@@ -187,32 +626,220 @@ fn gtk_runner(mut bevy_app: App, gtk_app: gtk::Application) -> AppExit {
 
     debug!("Starting GTK app");
 
+    let tx_activation = bevy_app
+        .world()
+        .resource::<activation::ActivationChannel>()
+        .tx
+        .clone();
+    let bevy_app = Rc::new(RefCell::new(bevy_app));
     let bevy_exit = Rc::new(Cell::new(None::<AppExit>));
-    glib::idle_add_local(clone!(
+    let update_source = Rc::new(Cell::new(None::<glib::SourceId>));
+    let activated = Cell::new(false);
+    // Bevy only starts updating once GTK is ready to create windows;
+    // returns whether this is the first activation
+    let activate = Rc::new(clone!(
+        #[strong]
+        bevy_app,
         #[strong]
         bevy_exit,
-        move || {
-            if let Some(exit) = idle_update(&mut bevy_app) {
-                bevy_exit.set(Some(exit));
-                glib::ControlFlow::Break
-            } else {
-                glib::ControlFlow::Continue
+        #[strong]
+        update_source,
+        move |gtk_app: &gtk::Application| {
+            if activated.replace(true) {
+                return false;
+            }
+            debug!("App activated");
+
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                bevy_app
+                    .borrow_mut()
+                    .world_mut()
+                    .run_schedule(GtkStartupSystems);
+            }));
+            if let Err(payload) = result {
+                bevy_exit.set(Some(AppExit::error()));
+                handle_panic(
+                    &mut bevy_app.borrow_mut(),
+                    gtk_app,
+                    &*payload,
+                    show_panic_dialog,
+                );
+                return true;
+            }
+
+            let source = start_updating(
+                bevy_app.clone(),
+                bevy_exit.clone(),
+                update_source.clone(),
+                gtk_app.clone(),
+                show_panic_dialog,
+            );
+            update_source.set(Some(source));
+            true
+        }
+    ));
+    let activate_handler = gtk_app.connect_activate(clone!(
+        #[strong]
+        activate,
+        #[strong]
+        tx_activation,
+        move |gtk_app| {
+            // another instance was launched, which activates this one
+            if !activate(gtk_app) {
+                _ = tx_activation.try_send(activation::Activation::Remote);
             }
         }
     ));
+    // GIO sends `open` instead of `activate` when there are files to open
+    let open_handler = gtk_app.connect_open(clone!(
+        #[strong]
+        activate,
+        move |gtk_app, files, hint| {
+            _ = tx_activation.try_send(activation::Activation::Open {
+                uris: files.iter().map(|file| file.uri().into()).collect(),
+                hint: hint.to_owned(),
+            });
+            activate(gtk_app);
+        }
+    ));
 
-    // don't handle CLI args, since that's Bevy's job
-    let gtk_exit = gtk_app.run_with_args::<&str>(&[]);
+    // don't handle CLI args, since that's Bevy's job;
+    // we only pass the files to open, so that GIO forwards them to the running
+    // instance if there is one
+    let mut args = Vec::new();
+    if !open_files.is_empty() {
+        if gtk_app
+            .flags()
+            .contains(gio::ApplicationFlags::HANDLES_OPEN)
+        {
+            let program = std::env::args().next().unwrap_or_default();
+            args.extend([program, "--".to_owned()]);
+            args.extend(
+                open_files
+                    .iter()
+                    .map(|path| path.to_string_lossy().into_owned()),
+            );
+        } else {
+            warn!(
+                "`GtkPlugin::open_files` is set, but the application doesn't have \
+                 `ApplicationFlags::HANDLES_OPEN`, so the files won't be opened"
+            );
+        }
+    }
+    let gtk_exit = gtk_app.run_with_args(&args);
     debug!("GTK app exited with code {gtk_exit:?}");
-    bevy_exit
-        .take()
-        .unwrap_or_else(|| AppExit::from_code(gtk_exit.get()))
+    let exit = bevy_exit.take().unwrap_or_else(|| {
+        // GTK shut down without Bevy asking it to,
+        // so give the app a chance to save its state
+        lifecycle::shut_down(&mut bevy_app.borrow_mut());
+        AppExit::from_code(gtk_exit.get())
+    });
+
+    // the signal handlers, our own `activate`, and the update loop all keep
+    // the Bevy app alive, so we remove them to drop the app here, while we're
+    // still on the GTK thread and GTK is still initialized
+    gtk_app.disconnect(activate_handler);
+    gtk_app.disconnect(open_handler);
+    drop(activate);
+    if let Some(source) = update_source.take() {
+        source.remove();
+    }
+    tear_down(bevy_app);
+    exit
+}
+
+fn tear_down(bevy_app: Rc<RefCell<App>>) {
+    debug!("Tearing down GTK objects");
+    if let Ok(bevy_app) = Rc::try_unwrap(bevy_app) {
+        let mut bevy_app = bevy_app.into_inner();
+        destroy_windows(bevy_app.world_mut());
+        bevy_app
+            .world_mut()
+            .remove_non_send_resource::<GtkApplication>();
+        drop(bevy_app);
+    } else {
+        warn!("Bevy app is still referenced after GTK exited, so it won't be dropped");
+    }
+    owned::tear_down();
+}
+
+fn destroy_windows(world: &mut World) {
+    if let Some(gtk_windows) = world.remove_non_send_resource::<GtkWindows>() {
+        for (_, proxy) in gtk_windows.iter() {
+            proxy.gtk_window.destroy();
+        }
+    }
+}
+
+fn start_updating(
+    bevy_app: Rc<RefCell<App>>,
+    bevy_exit: Rc<Cell<Option<AppExit>>>,
+    update_source: Rc<Cell<Option<glib::SourceId>>>,
+    gtk_app: gtk::Application,
+    show_panic_dialog: bool,
+) -> glib::SourceId {
+    glib::idle_add_local(move || {
+        let mut bevy_app = bevy_app.borrow_mut();
+        // if a panic unwinds into the GLib main loop, the windows are left
+        // frozen and the app never shuts down, so we catch it here
+        let result = catch_unwind(AssertUnwindSafe(|| idle_update(&mut bevy_app)));
+        match result {
+            Ok(None) => return glib::ControlFlow::Continue,
+            Ok(Some(exit)) => bevy_exit.set(Some(exit)),
+            Err(payload) => {
+                bevy_exit.set(Some(AppExit::error()));
+                handle_panic(&mut bevy_app, &gtk_app, &*payload, show_panic_dialog);
+            }
+        }
+        // GLib removes the source when we break, so it mustn't be removed again
+        drop(update_source.take());
+        glib::ControlFlow::Break
+    })
+}
+
+fn handle_panic(
+    bevy_app: &mut App,
+    gtk_app: &gtk::Application,
+    payload: &(dyn Any + Send),
+    show_panic_dialog: bool,
+) {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| (*s).to_owned())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "(unknown panic payload)".to_owned());
+    error!("Bevy app panicked while updating, shutting down GTK app: {message}");
+
+    destroy_windows(bevy_app.world_mut());
+
+    if show_panic_dialog {
+        let dialog = gtk::AlertDialog::builder()
+            .modal(true)
+            .message("The application has crashed")
+            .detail(message)
+            .buttons(["Close"])
+            .build();
+        dialog.choose(
+            None::<&gtk::Window>,
+            None::<&gio::Cancellable>,
+            clone!(
+                #[strong]
+                gtk_app,
+                move |_| gtk_app.quit()
+            ),
+        );
+    } else {
+        gtk_app.quit();
+    }
 }
 
 fn idle_update(bevy_app: &mut App) -> Option<AppExit> {
+    owned::release_dropped();
+    hooks::run_hooks(GtkRunnerStage::BeforeUpdate, bevy_app.world_mut());
     if bevy_app.plugins_state() == PluginsState::Cleaned {
         bevy_app.update();
     }
+    hooks::run_hooks(GtkRunnerStage::AboutToWait, bevy_app.world_mut());
 
     bevy_app.should_exit()
 }
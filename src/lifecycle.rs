@@ -0,0 +1,154 @@
+use {
+    crate::GtkApplication,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_window::AppLifecycle,
+    core::panic::AssertUnwindSafe,
+    derive_more::Deref,
+    glib::clone,
+    gtk::prelude::*,
+    log::{debug, error},
+    std::panic::catch_unwind,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GtkLifecycle>()
+        .add_systems(PreStartup, setup_lifecycle_forwarding)
+        .add_systems(PreUpdate, forward_lifecycle);
+}
+
+/// Current lifecycle state of the GTK application.
+///
+/// Whenever this changes, an [`AppLifecycle`] event with the new state is also
+/// sent, the same way that Bevy's own windowing backend does:
+/// - when all of the app's windows are suspended (e.g. minimized, or hidden by
+///   a mobile shell), or the display is closed, the app goes through
+///   [`AppLifecycle::WillSuspend`] and then [`AppLifecycle::Suspended`]
+/// - when any window is shown again, it goes through
+///   [`AppLifecycle::WillResume`] and then [`AppLifecycle::Running`]
+/// - when the GTK application shuts down by itself (e.g. the session is
+///   ending), one last update is run with [`AppLifecycle::WillSuspend`], and
+///   another with [`AppLifecycle::Suspended`], so you can save state before
+///   the process exits
+///
+/// Each state lasts for at least one update. While the app is suspended,
+/// viewports stop rendering and release their dmabufs, and recreate them once
+/// the app resumes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deref, Resource)]
+pub struct GtkLifecycle(AppLifecycle);
+
+impl Default for GtkLifecycle {
+    fn default() -> Self {
+        Self(AppLifecycle::Running)
+    }
+}
+
+impl GtkLifecycle {
+    /// Whether the app is suspended, and viewports don't render.
+    #[must_use]
+    pub fn is_suspended(&self) -> bool {
+        self.0 == AppLifecycle::Suspended
+    }
+}
+
+#[cfg(feature = "viewport")]
+impl bevy_render::extract_resource::ExtractResource for GtkLifecycle {
+    type Source = Self;
+
+    fn extract_resource(source: &Self::Source) -> Self {
+        *source
+    }
+}
+
+/// Sends whether the GTK side wants the app to be suspended.
+#[derive(Debug, Resource)]
+struct SuspendedChannel {
+    tx: async_channel::Sender<bool>,
+    rx: async_channel::Receiver<bool>,
+}
+
+// `NonSend` keeps this on the GTK thread
+fn setup_lifecycle_forwarding(gtk_app: NonSend<GtkApplication>, mut commands: Commands) {
+    let (tx_suspended, rx_suspended) = async_channel::unbounded();
+    commands.insert_resource(SuspendedChannel {
+        tx: tx_suspended.clone(),
+        rx: rx_suspended,
+    });
+
+    let send_suspended = clone!(
+        #[strong]
+        tx_suspended,
+        move |gtk_app: &gtk::Application| {
+            let windows = gtk_app.windows();
+            let suspended = !windows.is_empty() && windows.iter().all(GtkWindowExt::is_suspended);
+            _ = tx_suspended.try_send(suspended);
+        }
+    );
+    gtk_app.connect_window_added(clone!(
+        #[strong]
+        send_suspended,
+        move |gtk_app, window| {
+            window.connect_suspended_notify(clone!(
+                #[weak]
+                gtk_app,
+                #[strong]
+                send_suspended,
+                move |_| send_suspended(&gtk_app)
+            ));
+            send_suspended(gtk_app);
+        }
+    ));
+    gtk_app.connect_window_removed(move |gtk_app, _| send_suspended(gtk_app));
+
+    if let Some(display) = gdk::Display::default() {
+        display.connect_closed(move |_, is_error| {
+            debug!("GDK display closed (error: {is_error}), suspending app");
+            _ = tx_suspended.try_send(true);
+        });
+    }
+}
+
+fn forward_lifecycle(
+    channel: Res<SuspendedChannel>,
+    mut lifecycle: ResMut<GtkLifecycle>,
+    mut events: EventWriter<AppLifecycle>,
+    mut wants_suspend: Local<bool>,
+) {
+    while let Ok(suspended) = channel.rx.try_recv() {
+        *wants_suspend = suspended;
+    }
+
+    // each state lasts for at least one update, so the app can react to it
+    let next = match (**lifecycle, *wants_suspend) {
+        (AppLifecycle::WillSuspend, _) => AppLifecycle::Suspended,
+        (AppLifecycle::WillResume, _) => AppLifecycle::Running,
+        (AppLifecycle::Idle | AppLifecycle::Running, true) => AppLifecycle::WillSuspend,
+        (AppLifecycle::Suspended, false) => AppLifecycle::WillResume,
+        _ => return,
+    };
+    debug!("App lifecycle changed to {next:?}");
+    lifecycle.0 = next;
+    events.write(next);
+}
+
+/// Runs the last updates of the Bevy app after the GTK application has shut
+/// down by itself, so that it can react to being suspended.
+pub(super) fn shut_down(bevy_app: &mut App) {
+    let world = bevy_app.world();
+    let Some(channel) = world.get_resource::<SuspendedChannel>() else {
+        return;
+    };
+    if world.resource::<GtkLifecycle>().is_suspended() {
+        return;
+    }
+
+    debug!("GTK app shut down, running final updates");
+    _ = channel.tx.try_send(true);
+    // one update to go to `WillSuspend`, and one to go to `Suspended`
+    for _ in 0..2 {
+        if catch_unwind(AssertUnwindSafe(|| bevy_app.update())).is_err() {
+            error!("Bevy app panicked while shutting down");
+            return;
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use {
+    crate::{GtkSystems, GtkWindows},
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_time::{Fixed, Time},
+    bevy_window::{PrimaryWindow, Window},
+    gtk::prelude::*,
+    log::debug,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Last,
+        (update_refresh_rates, sync_fixed_timestep)
+            .chain()
+            .after(GtkSystems::SyncWindows),
+    );
+}
+
+/// Refresh rate of the monitor which a window is currently on.
+///
+/// This is inserted into every [`Window`] entity with a GTK window once it has
+/// been shown, and is updated when the window moves to another monitor. To
+/// run Bevy's fixed timestep at the refresh rate of the [`PrimaryWindow`]'s
+/// monitor, enable
+/// [`GtkPlugin::fixed_timestep_from_monitor`](crate::GtkPlugin::fixed_timestep_from_monitor).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Component)]
+pub struct GtkMonitorRefreshRate {
+    /// Refresh rate in Hz, or [`None`] if the window isn't on a monitor yet,
+    /// or the display server doesn't report refresh rates.
+    pub hz: Option<f64>,
+}
+
+/// Whether [`sync_fixed_timestep`] drives Bevy's [`Time<Fixed>`].
+#[derive(Debug, Resource)]
+pub(crate) struct FixedTimestepFromMonitor(pub bool);
+
+fn update_refresh_rates(
+    mut windows: Query<(Entity, Option<&mut GtkMonitorRefreshRate>), With<Window>>,
+    gtk_windows: NonSend<GtkWindows>,
+    mut commands: Commands,
+) {
+    for (entity, refresh_rate) in &mut windows {
+        let Some(surface) = gtk_windows
+            .get(entity)
+            .and_then(|proxy| proxy.gtk_window.surface())
+        else {
+            continue;
+        };
+        let monitor = surface.display().monitor_at_surface(&surface);
+        // GDK reports the refresh rate in mHz, or 0 if it's unknown
+        let hz = monitor
+            .map(|monitor| monitor.refresh_rate())
+            .filter(|millihertz| *millihertz > 0)
+            .map(|millihertz| f64::from(millihertz) / 1000.0);
+        let new = GtkMonitorRefreshRate { hz };
+
+        if let Some(mut refresh_rate) = refresh_rate {
+            if refresh_rate.set_if_neq(new) {
+                debug!("Window {entity} is now on a monitor with refresh rate {hz:?} Hz");
+            }
+        } else {
+            commands.entity(entity).insert(new);
+        }
+    }
+}
+
+fn sync_fixed_timestep(
+    enabled: Res<FixedTimestepFromMonitor>,
+    primary_window: Query<
+        &GtkMonitorRefreshRate,
+        (With<PrimaryWindow>, Changed<GtkMonitorRefreshRate>),
+    >,
+    fixed_time: Option<ResMut<Time<Fixed>>>,
+) {
+    if !enabled.0 {
+        return;
+    }
+    let (Ok(GtkMonitorRefreshRate { hz: Some(hz) }), Some(mut fixed_time)) =
+        (primary_window.single(), fixed_time)
+    else {
+        return;
+    };
+    debug!("Setting fixed timestep to {hz} Hz, to match the primary window's monitor");
+    fixed_time.set_timestep_hz(*hz);
+}
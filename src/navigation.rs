@@ -0,0 +1,269 @@
+use {
+    crate::{GtkSystems, GtkWindows, MakeWidget},
+    adw::prelude::*,
+    alloc::rc::Rc,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_platform::collections::HashMap,
+    bevy_state::{prelude::*, state::FreelyMutableState},
+    core::{cell::RefCell, marker::PhantomData},
+    log::{debug, warn},
+};
+
+/// Shows an [`adw::NavigationView`] in the [`PrimaryWindow`], where each page
+/// corresponds to a value of the Bevy state `S`.
+///
+/// Spawn a [`GtkNavigationPage`] for each state value which should have a
+/// page. When `S` changes, the page for the new state is pushed onto the
+/// navigation stack, or if it is already in the stack, every page above it is
+/// popped. When the user goes back (via the back button, a keyboard shortcut,
+/// or a swipe gesture), [`NextState`] is set to the state of the page which is
+/// now visible.
+///
+/// This requires [`GtkPlugin::use_adw`](crate::GtkPlugin::use_adw).
+///
+/// [`PrimaryWindow`]: bevy_window::PrimaryWindow
+pub struct GtkNavigationPlugin<S>(PhantomData<S>);
+
+impl<S> Default for GtkNavigationPlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: FreelyMutableState> Plugin for GtkNavigationPlugin<S> {
+    fn build(&self, app: &mut App) {
+        let (tx_popped, rx_popped) = async_channel::unbounded();
+        app.insert_resource(NavigationChannel::<S> {
+            tx_popped,
+            rx_popped,
+        })
+        .insert_non_send_resource(NavigationViewState::<S> {
+            view: None,
+            pages: Rc::default(),
+            page_states: HashMap::default(),
+        })
+        .add_systems(PreUpdate, forward_pops::<S>)
+        .add_systems(
+            Last,
+            (
+                create_view::<S>,
+                remove_pages::<S>,
+                create_pages::<S>,
+                sync_visible_page::<S>,
+            )
+                .chain()
+                .after(GtkSystems::SyncWindows),
+        );
+    }
+}
+
+/// Page of a [`GtkNavigationPlugin`] which is shown when the state is
+/// [`GtkNavigationPage::state`].
+///
+/// The content is made once, when this component is added, and is kept alive
+/// while it's not visible. This means a viewport made with
+/// [`WidgetFactory`](crate::WidgetFactory) keeps existing when the user
+/// navigates away from it. Despawning the entity, or removing this component,
+/// removes the page from the navigation view.
+#[derive(derive_more::Debug, Component)]
+pub struct GtkNavigationPage<S: States> {
+    /// State which this page is shown for.
+    pub state: S,
+    /// Title of the page, shown in the header bar.
+    pub title: String,
+    #[debug(skip)]
+    content: Option<Box<dyn MakeWidget>>,
+}
+
+impl<S: States> GtkNavigationPage<S> {
+    /// Creates a page for `state`, with the given content.
+    #[must_use]
+    pub fn new(state: S, title: impl Into<String>, content: impl MakeWidget) -> Self {
+        Self {
+            state,
+            title: title.into(),
+            content: Some(Box::new(content)),
+        }
+    }
+}
+
+#[derive(Debug, Resource)]
+struct NavigationChannel<S> {
+    tx_popped: async_channel::Sender<S>,
+    rx_popped: async_channel::Receiver<S>,
+}
+
+#[derive(Debug)]
+struct NavigationViewState<S> {
+    view: Option<adw::NavigationView>,
+    /// Shared with the view's `popped` handler, to map pages back to states.
+    pages: Rc<RefCell<HashMap<S, adw::NavigationPage>>>,
+    /// State of the page made for each [`GtkNavigationPage`] entity.
+    page_states: HashMap<Entity, S>,
+}
+
+fn create_view<S: FreelyMutableState>(
+    mut gtk_windows: NonSendMut<GtkWindows>,
+    mut state: NonSendMut<NavigationViewState<S>>,
+    channel: Res<NavigationChannel<S>>,
+    mut warned: Local<bool>,
+) {
+    if state.view.is_some() {
+        return;
+    }
+    if !gtk_windows.use_adw() {
+        if !*warned {
+            warn!("`GtkNavigationPlugin` requires `GtkPlugin::use_adw`");
+            *warned = true;
+        }
+        return;
+    }
+    let Some(proxy) = gtk_windows.primary_mut() else {
+        return;
+    };
+
+    debug!(
+        "Creating navigation view for {}",
+        core::any::type_name::<S>()
+    );
+    let view = adw::NavigationView::new();
+    // the page which was popped isn't useful to us,
+    // we care about the page which is now visible
+    let tx_popped = channel.tx_popped.clone();
+    let pages = state.pages.clone();
+    view.connect_popped(move |view, _| {
+        let Some(visible) = view.visible_page() else {
+            return;
+        };
+        let page_state = pages
+            .borrow()
+            .iter()
+            .find(|(_, page)| **page == visible)
+            .map(|(page_state, _)| page_state.clone());
+        if let Some(page_state) = page_state {
+            _ = tx_popped.try_send(page_state);
+        }
+    });
+    proxy.set_content(view.clone());
+    state.view = Some(view);
+}
+
+fn remove_pages<S: FreelyMutableState>(
+    mut removed_pages: RemovedComponents<GtkNavigationPage<S>>,
+    mut state: NonSendMut<NavigationViewState<S>>,
+    channel: Res<NavigationChannel<S>>,
+) {
+    for entity in removed_pages.read() {
+        let Some(page_state) = state.page_states.remove(&entity) else {
+            continue;
+        };
+        let Some(page) = state.pages.borrow_mut().remove(&page_state) else {
+            continue;
+        };
+        let Some(view) = &state.view else {
+            continue;
+        };
+
+        debug!("Removing {page_state:?} from navigation view");
+        let stack = view
+            .navigation_stack()
+            .iter::<adw::NavigationPage>()
+            .flatten()
+            .collect::<Vec<_>>();
+        if !stack.contains(&page) {
+            continue;
+        }
+        let was_visible = view.visible_page().as_ref() == Some(&page);
+        let remaining = stack
+            .into_iter()
+            .filter(|other| *other != page)
+            .collect::<Vec<_>>();
+        // replacing doesn't emit `popped`, so tell the state which page is now
+        // visible ourselves
+        view.replace(&remaining);
+        if was_visible {
+            let visible_state = view.visible_page().and_then(|visible| {
+                state
+                    .pages
+                    .borrow()
+                    .iter()
+                    .find(|(_, page)| **page == visible)
+                    .map(|(page_state, _)| page_state.clone())
+            });
+            if let Some(visible_state) = visible_state {
+                _ = channel.tx_popped.try_send(visible_state);
+            }
+        }
+    }
+}
+
+fn create_pages<S: FreelyMutableState>(
+    mut new_pages: Query<(Entity, &mut GtkNavigationPage<S>), Added<GtkNavigationPage<S>>>,
+    mut state: NonSendMut<NavigationViewState<S>>,
+) {
+    for (entity, mut page) in &mut new_pages {
+        let Some(content) = page.content.take() else {
+            continue;
+        };
+        let nav_page = adw::NavigationPage::new(&content.make(), &page.title);
+        // a newer page for the same state replaces the old one
+        state
+            .page_states
+            .retain(|_, page_state| *page_state != page.state);
+        state.page_states.insert(entity, page.state.clone());
+        state
+            .pages
+            .borrow_mut()
+            .insert(page.state.clone(), nav_page);
+    }
+}
+
+fn sync_visible_page<S: FreelyMutableState>(
+    current: Option<Res<State<S>>>,
+    state: NonSend<NavigationViewState<S>>,
+) {
+    let (Some(current), Some(view)) = (current, &state.view) else {
+        return;
+    };
+    let pages = state.pages.borrow();
+    let Some(page) = pages.get(current.get()) else {
+        return;
+    };
+    if view.visible_page().as_ref() == Some(page) {
+        return;
+    }
+
+    let in_stack = view
+        .navigation_stack()
+        .iter::<adw::NavigationPage>()
+        .flatten()
+        .any(|other| other == *page);
+    if in_stack {
+        debug!("Popping navigation view to {:?}", current.get());
+        _ = view.pop_to_page(page);
+    } else {
+        debug!("Pushing {:?} onto navigation view", current.get());
+        view.push(page);
+    }
+}
+
+fn forward_pops<S: FreelyMutableState>(
+    channel: Res<NavigationChannel<S>>,
+    current: Option<Res<State<S>>>,
+    next: Option<ResMut<NextState<S>>>,
+) {
+    let Some(mut next) = next else {
+        return;
+    };
+    while let Ok(page_state) = channel.rx_popped.try_recv() {
+        // we also get this when we pop pages ourselves,
+        // in which case the state already matches
+        if current
+            .as_ref()
+            .is_none_or(|current| *current.get() != page_state)
+        {
+            next.set(page_state);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use {
+    alloc::rc::Rc,
+    core::{
+        any::Any,
+        cell::RefCell,
+        fmt,
+        marker::PhantomData,
+        mem,
+        sync::atomic::{AtomicU64, Ordering},
+    },
+    std::sync::{Mutex, PoisonError},
+};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// IDs of [`GtkOwned`] handles which have been dropped, but whose values
+/// haven't been dropped by the GTK thread yet.
+static RELEASED: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+thread_local! {
+    /// Values owned by this thread through [`GtkOwned`] handles, in the order
+    /// they were created.
+    static OBJECTS: RefCell<Vec<(u64, Rc<dyn Any>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Handle to a value which is owned by the GTK thread.
+///
+/// GTK objects must only be created, used, and destroyed on the GTK thread,
+/// while GTK is running. Storing them directly in the Bevy world means that
+/// they're destroyed whenever the world is dropped - which may be after the
+/// GTK main loop has stopped, or on another thread entirely if the app is
+/// dropped somewhere unexpected.
+///
+/// Instead, the value is moved into a registry local to the thread which
+/// created it, and this handle, which is [`Send`] and [`Sync`], can be stored
+/// anywhere, e.g. in a [`Resource`](bevy_ecs::resource::Resource) or
+/// [`Component`](bevy_ecs::component::Component). Dropping the handle never
+/// drops the value directly: the value is dropped on the GTK thread before
+/// the next Bevy update, or when the app runner tears down after GTK exits,
+/// in the reverse order to which the values were created.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[derive(Resource)]
+/// struct TrayMenu(GtkOwned<gio::Menu>);
+///
+/// fn setup_tray_menu(_: NonSend<GtkApplication>, mut commands: Commands) {
+///     commands.insert_resource(TrayMenu(GtkOwned::new(gio::Menu::new())));
+/// }
+///
+/// fn add_tray_item(_: NonSend<GtkApplication>, menu: Res<TrayMenu>) {
+///     menu.0.with(|menu| menu.append(Some("Quit"), Some("app.quit")));
+/// }
+/// ```
+pub struct GtkOwned<T: 'static> {
+    id: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> GtkOwned<T> {
+    /// Moves `value` into the registry of the current thread, which must be
+    /// the GTK thread.
+    pub fn new(value: T) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        OBJECTS.with_borrow_mut(|objects| objects.push((id, Rc::new(value))));
+        Self {
+            id,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Runs `f` with the value.
+    ///
+    /// Returns [`None`] if this is not called on the thread which owns the
+    /// value, or the value has already been torn down. Systems which call
+    /// this should take a [`NonSend`](bevy_ecs::system::NonSend) parameter,
+    /// e.g. [`GtkApplication`](crate::GtkApplication), so that they run on
+    /// the GTK thread.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        // clone the value out, so that `f` can create or drop other handles
+        let value = OBJECTS.with_borrow(|objects| {
+            objects
+                .iter()
+                .find(|(id, _)| *id == self.id)
+                .map(|(_, value)| value.clone())
+        })?;
+        value.downcast_ref::<T>().map(f)
+    }
+}
+
+impl<T: 'static + Clone> GtkOwned<T> {
+    /// Gets a clone of the value, e.g. a new reference to a GTK object.
+    ///
+    /// See [`GtkOwned::with`].
+    #[must_use]
+    pub fn get(&self) -> Option<T> {
+        self.with(T::clone)
+    }
+}
+
+impl<T: 'static> Drop for GtkOwned<T> {
+    fn drop(&mut self) {
+        RELEASED
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(self.id);
+    }
+}
+
+impl<T: 'static> fmt::Debug for GtkOwned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GtkOwned")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Drops the values of handles which have been dropped since the last call.
+///
+/// Must be called on the GTK thread.
+pub(crate) fn release_dropped() {
+    let released = mem::take(&mut *RELEASED.lock().unwrap_or_else(PoisonError::into_inner));
+    if released.is_empty() {
+        return;
+    }
+    // drop the values outside of the borrow, since their destructors may
+    // drop other handles
+    let mut dropped = Vec::new();
+    OBJECTS.with_borrow_mut(|objects| {
+        objects.retain(|(id, value)| {
+            if released.contains(id) {
+                dropped.push(value.clone());
+                false
+            } else {
+                true
+            }
+        });
+    });
+    drop(dropped);
+}
+
+/// Drops all values owned by the GTK thread, most recently created first.
+///
+/// Must be called on the GTK thread, after the Bevy app has been dropped.
+pub(crate) fn tear_down() {
+    release_dropped();
+    let objects = OBJECTS.take();
+    for (_, value) in objects.into_iter().rev() {
+        drop(value);
+    }
+}
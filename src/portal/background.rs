@@ -0,0 +1,191 @@
+use {
+    crate::{GtkCapabilities, GtkSystems, GtkWindows},
+    ashpd::{WindowIdentifier, desktop::background::Background},
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    log::{debug, warn},
+};
+
+/// Asks the desktop for permission to keep running in the background, via the
+/// [Background portal].
+///
+/// Sandboxed apps may be killed by the desktop once all of their windows are
+/// closed, unless they're allowed to run in the background. This also lets the
+/// app ask to be started automatically when the user logs in.
+///
+/// The GTK application is always kept alive while the Bevy app runs, so to
+/// keep running with all windows closed, you also need to stop Bevy from
+/// exiting once the last window closes. To let users "close" a window into
+/// the background, hide it by setting [`Window::visible`] to `false` instead
+/// of despawning it, and set it to `true` again to bring it back.
+///
+/// The result of the request is stored in [`GtkBackgroundState`]. Outside of a
+/// sandbox, no permission is needed, so the portal isn't asked, and the state
+/// is immediately [`GtkBackgroundState::Allowed`].
+///
+/// # Examples
+///
+/// ```ignore
+/// App::new()
+///     .add_plugins(GtkInitPlugin)
+///     .add_plugins(DefaultPlugins.build().disable::<WinitPlugin>().set(WindowPlugin {
+///         exit_condition: ExitCondition::DontExit,
+///         close_when_requested: false,
+///         ..default()
+///     }))
+///     .add_plugins(GtkPlugin::new(APP_ID))
+///     .add_plugins(GtkBackgroundPlugin::new("Keep playing music"))
+///     .add_systems(Update, hide_on_close);
+///
+/// fn hide_on_close(mut requests: EventReader<WindowCloseRequested>, mut windows: Query<&mut Window>) {
+///     for request in requests.read() {
+///         if let Ok(mut window) = windows.get_mut(request.window) {
+///             window.visible = false;
+///         }
+///     }
+/// }
+/// ```
+///
+/// [Background portal]: https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Background.html
+/// [`Window::visible`]: bevy_window::Window::visible
+#[derive(Debug, Clone, Default)]
+pub struct GtkBackgroundPlugin {
+    /// User-facing reason for running in the background, which the desktop
+    /// may show when asking the user.
+    pub reason: Option<String>,
+    /// Whether to also ask to be started automatically when the user logs in.
+    pub auto_start: bool,
+}
+
+impl GtkBackgroundPlugin {
+    /// Creates a plugin which asks to run in the background for the given
+    /// reason, like "Keep playing music".
+    #[must_use]
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason: Some(reason.into()),
+            auto_start: false,
+        }
+    }
+
+    /// Enables [`GtkBackgroundPlugin::auto_start`].
+    #[must_use]
+    pub fn with_auto_start(self) -> Self {
+        Self {
+            auto_start: true,
+            ..self
+        }
+    }
+}
+
+/// Whether the app is allowed to run in the background, as requested by
+/// [`GtkBackgroundPlugin`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Resource)]
+pub enum GtkBackgroundState {
+    /// The request is still being made, which may be waiting on the user to
+    /// confirm it.
+    #[default]
+    Pending,
+    /// The app may run in the background.
+    Allowed {
+        /// Whether the app will be started automatically when the user logs
+        /// in.
+        auto_start: bool,
+    },
+    /// The user or desktop didn't allow the app to run in the background.
+    Denied,
+    /// The portal is unavailable, or the request failed.
+    Unavailable(String),
+}
+
+impl GtkBackgroundState {
+    /// Whether the app may run in the background.
+    #[must_use]
+    pub const fn is_allowed(&self) -> bool {
+        matches!(self, Self::Allowed { .. })
+    }
+}
+
+#[derive(Debug, Resource)]
+struct BackgroundChannel {
+    reason: Option<String>,
+    auto_start: bool,
+    tx: async_channel::Sender<GtkBackgroundState>,
+    rx: async_channel::Receiver<GtkBackgroundState>,
+}
+
+impl Plugin for GtkBackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        let (tx, rx) = async_channel::bounded(1);
+        app.init_resource::<GtkBackgroundState>()
+            .insert_resource(BackgroundChannel {
+                reason: self.reason.clone(),
+                auto_start: self.auto_start,
+                tx,
+                rx,
+            })
+            .add_systems(PreUpdate, forward_state)
+            .add_systems(Last, request_background.after(GtkSystems::SyncWindows));
+    }
+}
+
+// the window is only used to parent the confirmation dialog,
+// so we wait until the first update, when the primary window exists
+fn request_background(
+    gtk_windows: NonSend<GtkWindows>,
+    channel: Res<BackgroundChannel>,
+    capabilities: Option<Res<GtkCapabilities>>,
+    mut started: Local<bool>,
+) {
+    if *started {
+        return;
+    }
+    *started = true;
+
+    if !capabilities.is_some_and(|capabilities| capabilities.is_sandboxed()) {
+        debug!("Not sandboxed, so running in the background is always allowed");
+        _ = channel
+            .tx
+            .try_send(GtkBackgroundState::Allowed { auto_start: false });
+        return;
+    }
+
+    let gtk_window = gtk_windows.primary().map(|proxy| proxy.gtk_window.clone());
+    let reason = channel.reason.clone();
+    let auto_start = channel.auto_start;
+    let tx = channel.tx.clone();
+    glib::spawn_future_local(async move {
+        let identifier = match &gtk_window {
+            Some(gtk_window) => WindowIdentifier::from_native(gtk_window).await,
+            None => None,
+        };
+        let response = async {
+            Background::request()
+                .identifier(identifier)
+                .reason(reason.as_deref())
+                .auto_start(auto_start)
+                .dbus_activatable(false)
+                .send()
+                .await?
+                .response()
+        };
+        let state = match response.await {
+            Ok(background) if background.run_in_background() => GtkBackgroundState::Allowed {
+                auto_start: background.auto_start(),
+            },
+            Ok(_) | Err(ashpd::Error::Response(_)) => GtkBackgroundState::Denied,
+            Err(err) => {
+                warn!("Background portal is unavailable: {err}");
+                GtkBackgroundState::Unavailable(err.to_string())
+            }
+        };
+        debug!("Background state: {state:?}");
+        _ = tx.send(state).await;
+    });
+}
+
+fn forward_state(channel: Res<BackgroundChannel>, mut state: ResMut<GtkBackgroundState>) {
+    if let Ok(new_state) = channel.rx.try_recv() {
+        *state = new_state;
+    }
+}
@@ -0,0 +1,249 @@
+use {
+    crate::{GtkSystems, GtkWindows},
+    ashpd::{
+        WindowIdentifier,
+        desktop::global_shortcuts::{GlobalShortcuts, NewShortcut},
+    },
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    futures_util::{StreamExt, stream},
+    log::{debug, warn},
+};
+
+/// Registers system-wide keyboard shortcuts via the [GlobalShortcuts portal],
+/// which work even when none of the app's windows are focused.
+///
+/// When the app starts, the user is asked by the desktop to confirm the
+/// shortcuts, and can choose different triggers than the ones you prefer.
+/// When a shortcut is pressed, a [`GlobalShortcutActivated`] event is sent,
+/// and when it is released, a [`GlobalShortcutDeactivated`] event is sent.
+///
+/// If the portal is unavailable, the shortcuts are never triggered, and
+/// [`GlobalShortcutsState::Unavailable`] is set.
+///
+/// [GlobalShortcuts portal]: https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.GlobalShortcuts.html
+#[derive(Debug, Clone, Default)]
+pub struct GtkGlobalShortcutsPlugin {
+    /// Shortcuts to register.
+    pub shortcuts: Vec<GlobalShortcut>,
+}
+
+impl GtkGlobalShortcutsPlugin {
+    /// Adds a shortcut to register.
+    #[must_use]
+    pub fn with_shortcut(mut self, shortcut: GlobalShortcut) -> Self {
+        self.shortcuts.push(shortcut);
+        self
+    }
+}
+
+/// Shortcut registered by [`GtkGlobalShortcutsPlugin`].
+#[derive(Debug, Clone)]
+pub struct GlobalShortcut {
+    /// Unique ID of this shortcut, which is sent back in
+    /// [`GlobalShortcutActivated`].
+    pub id: String,
+    /// User-facing description of what this shortcut does, like "Toggle
+    /// overlay".
+    pub description: String,
+    /// Trigger that you would prefer for this shortcut, like `CTRL+SHIFT+o`,
+    /// as described by the [XDG shortcuts specification].
+    ///
+    /// The desktop may ignore this.
+    ///
+    /// [XDG shortcuts specification]: https://specifications.freedesktop.org/shortcuts-spec/latest/
+    pub preferred_trigger: Option<String>,
+}
+
+impl GlobalShortcut {
+    /// Creates a shortcut with no preferred trigger.
+    #[must_use]
+    pub fn new(id: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            description: description.into(),
+            preferred_trigger: None,
+        }
+    }
+
+    /// Sets [`GlobalShortcut::preferred_trigger`].
+    #[must_use]
+    pub fn with_preferred_trigger(self, preferred_trigger: impl Into<String>) -> Self {
+        Self {
+            preferred_trigger: Some(preferred_trigger.into()),
+            ..self
+        }
+    }
+}
+
+/// Whether the shortcuts of [`GtkGlobalShortcutsPlugin`] have been registered.
+#[derive(Debug, Clone, Default, Resource)]
+pub enum GlobalShortcutsState {
+    /// The shortcuts are still being registered, which may be waiting on the
+    /// user to confirm them.
+    #[default]
+    Pending,
+    /// The shortcuts are registered.
+    Bound(Vec<BoundGlobalShortcut>),
+    /// The portal is unavailable, or registering the shortcuts failed.
+    Unavailable(String),
+}
+
+/// Shortcut which the desktop has registered.
+#[derive(Debug, Clone)]
+pub struct BoundGlobalShortcut {
+    /// [`GlobalShortcut::id`] of this shortcut.
+    pub id: String,
+    /// [`GlobalShortcut::description`] of this shortcut.
+    pub description: String,
+    /// User-facing description of how to trigger this shortcut, which may not
+    /// be the preferred trigger.
+    pub trigger_description: String,
+}
+
+/// Emitted when a global shortcut is pressed.
+#[derive(Debug, Clone, Event)]
+pub struct GlobalShortcutActivated {
+    /// [`GlobalShortcut::id`] of the shortcut.
+    pub id: String,
+}
+
+/// Emitted when a global shortcut is released.
+#[derive(Debug, Clone, Event)]
+pub struct GlobalShortcutDeactivated {
+    /// [`GlobalShortcut::id`] of the shortcut.
+    pub id: String,
+}
+
+#[derive(Debug)]
+enum ShortcutMessage {
+    Bound(Vec<BoundGlobalShortcut>),
+    Unavailable(String),
+    Activated(String),
+    Deactivated(String),
+}
+
+#[derive(Debug, Resource)]
+struct ShortcutsChannel {
+    shortcuts: Vec<GlobalShortcut>,
+    tx: async_channel::Sender<ShortcutMessage>,
+    rx: async_channel::Receiver<ShortcutMessage>,
+}
+
+impl Plugin for GtkGlobalShortcutsPlugin {
+    fn build(&self, app: &mut App) {
+        let (tx, rx) = async_channel::unbounded();
+        app.add_event::<GlobalShortcutActivated>()
+            .add_event::<GlobalShortcutDeactivated>()
+            .init_resource::<GlobalShortcutsState>()
+            .insert_resource(ShortcutsChannel {
+                shortcuts: self.shortcuts.clone(),
+                tx,
+                rx,
+            })
+            .add_systems(PreUpdate, forward_messages)
+            .add_systems(Last, bind_shortcuts.after(GtkSystems::SyncWindows));
+    }
+}
+
+// the window is only used to parent the confirmation dialog,
+// so we wait until the first update, when the primary window exists
+fn bind_shortcuts(
+    gtk_windows: NonSend<GtkWindows>,
+    channel: Res<ShortcutsChannel>,
+    mut started: Local<bool>,
+) {
+    if *started {
+        return;
+    }
+    *started = true;
+
+    let gtk_window = gtk_windows.primary().map(|proxy| proxy.gtk_window.clone());
+    let shortcuts = channel
+        .shortcuts
+        .iter()
+        .map(|shortcut| {
+            NewShortcut::new(&shortcut.id, &shortcut.description)
+                .preferred_trigger(shortcut.preferred_trigger.as_deref())
+        })
+        .collect::<Vec<_>>();
+    let tx = channel.tx.clone();
+    glib::spawn_future_local(async move {
+        if let Err(err) = run_session(gtk_window, shortcuts, &tx).await {
+            warn!("Global shortcuts portal is unavailable: {err}");
+            _ = tx.send(ShortcutMessage::Unavailable(err.to_string())).await;
+        }
+    });
+}
+
+async fn run_session(
+    gtk_window: Option<gtk::ApplicationWindow>,
+    shortcuts: Vec<NewShortcut>,
+    tx: &async_channel::Sender<ShortcutMessage>,
+) -> Result<(), ashpd::Error> {
+    let portal = GlobalShortcuts::new().await?;
+    let session = portal.create_session().await?;
+    let identifier = match &gtk_window {
+        Some(gtk_window) => WindowIdentifier::from_native(gtk_window).await,
+        None => None,
+    };
+
+    let response = portal
+        .bind_shortcuts(&session, &shortcuts, identifier.as_ref())
+        .await?
+        .response()?;
+    let bound = response
+        .shortcuts()
+        .iter()
+        .map(|shortcut| BoundGlobalShortcut {
+            id: shortcut.id().to_owned(),
+            description: shortcut.description().to_owned(),
+            trigger_description: shortcut.trigger_description().to_owned(),
+        })
+        .collect::<Vec<_>>();
+    debug!("Bound {} global shortcuts", bound.len());
+    _ = tx.send(ShortcutMessage::Bound(bound)).await;
+
+    let activated = portal
+        .receive_activated()
+        .await?
+        .map(|event| ShortcutMessage::Activated(event.shortcut_id().to_owned()));
+    let deactivated = portal
+        .receive_deactivated()
+        .await?
+        .map(|event| ShortcutMessage::Deactivated(event.shortcut_id().to_owned()));
+    let mut messages = stream::select(Box::pin(activated), Box::pin(deactivated));
+    // the session stays open for as long as the app is listening
+    while let Some(message) = messages.next().await {
+        if tx.send(message).await.is_err() {
+            break;
+        }
+    }
+
+    session.close().await?;
+    Ok(())
+}
+
+fn forward_messages(
+    channel: Res<ShortcutsChannel>,
+    mut state: ResMut<GlobalShortcutsState>,
+    mut activated: EventWriter<GlobalShortcutActivated>,
+    mut deactivated: EventWriter<GlobalShortcutDeactivated>,
+) {
+    while let Ok(message) = channel.rx.try_recv() {
+        match message {
+            ShortcutMessage::Bound(shortcuts) => {
+                *state = GlobalShortcutsState::Bound(shortcuts);
+            }
+            ShortcutMessage::Unavailable(reason) => {
+                *state = GlobalShortcutsState::Unavailable(reason);
+            }
+            ShortcutMessage::Activated(id) => {
+                activated.write(GlobalShortcutActivated { id });
+            }
+            ShortcutMessage::Deactivated(id) => {
+                deactivated.write(GlobalShortcutDeactivated { id });
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! Integrations with [XDG desktop portals](https://flatpak.github.io/xdg-desktop-portal/),
+//! which let apps access desktop features, even from inside a sandbox.
+//!
+//! Portals are not available on every desktop, so every integration here
+//! degrades gracefully: if a portal can't be reached, a warning is logged and
+//! the feature reports itself as unavailable, instead of failing the app.
+
+use bevy_app::prelude::*;
+
+mod background;
+mod global_shortcuts;
+#[cfg(feature = "gstreamer")]
+mod screencast;
+
+#[cfg(feature = "gstreamer")]
+pub use screencast::*;
+pub use {background::*, global_shortcuts::*};
+
+#[cfg_attr(
+    not(feature = "gstreamer"),
+    expect(unused_variables, reason = "no portals need setup without GStreamer")
+)]
+pub(super) fn plugin(app: &mut App) {
+    #[cfg(feature = "gstreamer")]
+    screencast::plugin(app);
+}
@@ -0,0 +1,318 @@
+use {
+    crate::{GstVideoSinks, GtkCommands, GtkContext},
+    ashpd::{
+        WindowIdentifier,
+        desktop::{
+            PersistMode,
+            screencast::{CursorMode, Screencast, SourceType},
+        },
+        enumflags2::BitFlags,
+    },
+    bevy_app::prelude::*,
+    bevy_asset::Handle,
+    bevy_ecs::{error::BevyError, prelude::*, system::SystemParam},
+    bevy_image::Image,
+    core::pin::pin,
+    futures_util::{
+        StreamExt,
+        future::{self, Either},
+    },
+    gst::prelude::*,
+    log::{debug, warn},
+    std::os::fd::AsRawFd as _,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let (tx, rx) = async_channel::unbounded();
+    app.add_event::<ScreenCastStarted>()
+        .add_event::<ScreenCastFailed>()
+        .insert_resource(ScreenCastChannel { tx, rx })
+        .add_systems(PreUpdate, forward_messages);
+}
+
+/// Captures the screen via the [ScreenCast portal], and streams it into a Bevy
+/// [`Image`].
+///
+/// The desktop asks the user which monitor or window to share. Once they
+/// confirm, the portal gives us a PipeWire stream, which is played through a
+/// GStreamer pipeline into a [`GstVideoSinks`] sink, so the frames are
+/// imported as dmabufs and never leave the GPU. This requires the `pipewiresrc`
+/// GStreamer element, from the PipeWire GStreamer plugin.
+///
+/// There is no fallback to frames in system memory. If the desktop can't share
+/// the stream as dmabufs, `pipewiresrc` fails to negotiate a format with the
+/// sink, and [`ScreenCastFailed`] is sent.
+///
+/// # Examples
+///
+/// ```ignore
+/// fn setup(mut screen_casts: ScreenCasts, mut commands: Commands) {
+///     let cast = screen_casts.start(ScreenCastConfig::default());
+///     commands.spawn(Sprite::from_image(cast.image.clone()));
+///     commands.insert_resource(MyScreenCast(cast));
+/// }
+/// ```
+///
+/// [ScreenCast portal]: https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.ScreenCast.html
+#[derive(SystemParam)]
+pub struct ScreenCasts<'w, 's> {
+    video_sinks: GstVideoSinks<'w, 's>,
+    gtk_commands: GtkCommands<'w>,
+    channel: Res<'w, ScreenCastChannel>,
+    commands: Commands<'w, 's>,
+}
+
+/// Configuration for a screen cast started with [`ScreenCasts::start`].
+#[derive(Debug, Clone)]
+pub struct ScreenCastConfig {
+    /// Whether the user may pick monitors, windows, or both.
+    pub sources: ScreenCastSources,
+    /// Whether the cursor is drawn into the captured frames.
+    pub show_cursor: bool,
+    /// Token returned in [`ScreenCastStarted::restore_token`] from a previous
+    /// screen cast.
+    ///
+    /// If the desktop still knows about this token, the same source is
+    /// captured again without asking the user.
+    pub restore_token: Option<String>,
+}
+
+impl Default for ScreenCastConfig {
+    fn default() -> Self {
+        Self {
+            sources: ScreenCastSources::MonitorsAndWindows,
+            show_cursor: true,
+            restore_token: None,
+        }
+    }
+}
+
+/// What kind of source the user may pick for a screen cast.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ScreenCastSources {
+    /// Only entire monitors.
+    Monitors,
+    /// Only individual windows.
+    Windows,
+    /// Either monitors or windows.
+    #[default]
+    MonitorsAndWindows,
+}
+
+impl ScreenCastSources {
+    fn to_portal(self) -> BitFlags<SourceType> {
+        match self {
+            Self::Monitors => SourceType::Monitor.into(),
+            Self::Windows => SourceType::Window.into(),
+            Self::MonitorsAndWindows => SourceType::Monitor | SourceType::Window,
+        }
+    }
+}
+
+/// Handle to a screen cast started with [`ScreenCasts::start`].
+///
+/// The screen cast runs until its [`ScreenCast::entity`] is despawned.
+#[derive(Debug, Clone)]
+pub struct ScreenCast {
+    /// Entity which owns the screen cast.
+    pub entity: Entity,
+    /// Image which the captured frames are written to.
+    ///
+    /// This is empty until [`ScreenCastStarted`] is sent for this screen cast.
+    pub image: Handle<Image>,
+}
+
+/// Emitted when the user has picked a source for a screen cast, and the first
+/// frame has been captured.
+#[derive(Debug, Clone, Event)]
+pub struct ScreenCastStarted {
+    /// [`ScreenCast::entity`] of the screen cast.
+    pub screen_cast: Entity,
+    /// Token which can be passed in [`ScreenCastConfig::restore_token`] to
+    /// capture the same source again later, if the desktop supports it.
+    pub restore_token: Option<String>,
+}
+
+/// Emitted when a screen cast can't be started, e.g. because the portal is
+/// unavailable or the user cancelled the dialog, or when its pipeline fails
+/// after it has started.
+#[derive(Debug, Clone, Event)]
+pub struct ScreenCastFailed {
+    /// [`ScreenCast::entity`] of the screen cast.
+    pub screen_cast: Entity,
+    /// Description of the error.
+    pub message: String,
+}
+
+#[derive(Debug)]
+enum ScreenCastMessage {
+    Started(Entity, Option<String>),
+    Failed(Entity, String),
+}
+
+#[derive(Debug, Resource)]
+struct ScreenCastChannel {
+    tx: async_channel::Sender<ScreenCastMessage>,
+    rx: async_channel::Receiver<ScreenCastMessage>,
+}
+
+/// Stops the screen cast when this component is dropped, which drops the
+/// sender.
+#[derive(Debug, Component)]
+struct ScreenCastPrivate {
+    _tx_stop: async_channel::Sender<()>,
+}
+
+impl ScreenCasts<'_, '_> {
+    /// Asks the user to pick a source, and starts capturing it into an
+    /// [`Image`].
+    ///
+    /// Either [`ScreenCastStarted`] or [`ScreenCastFailed`] is sent once the
+    /// user has made a choice, and the first frame has been captured or the
+    /// pipeline has failed.
+    pub fn start(&mut self, config: ScreenCastConfig) -> ScreenCast {
+        let (image, sink) = self.video_sinks.create();
+        let (tx_stop, rx_stop) = async_channel::bounded(1);
+        let entity = self
+            .commands
+            .spawn(ScreenCastPrivate { _tx_stop: tx_stop })
+            .id();
+
+        let tx = self.channel.tx.clone();
+        self.gtk_commands.queue(move |ctx: &mut GtkContext| {
+            let gtk_window = ctx.windows.primary().map(|proxy| proxy.gtk_window.clone());
+            glib::spawn_future_local(async move {
+                if let Err(err) =
+                    run_screen_cast(entity, config, gtk_window, sink, rx_stop, &tx).await
+                {
+                    warn!("Screen cast {entity} failed: {err}");
+                    _ = tx
+                        .send(ScreenCastMessage::Failed(entity, err.to_string()))
+                        .await;
+                }
+            });
+        });
+
+        ScreenCast { entity, image }
+    }
+}
+
+async fn run_screen_cast(
+    entity: Entity,
+    config: ScreenCastConfig,
+    gtk_window: Option<gtk::ApplicationWindow>,
+    sink: gst_app::AppSink,
+    rx_stop: async_channel::Receiver<()>,
+    tx: &async_channel::Sender<ScreenCastMessage>,
+) -> Result<(), BevyError> {
+    let portal = Screencast::new().await?;
+    let session = portal.create_session().await?;
+    let cursor_mode = if config.show_cursor {
+        CursorMode::Embedded
+    } else {
+        CursorMode::Hidden
+    };
+    portal
+        .select_sources(
+            &session,
+            cursor_mode,
+            config.sources.to_portal(),
+            false,
+            config.restore_token.as_deref(),
+            PersistMode::ExplicitlyRevoked,
+        )
+        .await?
+        .response()?;
+
+    let identifier = match &gtk_window {
+        Some(gtk_window) => WindowIdentifier::from_native(gtk_window).await,
+        None => None,
+    };
+    let response = portal
+        .start(&session, identifier.as_ref())
+        .await?
+        .response()?;
+    let stream = response
+        .streams()
+        .first()
+        .ok_or("portal did not return any streams")?;
+    let node_id = stream.pipe_wire_node_id();
+    let fd = portal.open_pipe_wire_remote(&session).await?;
+    debug!("Screen cast {entity} started on PipeWire node {node_id}");
+
+    // `pipewiresrc` duplicates the fd when it connects,
+    // but we still have to keep ours open until then
+    let source = gst::ElementFactory::make("pipewiresrc")
+        .property("fd", fd.as_raw_fd())
+        .property("path", node_id.to_string())
+        .property("always-copy", false)
+        .build()?;
+    let pipeline = gst::Pipeline::new();
+    pipeline.add_many([&source, sink.upcast_ref::<gst::Element>()])?;
+    source.link(&sink)?;
+    let bus = pipeline.bus().ok_or("pipeline has no bus")?;
+
+    // a failed caps negotiation only shows up once the stream is running,
+    // so we wait for a frame to actually arrive
+    let restore_token = response.restore_token().map(ToOwned::to_owned);
+    let tx_started = tx.clone();
+    sink.static_pad("sink")
+        .ok_or("video sink has no sink pad")?
+        .add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+            _ = tx_started.try_send(ScreenCastMessage::Started(entity, restore_token.clone()));
+            gst::PadProbeReturn::Remove
+        });
+    pipeline.set_state(gst::State::Playing)?;
+
+    // runs until the entity is despawned, which drops the sender,
+    // or until the pipeline fails
+    let result = match future::select(pin!(rx_stop.recv()), pin!(pipeline_error(&bus))).await {
+        Either::Left(_) => {
+            debug!("Stopping screen cast {entity}");
+            Ok(())
+        }
+        Either::Right((err, _)) => Err(err),
+    };
+    pipeline.set_state(gst::State::Null)?;
+    drop(fd);
+    session.close().await?;
+    result
+}
+
+/// Waits until an error is posted on the bus of a pipeline.
+async fn pipeline_error(bus: &gst::Bus) -> BevyError {
+    let mut messages = bus.stream();
+    while let Some(message) = messages.next().await {
+        if let gst::MessageView::Error(err) = message.view() {
+            return match err.debug() {
+                Some(debug) => format!("pipeline failed: {} ({debug})", err.error()),
+                None => format!("pipeline failed: {}", err.error()),
+            }
+            .into();
+        }
+    }
+    "pipeline bus closed".into()
+}
+
+fn forward_messages(
+    channel: Res<ScreenCastChannel>,
+    mut started: EventWriter<ScreenCastStarted>,
+    mut failed: EventWriter<ScreenCastFailed>,
+) {
+    while let Ok(message) = channel.rx.try_recv() {
+        match message {
+            ScreenCastMessage::Started(screen_cast, restore_token) => {
+                started.write(ScreenCastStarted {
+                    screen_cast,
+                    restore_token,
+                });
+            }
+            ScreenCastMessage::Failed(screen_cast, message) => {
+                failed.write(ScreenCastFailed {
+                    screen_cast,
+                    message,
+                });
+            }
+        }
+    }
+}
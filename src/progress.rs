@@ -0,0 +1,339 @@
+use {
+    crate::{GtkSystems, GtkWindows},
+    alloc::rc::Rc,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_platform::collections::HashMap,
+    core::{cell::Cell, time::Duration},
+    glib::clone,
+    gtk::prelude::*,
+    log::debug,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let (tx_cancelled, rx_cancelled) = async_channel::unbounded();
+    app.add_event::<GtkProgressCancelled>()
+        .insert_resource(ProgressChannel {
+            tx_cancelled,
+            rx_cancelled,
+        })
+        .init_non_send_resource::<ProgressWidgets>()
+        .add_systems(PreUpdate, forward_cancellations)
+        .add_systems(Last, sync_progress.after(GtkSystems::SyncWindows));
+}
+
+/// Shows the progress of a long-running operation, like an asset import, on
+/// a window.
+///
+/// Spawn an entity with this component to show the progress, update the
+/// component to update the progress, and despawn the entity (or remove the
+/// component) to hide it again.
+///
+/// If [`GtkProgress::cancellable`] is set, a cancel button is shown, and
+/// pressing it sends a [`GtkProgressCancelled`] event. The progress is not
+/// hidden automatically when it's cancelled.
+#[derive(Debug, Clone, Component)]
+pub struct GtkProgress {
+    /// Entity of the window which the progress is shown on.
+    pub window: Entity,
+    /// How the progress is shown.
+    pub style: GtkProgressStyle,
+    /// Short description of the operation, like "Importing assets".
+    pub title: String,
+    /// How far along the operation is, from 0.0 to 1.0.
+    ///
+    /// If [`None`], the progress bar pulses to show that the operation is
+    /// running, without showing how far along it is.
+    pub fraction: Option<f64>,
+    /// Text shown on the progress bar, like "12 of 30 files".
+    pub text: Option<String>,
+    /// Whether a cancel button is shown.
+    pub cancellable: bool,
+}
+
+impl GtkProgress {
+    /// Creates a progress with the given title, which pulses until a
+    /// [`GtkProgress::fraction`] is set.
+    #[must_use]
+    pub fn new(window: Entity, title: impl Into<String>) -> Self {
+        Self {
+            window,
+            style: GtkProgressStyle::default(),
+            title: title.into(),
+            fraction: None,
+            text: None,
+            cancellable: false,
+        }
+    }
+
+    /// Sets [`GtkProgress::style`].
+    #[must_use]
+    pub fn with_style(self, style: GtkProgressStyle) -> Self {
+        Self { style, ..self }
+    }
+
+    /// Enables [`GtkProgress::cancellable`].
+    #[must_use]
+    pub fn with_cancel(self) -> Self {
+        Self {
+            cancellable: true,
+            ..self
+        }
+    }
+}
+
+/// How a [`GtkProgress`] is shown.
+///
+/// Changing the style of an existing progress has no effect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GtkProgressStyle {
+    /// A bar below the window's header bar, which leaves the rest of the
+    /// window interactive.
+    ///
+    /// This requires an [`adw::ToolbarView`] in the window, which is the case
+    /// for windows with a non-transparent titlebar when using Adwaita.
+    /// Otherwise, this falls back to [`GtkProgressStyle::Dialog`].
+    #[default]
+    Banner,
+    /// A modal dialog, which blocks input to the window until the progress is
+    /// hidden.
+    Dialog,
+}
+
+/// Emitted when the user presses the cancel button of a [`GtkProgress`].
+#[derive(Debug, Clone, Event)]
+pub struct GtkProgressCancelled {
+    /// Entity of the progress which was cancelled.
+    pub progress: Entity,
+}
+
+#[derive(Debug, Resource)]
+struct ProgressChannel {
+    tx_cancelled: async_channel::Sender<Entity>,
+    rx_cancelled: async_channel::Receiver<Entity>,
+}
+
+#[derive(Debug, Default)]
+struct ProgressWidgets(HashMap<Entity, ProgressWidget>);
+
+#[derive(Debug)]
+struct ProgressWidget {
+    container: ProgressContainer,
+    title: gtk::Label,
+    bar: gtk::ProgressBar,
+    cancel: gtk::Button,
+    pulsing: Rc<Cell<bool>>,
+}
+
+#[derive(Debug)]
+enum ProgressContainer {
+    Dialog(gtk::Window),
+    #[cfg(feature = "adwaita")]
+    Banner {
+        toolbar: adw::ToolbarView,
+        content: gtk::Widget,
+    },
+}
+
+impl Drop for ProgressWidget {
+    fn drop(&mut self) {
+        match &self.container {
+            ProgressContainer::Dialog(dialog) => dialog.destroy(),
+            #[cfg(feature = "adwaita")]
+            ProgressContainer::Banner { toolbar, content } => toolbar.remove(content),
+        }
+    }
+}
+
+fn forward_cancellations(
+    channel: Res<ProgressChannel>,
+    mut cancelled: EventWriter<GtkProgressCancelled>,
+) {
+    while let Ok(progress) = channel.rx_cancelled.try_recv() {
+        cancelled.write(GtkProgressCancelled { progress });
+    }
+}
+
+fn sync_progress(
+    changed: Query<(Entity, &GtkProgress), Changed<GtkProgress>>,
+    mut removed: RemovedComponents<GtkProgress>,
+    gtk_windows: NonSend<GtkWindows>,
+    mut widgets: NonSendMut<ProgressWidgets>,
+    channel: Res<ProgressChannel>,
+) {
+    for entity in removed.read() {
+        if widgets.0.remove(&entity).is_some() {
+            debug!("Hid progress {entity}");
+        }
+    }
+
+    for (entity, progress) in &changed {
+        if !widgets.0.contains_key(&entity) {
+            let Some(proxy) = gtk_windows.get(progress.window) else {
+                continue;
+            };
+            debug!("Showing progress {entity}");
+            let widget = make_widget(
+                entity,
+                progress.style,
+                &proxy.gtk_window,
+                channel.tx_cancelled.clone(),
+            );
+            widgets.0.insert(entity, widget);
+        }
+
+        if let Some(widget) = widgets.0.get(&entity) {
+            update_widget(widget, progress);
+        }
+    }
+}
+
+fn make_widget(
+    entity: Entity,
+    style: GtkProgressStyle,
+    gtk_window: &gtk::ApplicationWindow,
+    tx_cancelled: async_channel::Sender<Entity>,
+) -> ProgressWidget {
+    let title = gtk::Label::builder()
+        .halign(gtk::Align::Start)
+        .css_classes(["heading"])
+        .build();
+    let bar = gtk::ProgressBar::builder().hexpand(true).build();
+    let cancel = gtk::Button::builder()
+        .label("Cancel")
+        .valign(gtk::Align::Center)
+        .build();
+    cancel.connect_clicked(move |_| {
+        _ = tx_cancelled.try_send(entity);
+    });
+
+    let pulsing = Rc::new(Cell::new(false));
+    glib::timeout_add_local(
+        Duration::from_millis(100),
+        clone!(
+            #[weak]
+            bar,
+            #[strong]
+            pulsing,
+            #[upgrade_or]
+            glib::ControlFlow::Break,
+            move || {
+                if pulsing.get() {
+                    bar.pulse();
+                }
+                glib::ControlFlow::Continue
+            }
+        ),
+    );
+
+    let container = match style {
+        GtkProgressStyle::Banner => make_banner(gtk_window, &title, &bar, &cancel),
+        GtkProgressStyle::Dialog => None,
+    }
+    .unwrap_or_else(|| make_dialog(gtk_window, &title, &bar, &cancel));
+
+    ProgressWidget {
+        container,
+        title,
+        bar,
+        cancel,
+        pulsing,
+    }
+}
+
+#[cfg_attr(
+    not(feature = "adwaita"),
+    expect(unused_variables, reason = "banners need Adwaita")
+)]
+fn make_banner(
+    gtk_window: &gtk::ApplicationWindow,
+    title: &gtk::Label,
+    bar: &gtk::ProgressBar,
+    cancel: &gtk::Button,
+) -> Option<ProgressContainer> {
+    if_adw!(
+        {
+            use adw::prelude::*;
+
+            let adw_window = gtk_window.downcast_ref::<adw::ApplicationWindow>()?;
+            let toolbar = adw_window.content()?.downcast::<adw::ToolbarView>().ok()?;
+
+            let text = gtk::Box::builder()
+                .orientation(gtk::Orientation::Vertical)
+                .spacing(6)
+                .hexpand(true)
+                .build();
+            text.append(title);
+            text.append(bar);
+
+            let content = gtk::Box::builder()
+                .spacing(12)
+                .margin_start(12)
+                .margin_end(12)
+                .margin_top(6)
+                .margin_bottom(6)
+                .build();
+            content.append(&text);
+            content.append(cancel);
+
+            toolbar.add_top_bar(&content);
+            Some(ProgressContainer::Banner {
+                toolbar,
+                content: content.upcast(),
+            })
+        },
+        None,
+    )
+}
+
+fn make_dialog(
+    gtk_window: &gtk::ApplicationWindow,
+    title: &gtk::Label,
+    bar: &gtk::ProgressBar,
+    cancel: &gtk::Button,
+) -> ProgressContainer {
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_start(24)
+        .margin_end(24)
+        .margin_top(24)
+        .margin_bottom(24)
+        .build();
+    content.append(title);
+    content.append(bar);
+    cancel.set_halign(gtk::Align::End);
+    content.append(cancel);
+
+    let dialog = gtk::Window::builder()
+        .transient_for(gtk_window)
+        .modal(true)
+        .deletable(false)
+        .resizable(false)
+        .default_width(360)
+        .child(&content)
+        .build();
+    dialog.present();
+    ProgressContainer::Dialog(dialog)
+}
+
+fn update_widget(widget: &ProgressWidget, progress: &GtkProgress) {
+    widget.title.set_label(&progress.title);
+    match &widget.container {
+        ProgressContainer::Dialog(dialog) => dialog.set_title(Some(&progress.title)),
+        #[cfg(feature = "adwaita")]
+        ProgressContainer::Banner { .. } => {}
+    }
+
+    match progress.fraction {
+        Some(fraction) => {
+            widget.pulsing.set(false);
+            widget.bar.set_fraction(fraction.clamp(0.0, 1.0));
+        }
+        None => widget.pulsing.set(true),
+    }
+
+    widget.bar.set_show_text(progress.text.is_some());
+    widget.bar.set_text(progress.text.as_deref());
+    widget.cancel.set_visible(progress.cancellable);
+}
@@ -0,0 +1,146 @@
+use {
+    crate::GtkSystems, adw::prelude::*, alloc::borrow::Cow, bevy_app::prelude::*,
+    bevy_ecs::prelude::*, bevy_platform::collections::HashMap, core::cell::RefCell, log::debug,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let (tx_changed, rx_changed) = async_channel::unbounded();
+    app.init_resource::<GtkSidebars>()
+        .insert_resource(SidebarChannel {
+            tx_changed,
+            rx_changed,
+        })
+        .init_non_send_resource::<SidebarViews>()
+        .add_systems(PreUpdate, forward_sidebar_changes)
+        .add_systems(Last, sync_sidebars.after(GtkSystems::SyncWindows));
+}
+
+thread_local! {
+    /// Split views bound with [`bind_sidebar`], which haven't been picked up
+    /// by [`sync_sidebars`] yet.
+    static NEW_BINDINGS: RefCell<Vec<(Cow<'static, str>, adw::OverlaySplitView)>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+/// Whether the sidebars of [`adw::OverlaySplitView`]s are shown, by key.
+///
+/// Bind a split view to a key with [`bind_sidebar`]. From then on, its
+/// `show-sidebar` property and the value under that key are kept in sync both
+/// ways: changing the value here shows or hides the sidebar, and when the
+/// sidebar is shown or hidden on the GTK side, e.g. through a toggle button
+/// or because the split view collapsed, the value here is updated. Several
+/// split views may be bound to the same key.
+///
+/// Sidebars are shown unless set otherwise, like in Adwaita.
+///
+/// # Examples
+///
+/// ```ignore
+/// commands.spawn((
+///     Window::default(),
+///     GtkWindowContent::from(|| {
+///         let split_view = adw::OverlaySplitView::builder()
+///             .sidebar(&make_inspector())
+///             .content(&viewport_widget.make())
+///             .build();
+///         bind_sidebar("inspector", &split_view);
+///         split_view
+///     }),
+/// ));
+///
+/// fn toggle_inspector(keys: Res<ButtonInput<KeyCode>>, mut sidebars: ResMut<GtkSidebars>) {
+///     if keys.just_pressed(KeyCode::F9) {
+///         sidebars.toggle("inspector");
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Resource)]
+pub struct GtkSidebars {
+    shown: HashMap<Cow<'static, str>, bool>,
+}
+
+impl GtkSidebars {
+    /// Whether the sidebar under `key` is shown.
+    #[must_use]
+    pub fn is_shown(&self, key: &str) -> bool {
+        self.shown.get(key).copied().unwrap_or(true)
+    }
+
+    /// Shows or hides the sidebar under `key`.
+    pub fn set_shown(&mut self, key: impl Into<Cow<'static, str>>, shown: bool) {
+        self.shown.insert(key.into(), shown);
+    }
+
+    /// Shows the sidebar under `key` if it's hidden, and hides it if it's
+    /// shown.
+    pub fn toggle(&mut self, key: impl Into<Cow<'static, str>>) {
+        let key = key.into();
+        let shown = self.is_shown(&key);
+        self.shown.insert(key, !shown);
+    }
+}
+
+/// Binds the `show-sidebar` property of `split_view` to the value under `key`
+/// in [`GtkSidebars`].
+///
+/// The split view takes on the value in [`GtkSidebars`] before the next
+/// frame. Only a weak reference to the split view is kept, so this does not
+/// keep it alive.
+///
+/// Must be called on the GTK thread.
+pub fn bind_sidebar(key: impl Into<Cow<'static, str>>, split_view: &adw::OverlaySplitView) {
+    NEW_BINDINGS.with_borrow_mut(|bindings| bindings.push((key.into(), split_view.clone())));
+}
+
+#[derive(Debug, Resource)]
+struct SidebarChannel {
+    tx_changed: async_channel::Sender<(Cow<'static, str>, bool)>,
+    rx_changed: async_channel::Receiver<(Cow<'static, str>, bool)>,
+}
+
+#[derive(Debug, Default)]
+struct SidebarViews {
+    views: Vec<(Cow<'static, str>, glib::WeakRef<adw::OverlaySplitView>)>,
+}
+
+fn forward_sidebar_changes(channel: Res<SidebarChannel>, mut sidebars: ResMut<GtkSidebars>) {
+    while let Ok((key, shown)) = channel.rx_changed.try_recv() {
+        // we get notified of our own changes too
+        if sidebars.is_shown(&key) != shown {
+            debug!("Sidebar {key:?} shown: {shown}");
+            sidebars.set_shown(key, shown);
+        }
+    }
+}
+
+fn sync_sidebars(
+    sidebars: Res<GtkSidebars>,
+    channel: Res<SidebarChannel>,
+    mut views: NonSendMut<SidebarViews>,
+) {
+    let new_bindings = NEW_BINDINGS.take();
+    let bound = !new_bindings.is_empty();
+    for (key, split_view) in new_bindings {
+        debug!("Binding split view to sidebar {key:?}");
+        let tx_changed = channel.tx_changed.clone();
+        let changed_key = key.clone();
+        split_view.connect_show_sidebar_notify(move |split_view| {
+            _ = tx_changed.try_send((changed_key.clone(), split_view.shows_sidebar()));
+        });
+        views.views.push((key, split_view.downgrade()));
+    }
+
+    if !bound && !sidebars.is_changed() {
+        return;
+    }
+    views.views.retain(|(key, split_view)| {
+        let Some(split_view) = split_view.upgrade() else {
+            return false;
+        };
+        let shown = sidebars.is_shown(key);
+        if split_view.shows_sidebar() != shown {
+            split_view.set_show_sidebar(shown);
+        }
+        true
+    });
+}
@@ -0,0 +1,249 @@
+use {
+    crate::{GtkSystems, GtkWindows},
+    alloc::borrow::Cow,
+    bevy_app::prelude::*,
+    bevy_color::{Color, Srgba},
+    bevy_ecs::prelude::*,
+    bevy_platform::collections::HashMap,
+    core::fmt::Write as _,
+    gtk::prelude::*,
+    log::{debug, warn},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GtkStyleSheets>()
+        .init_non_send_resource::<StyleProviders>()
+        .add_systems(
+            Last,
+            (sync_style_sheets, sync_window_styles).after(GtkSystems::SyncWindows),
+        );
+}
+
+/// Application-level CSS, applied to every window of the app.
+///
+/// Each style sheet is installed as its own [`gtk::CssProvider`] for the
+/// default display, at [`gtk::STYLE_PROVIDER_PRIORITY_APPLICATION`]. When a
+/// style sheet is removed or replaced, its provider is removed as well, so
+/// the CSS can react to Bevy state.
+///
+/// # Examples
+///
+/// ```ignore
+/// fn setup_style(mut style_sheets: ResMut<GtkStyleSheets>) {
+///     style_sheets.insert(
+///         "viewport-frame",
+///         GtkStyleSheet::Data(".viewport-frame { border-radius: 12px; }".into()),
+///     );
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Resource)]
+pub struct GtkStyleSheets {
+    sheets: HashMap<Cow<'static, str>, GtkStyleSheet>,
+}
+
+impl GtkStyleSheets {
+    /// Installs a style sheet under `key`, replacing any existing style sheet
+    /// under the same key.
+    pub fn insert(&mut self, key: impl Into<Cow<'static, str>>, sheet: GtkStyleSheet) {
+        self.sheets.insert(key.into(), sheet);
+    }
+
+    /// Removes the style sheet under `key`, returning it if it existed.
+    pub fn remove(&mut self, key: &str) -> Option<GtkStyleSheet> {
+        self.sheets.remove(key)
+    }
+
+    /// Gets the style sheet under `key`.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&GtkStyleSheet> {
+        self.sheets.get(key)
+    }
+}
+
+/// Source of the CSS in a [`GtkStyleSheets`] entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GtkStyleSheet {
+    /// CSS source code.
+    Data(String),
+    /// Path of a CSS file in a registered [`gio::Resource`], e.g.
+    /// `/org/bevy/DemoApp/style.css`.
+    Resource(String),
+}
+
+/// Styles the GTK window backing a Bevy [`Window`](bevy_window::Window).
+///
+/// Removing this component removes the styling again.
+///
+/// # Examples
+///
+/// ```ignore
+/// commands.spawn((
+///     Window::default(),
+///     GtkWindowStyle {
+///         // Adwaita shows stripes in the title bar of development builds
+///         classes: vec!["devel".into()],
+///         background_color: Some(Color::BLACK),
+///     },
+/// ));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Component)]
+pub struct GtkWindowStyle {
+    /// CSS classes added to the window.
+    pub classes: Vec<String>,
+    /// Background color of the window, which shows through wherever its
+    /// content is transparent.
+    pub background_color: Option<Color>,
+}
+
+/// GTK-side state of the styles which we've applied.
+#[derive(Debug, Default)]
+struct StyleProviders {
+    sheets: HashMap<Cow<'static, str>, (GtkStyleSheet, gtk::CssProvider)>,
+    /// Styles which we've applied to each window.
+    windows: HashMap<Entity, GtkWindowStyle>,
+    /// Provider holding the background color of every window.
+    backgrounds: Option<gtk::CssProvider>,
+}
+
+fn sync_style_sheets(style_sheets: Res<GtkStyleSheets>, mut providers: NonSendMut<StyleProviders>) {
+    if !style_sheets.is_changed() {
+        return;
+    }
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+
+    providers.sheets.retain(|key, (sheet, provider)| {
+        if style_sheets.get(key) == Some(sheet) {
+            return true;
+        }
+        debug!("Removing style sheet {key:?}");
+        gtk::style_context_remove_provider_for_display(&display, provider);
+        false
+    });
+
+    for (key, sheet) in &style_sheets.sheets {
+        if providers.sheets.contains_key(key) {
+            continue;
+        }
+        debug!("Adding style sheet {key:?}");
+        let provider = css_provider(key);
+        match sheet {
+            GtkStyleSheet::Data(data) => provider.load_from_string(data),
+            GtkStyleSheet::Resource(path) => provider.load_from_resource(path),
+        }
+        gtk::style_context_add_provider_for_display(
+            &display,
+            &provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+        providers
+            .sheets
+            .insert(key.clone(), (sheet.clone(), provider));
+    }
+}
+
+fn css_provider(name: &str) -> gtk::CssProvider {
+    let provider = gtk::CssProvider::new();
+    let name = name.to_owned();
+    provider.connect_parsing_error(move |_, section, err| {
+        warn!("Failed to parse style sheet {name:?} at {section}: {err}");
+    });
+    provider
+}
+
+fn sync_window_styles(
+    styles: Query<(Entity, &GtkWindowStyle), Changed<GtkWindowStyle>>,
+    mut removed: RemovedComponents<GtkWindowStyle>,
+    gtk_windows: NonSend<GtkWindows>,
+    mut providers: NonSendMut<StyleProviders>,
+) {
+    let mut backgrounds_changed = false;
+
+    for entity in removed.read() {
+        let Some(old) = providers.windows.remove(&entity) else {
+            continue;
+        };
+        backgrounds_changed |= old.background_color.is_some();
+        if let Some(proxy) = gtk_windows.get(entity) {
+            for class in &old.classes {
+                proxy.gtk_window.remove_css_class(class);
+            }
+            proxy.gtk_window.remove_css_class(&background_class(entity));
+        }
+    }
+
+    for (entity, style) in &styles {
+        let Some(proxy) = gtk_windows.get(entity) else {
+            continue;
+        };
+        let old = providers.windows.insert(entity, style.clone());
+        let old = old.unwrap_or_default();
+
+        for class in &old.classes {
+            if !style.classes.contains(class) {
+                proxy.gtk_window.remove_css_class(class);
+            }
+        }
+        for class in &style.classes {
+            proxy.gtk_window.add_css_class(class);
+        }
+
+        if old.background_color != style.background_color {
+            backgrounds_changed = true;
+            let class = background_class(entity);
+            if style.background_color.is_some() {
+                proxy.gtk_window.add_css_class(&class);
+            } else {
+                proxy.gtk_window.remove_css_class(&class);
+            }
+        }
+    }
+
+    if backgrounds_changed {
+        update_backgrounds(&mut providers);
+    }
+}
+
+/// CSS class which selects a single window, to give it a background color.
+fn background_class(entity: Entity) -> String {
+    format!("bevy-gtk-window-{}", entity.to_bits())
+}
+
+fn update_backgrounds(providers: &mut StyleProviders) {
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+
+    let mut css = String::new();
+    for (entity, style) in &providers.windows {
+        let Some(color) = style.background_color else {
+            continue;
+        };
+        let Srgba {
+            red,
+            green,
+            blue,
+            alpha,
+        } = color.to_srgba();
+        _ = writeln!(
+            css,
+            "window.{} {{ background-color: rgba({}, {}, {}, {alpha}); }}",
+            background_class(*entity),
+            red * 255.0,
+            green * 255.0,
+            blue * 255.0,
+        );
+    }
+
+    let provider = providers.backgrounds.get_or_insert_with(|| {
+        let provider = css_provider("window backgrounds");
+        gtk::style_context_add_provider_for_display(
+            &display,
+            &provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+        provider
+    });
+    provider.load_from_string(&css);
+}
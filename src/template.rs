@@ -0,0 +1,183 @@
+use {
+    crate::MakeWidget, alloc::borrow::Cow, bevy_ecs::error::BevyError, gtk::prelude::*, log::error,
+    std::path::PathBuf,
+};
+
+/// [`MakeWidget`] which loads a widget tree from a [`gtk::Builder`] UI
+/// definition, and fills named placeholder widgets with other widgets, such as
+/// Bevy viewports.
+///
+/// Blueprint files must be compiled to `.ui` files with `blueprint-compiler`
+/// before they can be loaded here.
+///
+/// A placeholder is any widget in the UI definition with an `id`. When the
+/// template is made, the slot's widget is placed inside of the placeholder
+/// widget. The placeholder must be a [`gtk::Box`], or a widget with a `child`
+/// property, like `adw::Bin` or [`gtk::Frame`].
+///
+/// Use this as a [`MakeWidget`], e.g. in a [`GtkWindowContent`], or build it
+/// yourself with [`GtkTemplate::build`] to handle errors in the UI definition.
+///
+/// Lifecycle cleanup is automatic: viewports inside the template live for as
+/// long as their placeholders do, which is as long as the template's root
+/// widget lives.
+///
+/// # Examples
+///
+/// ```ignore
+/// let (viewport, widget_factory) = viewports.create();
+/// commands.entity(camera).insert(viewport);
+/// commands.entity(window).insert(GtkWindowContent::from(
+///     GtkTemplate::from_string(include_str!("editor.ui"), "root")
+///         .with_slot("game_view", widget_factory),
+/// ));
+/// ```
+///
+/// [`GtkWindowContent`]: crate::GtkWindowContent
+pub struct GtkTemplate {
+    source: TemplateSource,
+    root_id: Cow<'static, str>,
+    slots: Vec<(Cow<'static, str>, Box<dyn MakeWidget>)>,
+}
+
+#[derive(Debug)]
+enum TemplateSource {
+    String(Cow<'static, str>),
+    Resource(Cow<'static, str>),
+    File(PathBuf),
+}
+
+impl GtkTemplate {
+    /// Creates a template from a UI definition string.
+    ///
+    /// `root_id` is the ID of the object in the UI definition which will be
+    /// used as the widget made by this template.
+    #[must_use]
+    pub fn from_string(
+        ui: impl Into<Cow<'static, str>>,
+        root_id: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self::new(TemplateSource::String(ui.into()), root_id.into())
+    }
+
+    /// Creates a template from a UI definition stored at a [`gio::Resource`]
+    /// path.
+    ///
+    /// See [`GtkTemplate::from_string`].
+    #[must_use]
+    pub fn from_resource(
+        resource_path: impl Into<Cow<'static, str>>,
+        root_id: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self::new(
+            TemplateSource::Resource(resource_path.into()),
+            root_id.into(),
+        )
+    }
+
+    /// Creates a template from a UI definition file on disk.
+    ///
+    /// See [`GtkTemplate::from_string`].
+    #[must_use]
+    pub fn from_file(path: impl Into<PathBuf>, root_id: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(TemplateSource::File(path.into()), root_id.into())
+    }
+
+    fn new(source: TemplateSource, root_id: Cow<'static, str>) -> Self {
+        Self {
+            source,
+            root_id,
+            slots: Vec::new(),
+        }
+    }
+
+    /// Fills the placeholder with ID `placeholder_id` with the widget made by
+    /// `content`.
+    ///
+    /// For example, `content` may be a [`WidgetFactory`] for a Bevy viewport.
+    ///
+    /// [`WidgetFactory`]: crate::WidgetFactory
+    #[must_use]
+    pub fn with_slot(
+        mut self,
+        placeholder_id: impl Into<Cow<'static, str>>,
+        content: impl MakeWidget,
+    ) -> Self {
+        self.slots.push((placeholder_id.into(), Box::new(content)));
+        self
+    }
+}
+
+impl GtkTemplate {
+    /// Loads the UI definition and fills its placeholders, returning the root
+    /// widget.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the UI definition can't be loaded, the root or a placeholder
+    /// widget doesn't exist, or a placeholder can't hold a child.
+    pub fn build(self) -> Result<gtk::Widget, BevyError> {
+        let Self {
+            source,
+            root_id,
+            slots,
+        } = self;
+
+        let builder = gtk::Builder::new();
+        let result = match &source {
+            TemplateSource::String(ui) => builder.add_from_string(ui),
+            TemplateSource::Resource(path) => builder.add_from_resource(path),
+            TemplateSource::File(path) => builder.add_from_file(path),
+        };
+        if let Err(err) = result {
+            return Err(format!("failed to load GTK template from {source:?}: {err}").into());
+        }
+
+        let root = builder
+            .object::<gtk::Widget>(&*root_id)
+            .ok_or_else(|| format!("GTK template has no root widget with ID `{root_id}`"))?;
+
+        for (placeholder_id, content) in slots {
+            let placeholder = builder
+                .object::<gtk::Widget>(&*placeholder_id)
+                .ok_or_else(|| {
+                    format!("GTK template has no placeholder widget with ID `{placeholder_id}`")
+                })?;
+            fill_placeholder(&placeholder, &content.make())?;
+        }
+
+        Ok(root)
+    }
+}
+
+impl MakeWidget for GtkTemplate {
+    /// [Builds](GtkTemplate::build) the template, or makes a label saying that
+    /// it failed if it can't be built.
+    fn make(self: Box<Self>) -> gtk::Widget {
+        self.build().unwrap_or_else(|err| {
+            error!("Failed to build GTK template: {err}");
+            gtk::Label::builder()
+                .label("Failed to load UI")
+                .css_classes(["dim-label"])
+                .build()
+                .upcast()
+        })
+    }
+}
+
+fn fill_placeholder(placeholder: &gtk::Widget, content: &gtk::Widget) -> Result<(), BevyError> {
+    if let Some(placeholder) = placeholder.downcast_ref::<gtk::Box>() {
+        placeholder.append(content);
+        return Ok(());
+    }
+    if placeholder.find_property("child").is_some() {
+        placeholder.set_property("child", content);
+        return Ok(());
+    }
+
+    Err(format!(
+        "invalid placeholder widget {placeholder:?}; must be a `gtk::Box` or have a `child` \
+         property"
+    )
+    .into())
+}
@@ -0,0 +1,79 @@
+use {
+    crate::{GtkInitPlugin, GtkPlugin},
+    bevy_app::{TaskPoolPlugin, prelude::*},
+    bevy_diagnostic::FrameCountPlugin,
+    bevy_ecs::prelude::*,
+    bevy_time::TimePlugin,
+    bevy_window::WindowPlugin,
+};
+
+/// Application ID of apps made with [`test_app`].
+pub const TEST_APP_ID: &str = "io.github.aecsocket.BevyGtk.Test";
+
+/// Number of updates that an app made with [`test_app`] runs for, before it
+/// exits with [`AppExit::Success`].
+///
+/// Defaults to 8, which is enough for windows to be created, shown, and laid
+/// out. Insert this resource to run for longer.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct GtkTestUpdates(pub u32);
+
+/// Creates an app which runs the GTK plugin stack on a headless display
+/// server, so that you can test how your own plugins integrate with GTK in CI.
+///
+/// The app has [`GtkInitPlugin`], the plugins in Bevy's `MinimalPlugins`
+/// except the schedule runner, a [`WindowPlugin`] with a primary window, and
+/// a non-unique [`GtkPlugin`]. When [run](App::run), it creates its windows
+/// and updates [`GtkTestUpdates`] times, then exits.
+///
+/// Unless `GDK_BACKEND` is set, GDK prefers the Broadway backend, which
+/// renders into a `gtk4-broadwayd` server instead of a compositor, and falls
+/// back to any other display, e.g. Xvfb. Start the server before running your
+/// tests:
+///
+/// ```sh
+/// gtk4-broadwayd :5 &
+/// BROADWAY_DISPLAY=:5 cargo test
+/// ```
+///
+/// GTK can only be initialized once per process, on a single thread, but the
+/// default test harness runs each test on its own thread. Put tests which run
+/// this app in a test target with `harness = false`, and run a single app per
+/// process.
+///
+/// # Examples
+///
+/// ```ignore
+/// fn main() {
+///     let mut app = bevy_gtk::test_app();
+///     app.add_plugins(MyPlugin).insert_resource(GtkTestUpdates(32));
+///     assert_eq!(app.run(), AppExit::Success);
+/// }
+/// ```
+#[must_use]
+pub fn test_app() -> App {
+    if std::env::var_os("GDK_BACKEND").is_none() {
+        gdk::set_allowed_backends("broadway,*");
+    }
+
+    let mut app = App::new();
+    app.add_plugins((
+        GtkInitPlugin,
+        TaskPoolPlugin::default(),
+        FrameCountPlugin,
+        TimePlugin,
+        WindowPlugin::default(),
+        GtkPlugin::new(TEST_APP_ID).non_unique(),
+    ))
+    .insert_resource(GtkTestUpdates(8))
+    .add_systems(Last, count_test_updates);
+    app
+}
+
+fn count_test_updates(mut updates: ResMut<GtkTestUpdates>, mut exit: EventWriter<AppExit>) {
+    if updates.0 == 0 {
+        exit.write(AppExit::Success);
+    } else {
+        updates.0 -= 1;
+    }
+}
@@ -0,0 +1,111 @@
+#[cfg(feature = "adwaita")]
+use glib::clone;
+use {crate::GtkApplication, bevy_app::prelude::*, bevy_color::Srgba, bevy_ecs::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<SystemThemeChanged>()
+        .add_systems(PreStartup, setup_theme_forwarding)
+        .add_systems(PreUpdate, forward_theme_changes);
+}
+
+/// Desktop-wide theme preferences, as reported by GTK.
+///
+/// This is inserted as a resource at startup, and kept up to date as the user
+/// changes their preferences. When it changes, a [`SystemThemeChanged`] event
+/// is also sent.
+///
+/// With Adwaita, this is read from [`adw::StyleManager`]. Otherwise, this is
+/// read from [`gtk::Settings`], which doesn't know about accent colors.
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct SystemTheme {
+    /// Whether the app should use a dark color scheme.
+    pub dark: bool,
+    /// Whether the user has requested a high-contrast appearance.
+    pub high_contrast: bool,
+    /// User's preferred accent color, if the platform supports accent colors.
+    pub accent_color: Option<Srgba>,
+}
+
+/// Emitted when the [`SystemTheme`] changes.
+#[derive(Debug, Clone, Event)]
+pub struct SystemThemeChanged {
+    /// New theme.
+    pub theme: SystemTheme,
+}
+
+#[derive(Debug, Resource)]
+struct RxSystemTheme(async_channel::Receiver<SystemTheme>);
+
+// `NonSend` keeps this on the GTK thread
+fn setup_theme_forwarding(_: NonSend<GtkApplication>, mut commands: Commands) {
+    let (tx_theme, rx_theme) = async_channel::unbounded();
+    commands.insert_resource(RxSystemTheme(rx_theme));
+
+    #[cfg(feature = "adwaita")]
+    if adw::is_initialized() {
+        let style_manager = adw::StyleManager::default();
+        commands.insert_resource(adw_theme(&style_manager));
+
+        let send_theme = clone!(
+            #[strong]
+            tx_theme,
+            move |style_manager: &adw::StyleManager| {
+                _ = tx_theme.try_send(adw_theme(style_manager));
+            }
+        );
+        style_manager.connect_dark_notify(send_theme.clone());
+        style_manager.connect_high_contrast_notify(send_theme.clone());
+        style_manager.connect_accent_color_rgba_notify(send_theme);
+        return;
+    }
+
+    let Some(settings) = gtk::Settings::default() else {
+        return;
+    };
+    commands.insert_resource(gtk_theme(&settings));
+
+    let send_theme = move |settings: &gtk::Settings| {
+        _ = tx_theme.try_send(gtk_theme(settings));
+    };
+    settings.connect_gtk_application_prefer_dark_theme_notify(send_theme.clone());
+    settings.connect_gtk_theme_name_notify(send_theme);
+}
+
+#[cfg(feature = "adwaita")]
+fn adw_theme(style_manager: &adw::StyleManager) -> SystemTheme {
+    let accent_color = style_manager.system_supports_accent_colors().then(|| {
+        let rgba = style_manager.accent_color_rgba();
+        Srgba::new(rgba.red(), rgba.green(), rgba.blue(), rgba.alpha())
+    });
+    SystemTheme {
+        dark: style_manager.is_dark(),
+        high_contrast: style_manager.is_high_contrast(),
+        accent_color,
+    }
+}
+
+fn gtk_theme(settings: &gtk::Settings) -> SystemTheme {
+    SystemTheme {
+        dark: settings.is_gtk_application_prefer_dark_theme(),
+        high_contrast: settings
+            .gtk_theme_name()
+            .is_some_and(|name| name.contains("HighContrast")),
+        accent_color: None,
+    }
+}
+
+fn forward_theme_changes(
+    rx_theme: Option<Res<RxSystemTheme>>,
+    theme: Option<ResMut<SystemTheme>>,
+    mut changed_events: EventWriter<SystemThemeChanged>,
+) {
+    let (Some(rx_theme), Some(mut theme)) = (rx_theme, theme) else {
+        return;
+    };
+    while let Ok(new_theme) = rx_theme.0.try_recv() {
+        if *theme != new_theme {
+            theme.clone_from(&new_theme);
+            changed_events.write(SystemThemeChanged { theme: new_theme });
+        }
+    }
+}
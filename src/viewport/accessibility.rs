@@ -0,0 +1,209 @@
+use {
+    super::ViewportPrivate, bevy_app::prelude::*, bevy_ecs::prelude::*, bevy_math::Rect,
+    bevy_platform::collections::HashMap, gtk::prelude::*, log::trace,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<AccessibleNodeActivated>()
+        .add_systems(PostUpdate, (sync_nodes, forward_activations));
+}
+
+/// Publishes an accessibility node for something rendered inside of a
+/// viewport, like a Bevy UI button.
+///
+/// Viewports are a single opaque texture as far as GTK is concerned, so
+/// screen readers can't see anything rendered inside of them. Adding this
+/// component exposes a node to the accessibility tree (via AT-SPI) as a child
+/// of the viewport widget, with the given role, label and bounds.
+///
+/// When an assistive technology activates the node, an
+/// [`AccessibleNodeActivated`] event is sent with this entity.
+///
+/// Nodes are only published for viewports displayed with
+/// [`WidgetFactory::make`](crate::WidgetFactory::make).
+#[derive(Debug, Clone, Component)]
+pub struct AccessibleNode {
+    /// Entity of the viewport that this node is rendered in.
+    ///
+    /// See [`GtkViewport::entity`](crate::GtkViewport::entity).
+    pub viewport: Entity,
+    /// Role of this node, like [`gtk::AccessibleRole::Button`].
+    pub role: gtk::AccessibleRole,
+    /// Name of this node, which is read out by screen readers.
+    pub label: String,
+    /// Longer description of this node.
+    pub description: Option<String>,
+    /// Bounds of this node relative to the top-left of the viewport, in
+    /// logical pixels.
+    pub bounds: Rect,
+}
+
+/// Emitted when an assistive technology activates an [`AccessibleNode`].
+#[derive(Debug, Clone, Event)]
+pub struct AccessibleNodeActivated {
+    /// Entity of the node which was activated.
+    pub node: Entity,
+}
+
+#[derive(Debug)]
+enum NodeUpdate {
+    Set(Entity, AccessibleNode),
+    Remove(Entity),
+}
+
+/// Bevy side of a viewport's accessibility bridge.
+#[derive(Debug)]
+pub(super) struct AccessibilityBridge {
+    tx_update: async_channel::Sender<NodeUpdate>,
+    rx_activated: async_channel::Receiver<Entity>,
+}
+
+/// GTK side of a viewport's accessibility bridge.
+#[derive(Debug)]
+pub(super) struct AccessibilityWidget {
+    rx_update: async_channel::Receiver<NodeUpdate>,
+    tx_activated: async_channel::Sender<Entity>,
+}
+
+pub(super) fn bridge() -> (AccessibilityBridge, AccessibilityWidget) {
+    let (tx_update, rx_update) = async_channel::unbounded();
+    let (tx_activated, rx_activated) = async_channel::unbounded();
+    (
+        AccessibilityBridge {
+            tx_update,
+            rx_activated,
+        },
+        AccessibilityWidget {
+            rx_update,
+            tx_activated,
+        },
+    )
+}
+
+fn sync_nodes(
+    nodes: Query<(Entity, &AccessibleNode), Changed<AccessibleNode>>,
+    mut removed: RemovedComponents<AccessibleNode>,
+    viewports: Query<&ViewportPrivate>,
+    mut node_viewports: Local<HashMap<Entity, Entity>>,
+) {
+    let send = |viewport: Entity, update: NodeUpdate| {
+        if let Ok(viewport) = viewports.get(viewport) {
+            _ = viewport.accessibility.tx_update.try_send(update);
+        }
+    };
+
+    for node in removed.read() {
+        if let Some(viewport) = node_viewports.remove(&node) {
+            send(viewport, NodeUpdate::Remove(node));
+        }
+    }
+
+    for (node, accessible) in &nodes {
+        let old_viewport = node_viewports.insert(node, accessible.viewport);
+        if let Some(old_viewport) = old_viewport.filter(|&old| old != accessible.viewport) {
+            send(old_viewport, NodeUpdate::Remove(node));
+        }
+        send(
+            accessible.viewport,
+            NodeUpdate::Set(node, accessible.clone()),
+        );
+    }
+}
+
+fn forward_activations(
+    viewports: Query<&ViewportPrivate>,
+    mut activated: EventWriter<AccessibleNodeActivated>,
+) {
+    for viewport in &viewports {
+        while let Ok(node) = viewport.accessibility.rx_activated.try_recv() {
+            activated.write(AccessibleNodeActivated { node });
+        }
+    }
+}
+
+impl AccessibilityWidget {
+    /// Wraps the viewport widget in an overlay which holds the accessible
+    /// nodes.
+    ///
+    /// Each node is an invisible [`gtk::Button`] with the node's role, placed
+    /// at the node's bounds. Buttons give us an activation action for free, and
+    /// they don't take any pointer input away from the viewport.
+    pub fn wrap(self, content: &gtk::Widget) -> gtk::Widget {
+        let Self {
+            rx_update,
+            tx_activated,
+        } = self;
+
+        let fixed = gtk::Fixed::builder().can_target(false).build();
+        let overlay = gtk::Overlay::builder().child(content).build();
+        overlay.add_overlay(&fixed);
+
+        let mut buttons = HashMap::<Entity, gtk::Button>::default();
+        overlay.add_tick_callback(move |_, _| {
+            while let Ok(update) = rx_update.try_recv() {
+                match update {
+                    NodeUpdate::Set(node, accessible) => {
+                        // roles can only be set on construction,
+                        // so if the role changes we make a new button
+                        let role_changed = buttons
+                            .get(&node)
+                            .is_some_and(|button| button.accessible_role() != accessible.role);
+                        if role_changed {
+                            if let Some(button) = buttons.remove(&node) {
+                                fixed.remove(&button);
+                            }
+                        }
+
+                        let button = buttons.entry(node).or_insert_with(|| {
+                            trace!("Creating accessible node for {node}");
+                            let button = make_node_button(accessible.role);
+                            let tx_activated = tx_activated.clone();
+                            button.connect_clicked(move |_| {
+                                _ = tx_activated.try_send(node);
+                            });
+                            fixed.put(&button, 0.0, 0.0);
+                            button
+                        });
+                        update_node_button(&fixed, button, &accessible);
+                    }
+                    NodeUpdate::Remove(node) => {
+                        if let Some(button) = buttons.remove(&node) {
+                            trace!("Removing accessible node for {node}");
+                            fixed.remove(&button);
+                        }
+                    }
+                }
+            }
+            glib::ControlFlow::Continue
+        });
+
+        overlay.upcast()
+    }
+}
+
+fn make_node_button(role: gtk::AccessibleRole) -> gtk::Button {
+    gtk::Button::builder()
+        .accessible_role(role)
+        .opacity(0.0)
+        .can_target(false)
+        .focusable(false)
+        .build()
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "node sizes are relatively small"
+)]
+fn update_node_button(fixed: &gtk::Fixed, button: &gtk::Button, accessible: &AccessibleNode) {
+    let bounds = accessible.bounds;
+    fixed.move_(button, f64::from(bounds.min.x), f64::from(bounds.min.y));
+    button.set_size_request(bounds.width() as i32, bounds.height() as i32);
+
+    button.update_property(&[gtk::accessible::Property::Label(&accessible.label)]);
+    match &accessible.description {
+        Some(description) => {
+            button.update_property(&[gtk::accessible::Property::Description(description)]);
+        }
+        None => button.reset_property(gtk::AccessibleProperty::Description),
+    }
+}
@@ -0,0 +1,223 @@
+use {
+    bevy_render::settings::WgpuSettings,
+    bevy_utils::default,
+    core::{
+        ffi::{CStr, c_char, c_void},
+        mem, ptr,
+    },
+    gdk::prelude::*,
+    khronos_egl as egl,
+    log::{debug, info},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Which GPU Bevy renders viewports on.
+///
+/// On systems with multiple GPUs, like laptops with an integrated and a
+/// discrete GPU, Bevy may pick a different GPU to the one which GTK renders
+/// its windows on. Dmabufs then have to be copied between GPUs, which is slow,
+/// or can't be imported at all.
+///
+/// Bevy picks its adapter while its `RenderPlugin` is built, from
+/// [`WgpuSettings::adapter_name`]. [`GtkDefaultPlugins`] applies this to its
+/// `RenderPlugin` for you, and uses [`GtkAdapterSelection::Display`] by
+/// default. If you add `DefaultPlugins` yourself, apply it to the
+/// `RenderPlugin` with [`GtkAdapterSelection::wgpu_settings`]:
+///
+/// ```ignore
+/// App::new().add_plugins((
+///     GtkInitPlugin,
+///     DefaultPlugins
+///         .build()
+///         .disable::<WinitPlugin>()
+///         .set(RenderPlugin {
+///             render_creation: GtkAdapterSelection::Display.wgpu_settings().into(),
+///             ..default()
+///         }),
+///     GtkPlugin::new(APP_ID),
+/// ));
+/// ```
+///
+/// If `WGPU_ADAPTER_NAME` is set, Bevy uses that adapter instead.
+///
+/// [`GtkDefaultPlugins`]: crate::GtkDefaultPlugins
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum GtkAdapterSelection {
+    /// Uses the GPU which GDK renders with on the default display.
+    ///
+    /// This is the device behind GDK's EGL display, which on Wayland is the
+    /// compositor's main device. If it can't be found, e.g. because GDK can't
+    /// use OpenGL, Bevy picks an adapter itself.
+    #[default]
+    Display,
+    /// Uses the adapter with this name, like
+    /// [`WgpuSettings::adapter_name`].
+    Name(String),
+    /// Leaves picking the adapter to Bevy.
+    Bevy,
+}
+
+impl GtkAdapterSelection {
+    /// Sets [`WgpuSettings::adapter_name`] to the adapter picked by this
+    /// selection.
+    ///
+    /// [`GtkAdapterSelection::Display`] asks GDK which GPU it renders with, so
+    /// this initializes GTK. Call this on the thread that runs the app.
+    pub fn apply(&self, settings: &mut WgpuSettings) {
+        let adapter_name = match self {
+            Self::Display => display_adapter_name(),
+            Self::Name(name) => Some(name.clone()),
+            Self::Bevy => None,
+        };
+        if let Some(adapter_name) = adapter_name {
+            info!("Selecting render adapter {adapter_name:?}");
+            settings.adapter_name = Some(adapter_name);
+        }
+    }
+
+    /// Creates Bevy's default [`WgpuSettings`], with this selection
+    /// [applied](GtkAdapterSelection::apply).
+    #[must_use]
+    pub fn wgpu_settings(&self) -> WgpuSettings {
+        let mut settings = WgpuSettings::default();
+        self.apply(&mut settings);
+        settings
+    }
+}
+
+/// Finds the name of the Vulkan adapter for the GPU which GDK renders with.
+fn display_adapter_name() -> Option<String> {
+    if let Err(err) = gtk::init() {
+        debug!("Failed to initialize GTK, can't find the display's GPU: {err}");
+        return None;
+    }
+    let display = gdk::Display::default()?;
+    let node = display_drm_node(&display)?;
+    let (vendor, device) = drm_pci_id(&node)?;
+
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::VULKAN,
+        ..default()
+    });
+    let adapters = instance.enumerate_adapters(wgpu::Backends::VULKAN);
+    // hybrid setups may have 2 of the same GPU, but then it doesn't matter
+    // which one we pick
+    let adapter = adapters.iter().find(|adapter| {
+        let info = adapter.get_info();
+        info.vendor == vendor && info.device == device
+    });
+    if adapter.is_none() {
+        debug!(
+            "No Vulkan adapter for display GPU {vendor:04x}:{device:04x} at {}",
+            node.display()
+        );
+    }
+    adapter.map(|adapter| adapter.get_info().name)
+}
+
+// from `EGL_EXT_device_query` and `EGL_EXT_device_drm(_render_node)`
+const EGL_DEVICE_EXT: egl::Int = 0x322C;
+const EGL_DRM_DEVICE_FILE_EXT: egl::Int = 0x3233;
+const EGL_DRM_RENDER_NODE_FILE_EXT: egl::Int = 0x3377;
+
+type QueryDisplayAttribExt =
+    unsafe extern "system" fn(egl::EGLDisplay, egl::Int, *mut egl::Attrib) -> egl::Boolean;
+type QueryDeviceStringExt = unsafe extern "system" fn(*mut c_void, egl::Int) -> *const c_char;
+
+/// Gets the path of the DRM node which GDK's EGL display renders with.
+fn display_drm_node(display: &gdk::Display) -> Option<PathBuf> {
+    if let Err(err) = display.prepare_gl() {
+        debug!("GDK can't use OpenGL, can't find the display's GPU: {err}");
+        return None;
+    }
+    let egl_display = if let Some(display) = display.downcast_ref::<gdk_wayland::WaylandDisplay>() {
+        display.egl_display()
+    } else if let Some(display) = display.downcast_ref::<gdk_x11::X11Display>() {
+        display.egl_display()
+    } else {
+        None
+    };
+    let Some(egl_display) = egl_display else {
+        debug!("GDK display has no EGL display, can't find the display's GPU");
+        return None;
+    };
+
+    let client_extensions = egl::API.query_string(None, egl::EXTENSIONS).ok()?;
+    if !has_extension(client_extensions, "EGL_EXT_device_query") {
+        debug!("EGL doesn't support `EGL_EXT_device_query`, can't find the display's GPU");
+        return None;
+    }
+    let query_display_attrib = egl::API.get_proc_address("eglQueryDisplayAttribEXT")?;
+    let query_device_string = egl::API.get_proc_address("eglQueryDeviceStringEXT")?;
+    // SAFETY: these are the signatures of these functions in the EGL spec
+    let (query_display_attrib, query_device_string) = unsafe {
+        (
+            mem::transmute::<extern "system" fn(), QueryDisplayAttribExt>(query_display_attrib),
+            mem::transmute::<extern "system" fn(), QueryDeviceStringExt>(query_device_string),
+        )
+    };
+
+    let mut device: egl::Attrib = 0;
+    // SAFETY: `egl_display` is initialized, since GDK has prepared GL on it
+    if unsafe { query_display_attrib(egl_display.as_ptr(), EGL_DEVICE_EXT, &raw mut device) }
+        != egl::TRUE
+    {
+        debug!("Failed to query the EGL device of GDK's display");
+        return None;
+    }
+    let device = ptr::with_exposed_provenance_mut::<c_void>(device);
+    let query_string = |name| {
+        // SAFETY: `device` is a valid device
+        let value = unsafe { query_device_string(device, name) };
+        if value.is_null() {
+            None
+        } else {
+            // SAFETY: EGL returns static, nul-terminated strings
+            Some(unsafe { CStr::from_ptr(value) })
+        }
+    };
+
+    let device_extensions = query_string(egl::EXTENSIONS)?;
+    let query_node = |extension, name| {
+        if has_extension(device_extensions, extension) {
+            query_string(name)
+        } else {
+            None
+        }
+    };
+    // render nodes are what Vulkan drivers open, but either node leads to the
+    // same GPU
+    let node = query_node(
+        "EGL_EXT_device_drm_render_node",
+        EGL_DRM_RENDER_NODE_FILE_EXT,
+    )
+    .or_else(|| query_node("EGL_EXT_device_drm", EGL_DRM_DEVICE_FILE_EXT));
+    let Some(node) = node else {
+        debug!("EGL device of GDK's display has no DRM node");
+        return None;
+    };
+    Some(PathBuf::from(node.to_string_lossy().into_owned()))
+}
+
+fn has_extension(extensions: &CStr, name: &str) -> bool {
+    extensions
+        .to_string_lossy()
+        .split_ascii_whitespace()
+        .any(|extension| extension == name)
+}
+
+/// Gets the PCI vendor and device ID of the GPU behind a DRM node, like
+/// `/dev/dri/renderD128`.
+fn drm_pci_id(node: &Path) -> Option<(u32, u32)> {
+    let device_path = Path::new("/sys/class/drm")
+        .join(node.file_name()?)
+        .join("device");
+    let read_id = |file: &str| {
+        let id = fs::read_to_string(device_path.join(file)).ok()?;
+        u32::from_str_radix(id.trim().trim_start_matches("0x"), 16).ok()
+    };
+    Some((read_id("vendor")?, read_id("device")?))
+}
@@ -0,0 +1,96 @@
+use {
+    core::cell::RefCell,
+    gtk::{prelude::*, subclass::prelude::*},
+};
+
+glib::wrapper! {
+    /// Widget with a single child, which calls a function whenever GTK
+    /// allocates it a new size.
+    ///
+    /// See [`ViewportSizeDetection::Allocation`](super::ViewportSizeDetection::Allocation).
+    pub(super) struct AllocationBin(ObjectSubclass<imp::AllocationBin>)
+        @extends gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl AllocationBin {
+    /// Wraps `child`, calling `on_allocate` with this widget, and its width
+    /// and height in logical pixels, every time it's allocated.
+    pub fn new(
+        child: &impl IsA<gtk::Widget>,
+        on_allocate: impl Fn(&Self, i32, i32) + 'static,
+    ) -> Self {
+        let widget = glib::Object::builder::<Self>()
+            .property("hexpand", true)
+            .property("vexpand", true)
+            .build();
+        child.set_parent(&widget);
+        widget.imp().child.replace(Some(child.clone().upcast()));
+        widget
+            .imp()
+            .on_allocate
+            .replace(Some(Box::new(on_allocate)));
+        widget
+    }
+}
+
+mod imp {
+    use super::*;
+
+    type OnAllocate = Box<dyn Fn(&super::AllocationBin, i32, i32)>;
+
+    #[derive(Default)]
+    pub struct AllocationBin {
+        pub(super) child: RefCell<Option<gtk::Widget>>,
+        pub(super) on_allocate: RefCell<Option<OnAllocate>>,
+    }
+
+    impl core::fmt::Debug for AllocationBin {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.debug_struct("AllocationBin")
+                .field("child", &self.child)
+                .finish_non_exhaustive()
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AllocationBin {
+        const NAME: &'static str = "BevyGtkAllocationBin";
+        type Type = super::AllocationBin;
+        type ParentType = gtk::Widget;
+    }
+
+    impl ObjectImpl for AllocationBin {
+        fn dispose(&self) {
+            self.on_allocate.take();
+            if let Some(child) = self.child.take() {
+                child.unparent();
+            }
+        }
+    }
+
+    impl WidgetImpl for AllocationBin {
+        fn request_mode(&self) -> gtk::SizeRequestMode {
+            self.child
+                .borrow()
+                .as_ref()
+                .map_or(gtk::SizeRequestMode::ConstantSize, WidgetExt::request_mode)
+        }
+
+        fn measure(&self, orientation: gtk::Orientation, for_size: i32) -> (i32, i32, i32, i32) {
+            self.child
+                .borrow()
+                .as_ref()
+                .map_or((0, 0, -1, -1), |child| child.measure(orientation, for_size))
+        }
+
+        fn size_allocate(&self, width: i32, height: i32, baseline: i32) {
+            if let Some(child) = &*self.child.borrow() {
+                child.allocate(width, height, baseline, None);
+            }
+            if let Some(on_allocate) = &*self.on_allocate.borrow() {
+                on_allocate(&self.obj(), width, height);
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use {
+    super::{GtkCanvas, GtkViewport, GtkViewports},
+    crate::{GtkAdoptedWindow, GtkRetainedWindowContent, GtkWindowContent},
+    bevy_app::prelude::*,
+    bevy_camera::{Camera, RenderTarget},
+    bevy_ecs::prelude::*,
+    bevy_window::{PrimaryWindow, Window, WindowRef},
+    log::debug,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        PostUpdate,
+        wrap_window_cameras.before(super::sync_viewport_and_camera),
+    );
+}
+
+/// Viewport which was made as the content of this window automatically,
+/// because a camera renders to the window.
+///
+/// Under GTK, Bevy can't render to a [`Window`] directly. So when a [`Camera`]
+/// without a [`GtkViewport`] targets a window which has no content of its own
+/// (no [`GtkWindowContent`], [`GtkRetainedWindowContent`] or
+/// [`GtkAdoptedWindow`]), a viewport is created, inserted into the camera, and
+/// set as the window's content. This makes the common case of a game with a
+/// single window work without any GTK code:
+///
+/// ```ignore
+/// commands.spawn(Camera3d::default());
+/// ```
+///
+/// Every camera which targets the same window renders into the same viewport,
+/// so e.g. a UI camera layered over a 3D camera keeps working. This component
+/// is inserted into the window entity, and holds the viewport.
+#[derive(Debug, Clone, Component)]
+pub struct GtkWindowViewport(pub GtkViewport);
+
+fn wrap_window_cameras(
+    cameras: Query<(Entity, &Camera), (Without<GtkViewport>, Without<GtkCanvas>)>,
+    windows: Query<
+        Option<&GtkWindowViewport>,
+        (
+            With<Window>,
+            Without<GtkWindowContent>,
+            Without<GtkRetainedWindowContent>,
+            Without<GtkAdoptedWindow>,
+        ),
+    >,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    mut viewports: GtkViewports,
+    mut commands: Commands,
+) {
+    let mut made = Vec::<(Entity, GtkViewport)>::new();
+    for (camera_entity, camera) in &cameras {
+        let RenderTarget::Window(window_ref) = &camera.target else {
+            continue;
+        };
+        let window = match window_ref {
+            WindowRef::Primary => {
+                let Ok(window) = primary_window.single() else {
+                    continue;
+                };
+                window
+            }
+            WindowRef::Entity(window) => *window,
+        };
+        let Ok(window_viewport) = windows.get(window) else {
+            continue;
+        };
+
+        let existing = window_viewport
+            .map(|GtkWindowViewport(viewport)| viewport)
+            .or_else(|| {
+                made.iter()
+                    .find(|(made_window, _)| *made_window == window)
+                    .map(|(_, viewport)| viewport)
+            });
+        let viewport = if let Some(viewport) = existing {
+            viewport.clone()
+        } else {
+            debug!(
+                "Making a viewport as the content of window {window} for camera {camera_entity}"
+            );
+            let (viewport, widget_factory) = viewports.create();
+            commands.entity(window).insert((
+                GtkWindowViewport(viewport.clone()),
+                GtkWindowContent::from(move || widget_factory.make()),
+            ));
+            made.push((window, viewport.clone()));
+            viewport
+        };
+        commands.entity(camera_entity).insert(viewport);
+    }
+}
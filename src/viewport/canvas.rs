@@ -0,0 +1,493 @@
+use {
+    super::{
+        AtomicSize, GtkViewport, GtkViewports, LoadingPlaceholder, ViewportConfig,
+        ViewportSizeDetection, VisibleRect,
+    },
+    crate::{GtkCommands, GtkContext},
+    alloc::sync::Arc,
+    atomic_float::{AtomicF32, AtomicF64},
+    bevy_app::prelude::*,
+    bevy_camera::{Camera, CameraUpdateSystems, SubCameraView},
+    bevy_ecs::prelude::*,
+    bevy_math::{URect, UVec2},
+    bevy_platform::collections::{HashMap, HashSet},
+    bevy_render::sync_world::RenderEntity,
+    core::{
+        cell::RefCell,
+        mem,
+        sync::atomic::{self, AtomicU64},
+    },
+    glib::clone,
+    gtk::prelude::*,
+    log::{debug, trace},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        PostUpdate,
+        update_canvas_tiles
+            .before(super::sync_viewport_and_camera)
+            .before(CameraUpdateSystems),
+    );
+}
+
+/// Canvas which Bevy renders in tiles, for apps like node editors where the
+/// content can be far bigger than any texture should be.
+///
+/// Insert this into a camera entity. Instead of rendering into one image the
+/// size of the whole canvas, the camera is used as a template: for every tile
+/// of [`GtkCanvas::tile_size`] physical pixels which is scrolled into view, a
+/// tile camera is spawned with a copy of the template's components, and
+/// renders its part of the full view into its own small viewport through
+/// [`Camera::sub_camera_view`]. Tiles which scroll out of view are despawned
+/// again, so only the visible tiles cost any memory. The template camera
+/// itself is deactivated.
+///
+/// The GTK side is made with [`CanvasWidgetFactory::make`], which gives a
+/// [`gtk::ScrolledWindow`] that composes the tiles. Scroll to pan, and hold
+/// <kbd>Ctrl</kbd> while scrolling to zoom.
+///
+/// Components of the template camera, like its transform and projection, are
+/// copied to the tiles every frame, so change the template and not the tiles.
+///
+/// # Examples
+///
+/// ```ignore
+/// fn setup(mut viewports: GtkViewports, mut commands: Commands) {
+///     let (canvas, widget_factory) = viewports.create_canvas(UVec2::new(20_000, 20_000));
+///     commands.spawn((Camera2d, canvas));
+///     commands.spawn((
+///         Window::default(),
+///         GtkWindowContent::from(move || widget_factory.make()),
+///     ));
+/// }
+/// ```
+#[derive(Debug, Component)]
+pub struct GtkCanvas {
+    id: u64,
+    shared: Arc<CanvasShared>,
+    tile_size: u32,
+    /// Tile cameras which are alive, and where their widgets are placed, by
+    /// tile index.
+    tiles: HashMap<(u32, u32), (Entity, TilePlacement)>,
+    /// Size of the content in physical pixels that the tiles were placed for.
+    content_size: Option<UVec2>,
+    /// Whether every tile has to be made again, since the tile size changed.
+    relayout: bool,
+}
+
+/// Where the widget of a tile is placed in the canvas, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TilePlacement {
+    left: f64,
+    top: f64,
+    width: i32,
+    height: i32,
+}
+
+/// State shared between a [`GtkCanvas`] and its widget.
+#[derive(Debug)]
+struct CanvasShared {
+    /// Size of the canvas in logical pixels, at a zoom of `1.0`.
+    size: AtomicSize,
+    zoom: AtomicF32,
+    scale_factor: AtomicF64,
+    /// Part of the content which is scrolled into view, in physical pixels.
+    visible_rect: VisibleRect,
+}
+
+/// Marks a camera which renders a tile of a [`GtkCanvas`].
+#[derive(Debug, Component)]
+struct CanvasTile {
+    canvas: Entity,
+}
+
+static NEXT_CANVAS_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Smallest and largest [`GtkCanvas::zoom`].
+const ZOOM_RANGE: (f32, f32) = (0.05, 20.0);
+
+impl GtkCanvas {
+    /// Default [`GtkCanvas::tile_size`].
+    pub const DEFAULT_TILE_SIZE: u32 = 512;
+
+    /// Side length of each tile in physical pixels.
+    #[must_use]
+    pub const fn tile_size(&self) -> u32 {
+        self.tile_size
+    }
+
+    /// Sets [`GtkCanvas::tile_size`].
+    ///
+    /// Bigger tiles mean fewer cameras, but more pixels rendered outside of
+    /// the visible area.
+    pub fn set_tile_size(&mut self, tile_size: u32) {
+        self.tile_size = tile_size.max(1);
+        self.relayout = true;
+    }
+
+    /// Size of the canvas in logical pixels, at a zoom of `1.0`.
+    #[must_use]
+    pub fn size(&self) -> UVec2 {
+        let (width, height) = self.shared.size.load();
+        UVec2::new(width, height)
+    }
+
+    /// Sets [`GtkCanvas::size`].
+    pub fn set_size(&self, size: UVec2) {
+        self.shared.size.store(size.x, size.y);
+    }
+
+    /// How far the canvas is zoomed in.
+    #[must_use]
+    pub fn zoom(&self) -> f32 {
+        self.shared.zoom.load(atomic::Ordering::SeqCst)
+    }
+
+    /// Sets [`GtkCanvas::zoom`].
+    pub fn set_zoom(&self, zoom: f32) {
+        self.shared
+            .zoom
+            .store(clamp_zoom(zoom), atomic::Ordering::SeqCst);
+    }
+
+    /// Number of tiles which are currently rendered.
+    #[must_use]
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+}
+
+fn clamp_zoom(zoom: f32) -> f32 {
+    if zoom.is_finite() {
+        zoom.clamp(ZOOM_RANGE.0, ZOOM_RANGE.1)
+    } else {
+        1.0
+    }
+}
+
+/// Makes the GTK side of a [`GtkCanvas`].
+#[derive(Debug)]
+pub struct CanvasWidgetFactory {
+    id: u64,
+    shared: Arc<CanvasShared>,
+}
+
+impl GtkViewports<'_, '_> {
+    /// Creates a tiled canvas of `size` logical pixels, exposing the Bevy
+    /// [`GtkCanvas`] and GTK [`CanvasWidgetFactory`] for it.
+    ///
+    /// See [`GtkCanvas`].
+    pub fn create_canvas(&mut self, size: UVec2) -> (GtkCanvas, CanvasWidgetFactory) {
+        let id = NEXT_CANVAS_ID.fetch_add(1, atomic::Ordering::Relaxed);
+        let shared = Arc::new(CanvasShared {
+            size: AtomicSize::default(),
+            zoom: AtomicF32::new(1.0),
+            scale_factor: AtomicF64::new(1.0),
+            visible_rect: VisibleRect::default(),
+        });
+        shared.size.store(size.x, size.y);
+        (
+            GtkCanvas {
+                id,
+                shared: shared.clone(),
+                tile_size: GtkCanvas::DEFAULT_TILE_SIZE,
+                tiles: HashMap::new(),
+                content_size: None,
+                relayout: false,
+            },
+            CanvasWidgetFactory { id, shared },
+        )
+    }
+}
+
+thread_local! {
+    /// GTK side of the canvases which are alive, by canvas ID.
+    static CANVAS_WIDGETS: RefCell<HashMap<u64, CanvasWidget>> =
+        RefCell::new(HashMap::default());
+}
+
+#[derive(Debug)]
+struct CanvasWidget {
+    fixed: glib::WeakRef<gtk::Fixed>,
+    tiles: HashMap<(u32, u32), gtk::Widget>,
+}
+
+impl CanvasWidgetFactory {
+    /// Makes the widget which composes the tiles of the canvas.
+    ///
+    /// Must be called on the GTK thread.
+    #[must_use]
+    pub fn make(self) -> gtk::Widget {
+        let Self { id, shared } = self;
+
+        let fixed = gtk::Fixed::new();
+        let scrolled = gtk::ScrolledWindow::builder()
+            .child(&fixed)
+            .hexpand(true)
+            .vexpand(true)
+            .build();
+        CANVAS_WIDGETS.with_borrow_mut(|canvases| {
+            canvases.retain(|_, canvas| canvas.fixed.upgrade().is_some());
+            canvases.insert(
+                id,
+                CanvasWidget {
+                    fixed: fixed.downgrade(),
+                    tiles: HashMap::new(),
+                },
+            );
+        });
+
+        let zoom_controller =
+            gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
+        zoom_controller.connect_scroll(clone!(
+            #[strong]
+            shared,
+            move |controller, _, dy| {
+                if !controller
+                    .current_event_state()
+                    .contains(gdk::ModifierType::CONTROL_MASK)
+                {
+                    return glib::Propagation::Proceed;
+                }
+                let zoom = shared.zoom.load(atomic::Ordering::SeqCst);
+                #[expect(clippy::cast_possible_truncation, reason = "scroll deltas are small")]
+                let zoom = clamp_zoom(zoom * 1.1_f32.powf(-dy as f32));
+                shared.zoom.store(zoom, atomic::Ordering::SeqCst);
+                glib::Propagation::Stop
+            }
+        ));
+        // zoom before the scrolled window scrolls
+        zoom_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+        scrolled.add_controller(zoom_controller);
+
+        scrolled.add_tick_callback(move |scrolled, _| {
+            let (width, height) = shared.size.load();
+            let zoom = f64::from(shared.zoom.load(atomic::Ordering::SeqCst));
+            #[expect(
+                clippy::cast_possible_truncation,
+                reason = "canvas sizes are relatively small"
+            )]
+            let content_size = (
+                (f64::from(width) * zoom).round() as i32,
+                (f64::from(height) * zoom).round() as i32,
+            );
+            if fixed.size_request() != content_size {
+                fixed.set_size_request(content_size.0, content_size.1);
+            }
+
+            let Some(scale) = super::widget_scale(scrolled.upcast_ref()) else {
+                return glib::ControlFlow::Continue;
+            };
+            shared.scale_factor.store(scale, atomic::Ordering::SeqCst);
+
+            let (hadjustment, vadjustment) = (scrolled.hadjustment(), scrolled.vadjustment());
+            #[expect(
+                clippy::cast_sign_loss,
+                clippy::cast_possible_truncation,
+                reason = "value is clamped to be non-negative, and widget sizes are relatively \
+                          small"
+            )]
+            let to_physical = |value: f64| (value * scale).max(0.0) as u32;
+            let visible = URect::new(
+                to_physical(hadjustment.value().floor()),
+                to_physical(vadjustment.value().floor()),
+                to_physical((hadjustment.value() + hadjustment.page_size()).ceil()),
+                to_physical((vadjustment.value() + vadjustment.page_size()).ceil()),
+            );
+            shared
+                .visible_rect
+                .store((scrolled.is_mapped() && !visible.is_empty()).then_some(visible));
+            glib::ControlFlow::Continue
+        });
+
+        scrolled.upcast()
+    }
+}
+
+#[expect(
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    reason = "sizes are non-negative, and canvas sizes are relatively small"
+)]
+fn update_canvas_tiles(
+    mut canvases: Query<(Entity, &mut GtkCanvas, &mut Camera)>,
+    tiles: Query<(Entity, &CanvasTile, &GtkViewport)>,
+    mut viewports: GtkViewports,
+    mut gtk_commands: GtkCommands,
+    mut commands: Commands,
+) {
+    // tiles of canvases which no longer exist
+    for (tile_camera, tile, viewport) in &tiles {
+        if !canvases.contains(tile.canvas) {
+            viewports.destroy(viewport);
+            commands.entity(tile_camera).despawn();
+        }
+    }
+
+    for (canvas_entity, mut canvas, mut template) in &mut canvases {
+        if template.is_active {
+            template.is_active = false;
+        }
+
+        let (width, height) = canvas.shared.size.load();
+        let zoom = canvas.zoom();
+        let scale = canvas.shared.scale_factor.load(atomic::Ordering::SeqCst) as f32;
+        let content_size = UVec2::new(
+            (width as f32 * zoom * scale).round() as u32,
+            (height as f32 * zoom * scale).round() as u32,
+        )
+        .max(UVec2::ONE);
+        let tile_size = canvas.tile_size;
+
+        // tiles keep their index when the content is resized, e.g. while
+        // zooming, and only have their sub view and widget moved, so that
+        // they keep showing their last frame until they render the next one
+        let resized = canvas.content_size.replace(content_size) != Some(content_size);
+        let relayout = mem::take(&mut canvas.relayout);
+
+        let mut wanted = HashSet::new();
+        if let Some(visible) = canvas.shared.visible_rect.load() {
+            let max = visible.max.min(content_size);
+            let min = visible.min.min(max);
+            if min.x < max.x && min.y < max.y {
+                for y in (min.y / tile_size)..=((max.y - 1) / tile_size) {
+                    for x in (min.x / tile_size)..=((max.x - 1) / tile_size) {
+                        wanted.insert((x, y));
+                    }
+                }
+            }
+        }
+
+        let id = canvas.id;
+        let mut removed = Vec::new();
+        canvas.tiles.retain(|&index, &mut (tile_camera, _)| {
+            let keep = !relayout && wanted.contains(&index);
+            if !keep {
+                removed.push((index, tile_camera));
+            }
+            keep
+        });
+        for (index, tile_camera) in removed {
+            trace!("Removing tile {index:?} of canvas {canvas_entity}");
+            if let Ok((_, _, viewport)) = tiles.get(tile_camera) {
+                viewports.destroy(viewport);
+            }
+            commands.entity(tile_camera).despawn();
+            gtk_commands.queue(move |_: &mut GtkContext| {
+                CANVAS_WIDGETS.with_borrow_mut(|canvases| {
+                    let Some(canvas) = canvases.get_mut(&id) else {
+                        return;
+                    };
+                    if let (Some(fixed), Some(widget)) =
+                        (canvas.fixed.upgrade(), canvas.tiles.remove(&index))
+                    {
+                        fixed.remove(&widget);
+                    }
+                });
+            });
+        }
+
+        for &(x, y) in &wanted {
+            let origin = UVec2::new(x, y) * tile_size;
+            let size = (content_size - origin).min(UVec2::splat(tile_size));
+            let scale = f64::from(scale);
+            let placement = TilePlacement {
+                left: f64::from(origin.x) / scale,
+                top: f64::from(origin.y) / scale,
+                width: (f64::from(size.x) / scale).ceil() as i32,
+                height: (f64::from(size.y) / scale).ceil() as i32,
+            };
+
+            let existing = canvas.tiles.get_mut(&(x, y));
+            let tile_camera = if let Some((tile_camera, old_placement)) = existing {
+                if *old_placement != placement {
+                    *old_placement = placement;
+                    gtk_commands.queue(move |_: &mut GtkContext| {
+                        CANVAS_WIDGETS.with_borrow(|canvases| {
+                            let Some(canvas) = canvases.get(&id) else {
+                                return;
+                            };
+                            if let (Some(fixed), Some(widget)) =
+                                (canvas.fixed.upgrade(), canvas.tiles.get(&(x, y)))
+                            {
+                                widget.set_size_request(placement.width, placement.height);
+                                fixed.move_(widget, placement.left, placement.top);
+                            }
+                        });
+                    });
+                }
+                *tile_camera
+            } else {
+                trace!("Adding tile {:?} of canvas {canvas_entity}", (x, y));
+                let (viewport, widget_factory) = viewports.create_with(ViewportConfig {
+                    size_detection: ViewportSizeDetection::Allocation,
+                    ..Default::default()
+                });
+                let widget_factory =
+                    widget_factory.with_loading_placeholder(LoadingPlaceholder::None);
+                let tile_camera = commands
+                    .spawn((
+                        CanvasTile {
+                            canvas: canvas_entity,
+                        },
+                        viewport,
+                    ))
+                    .id();
+                canvas.tiles.insert((x, y), (tile_camera, placement));
+
+                gtk_commands.queue(move |_: &mut GtkContext| {
+                    CANVAS_WIDGETS.with_borrow_mut(|canvases| {
+                        let Some(canvas) = canvases.get_mut(&id) else {
+                            return;
+                        };
+                        let Some(fixed) = canvas.fixed.upgrade() else {
+                            return;
+                        };
+                        let widget = widget_factory.make();
+                        widget.set_size_request(placement.width, placement.height);
+                        fixed.put(&widget, placement.left, placement.top);
+                        canvas.tiles.insert((x, y), widget);
+                    });
+                });
+                tile_camera
+            };
+
+            // the template may have changed, so copy it over again
+            commands
+                .entity(canvas_entity)
+                .clone_with_opt_out(tile_camera, |builder| {
+                    // keep `Camera2d` and `Camera3d`, even though they require
+                    // the `Camera` which we give each tile ourselves
+                    builder.without_required_by_components(|builder| {
+                        builder.deny::<(
+                            GtkCanvas,
+                            CanvasTile,
+                            GtkViewport,
+                            Camera,
+                            RenderEntity,
+                            Children,
+                        )>();
+                    });
+                });
+            let mut camera = Camera::clone(&template);
+            camera.is_active = true;
+            camera.sub_camera_view = Some(SubCameraView {
+                full_size: content_size,
+                offset: origin.as_vec2(),
+                size,
+            });
+            commands.entity(tile_camera).insert(camera);
+        }
+
+        if resized || relayout {
+            debug!(
+                "Laid out canvas {canvas_entity} for {}x{} content, with {} visible tiles",
+                content_size.x,
+                content_size.y,
+                canvas.tiles.len()
+            );
+        }
+    }
+}
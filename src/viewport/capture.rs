@@ -0,0 +1,410 @@
+use {
+    super::{RenderViewport, ViewportPrivate},
+    alloc::sync::Arc,
+    bevy_app::prelude::*,
+    bevy_ecs::{error::BevyError, prelude::*},
+    bevy_render::renderer::{RenderDevice, RenderQueue},
+    log::{debug, error, info, trace, warn},
+    std::{
+        path::{Path, PathBuf},
+        sync::Mutex,
+        thread,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<StartRecording>()
+        .add_event::<StopRecording>()
+        .add_systems(PostUpdate, start_stop_recordings);
+
+    #[cfg(feature = "gstreamer")]
+    app.insert_resource(RecordingEncoder::new(gst_encoder::GstFrameEncoder::new));
+}
+
+/// Starts recording the frames presented by a viewport to a video file.
+///
+/// Frames are read back from the GPU after they are rendered, and passed to
+/// the [`RecordingEncoder`] on a separate thread. If the encoder can't keep up,
+/// frames are dropped.
+///
+/// If the viewport is already being recorded, the old recording is stopped.
+/// A recording also stops when its viewport is despawned.
+#[derive(Debug, Clone, Event)]
+pub struct StartRecording {
+    /// Entity of the viewport to record.
+    ///
+    /// See [`GtkViewport::entity`](crate::GtkViewport::entity).
+    pub viewport: Entity,
+    /// Path of the video file to write to.
+    pub path: PathBuf,
+}
+
+/// Stops recording a viewport which was started with [`StartRecording`].
+///
+/// The encoder finishes writing the file in the background.
+#[derive(Debug, Clone, Event)]
+pub struct StopRecording {
+    /// Entity of the viewport to stop recording.
+    pub viewport: Entity,
+}
+
+/// Encodes frames captured from a viewport.
+///
+/// This runs on a dedicated thread per recording.
+pub trait FrameEncoder: Send + 'static {
+    /// Encodes a single frame.
+    ///
+    /// If this errors, the recording is stopped.
+    fn encode_frame(&mut self, frame: &CapturedFrame) -> Result<(), BevyError>;
+
+    /// Finishes writing the video after the recording stops.
+    fn finish(self: Box<Self>) -> Result<(), BevyError>;
+}
+
+/// Creates a [`FrameEncoder`] which writes to the given path.
+///
+/// This is automatically implemented for closures accepting a [`&Path`](Path).
+pub trait MakeFrameEncoder: Send + Sync + 'static {
+    /// Creates the encoder.
+    fn make(&self, path: &Path) -> Result<Box<dyn FrameEncoder>, BevyError>;
+}
+
+impl<E, F> MakeFrameEncoder for F
+where
+    E: FrameEncoder,
+    F: Fn(&Path) -> Result<E, BevyError> + Send + Sync + 'static,
+{
+    fn make(&self, path: &Path) -> Result<Box<dyn FrameEncoder>, BevyError> {
+        Ok(Box::new((self)(path)?))
+    }
+}
+
+/// Creates the [`FrameEncoder`] for each [`StartRecording`].
+///
+/// With the `gstreamer` feature, this defaults to an encoder which writes
+/// H.264 video in an MP4 container. Without it, there is no default, and you
+/// must insert this resource yourself to be able to record.
+#[derive(Resource)]
+pub struct RecordingEncoder(pub Box<dyn MakeFrameEncoder>);
+
+impl RecordingEncoder {
+    /// Creates a resource from a [`MakeFrameEncoder`].
+    #[must_use]
+    pub fn new(make: impl MakeFrameEncoder) -> Self {
+        Self(Box::new(make))
+    }
+}
+
+/// Frame read back from a viewport, in the viewport's texture format.
+#[derive(Debug)]
+pub struct CapturedFrame<'a> {
+    /// Width of the frame in pixels.
+    pub width: u32,
+    /// Height of the frame in pixels.
+    pub height: u32,
+    /// Number of bytes between the start of each row in `data`.
+    ///
+    /// This may be larger than `width * 4`, as rows are padded to
+    /// [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`].
+    pub bytes_per_row: u32,
+    /// Format of the pixels in `data`.
+    pub format: wgpu::TextureFormat,
+    /// Raw pixel data.
+    pub data: &'a [u8],
+}
+
+/// Sender for the recording of a single viewport, shared between the main and
+/// render world.
+pub(super) type Recorder = Arc<Mutex<Option<async_channel::Sender<PendingFrame>>>>;
+
+/// Buffer which a frame is being read back into.
+#[derive(Debug)]
+pub(super) struct PendingFrame {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    bytes_per_row: u32,
+    format: wgpu::TextureFormat,
+}
+
+/// Number of frames which may be waiting to be encoded before we start
+/// dropping frames.
+const MAX_PENDING_FRAMES: usize = 4;
+
+fn start_stop_recordings(
+    mut start_events: EventReader<StartRecording>,
+    mut stop_events: EventReader<StopRecording>,
+    viewports: Query<&ViewportPrivate>,
+    encoder: Option<Res<RecordingEncoder>>,
+) {
+    for StopRecording { viewport } in stop_events.read() {
+        let Ok(viewport_private) = viewports.get(*viewport) else {
+            warn!("Tried to stop recording {viewport}, which is not a viewport");
+            continue;
+        };
+        if set_recorder(&viewport_private.recorder, None) {
+            info!("Stopped recording viewport {viewport}");
+        }
+    }
+
+    for StartRecording { viewport, path } in start_events.read() {
+        let Ok(viewport_private) = viewports.get(*viewport) else {
+            warn!("Tried to start recording {viewport}, which is not a viewport");
+            continue;
+        };
+        let Some(encoder) = &encoder else {
+            error!(
+                "Failed to start recording viewport {viewport}: no `RecordingEncoder` resource; \
+                 enable the `gstreamer` feature or insert one yourself"
+            );
+            continue;
+        };
+        let encoder = match encoder.0.make(path) {
+            Ok(encoder) => encoder,
+            Err(err) => {
+                error!("Failed to start recording viewport {viewport} to {path:?}: {err}");
+                continue;
+            }
+        };
+
+        let (tx_frame, rx_frame) = async_channel::bounded(MAX_PENDING_FRAMES);
+        let viewport = *viewport;
+        let spawn_result = thread::Builder::new()
+            .name(format!("bevy_gtk recorder {viewport}"))
+            .spawn(move || run_encoder(viewport, encoder, &rx_frame));
+        if let Err(err) = spawn_result {
+            error!("Failed to spawn recorder thread for viewport {viewport}: {err}");
+            continue;
+        }
+
+        set_recorder(&viewport_private.recorder, Some(tx_frame));
+        info!("Started recording viewport {viewport} to {path:?}");
+    }
+}
+
+/// Sets the sender of a recorder, returning if there was a recording running
+/// before.
+fn set_recorder(
+    recorder: &Recorder,
+    tx_frame: Option<async_channel::Sender<PendingFrame>>,
+) -> bool {
+    let mut recorder = recorder.lock().unwrap_or_else(|err| err.into_inner());
+    // dropping the old sender ends its encoder thread
+    // once it has encoded all pending frames
+    let old = core::mem::replace(&mut *recorder, tx_frame);
+    old.is_some()
+}
+
+fn run_encoder(
+    viewport: Entity,
+    mut encoder: Box<dyn FrameEncoder>,
+    rx_frame: &async_channel::Receiver<PendingFrame>,
+) {
+    while let Ok(frame) = rx_frame.recv_blocking() {
+        let result = {
+            let data = frame.buffer.slice(..).get_mapped_range();
+            encoder.encode_frame(&CapturedFrame {
+                width: frame.width,
+                height: frame.height,
+                bytes_per_row: frame.bytes_per_row,
+                format: frame.format,
+                data: &data,
+            })
+        };
+        frame.buffer.unmap();
+
+        if let Err(err) = result {
+            error!("Failed to encode frame of viewport {viewport}, stopping recording: {err}");
+            rx_frame.close();
+            break;
+        }
+    }
+
+    match encoder.finish() {
+        Ok(()) => debug!("Finished recording viewport {viewport}"),
+        Err(err) => error!("Failed to finish recording viewport {viewport}: {err}"),
+    }
+}
+
+// frame-to-frame logic, in the render world
+
+pub(super) fn capture_frames(
+    viewports: Query<&RenderViewport>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    for viewport in &viewports {
+        let Some((texture, _)) = &viewport.back_buffer else {
+            continue;
+        };
+        let tx_frame = {
+            let mut recorder = viewport
+                .recorder
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            match &*recorder {
+                Some(tx_frame) if tx_frame.is_closed() => {
+                    // the encoder thread stopped because of an error
+                    *recorder = None;
+                    continue;
+                }
+                Some(tx_frame) if tx_frame.is_full() => {
+                    trace!("Recorder is behind, dropping frame");
+                    continue;
+                }
+                Some(tx_frame) => tx_frame.clone(),
+                None => continue,
+            }
+        };
+
+        let (width, height) = (texture.width(), texture.height());
+        let format = texture.format();
+        let Some(pixel_size) = format.block_copy_size(None) else {
+            continue;
+        };
+        let bytes_per_row =
+            (width * pixel_size).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let device = render_device.wgpu_device();
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("bevy_gtk recording readback"),
+            size: u64::from(bytes_per_row) * u64::from(height),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("bevy_gtk recording readback"),
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            texture.size(),
+        );
+        render_queue.submit([encoder.finish()]);
+
+        let frame = PendingFrame {
+            buffer: buffer.clone(),
+            width,
+            height,
+            bytes_per_row,
+            format,
+        };
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| match result {
+                Ok(()) => _ = tx_frame.try_send(frame),
+                Err(err) => warn!("Failed to read back recorded frame: {err}"),
+            });
+    }
+}
+
+#[cfg(feature = "gstreamer")]
+mod gst_encoder {
+    use {super::*, gst::prelude::*};
+
+    /// Encodes frames to H.264 in an MP4 container using GStreamer.
+    ///
+    /// The video has the size of the first frame; later frames are scaled to
+    /// fit.
+    pub struct GstFrameEncoder {
+        pipeline: gst::Pipeline,
+        appsrc: gst_app::AppSrc,
+        size_filter: gst::Element,
+        size: Option<(u32, u32, wgpu::TextureFormat)>,
+    }
+
+    impl GstFrameEncoder {
+        pub fn new(path: &Path) -> Result<Self, BevyError> {
+            let pipeline = gst::parse::launch(
+                "appsrc name=src is-live=true do-timestamp=true format=time ! videoconvert ! \
+                 videoscale ! capsfilter name=size ! x264enc tune=zerolatency ! mp4mux ! filesink \
+                 name=sink",
+            )?
+            .downcast::<gst::Pipeline>()
+            .map_err(|_| "recording pipeline is not a pipeline")?;
+            let appsrc = pipeline
+                .by_name("src")
+                .and_then(|element| element.downcast::<gst_app::AppSrc>().ok())
+                .ok_or("recording pipeline has no app source")?;
+            let size_filter = pipeline
+                .by_name("size")
+                .ok_or("recording pipeline has no size filter")?;
+            let sink = pipeline
+                .by_name("sink")
+                .ok_or("recording pipeline has no file sink")?;
+            sink.set_property("location", path.to_string_lossy().as_ref());
+
+            Ok(Self {
+                pipeline,
+                appsrc,
+                size_filter,
+                size: None,
+            })
+        }
+    }
+
+    impl FrameEncoder for GstFrameEncoder {
+        fn encode_frame(&mut self, frame: &CapturedFrame) -> Result<(), BevyError> {
+            let size = (frame.width, frame.height, frame.format);
+            if self.size != Some(size) {
+                let video_format = match frame.format {
+                    wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {
+                        gst_video::VideoFormat::Rgba
+                    }
+                    wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+                        gst_video::VideoFormat::Bgra
+                    }
+                    format => {
+                        return Err(format!("cannot record frames of format {format:?}").into());
+                    }
+                };
+                let caps = gst_video::VideoInfo::builder(video_format, frame.width, frame.height)
+                    .build()?
+                    .to_caps()?;
+                self.appsrc.set_caps(Some(&caps));
+
+                if self.size.is_none() {
+                    // H.264 requires even dimensions
+                    let output_caps = gst::Caps::builder("video/x-raw")
+                        .field("width", i32::try_from(frame.width & !1)?)
+                        .field("height", i32::try_from(frame.height & !1)?)
+                        .build();
+                    self.size_filter.set_property("caps", &output_caps);
+                    self.pipeline.set_state(gst::State::Playing)?;
+                }
+                self.size = Some(size);
+            }
+
+            // strip the row padding
+            let row_len = frame.width as usize * 4;
+            let mut data = Vec::with_capacity(row_len * frame.height as usize);
+            for row in frame.data.chunks(frame.bytes_per_row as usize) {
+                data.extend_from_slice(&row[..row_len]);
+            }
+            self.appsrc.push_buffer(gst::Buffer::from_mut_slice(data))?;
+            Ok(())
+        }
+
+        fn finish(self: Box<Self>) -> Result<(), BevyError> {
+            if self.size.is_some() {
+                self.appsrc.end_of_stream()?;
+                if let Some(bus) = self.pipeline.bus() {
+                    // wait for the muxer to finish writing the file
+                    _ = bus.timed_pop_filtered(
+                        gst::ClockTime::NONE,
+                        &[gst::MessageType::Eos, gst::MessageType::Error],
+                    );
+                }
+            }
+            self.pipeline.set_state(gst::State::Null)?;
+            Ok(())
+        }
+    }
+}
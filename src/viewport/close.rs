@@ -0,0 +1,109 @@
+use {
+    crate::{GtkSystems, GtkWindows},
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_window::WindowCloseRequested,
+    gtk::prelude::*,
+    log::debug,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<ViewportWillClose>().add_systems(
+        Last,
+        notify_closing_viewports.after(GtkSystems::SyncWindows),
+    );
+}
+
+/// Emitted for every viewport inside of a window, when the user requests to
+/// close that window.
+///
+/// Viewports live for as long as their GTK widget lives, so closing a window
+/// destroys all of the viewports inside of it. This is emitted alongside the
+/// window's [`WindowCloseRequested`], before any widgets are destroyed. With
+/// Bevy's default close policy, the window is only despawned on the next
+/// update, so you have a frame to save the state of each viewport.
+///
+/// To veto the close instead, disable `WindowPlugin::close_when_requested` and
+/// decide whether to despawn the window yourself when reading
+/// [`WindowCloseRequested`].
+///
+/// This is not emitted when a window is despawned from the Bevy side, or for
+/// viewports displayed through a [`BevyPaintable`](crate::BevyPaintable).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Event)]
+pub struct ViewportWillClose {
+    /// Entity of the viewport.
+    ///
+    /// See [`GtkViewport::entity`](crate::GtkViewport::entity).
+    pub viewport: Entity,
+    /// Entity of the window which the viewport is in.
+    pub window: Entity,
+}
+
+/// Key of the viewport entity on the widget made by
+/// [`WidgetFactory::make`](crate::WidgetFactory::make).
+const VIEWPORT_ENTITY_KEY: &str = "bevy-gtk-viewport-entity";
+
+/// Marks `widget` as the widget of the viewport `entity`, so that we can find
+/// it later, e.g. when its window closes.
+pub(super) fn mark_widget(widget: &gtk::Widget, entity: Entity) {
+    // SAFETY: this key is only ever used to store an `Entity`
+    unsafe {
+        widget.set_data(VIEWPORT_ENTITY_KEY, entity);
+    }
+}
+
+/// Finds the viewports inside of `widget`, including `widget` itself.
+fn find_viewports(widget: &gtk::Widget, viewports: &mut Vec<Entity>) {
+    // SAFETY: this key is only ever used to store an `Entity`
+    if let Some(entity) = unsafe { widget.data::<Entity>(VIEWPORT_ENTITY_KEY) } {
+        // SAFETY: the data is valid for as long as the widget is
+        viewports.push(unsafe { *entity.as_ref() });
+    }
+    let mut child = widget.first_child();
+    while let Some(widget) = child {
+        find_viewports(&widget, viewports);
+        child = widget.next_sibling();
+    }
+}
+
+/// Finds the widget of the viewport `entity` inside of `widget`, including
+/// `widget` itself.
+pub(super) fn find_widget(widget: &gtk::Widget, entity: Entity) -> Option<gtk::Widget> {
+    // SAFETY: this key is only ever used to store an `Entity`
+    if let Some(marked) = unsafe { widget.data::<Entity>(VIEWPORT_ENTITY_KEY) } {
+        // SAFETY: the data is valid for as long as the widget is
+        if unsafe { *marked.as_ref() } == entity {
+            return Some(widget.clone());
+        }
+    }
+    let mut child = widget.first_child();
+    while let Some(widget) = child {
+        if let Some(found) = find_widget(&widget, entity) {
+            return Some(found);
+        }
+        child = widget.next_sibling();
+    }
+    None
+}
+
+fn notify_closing_viewports(
+    mut close_requested: EventReader<WindowCloseRequested>,
+    mut will_close: EventWriter<ViewportWillClose>,
+    gtk_windows: NonSend<GtkWindows>,
+) {
+    let mut viewports = Vec::new();
+    for WindowCloseRequested { window } in close_requested.read() {
+        let Some(proxy) = gtk_windows.get(*window) else {
+            continue;
+        };
+        viewports.clear();
+        find_viewports(proxy.gtk_window.upcast_ref(), &mut viewports);
+        for viewport in &viewports {
+            debug!("Viewport {viewport} will close along with window {window}");
+            will_close.write(ViewportWillClose {
+                viewport: *viewport,
+                window: *window,
+            });
+        }
+    }
+}
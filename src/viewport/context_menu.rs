@@ -0,0 +1,297 @@
+use {
+    super::{ViewportPrivate, close, snapshot},
+    crate::{GtkSystems, GtkWindows},
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_math::Vec2,
+    glib::clone,
+    gtk::prelude::*,
+    log::{debug, warn},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let (tx_activated, rx_activated) = async_channel::unbounded();
+    app.add_event::<ShowContextMenu>()
+        .add_event::<ContextMenuActivated>()
+        .insert_resource(ContextMenuChannel {
+            tx_activated,
+            rx_activated,
+        })
+        .add_systems(PreUpdate, forward_activations)
+        .add_systems(Last, show_context_menus.after(GtkSystems::SyncWindows));
+}
+
+/// Menu which is shown as a [`gtk::PopoverMenu`] by [`ShowContextMenu`].
+///
+/// This is a description of a [`gio::Menu`] which can be built on the Bevy
+/// side, since GIO objects can only be used on the GTK thread.
+///
+/// # Examples
+///
+/// ```ignore
+/// let menu = GtkMenu::new()
+///     .with_action("Rename", "rename")
+///     .with_action("Duplicate", "duplicate")
+///     .with_section(GtkMenu::new().with_action("Delete", "delete"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GtkMenu {
+    /// Items of this menu, from top to bottom.
+    pub items: Vec<GtkMenuItem>,
+}
+
+/// Item of a [`GtkMenu`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GtkMenuItem {
+    /// Entry which emits [`ContextMenuActivated`] with `action` when clicked.
+    Action { label: String, action: String },
+    /// Group of items, which is separated from the items around it.
+    Section {
+        label: Option<String>,
+        menu: GtkMenu,
+    },
+    /// Entry which opens a nested menu.
+    Submenu { label: String, menu: GtkMenu },
+}
+
+impl GtkMenu {
+    /// Creates an empty menu.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a [`GtkMenuItem::Action`].
+    #[must_use]
+    pub fn with_action(mut self, label: impl Into<String>, action: impl Into<String>) -> Self {
+        self.items.push(GtkMenuItem::Action {
+            label: label.into(),
+            action: action.into(),
+        });
+        self
+    }
+
+    /// Adds a [`GtkMenuItem::Section`] without a label.
+    #[must_use]
+    pub fn with_section(mut self, menu: Self) -> Self {
+        self.items.push(GtkMenuItem::Section { label: None, menu });
+        self
+    }
+
+    /// Adds a [`GtkMenuItem::Submenu`].
+    #[must_use]
+    pub fn with_submenu(mut self, label: impl Into<String>, menu: Self) -> Self {
+        self.items.push(GtkMenuItem::Submenu {
+            label: label.into(),
+            menu,
+        });
+        self
+    }
+}
+
+/// Shows a context menu over a viewport, pointing at a position inside of it.
+///
+/// Use this to open a menu when the user right-clicks on something rendered
+/// in the viewport, e.g. an entity in an editor. When an item is clicked,
+/// [`ContextMenuActivated`] is emitted with its action. GTK closes the menu
+/// when an item is clicked, or when the user clicks elsewhere.
+///
+/// Menus are only shown for viewports displayed with
+/// [`WidgetFactory::make`](crate::WidgetFactory::make) inside of a window.
+#[derive(Debug, Clone, Event)]
+pub struct ShowContextMenu {
+    /// Entity of the viewport to show the menu over.
+    ///
+    /// See [`GtkViewport::entity`](crate::GtkViewport::entity).
+    pub viewport: Entity,
+    /// Position that the menu points at, relative to the top-left of the
+    /// viewport, in logical pixels.
+    ///
+    /// This is the same space as the camera's viewport coordinates, e.g. from
+    /// [`Camera::world_to_viewport`]. If the viewport has a fixed
+    /// [`ViewportConfig::resolution`], this is in pixels of that resolution,
+    /// and is mapped to wherever those pixels are shown after applying
+    /// [`ViewportConfig::content_fit`].
+    ///
+    /// [`Camera::world_to_viewport`]: bevy_camera::Camera::world_to_viewport
+    /// [`ViewportConfig::resolution`]: crate::ViewportConfig::resolution
+    /// [`ViewportConfig::content_fit`]: crate::ViewportConfig::content_fit
+    pub position: Vec2,
+    /// Menu to show.
+    pub menu: GtkMenu,
+}
+
+/// Emitted when an item of a menu shown by [`ShowContextMenu`] is clicked.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Event)]
+pub struct ContextMenuActivated {
+    /// Entity of the viewport that the menu was shown over.
+    pub viewport: Entity,
+    /// `action` of the [`GtkMenuItem::Action`] which was clicked.
+    pub action: String,
+}
+
+#[derive(Debug, Resource)]
+struct ContextMenuChannel {
+    tx_activated: async_channel::Sender<ContextMenuActivated>,
+    rx_activated: async_channel::Receiver<ContextMenuActivated>,
+}
+
+/// Prefix of the actions of a context menu, which are inserted into its
+/// popover.
+const ACTION_GROUP: &str = "bevy-context-menu";
+
+fn forward_activations(
+    channel: Res<ContextMenuChannel>,
+    mut activated: EventWriter<ContextMenuActivated>,
+) {
+    while let Ok(event) = channel.rx_activated.try_recv() {
+        activated.write(event);
+    }
+}
+
+fn show_context_menus(
+    mut requests: EventReader<ShowContextMenu>,
+    viewports: Query<&ViewportPrivate>,
+    channel: Res<ContextMenuChannel>,
+    gtk_windows: NonSend<GtkWindows>,
+) {
+    for request in requests.read() {
+        let viewport = request.viewport;
+        let Some(widget) = gtk_windows
+            .iter()
+            .find_map(|(_, proxy)| close::find_widget(proxy.gtk_window.upcast_ref(), viewport))
+        else {
+            warn!("Can't show a context menu for viewport {viewport}, since it isn't in a window");
+            continue;
+        };
+
+        let resolution = viewports
+            .get(viewport)
+            .ok()
+            .and_then(|viewport| viewport.resolution);
+        let position = match resolution {
+            Some((width, height)) => image_to_widget(&widget, request.position, width, height),
+            None => Some(request.position),
+        };
+        let Some(position) = position else {
+            warn!(
+                "Can't show a context menu for viewport {viewport}, since its frames aren't laid \
+                 out yet"
+            );
+            continue;
+        };
+
+        debug!("Showing context menu for viewport {viewport} at {position}");
+        show(
+            &widget,
+            position,
+            viewport,
+            &request.menu,
+            &channel.tx_activated,
+        );
+    }
+}
+
+/// Maps a position in pixels of a fixed-resolution image to the position in
+/// `widget` where GTK shows that pixel.
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "image sizes are small enough to fit in an `f32`"
+)]
+fn image_to_widget(widget: &gtk::Widget, position: Vec2, width: u32, height: u32) -> Option<Vec2> {
+    let picture = snapshot::picture(widget)?;
+    let picture_size = Vec2::new(picture.width() as f32, picture.height() as f32);
+    let image_size = Vec2::new(width as f32, height as f32);
+    let fit = picture_size / image_size;
+    let scale = match picture.content_fit() {
+        gtk::ContentFit::Cover => Vec2::splat(fit.max_element()),
+        gtk::ContentFit::Contain => Vec2::splat(fit.min_element()),
+        gtk::ContentFit::ScaleDown => Vec2::splat(fit.min_element().min(1.0)),
+        _ => fit,
+    };
+    // the image is centered in the picture
+    let offset = (picture_size - image_size * scale) / 2.0;
+    let local = offset + position * scale;
+    let point = picture.compute_point(widget, &gtk::graphene::Point::new(local.x, local.y))?;
+    Some(Vec2::new(point.x(), point.y()))
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "widget positions are small enough to fit in an `i32`"
+)]
+fn show(
+    widget: &gtk::Widget,
+    position: Vec2,
+    viewport: Entity,
+    menu: &GtkMenu,
+    tx_activated: &async_channel::Sender<ContextMenuActivated>,
+) {
+    let actions = gio::SimpleActionGroup::new();
+    let model = build_menu(menu, &actions, viewport, tx_activated);
+    let popover = gtk::PopoverMenu::builder()
+        .menu_model(&model)
+        .has_arrow(false)
+        .halign(gtk::Align::Start)
+        .pointing_to(&gdk::Rectangle::new(
+            position.x.round() as i32,
+            position.y.round() as i32,
+            1,
+            1,
+        ))
+        .build();
+    popover.insert_action_group(ACTION_GROUP, Some(&actions));
+    popover.set_parent(widget);
+    // the clicked item's action is activated after the popover closes,
+    // so we wait until then to remove it
+    popover.connect_closed(|popover| {
+        glib::idle_add_local_once(clone!(
+            #[strong]
+            popover,
+            move || popover.unparent()
+        ));
+    });
+    popover.popup();
+}
+
+fn build_menu(
+    menu: &GtkMenu,
+    actions: &gio::SimpleActionGroup,
+    viewport: Entity,
+    tx_activated: &async_channel::Sender<ContextMenuActivated>,
+) -> gio::Menu {
+    let model = gio::Menu::new();
+    for item in &menu.items {
+        match item {
+            GtkMenuItem::Action { label, action } => {
+                // the user's action names may not be valid `GAction` names,
+                // so we name our actions by index instead
+                let name = format!("item-{}", actions.list_actions().len());
+                let gio_action = gio::SimpleAction::new(&name, None);
+                gio_action.connect_activate(clone!(
+                    #[strong]
+                    tx_activated,
+                    #[strong]
+                    action,
+                    move |_, _| {
+                        _ = tx_activated.try_send(ContextMenuActivated {
+                            viewport,
+                            action: action.clone(),
+                        });
+                    }
+                ));
+                actions.add_action(&gio_action);
+                model.append(Some(label), Some(&format!("{ACTION_GROUP}.{name}")));
+            }
+            GtkMenuItem::Section { label, menu } => {
+                let section = build_menu(menu, actions, viewport, tx_activated);
+                model.append_section(label.as_deref(), &section);
+            }
+            GtkMenuItem::Submenu { label, menu } => {
+                let submenu = build_menu(menu, actions, viewport, tx_activated);
+                model.append_submenu(Some(label), &submenu);
+            }
+        }
+    }
+    model
+}
@@ -0,0 +1,73 @@
+use {
+    bevy_asset::AssetId,
+    bevy_ecs::prelude::*,
+    bevy_image::Image,
+    bevy_platform::collections::HashMap,
+    bevy_render::{
+        render_resource::{Texture, TextureView},
+        renderer::RenderDevice,
+    },
+    wgpu::{
+        Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        TextureViewDescriptor,
+    },
+};
+
+/// Depth textures of viewports which were created with a
+/// [`ViewportConfig::depth_format`](crate::ViewportConfig::depth_format), keyed
+/// by the viewport's [image](crate::GtkViewport::image_handle).
+///
+/// This is a render world resource. Custom render graph nodes which target a
+/// viewport can look up the depth texture of the viewport by the camera's
+/// target image, the same way they would use a window's depth texture.
+///
+/// The depth texture always has the same size as the viewport's color texture,
+/// and is reallocated whenever the viewport is resized. It is never shared
+/// with GTK.
+#[derive(Debug, Default, Resource)]
+pub struct ViewportDepthTextures(pub(super) HashMap<AssetId<Image>, ViewportDepthTexture>);
+
+impl ViewportDepthTextures {
+    /// Gets the depth texture of the viewport rendering into `image`.
+    #[must_use]
+    pub fn get(&self, image: impl Into<AssetId<Image>>) -> Option<&ViewportDepthTexture> {
+        self.0.get(&image.into())
+    }
+}
+
+/// Depth texture allocated alongside a viewport's color texture.
+///
+/// See [`ViewportDepthTextures`].
+#[derive(Debug, Clone)]
+pub struct ViewportDepthTexture {
+    /// Depth texture.
+    pub texture: Texture,
+    /// Default view of [`ViewportDepthTexture::texture`].
+    pub view: TextureView,
+}
+
+impl ViewportDepthTexture {
+    pub(super) fn new(
+        render_device: &RenderDevice,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> Self {
+        let texture = render_device.create_texture(&TextureDescriptor {
+            label: Some("bevy_gtk viewport depth"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&TextureViewDescriptor::default());
+        Self { texture, view }
+    }
+}
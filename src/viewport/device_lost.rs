@@ -0,0 +1,114 @@
+use {
+    super::{RenderViewport, release_textures, set_target_images},
+    alloc::sync::Arc,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_render::{Render, RenderApp, RenderSystems, renderer::RenderDevice},
+    core::sync::atomic::{self, AtomicBool},
+    log::{error, info},
+    wgpu::DeviceLostReason,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let (tx, rx) = async_channel::unbounded();
+    app.add_event::<RenderDeviceLost>()
+        .insert_resource(RxDeviceLost(rx))
+        .add_systems(PreUpdate, forward_device_lost);
+
+    app.get_sub_app_mut(RenderApp)
+        .expect("`viewport::plugin` checks that `RenderApp` exists")
+        .insert_resource(TxDeviceLost(tx))
+        .init_resource::<RenderDeviceState>()
+        .add_systems(
+            Render,
+            watch_device_lost
+                .after(RenderSystems::ExtractCommands)
+                .before(set_target_images),
+        );
+}
+
+/// Emitted when the render device is lost, e.g. because of a driver reset or a
+/// GPU hang.
+///
+/// All dmabufs which were allocated on the device become invalid, so every
+/// viewport releases its textures and stops rendering, and its GTK widget
+/// keeps showing the last frame that it received. The GTK side of the app
+/// keeps running.
+///
+/// If the [`RenderDevice`] in the render world is replaced with a new device,
+/// viewports allocate new dmabufs on it and start rendering again. Bevy doesn't
+/// recreate its render device by itself, so unless your app does that, use
+/// this event to let the user save their work and restart the app.
+#[derive(Debug, Clone, Event)]
+pub struct RenderDeviceLost {
+    /// Why the device was lost.
+    pub reason: DeviceLostReason,
+    /// Description of why the device was lost, from the driver.
+    pub message: String,
+}
+
+#[derive(Debug, Resource)]
+struct TxDeviceLost(async_channel::Sender<RenderDeviceLost>);
+
+#[derive(Debug, Resource)]
+struct RxDeviceLost(async_channel::Receiver<RenderDeviceLost>);
+
+/// Tracks whether the current render device is lost, in the render world.
+#[derive(Debug, Default, Resource)]
+pub(super) struct RenderDeviceState {
+    /// Device which [`RenderDeviceState::lost`] belongs to.
+    device: Option<wgpu::Device>,
+    /// Set by the device lost callback of [`RenderDeviceState::device`].
+    ///
+    /// Each device has its own flag, so that the callback of an old device
+    /// doesn't mark its replacement as lost when it's dropped.
+    lost: Arc<AtomicBool>,
+}
+
+impl RenderDeviceState {
+    /// Whether the current render device is lost, so nothing can be allocated
+    /// on it.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(atomic::Ordering::SeqCst)
+    }
+}
+
+// the render device only exists once the renderer has finished initializing,
+// so we can't do this when building the plugin
+fn watch_device_lost(
+    render_device: Res<RenderDevice>,
+    tx: Res<TxDeviceLost>,
+    mut state: ResMut<RenderDeviceState>,
+    mut viewports: Query<&mut RenderViewport>,
+) {
+    let device = render_device.wgpu_device();
+    if state.device.as_ref() == Some(device) {
+        return;
+    }
+
+    let replaced = state.device.is_some();
+    let lost = Arc::new(AtomicBool::new(false));
+    state.device = Some(device.clone());
+    state.lost = lost.clone();
+
+    let tx = tx.0.clone();
+    device.set_device_lost_callback(move |reason, message| {
+        error!("Render device was lost ({reason:?}): {message}");
+        lost.store(true, atomic::Ordering::SeqCst);
+        _ = tx.try_send(RenderDeviceLost { reason, message });
+    });
+
+    if replaced {
+        info!("Render device was replaced, recreating viewport textures");
+        // `set_target_images` allocates them again, on the new device
+        for mut viewport in &mut viewports {
+            release_textures(&mut viewport);
+        }
+    }
+}
+
+fn forward_device_lost(rx: Res<RxDeviceLost>, mut events: EventWriter<RenderDeviceLost>) {
+    while let Ok(event) = rx.0.try_recv() {
+        events.write(event);
+    }
+}
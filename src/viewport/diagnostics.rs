@@ -0,0 +1,134 @@
+use {
+    super::ViewportPrivate,
+    alloc::sync::Arc,
+    bevy_app::prelude::*,
+    bevy_diagnostic::{
+        DEFAULT_MAX_HISTORY_LENGTH, Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic,
+    },
+    bevy_ecs::prelude::*,
+    bevy_render::RenderApp,
+    bevy_time::{Real, Time},
+    core::{
+        sync::atomic::{self, AtomicU64},
+        time::Duration,
+    },
+};
+
+/// Adds diagnostics measuring how much work viewports cost, to find out how
+/// many viewports an app can have before it slows down.
+///
+/// Use `LogDiagnosticsPlugin` to print them, or read them from
+/// [`DiagnosticsStore`](bevy_diagnostic::DiagnosticsStore) under the paths
+/// on this type. Add this after Bevy's render plugin, since the render world
+/// reports some of these measurements.
+#[derive(Debug, Clone)]
+pub struct GtkViewportDiagnosticsPlugin {
+    /// Number of samples to keep for averaging.
+    pub max_history_length: usize,
+}
+
+impl Default for GtkViewportDiagnosticsPlugin {
+    fn default() -> Self {
+        Self {
+            max_history_length: DEFAULT_MAX_HISTORY_LENGTH,
+        }
+    }
+}
+
+impl GtkViewportDiagnosticsPlugin {
+    /// Number of viewports which are alive.
+    pub const VIEWPORTS: DiagnosticPath = DiagnosticPath::const_new("gtk/viewports");
+
+    /// Dmabufs allocated for viewports, per second.
+    ///
+    /// Viewports allocate new dmabufs whenever their size changes, so this
+    /// spikes while windows are being resized.
+    pub const DMABUF_ALLOCATIONS: DiagnosticPath =
+        DiagnosticPath::const_new("gtk/dmabuf_allocations");
+
+    /// Time that the render world spends handing frames of all viewports over
+    /// to GTK, per frame, in milliseconds.
+    pub const PRESENT_TIME: DiagnosticPath = DiagnosticPath::const_new("gtk/present_time");
+}
+
+impl Plugin for GtkViewportDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        let counters = ViewportCounters::default();
+        app.register_diagnostic(
+            Diagnostic::new(Self::VIEWPORTS).with_max_history_length(self.max_history_length),
+        )
+        .register_diagnostic(
+            Diagnostic::new(Self::DMABUF_ALLOCATIONS)
+                .with_suffix("/s")
+                .with_max_history_length(self.max_history_length),
+        )
+        .register_diagnostic(
+            Diagnostic::new(Self::PRESENT_TIME)
+                .with_suffix("ms")
+                .with_max_history_length(self.max_history_length),
+        )
+        .insert_resource(counters.clone())
+        .add_systems(Update, measure_viewports);
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.insert_resource(counters);
+        }
+    }
+}
+
+/// Counters which the render world adds to, and the main world reads and
+/// resets every update.
+#[derive(Debug, Clone, Default, Resource)]
+pub(super) struct ViewportCounters {
+    dmabuf_allocations: Arc<AtomicU64>,
+    /// Total time spent presenting, in nanoseconds.
+    present_nanos: Arc<AtomicU64>,
+    /// Number of frames in which we presented.
+    presents: Arc<AtomicU64>,
+}
+
+impl ViewportCounters {
+    pub fn add_dmabuf_allocations(&self, count: usize) {
+        self.dmabuf_allocations
+            .fetch_add(count as u64, atomic::Ordering::Relaxed);
+    }
+
+    pub fn add_present_time(&self, time: Duration) {
+        let nanos = u64::try_from(time.as_nanos()).unwrap_or(u64::MAX);
+        self.present_nanos
+            .fetch_add(nanos, atomic::Ordering::Relaxed);
+        self.presents.fetch_add(1, atomic::Ordering::Relaxed);
+    }
+}
+
+fn measure_viewports(
+    viewports: Query<(), With<ViewportPrivate>>,
+    counters: Res<ViewportCounters>,
+    time: Res<Time<Real>>,
+    mut diagnostics: Diagnostics,
+) {
+    #[expect(clippy::cast_precision_loss, reason = "counts are small")]
+    {
+        diagnostics.add_measurement(&GtkViewportDiagnosticsPlugin::VIEWPORTS, || {
+            viewports.iter().count() as f64
+        });
+
+        let allocations = counters
+            .dmabuf_allocations
+            .swap(0, atomic::Ordering::Relaxed);
+        let delta = time.delta_secs_f64();
+        if delta > 0.0 {
+            diagnostics.add_measurement(&GtkViewportDiagnosticsPlugin::DMABUF_ALLOCATIONS, || {
+                allocations as f64 / delta
+            });
+        }
+
+        let presents = counters.presents.swap(0, atomic::Ordering::Relaxed);
+        let present_nanos = counters.present_nanos.swap(0, atomic::Ordering::Relaxed);
+        if presents > 0 {
+            diagnostics.add_measurement(&GtkViewportDiagnosticsPlugin::PRESENT_TIME, || {
+                present_nanos as f64 / presents as f64 / 1_000_000.0
+            });
+        }
+    }
+}
@@ -2,33 +2,182 @@ use {
     arrayvec::ArrayVec,
     ash::vk,
     bevy_app::prelude::*,
-    bevy_ecs::error::BevyError,
-    bevy_render::renderer::raw_vulkan_init::RawVulkanInitSettings,
+    bevy_ecs::{error::BevyError, prelude::*},
+    bevy_render::renderer::raw_vulkan_init::{AdditionalVulkanFeatures, RawVulkanInitSettings},
     bevy_utils::default,
+    core::ffi::CStr,
     derive_more::{Debug, Deref},
     drm_fourcc::{DrmFormat, DrmFourcc, DrmModifier},
-    log::trace,
-    std::os::fd::{AsRawFd as _, FromRawFd, OwnedFd},
+    log::{error, trace, warn},
+    std::{
+        env,
+        os::fd::{AsRawFd as _, FromRawFd, IntoRawFd as _, OwnedFd},
+    },
 };
 
+/// Vulkan device extensions which are needed to share dmabufs with GTK.
+const REQUIRED_EXTENSIONS: [&CStr; 4] = [
+    ash::khr::external_memory::NAME,
+    ash::khr::external_memory_fd::NAME,
+    ash::ext::image_drm_format_modifier::NAME,
+    ash::ext::external_memory_dma_buf::NAME,
+];
+
+/// Environment variable which, if set, has the same effect as inserting
+/// [`GtkLinearDmabufs`].
+pub const LINEAR_DMABUFS_ENV: &str = "BEVY_GTK_LINEAR_DMABUFS";
+
+/// Makes viewports allocate linear dmabufs without DRM format modifiers, so
+/// that frames can be captured in Renderdoc.
+///
+/// As of Renderdoc v1.39, `VK_EXT_image_drm_format_modifier` is unsupported,
+/// and Vulkan init fails if it's enabled. In this mode, that extension is not
+/// enabled, and dmabufs are created with [`vk::ImageTiling::LINEAR`] and
+/// shared with GTK using the linear DRM modifier. Rendering into and
+/// presenting linear images is slower than with the tiling that the driver
+/// picks, so only use this for debugging.
+///
+/// Insert this as a resource before adding [`GtkInitPlugin`], or set the
+/// [`LINEAR_DMABUFS_ENV`] environment variable:
+///
+/// ```sh
+/// BEVY_GTK_LINEAR_DMABUFS=1 renderdoccmd capture ./my_app
+/// ```
+///
+/// Importing dmabufs from elsewhere, e.g. with [`ImportedDmabufTexture`],
+/// needs the extension, so it doesn't work in this mode.
+///
+/// [`GtkInitPlugin`]: crate::GtkInitPlugin
+#[derive(Debug, Clone, Copy, Default, Resource)]
+pub struct GtkLinearDmabufs;
+
+/// Marks that the render device was created with all of
+/// [`REQUIRED_EXTENSIONS`], or all of them except
+/// `VK_EXT_image_drm_format_modifier` if [`LinearDmabufs`] is also present.
+///
+/// This is stored in Bevy's [`AdditionalVulkanFeatures`], which is only
+/// inserted into the render world. If it's missing, viewports fall back to
+/// copying frames to GTK through the CPU.
+pub(super) struct DmabufExtensions;
+
+/// Marks that [`GtkLinearDmabufs`] was enabled when the render device was
+/// created.
+pub(super) struct LinearDmabufs;
+
 pub(super) fn init_plugin(app: &mut App) {
+    let linear = app.world().contains_resource::<GtkLinearDmabufs>()
+        || env::var_os(LINEAR_DMABUFS_ENV).is_some();
     let mut raw_vulkan_settings = app
         .world_mut()
         .get_resource_or_init::<RawVulkanInitSettings>();
 
-    // SAFETY: we do not remove any features or functionality
+    // SAFETY: we do not remove any features or functionality, and only add
+    // extensions which the physical device supports
     unsafe {
-        raw_vulkan_settings.add_create_device_callback(|args, _, _| {
-            args.extensions.extend_from_slice(&[
-                ash::khr::external_memory::NAME,
-                ash::khr::external_memory_fd::NAME,
-                ash::ext::image_drm_format_modifier::NAME,
-                ash::ext::external_memory_dma_buf::NAME,
-            ]);
+        raw_vulkan_settings.add_create_device_callback(move |args, adapter, features| {
+            let extensions = REQUIRED_EXTENSIONS
+                .into_iter()
+                .filter(|extension| {
+                    !linear || *extension != ash::ext::image_drm_format_modifier::NAME
+                })
+                .collect::<Vec<_>>();
+            let capabilities = adapter.physical_device_capabilities();
+            let missing = extensions
+                .iter()
+                .filter(|extension| !capabilities.supports_extension(extension))
+                .map(|extension| extension.to_string_lossy())
+                .collect::<Vec<_>>();
+            if missing.is_empty() {
+                args.extensions.extend_from_slice(&extensions);
+                features.insert::<DmabufExtensions>();
+                if linear {
+                    warn!(
+                        "Creating linear dmabufs without DRM format modifiers, which is slower; \
+                         only use this for debugging"
+                    );
+                    features.insert::<LinearDmabufs>();
+                }
+                return;
+            }
+
+            let properties = capabilities.properties();
+            let device_name = properties
+                .device_name_as_c_str()
+                .map_or_else(|_| "<unknown>".into(), CStr::to_string_lossy);
+            error!(
+                "Vulkan device {device_name:?} is missing extensions which are needed to share \
+                 viewports with GTK: {}. Viewports will copy every frame through the CPU instead, \
+                 which is much slower. Updating your graphics driver may fix this.",
+                missing.join(", ")
+            );
         });
     }
 }
 
+/// Which kind of memory to prefer when allocating a [`DmabufTexture`].
+///
+/// Only memory types which can be exported as a dmabuf are considered. If
+/// allocating the preferred kind of memory fails, e.g. because that heap is
+/// full, the other kinds are tried in turn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DmabufMemoryPreference {
+    /// Prefer memory which is local to the GPU.
+    ///
+    /// On discrete GPUs, this is VRAM, which is the fastest memory to render
+    /// into, and which the compositor can usually sample or scan out without a
+    /// copy. On integrated GPUs, all memory is usually both device-local and
+    /// host-visible, so this makes no difference.
+    #[default]
+    DeviceLocal,
+    /// Prefer memory which is also visible to the CPU.
+    ///
+    /// This may be faster if the dmabuf ends up being copied through the CPU
+    /// anyway, e.g. by a GTK renderer or compositor which can't import it
+    /// onto the GPU.
+    HostVisible,
+}
+
+/// Parameters for creating a [`DmabufTexture`] with
+/// [`DmabufTexture::new_with_params`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DmabufParams<'a> {
+    /// DRM modifiers which the texture may use.
+    ///
+    /// See [`DmabufTexture::new_with_modifiers`].
+    pub modifiers: &'a [DrmModifier],
+    /// Which kind of memory to allocate the texture in.
+    pub memory: DmabufMemoryPreference,
+    /// Vulkan queue families, other than wgpu's own, which access the
+    /// texture.
+    ///
+    /// wgpu submits all of its work to a single queue family, and has no way
+    /// to transfer ownership of an image between queue families. If you touch
+    /// the texture from other queues through raw Vulkan, e.g. on a dedicated
+    /// compute or transfer queue, list their families here, and the image is
+    /// created with [`vk::SharingMode::CONCURRENT`] between all of them.
+    /// Otherwise, the image is exclusively owned by wgpu's queue family.
+    pub queue_families: &'a [u32],
+    /// Whether to create the texture with [`vk::ImageTiling::LINEAR`] and the
+    /// linear DRM modifier, ignoring [`DmabufParams::modifiers`].
+    ///
+    /// This doesn't use `VK_EXT_image_drm_format_modifier`, so it works even
+    /// if the device was created without it. See [`GtkLinearDmabufs`].
+    pub linear: bool,
+}
+
+/// Whether the render device can share dmabufs with GTK.
+///
+/// This is also false if the render device isn't a Vulkan device at all.
+pub(super) fn dmabuf_supported(features: Option<&AdditionalVulkanFeatures>) -> bool {
+    features.is_some_and(AdditionalVulkanFeatures::has::<DmabufExtensions>)
+}
+
+/// Whether dmabufs must be created with [`DmabufParams::linear`], because the
+/// render device was created without `VK_EXT_image_drm_format_modifier`.
+pub(super) fn linear_dmabufs(features: Option<&AdditionalVulkanFeatures>) -> bool {
+    features.is_some_and(AdditionalVulkanFeatures::has::<LinearDmabufs>)
+}
+
 /// [`wgpu::Texture`] which is backed by DMA buffers.
 ///
 /// See <https://docs.kernel.org/userspace-api/dma-buf-alloc-exchange.html> for
@@ -54,6 +203,7 @@ pub struct DmabufTexture {
     drm_format: DrmFormat,
     #[debug(skip)]
     vk_memory: vk::DeviceMemory,
+    memory_flags: vk::MemoryPropertyFlags,
     planes: ArrayVec<DmabufPlane, MAX_PLANES_U>,
 }
 
@@ -75,7 +225,66 @@ impl DmabufTexture {
         height: u32,
         format: wgpu::TextureFormat,
     ) -> Result<Self, BevyError> {
-        create_dmabuf_texture(adapter, device, width, height, format)
+        create_dmabuf_texture(
+            adapter,
+            device,
+            width,
+            height,
+            format,
+            DmabufParams::default(),
+        )
+    }
+
+    /// Creates a dmabuf-backed texture, which may only use one of the given
+    /// DRM modifiers.
+    ///
+    /// Use this to make sure that the consumer of the dmabuf can import it,
+    /// e.g. with [`GtkRenderData::modifiers_for`]. If none of the modifiers are
+    /// supported by the device, or `modifiers` is empty, any modifier which
+    /// the device supports may be used.
+    ///
+    /// [`GtkRenderData::modifiers_for`]: crate::GtkRenderData::modifiers_for
+    pub fn new_with_modifiers(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        modifiers: &[DrmModifier],
+    ) -> Result<Self, BevyError> {
+        let params = DmabufParams {
+            modifiers,
+            ..default()
+        };
+        create_dmabuf_texture(adapter, device, width, height, format, params)
+    }
+
+    /// Creates a dmabuf-backed texture with the given parameters.
+    ///
+    /// See [`DmabufParams`].
+    pub fn new_with_params(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        params: DmabufParams<'_>,
+    ) -> Result<Self, BevyError> {
+        create_dmabuf_texture(adapter, device, width, height, format, params)
+    }
+
+    /// Whether this texture's memory is local to the GPU.
+    #[must_use]
+    pub fn is_device_local(&self) -> bool {
+        self.memory_flags
+            .contains(vk::MemoryPropertyFlags::DEVICE_LOCAL)
+    }
+
+    /// Whether this texture's memory is visible to the CPU.
+    #[must_use]
+    pub fn is_host_visible(&self) -> bool {
+        self.memory_flags
+            .contains(vk::MemoryPropertyFlags::HOST_VISIBLE)
     }
 
     #[must_use]
@@ -91,10 +300,130 @@ impl DmabufTexture {
     /// Errors if opening the plane file descriptors or building the
     /// [`gdk::DmabufTexture`] fails.
     pub fn build_gdk_texture(&self) -> Result<gdk::Texture, BevyError> {
-        let (width, height) = (self.width(), self.height());
+        self.export()?.build_gdk_texture()
+    }
+
+    /// Builds a [`gdk::Texture`] like [`DmabufTexture::build_gdk_texture`],
+    /// which GTK interprets in `color_state`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if opening the plane file descriptors or building the
+    /// [`gdk::DmabufTexture`] fails.
+    pub fn build_gdk_texture_with_color_state(
+        &self,
+        color_state: &gdk::ColorState,
+    ) -> Result<gdk::Texture, BevyError> {
+        self.export()?
+            .build_gdk_texture_with_color_state(color_state)
+    }
+
+    /// Opens a new file descriptor to this DMA buffer, along with the layout
+    /// that a consumer needs to import it.
+    ///
+    /// Use this to hand the buffer to a consumer outside of this process, e.g.
+    /// by sending the fd over a Unix socket. The memory stays alive for as
+    /// long as the fd is open, even after this texture is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Errors if opening the file descriptor fails.
+    pub fn export(&self) -> Result<DmabufImport, BevyError> {
+        Ok(DmabufImport {
+            fd: self.open_fd()?,
+            width: self.width(),
+            height: self.height(),
+            drm_format: self.drm_format,
+            planes: self
+                .planes
+                .iter()
+                .map(|plane| DmabufImportPlane {
+                    offset: plane.offset,
+                    stride: plane.stride,
+                })
+                .collect(),
+        })
+    }
+
+    fn open_fd(&self) -> Result<OwnedFd, BevyError> {
+        let get_fd_info = vk::MemoryGetFdInfoKHR {
+            memory: self.vk_memory,
+            handle_type: MEMORY_HANDLE_TYPE,
+            ..default()
+        };
+        let raw_fd = unsafe {
+            ash::khr::external_memory_fd::Device::new(&self.vk_instance, &self.vk_device)
+                .get_memory_fd(&get_fd_info)
+        }?;
+        // SAFETY: Vulkan just created a new open fd for us.
+        // <https://registry.khronos.org/vulkan/specs/latest/man/html/vkGetMemoryFdKHR.html>
+        //
+        //     Each call to vkGetMemoryFdKHR must create a new file descriptor...
+        //
+        Ok(unsafe { OwnedFd::from_raw_fd(raw_fd) })
+    }
+}
+
+/// Externally allocated DMA buffer which can be imported as an
+/// [`ImportedDmabufTexture`].
+///
+/// This is the same information which other dmabuf producers give you, e.g.
+/// GStreamer's `GstVideoMeta` + `GstDmaBufMemory`, or a `PipeWire` buffer.
+///
+/// Only single-buffer images are supported: every plane must live in the same
+/// buffer as `fd`, at the given offset.
+#[derive(Debug)]
+pub struct DmabufImport {
+    /// File descriptor of the DMA buffer.
+    ///
+    /// Ownership of this fd is transferred to Vulkan on a successful import.
+    pub fd: OwnedFd,
+    /// Width of the image in pixels.
+    pub width: u32,
+    /// Height of the image in pixels.
+    pub height: u32,
+    /// DRM fourcc and modifier describing the pixel format and memory layout.
+    pub drm_format: DrmFormat,
+    /// Layout of each memory plane in the buffer.
+    pub planes: ArrayVec<DmabufImportPlane, MAX_PLANES_U>,
+}
+
+impl DmabufImport {
+    /// Builds a [`gdk::Texture`] backed by this DMA buffer.
+    ///
+    /// Every plane gets its own duplicate of [`DmabufImport::fd`], so this may
+    /// be called multiple times for the same buffer.
+    ///
+    /// # Errors
+    ///
+    /// Errors if duplicating the file descriptor or building the
+    /// [`gdk::DmabufTexture`] fails.
+    pub fn build_gdk_texture(&self) -> Result<gdk::Texture, BevyError> {
+        self.build(None)
+    }
+
+    /// Builds a [`gdk::Texture`] like [`DmabufImport::build_gdk_texture`],
+    /// which GTK interprets in `color_state`.
+    ///
+    /// By default, GTK interprets dmabufs as sRGB, unless their format is
+    /// YUV.
+    ///
+    /// # Errors
+    ///
+    /// Errors if duplicating the file descriptor or building the
+    /// [`gdk::DmabufTexture`] fails.
+    pub fn build_gdk_texture_with_color_state(
+        &self,
+        color_state: &gdk::ColorState,
+    ) -> Result<gdk::Texture, BevyError> {
+        self.build(Some(color_state))
+    }
+
+    fn build(&self, color_state: Option<&gdk::ColorState>) -> Result<gdk::Texture, BevyError> {
         let mut builder = gdk::DmabufTextureBuilder::new()
-            .set_width(width)
-            .set_height(height)
+            .set_color_state(color_state)
+            .set_width(self.width)
+            .set_height(self.height)
             .set_fourcc(self.drm_format.code as u32)
             .set_modifier(self.drm_format.modifier.into());
 
@@ -107,7 +436,7 @@ impl DmabufTexture {
             builder = builder.set_n_planes(self.planes.len() as u32);
             for (plane_index, plane) in self.planes.iter().enumerate() {
                 let plane_index = plane_index as u32;
-                let fd = self.open_fd()?;
+                let fd = self.fd.try_clone()?;
                 // SAFETY: we use `build_with_release_func` to:
                 // - move `fd` under the ownership of `gdk_texture`
                 // - close `fd` when `gdk_texture` is destroyed
@@ -122,27 +451,64 @@ impl DmabufTexture {
         let gdk_texture = unsafe { builder.build_with_release_func(move || drop(plane_fds))? };
         Ok(gdk_texture)
     }
+}
 
-    fn open_fd(&self) -> Result<OwnedFd, BevyError> {
-        let get_fd_info = vk::MemoryGetFdInfoKHR {
-            memory: self.vk_memory,
-            handle_type: MEMORY_HANDLE_TYPE,
-            ..default()
-        };
-        let raw_fd = unsafe {
-            ash::khr::external_memory_fd::Device::new(&self.vk_instance, &self.vk_device)
-                .get_memory_fd(&get_fd_info)
-        }?;
-        // SAFETY: Vulkan just created a new open fd for us.
-        // <https://registry.khronos.org/vulkan/specs/latest/man/html/vkGetMemoryFdKHR.html>
-        //
-        //     Each call to vkGetMemoryFdKHR must create a new file descriptor...
-        //
-        Ok(unsafe { OwnedFd::from_raw_fd(raw_fd) })
+/// Layout of a single memory plane in a [`DmabufImport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DmabufImportPlane {
+    /// Offset of this plane from the start of the buffer, in bytes.
+    pub offset: u32,
+    /// Number of bytes between the start of each row in this plane.
+    pub stride: u32,
+}
+
+/// [`wgpu::Texture`] which reads from a DMA buffer allocated outside of Bevy.
+///
+/// This is the reverse of [`DmabufTexture`]: instead of exporting Bevy's
+/// rendered content to GTK, this lets Bevy sample content produced by someone
+/// else, like a video decoder or camera. The texture can be sampled and copied
+/// from, but not rendered to.
+///
+/// The imported memory is freed when the [`wgpu::Texture`] is dropped, so it is
+/// safe to keep the texture around after dropping this struct.
+#[derive(Debug, Clone, Deref)]
+pub struct ImportedDmabufTexture {
+    #[deref]
+    wgpu_texture: wgpu::Texture,
+    drm_format: DrmFormat,
+}
+
+impl ImportedDmabufTexture {
+    /// Imports an external DMA buffer as a texture on a Vulkan
+    /// [`wgpu::Device`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the device is not a Vulkan device, the DRM format can't be
+    /// mapped to a [`wgpu::TextureFormat`], or the driver rejects the buffer.
+    /// On error, `import.fd` is closed.
+    pub fn new(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        import: DmabufImport,
+    ) -> Result<Self, BevyError> {
+        import_dmabuf_texture(adapter, device, import)
+    }
+
+    #[must_use]
+    pub fn wgpu_texture(&self) -> &wgpu::Texture {
+        &self.wgpu_texture
+    }
+
+    /// Gets the DRM format (fourcc and modifier) of the imported buffer.
+    #[must_use]
+    pub fn drm_format(&self) -> DrmFormat {
+        self.drm_format
     }
 }
 
 const LABEL: &str = "bevy_gtk dmabuf texture";
+const IMPORT_LABEL: &str = "bevy_gtk imported dmabuf texture";
 const VK_DIM: vk::ImageType = vk::ImageType::TYPE_2D;
 const WGPU_DIM: wgpu::TextureDimension = wgpu::TextureDimension::D2;
 const VK_TILING: vk::ImageTiling = vk::ImageTiling::DRM_FORMAT_MODIFIER_EXT;
@@ -153,15 +519,38 @@ const MEMORY_HANDLE_TYPE: vk::ExternalMemoryHandleTypeFlags =
     vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT;
 
 fn vk_usage() -> vk::ImageUsageFlags {
-    vk::ImageUsageFlags::TRANSFER_SRC | vk::ImageUsageFlags::COLOR_ATTACHMENT
+    vk::ImageUsageFlags::TRANSFER_SRC
+        | vk::ImageUsageFlags::TRANSFER_DST
+        | vk::ImageUsageFlags::COLOR_ATTACHMENT
 }
 
 fn hal_usage() -> wgpu::TextureUses {
-    wgpu::TextureUses::COPY_SRC | wgpu::TextureUses::COLOR_TARGET
+    wgpu::TextureUses::COPY_SRC | wgpu::TextureUses::COPY_DST | wgpu::TextureUses::COLOR_TARGET
 }
 
 fn wgpu_usage() -> wgpu::TextureUsages {
-    wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::RENDER_ATTACHMENT
+    wgpu::TextureUsages::COPY_SRC
+        | wgpu::TextureUsages::COPY_DST
+        | wgpu::TextureUsages::RENDER_ATTACHMENT
+}
+
+fn import_vk_usage() -> vk::ImageUsageFlags {
+    vk::ImageUsageFlags::TRANSFER_SRC | vk::ImageUsageFlags::SAMPLED
+}
+
+fn import_hal_usage() -> wgpu::TextureUses {
+    wgpu::TextureUses::COPY_SRC | wgpu::TextureUses::RESOURCE
+}
+
+fn import_wgpu_usage() -> wgpu::TextureUsages {
+    wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::TEXTURE_BINDING
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TextureParams {
+    label: &'static str,
+    hal: wgpu::TextureUses,
+    wgpu: wgpu::TextureUsages,
 }
 
 fn create_dmabuf_texture(
@@ -170,13 +559,14 @@ fn create_dmabuf_texture(
     width: u32,
     height: u32,
     wgpu_format: wgpu::TextureFormat,
+    params: DmabufParams<'_>,
 ) -> Result<DmabufTexture, BevyError> {
     // Renderdoc doesn't support capturing processes which export memory.
     // As of renderdoc v1.39, [`ash::ext::image_drm_format_modifier::NAME`] is
     // unsupported and causes Vulkan init to fail. You can sort of get around
     // this extension if you use a `vk::ImageTiling::LINEAR` image instead of
     // `vk::ImageTiling::DRM_FORMAT_MODIFIER_EXT`, but I think this is less
-    // correct.
+    // correct. That's what `DmabufParams::linear` does, for debugging only.
     //
     // Advice to anyone looking at this code: READ THESE DOCS!!!
     // - <https://docs.kernel.org/userspace-api/dma-buf-alloc-exchange.html>
@@ -187,10 +577,10 @@ fn create_dmabuf_texture(
 
     // SAFETY: `hal_adapter` is not manually destroyed by us
     let hal_adapter = unsafe { wgpu_adapter.as_hal::<wgpu_hal::vulkan::Api>() }
-        .expect("render adapter is not a Vulkan adapter");
+        .ok_or("render adapter is not a Vulkan adapter")?;
     // SAFETY: `hal_device` is not manually destroyed by us
     let hal_device = unsafe { wgpu_device.as_hal::<wgpu_hal::vulkan::Api>() }
-        .expect("render device is not a Vulkan device");
+        .ok_or("render device is not a Vulkan device")?;
 
     let dev = Devices {
         vk_instance: hal_device.shared_instance().raw_instance(),
@@ -209,8 +599,12 @@ fn create_dmabuf_texture(
     // the DRM modifier may force the image to have multiple MEMORY planes
     // (not COLOR planes).
     // the `plane_count` here is the number of MEMORY planes.
-    let (vk_image, drm_modifier, plane_count) =
-        unsafe { create_image(&dev, width, height, wgpu_format) }?;
+    let sharing = QueueSharing::new(&dev, params.queue_families)?;
+    let (vk_image, drm_modifier, plane_count) = if params.linear {
+        unsafe { create_linear_image(&dev, width, height, wgpu_format, &sharing) }?
+    } else {
+        unsafe { create_image(&dev, width, height, wgpu_format, params.modifiers, &sharing) }?
+    };
     trace!(
         "Using DRM format {drm_format}:0x{:016x} with {plane_count} plane(s) ({drm_modifier:?} \
          vendor {:?})",
@@ -218,16 +612,73 @@ fn create_dmabuf_texture(
         drm_modifier.vendor(),
     );
 
+    // until the image is owned by a wgpu texture, we're responsible for
+    // cleaning it up if anything fails
+    let bound =
+        unsafe { bind_image_memory(&dev, vk_image, plane_count, params.linear, params.memory) };
+    let (planes, vk_memory, memory_flags) = match bound {
+        Ok(result) => result,
+        Err(err) => {
+            unsafe { dev.vk_device.destroy_image(vk_image, None) };
+            return Err(err);
+        }
+    };
+
+    let texture_params = TextureParams {
+        label: LABEL,
+        hal: hal_usage(),
+        wgpu: wgpu_usage(),
+    };
+    let wgpu_texture = vk_texture_to_wgpu(
+        &dev,
+        vk_image,
+        vk_memory,
+        width,
+        height,
+        wgpu_format,
+        texture_params,
+    );
+    Ok(DmabufTexture {
+        vk_instance: dev.vk_instance.clone(),
+        vk_device: dev.vk_device.clone(),
+        wgpu_texture,
+        drm_format: DrmFormat {
+            code: drm_format,
+            modifier: drm_modifier,
+        },
+        vk_memory,
+        memory_flags,
+        planes,
+    })
+}
+
+unsafe fn bind_image_memory(
+    dev: &Devices,
+    vk_image: vk::Image,
+    plane_count: u32,
+    linear: bool,
+    memory: DmabufMemoryPreference,
+) -> Result<
+    (
+        ArrayVec<DmabufPlane, MAX_PLANES_U>,
+        vk::DeviceMemory,
+        vk::MemoryPropertyFlags,
+    ),
+    BevyError,
+> {
     // read MEMORY plane info for each plane, to figure out the offset to give
     // to the dmabuf importer (GTK)
     let planes = (0..plane_count)
         .map(|plane_index| {
             let plane_aspect = match plane_index {
+                // memory plane aspects are only valid for images with a DRM
+                // format modifier
+                0 if linear => vk::ImageAspectFlags::COLOR,
                 0 => vk::ImageAspectFlags::MEMORY_PLANE_0_EXT,
                 1 => vk::ImageAspectFlags::MEMORY_PLANE_1_EXT,
                 2 => vk::ImageAspectFlags::MEMORY_PLANE_2_EXT,
                 3 => vk::ImageAspectFlags::MEMORY_PLANE_3_EXT,
-                _ => panic!("there should be no more than 4 memory planes"),
+                _ => return Err(format!("image has {plane_count} memory planes, max is 4").into()),
             };
 
             let subresource = vk::ImageSubresource {
@@ -244,28 +695,19 @@ fn create_dmabuf_texture(
             let row_pitch = subresource_layout.row_pitch;
             trace!("Plane {plane_index} has offset {offset} stride/row pitch {row_pitch}");
 
-            DmabufPlane {
-                offset: u32::try_from(offset).expect("offset too large"),
-                stride: u32::try_from(row_pitch).expect("stride too large"),
-            }
+            Ok(DmabufPlane {
+                offset: u32::try_from(offset).map_err(|_| "plane offset too large")?,
+                stride: u32::try_from(row_pitch).map_err(|_| "plane stride too large")?,
+            })
         })
-        .collect();
-
-    let vk_memory = unsafe { allocate_memory(&dev, vk_image) }?;
-    unsafe { dev.vk_device.bind_image_memory(vk_image, vk_memory, 0) }?;
+        .collect::<Result<_, BevyError>>()?;
 
-    let wgpu_texture = vk_texture_to_wgpu(&dev, vk_image, vk_memory, width, height, wgpu_format);
-    Ok(DmabufTexture {
-        vk_instance: dev.vk_instance.clone(),
-        vk_device: dev.vk_device.clone(),
-        wgpu_texture,
-        drm_format: DrmFormat {
-            code: drm_format,
-            modifier: drm_modifier,
-        },
-        vk_memory,
-        planes,
-    })
+    let (vk_memory, memory_flags) = unsafe { allocate_memory(dev, vk_image, memory) }?;
+    if let Err(err) = unsafe { dev.vk_device.bind_image_memory(vk_image, vk_memory, 0) } {
+        unsafe { dev.vk_device.free_memory(vk_memory, None) };
+        return Err(err.into());
+    }
+    Ok((planes, vk_memory, memory_flags))
 }
 
 struct Devices<'a> {
@@ -277,6 +719,58 @@ struct Devices<'a> {
     wgpu_device: &'a wgpu::Device,
 }
 
+/// How an image is shared between Vulkan queue families.
+#[derive(Debug)]
+struct QueueSharing {
+    mode: vk::SharingMode,
+    /// Queue families which may access the image, if `mode` is
+    /// [`vk::SharingMode::CONCURRENT`].
+    families: Vec<u32>,
+}
+
+impl QueueSharing {
+    fn new(dev: &Devices, extra_families: &[u32]) -> Result<Self, BevyError> {
+        // wgpu's queue isn't necessarily in family 0
+        let wgpu_family = dev.hal_device.queue_family_index();
+        let family_count = unsafe {
+            dev.vk_instance
+                .get_physical_device_queue_family_properties(dev.vk_physical_device)
+        }
+        .len();
+
+        let mut families = vec![wgpu_family];
+        for &family in extra_families {
+            if usize::try_from(family)
+                .ok()
+                .is_none_or(|family| family >= family_count)
+            {
+                return Err(format!(
+                    "queue family {family} does not exist, device has {family_count} queue \
+                     families"
+                )
+                .into());
+            }
+            if !families.contains(&family) {
+                families.push(family);
+            }
+        }
+
+        if families.len() > 1 {
+            trace!("Sharing image concurrently between queue families {families:?}");
+            Ok(Self {
+                mode: vk::SharingMode::CONCURRENT,
+                families,
+            })
+        } else {
+            // queue family indices are ignored for exclusive images
+            Ok(Self {
+                mode: vk::SharingMode::EXCLUSIVE,
+                families: Vec::new(),
+            })
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct DrmModifierInfo {
     modifier: DrmModifier,
@@ -284,18 +778,20 @@ struct DrmModifierInfo {
 }
 
 unsafe fn get_drm_modifier_infos(
-    dev: &Devices,
+    hal_adapter: &wgpu_hal::vulkan::Adapter,
     wgpu_format: wgpu::TextureFormat,
 ) -> Box<[DrmModifierInfo]> {
-    let vk_format = dev.hal_adapter.texture_format_as_raw(wgpu_format);
+    let vk_format = hal_adapter.texture_format_as_raw(wgpu_format);
+    let vk_instance = hal_adapter.shared_instance().raw_instance();
+    let vk_physical_device = hal_adapter.raw_physical_device();
 
     // we start by getting the number of modifiers `drm_modifier_count`
     let drm_modifier_count = {
         let mut drm_modifier_out = vk::DrmFormatModifierPropertiesList2EXT::default();
         let mut format_out = vk::FormatProperties2::default().push_next(&mut drm_modifier_out);
         unsafe {
-            dev.vk_instance.get_physical_device_format_properties2(
-                dev.vk_physical_device,
+            vk_instance.get_physical_device_format_properties2(
+                vk_physical_device,
                 vk_format,
                 &mut format_out,
             );
@@ -315,8 +811,8 @@ unsafe fn get_drm_modifier_infos(
     };
     let mut format_out = vk::FormatProperties2::default().push_next(&mut drm_modifier_out);
     unsafe {
-        dev.vk_instance.get_physical_device_format_properties2(
-            dev.vk_physical_device,
+        vk_instance.get_physical_device_format_properties2(
+            vk_physical_device,
             vk_format,
             &mut format_out,
         );
@@ -329,16 +825,70 @@ unsafe fn get_drm_modifier_infos(
         .collect::<Box<[_]>>()
 }
 
+/// Creates an image with [`vk::ImageTiling::LINEAR`], without using
+/// `VK_EXT_image_drm_format_modifier`.
+///
+/// A linear image always has a single memory plane, and is shared with the
+/// linear DRM modifier.
+unsafe fn create_linear_image(
+    dev: &Devices,
+    width: u32,
+    height: u32,
+    wgpu_format: wgpu::TextureFormat,
+    sharing: &QueueSharing,
+) -> Result<(vk::Image, DrmModifier, u32), BevyError> {
+    let vk_format = dev.hal_adapter.texture_format_as_raw(wgpu_format);
+    let mut with_external_memory = vk::ExternalMemoryImageCreateInfo {
+        handle_types: MEMORY_HANDLE_TYPE,
+        ..default()
+    };
+    let params = vk::ImageCreateInfo {
+        image_type: VK_DIM,
+        format: vk_format,
+        extent: vk::Extent3D {
+            width,
+            height,
+            depth: 1,
+        },
+        mip_levels: MIP_LEVELS,
+        array_layers: 1,
+        samples: VK_SAMPLES,
+        tiling: vk::ImageTiling::LINEAR,
+        usage: vk_usage(),
+        initial_layout: vk::ImageLayout::UNDEFINED,
+        ..default()
+    }
+    .sharing_mode(sharing.mode)
+    .queue_family_indices(&sharing.families)
+    .push_next(&mut with_external_memory);
+    let vk_image = unsafe { dev.vk_device.create_image(&params, None) }?;
+    Ok((vk_image, DrmModifier::Linear, 1))
+}
+
 unsafe fn create_image(
     dev: &Devices,
     width: u32,
     height: u32,
     wgpu_format: wgpu::TextureFormat,
+    allowed_modifiers: &[DrmModifier],
+    sharing: &QueueSharing,
 ) -> Result<(vk::Image, DrmModifier, u32), BevyError> {
     let vk_format = dev.hal_adapter.texture_format_as_raw(wgpu_format);
 
     // for this texture format, figure out what DRM modifiers we can use
-    let drm_modifier_infos = unsafe { get_drm_modifier_infos(dev, wgpu_format) };
+    let mut drm_modifier_infos = unsafe { get_drm_modifier_infos(dev.hal_adapter, wgpu_format) };
+    if !allowed_modifiers.is_empty() {
+        let allowed = drm_modifier_infos
+            .iter()
+            .filter(|info| allowed_modifiers.contains(&info.modifier))
+            .copied()
+            .collect::<Box<[_]>>();
+        if allowed.is_empty() {
+            trace!("None of the allowed DRM modifiers are supported, using any modifier");
+        } else {
+            drm_modifier_infos = allowed;
+        }
+    }
     trace!("Available DRM format modifiers");
     for info in &drm_modifier_infos {
         trace!(
@@ -385,10 +935,11 @@ unsafe fn create_image(
         samples: VK_SAMPLES,
         tiling: VK_TILING,
         usage: vk_usage(),
-        sharing_mode: vk::SharingMode::EXCLUSIVE,
         initial_layout: vk::ImageLayout::UNDEFINED,
         ..default()
     }
+    .sharing_mode(sharing.mode)
+    .queue_family_indices(&sharing.families)
     .push_next(&mut with_drm_modifiers)
     .push_next(&mut with_external_memory);
     let vk_image = unsafe { dev.vk_device.create_image(&params, None) }?;
@@ -400,19 +951,26 @@ unsafe fn create_image(
         let mut out = vk::ImageDrmFormatModifierPropertiesEXT::default();
         let device =
             ash::ext::image_drm_format_modifier::Device::new(dev.vk_instance, dev.vk_device);
-        unsafe { device.get_image_drm_format_modifier_properties(vk_image, &mut out) }?;
+        if let Err(err) =
+            unsafe { device.get_image_drm_format_modifier_properties(vk_image, &mut out) }
+        {
+            unsafe { dev.vk_device.destroy_image(vk_image, None) };
+            return Err(err.into());
+        }
         DrmModifier::from(out.drm_format_modifier)
     };
 
-    let drm_modifier_info = drm_modifier_infos
+    let Some(drm_modifier_info) = drm_modifier_infos
         .iter()
         .find(|info| info.modifier == drm_modifier)
-        .unwrap_or_else(|| {
-            panic!(
-                "created an image with DRM modifier {drm_modifier:?}, but this was not in the \
-                 initial modifier list - Vulkan driver bug?"
-            )
-        });
+    else {
+        unsafe { dev.vk_device.destroy_image(vk_image, None) };
+        return Err(format!(
+            "created an image with DRM modifier {drm_modifier:?}, but this was not in the initial \
+             modifier list - Vulkan driver bug?"
+        )
+        .into());
+    };
 
     Ok((
         vk_image,
@@ -424,13 +982,15 @@ unsafe fn create_image(
 unsafe fn allocate_memory(
     dev: &Devices,
     vk_image: vk::Image,
-) -> Result<vk::DeviceMemory, BevyError> {
+    memory: DmabufMemoryPreference,
+) -> Result<(vk::DeviceMemory, vk::MemoryPropertyFlags), BevyError> {
+    let mut dedicated_requirements = vk::MemoryDedicatedRequirements::default();
     let memory_requirements = {
         let image_memory_requirements = vk::ImageMemoryRequirementsInfo2 {
             image: vk_image,
             ..default()
         };
-        let mut out = vk::MemoryRequirements2::default();
+        let mut out = vk::MemoryRequirements2::default().push_next(&mut dedicated_requirements);
         unsafe {
             dev.vk_device
                 .get_image_memory_requirements2(&image_memory_requirements, &mut out);
@@ -439,6 +999,12 @@ unsafe fn allocate_memory(
     };
     let allocation_size = memory_requirements.size;
     let memory_type_bits = memory_requirements.memory_type_bits;
+    // many drivers require, or at least prefer, exported images to have their
+    // own allocation. we never create `DISJOINT` images, so all memory planes
+    // are bound to this one allocation at their own offsets, whether or not
+    // it's dedicated
+    let dedicated = dedicated_requirements.requires_dedicated_allocation == vk::TRUE
+        || dedicated_requirements.prefers_dedicated_allocation == vk::TRUE;
 
     // ask the device what memory types it has
     let memory_props = {
@@ -452,45 +1018,80 @@ unsafe fn allocate_memory(
 
     // given what memory types the device has (`memory_props`),
     // and what kinds we can use for our image allocation (`memory_type_bits`),
-    // figure out what memory type index in that bitset is best for us
-    let memory_type_index = (0..memory_props.memory_type_count).find(|index| {
-        if memory_type_bits & (1 << index) == 0 {
-            return false;
+    // order the memory type indices in that bitset from best to worst for us.
+    // since the image was created as exportable, `memory_type_bits` only
+    // contains memory types which can back a dmabuf
+    let host_visible =
+        vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT;
+    let rank = |flags: vk::MemoryPropertyFlags| {
+        let is_device_local = flags.contains(vk::MemoryPropertyFlags::DEVICE_LOCAL);
+        let is_host_visible = flags.contains(host_visible);
+        match memory {
+            // memory which is also host-visible on a dGPU is the small BAR
+            // heap, which we'd rather leave to Bevy's staging buffers
+            DmabufMemoryPreference::DeviceLocal => match (is_device_local, is_host_visible) {
+                (true, false) => 0,
+                (true, true) => 1,
+                (false, true) => 2,
+                (false, false) => 3,
+            },
+            DmabufMemoryPreference::HostVisible => match (is_host_visible, is_device_local) {
+                (true, _) => 0,
+                (false, true) => 1,
+                (false, false) => 2,
+            },
         }
-
-        let memory_type_props = memory_props.memory_types[*index as usize].property_flags;
-        // we want a memory type which is visible on the CPU as well
-        // since the CPU will likely need to copy it through GTK
-        // Testing this vs `DEVICE_LOCAL`, this set of flags seems to have
-        // slightly better performance. But this warrants more profiling.
-        memory_type_props.contains(
-            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
-        )
-    });
-    let Some(memory_type_index) = memory_type_index else {
-        return Err("no compatible memory type found".into());
     };
+    let mut memory_type_indices = (0..memory_props.memory_type_count)
+        .filter(|index| memory_type_bits & (1 << index) != 0)
+        .collect::<Vec<_>>();
+    // Vulkan already orders memory types from fastest to slowest within the
+    // same flags, and this sort is stable
+    memory_type_indices
+        .sort_by_key(|index| rank(memory_props.memory_types[*index as usize].property_flags));
 
-    // this memory will be bound to exactly one image
-    // it's recommended to use a dedicated memory allocation for exported resources
-    let mut with_dedicated = vk::MemoryDedicatedAllocateInfo {
-        image: vk_image,
-        ..default()
-    };
-    // this memory must be exportable
-    let mut with_export = vk::ExportMemoryAllocateInfo {
-        handle_types: MEMORY_HANDLE_TYPE,
-        ..default()
-    };
+    let mut last_err = None;
+    for memory_type_index in memory_type_indices {
+        let memory_flags = memory_props.memory_types[memory_type_index as usize].property_flags;
 
-    let params = vk::MemoryAllocateInfo {
-        allocation_size,
-        memory_type_index,
-        ..default()
+        // this memory will be bound to exactly one image
+        let mut with_dedicated = vk::MemoryDedicatedAllocateInfo {
+            image: vk_image,
+            ..default()
+        };
+        // this memory must be exportable
+        let mut with_export = vk::ExportMemoryAllocateInfo {
+            handle_types: MEMORY_HANDLE_TYPE,
+            ..default()
+        };
+
+        let mut params = vk::MemoryAllocateInfo {
+            allocation_size,
+            memory_type_index,
+            ..default()
+        }
+        .push_next(&mut with_export);
+        if dedicated {
+            params = params.push_next(&mut with_dedicated);
+        }
+        match unsafe { dev.vk_device.allocate_memory(&params, None) } {
+            Ok(vk_memory) => {
+                trace!(
+                    "Allocated {allocation_size} bytes of memory type {memory_type_index} \
+                     ({memory_flags:?}, preferred {memory:?}, dedicated: {dedicated})"
+                );
+                return Ok((vk_memory, memory_flags));
+            }
+            Err(err) => {
+                trace!(
+                    "Failed to allocate memory type {memory_type_index} ({memory_flags:?}): \
+                     {err}, trying the next one"
+                );
+                last_err = Some(err);
+            }
+        }
     }
-    .push_next(&mut with_export)
-    .push_next(&mut with_dedicated);
-    Ok(unsafe { dev.vk_device.allocate_memory(&params, None) }?)
+    Err(last_err.map_or_else(|| "no compatible memory type found".into(), BevyError::from))
 }
 
 fn vk_texture_to_wgpu(
@@ -500,10 +1101,11 @@ fn vk_texture_to_wgpu(
     width: u32,
     height: u32,
     wgpu_format: wgpu::TextureFormat,
+    texture_params: TextureParams,
 ) -> wgpu::Texture {
     let hal_texture = {
         let hal_descriptor = wgpu_hal::TextureDescriptor {
-            label: Some(LABEL),
+            label: Some(texture_params.label),
             size: wgpu::Extent3d {
                 width,
                 height,
@@ -513,7 +1115,7 @@ fn vk_texture_to_wgpu(
             sample_count: WGPU_SAMPLES,
             dimension: WGPU_DIM,
             format: wgpu_format,
-            usage: hal_usage(),
+            usage: texture_params.hal,
             memory_flags: wgpu_hal::MemoryFlags::empty(),
             view_formats: Vec::new(),
         };
@@ -535,7 +1137,7 @@ fn vk_texture_to_wgpu(
     };
 
     let wgpu_descriptor = wgpu::TextureDescriptor {
-        label: Some(LABEL),
+        label: Some(texture_params.label),
         size: wgpu::Extent3d {
             width,
             height,
@@ -545,7 +1147,7 @@ fn vk_texture_to_wgpu(
         sample_count: WGPU_SAMPLES,
         dimension: WGPU_DIM,
         format: wgpu_format,
-        usage: wgpu_usage(),
+        usage: texture_params.wgpu,
         view_formats: &[],
     };
     // SAFETY:
@@ -559,11 +1161,295 @@ fn vk_texture_to_wgpu(
     }
 }
 
-fn format_to_fourcc(format: wgpu::TextureFormat) -> Option<DrmFourcc> {
+fn import_dmabuf_texture(
+    wgpu_adapter: &wgpu::Adapter,
+    wgpu_device: &wgpu::Device,
+    import: DmabufImport,
+) -> Result<ImportedDmabufTexture, BevyError> {
+    // See `create_dmabuf_texture` for required reading.
+    // Importing is simpler than exporting: the producer has already picked a
+    // modifier and plane layout, so we describe exactly that layout to Vulkan
+    // with `VkImageDrmFormatModifierExplicitCreateInfoEXT`, then bind the
+    // buffer's memory to the image.
+
+    // SAFETY: `hal_adapter` is not manually destroyed by us
+    let hal_adapter = unsafe { wgpu_adapter.as_hal::<wgpu_hal::vulkan::Api>() }
+        .ok_or("render adapter is not a Vulkan adapter")?;
+    // SAFETY: `hal_device` is not manually destroyed by us
+    let hal_device = unsafe { wgpu_device.as_hal::<wgpu_hal::vulkan::Api>() }
+        .ok_or("render device is not a Vulkan device")?;
+
+    let dev = Devices {
+        vk_instance: hal_device.shared_instance().raw_instance(),
+        hal_adapter: &hal_adapter,
+        vk_physical_device: hal_device.raw_physical_device(),
+        vk_device: hal_device.raw_device(),
+        hal_device: &hal_device,
+        wgpu_device,
+    };
+
+    let DmabufImport {
+        fd,
+        width,
+        height,
+        drm_format,
+        planes,
+    } = import;
+    if planes.is_empty() {
+        return Err("dmabuf import must have at least 1 plane".into());
+    }
+    let wgpu_format = fourcc_to_format(drm_format.code).ok_or_else(|| {
+        format!(
+            "DRM format {} cannot be mapped to a texture format",
+            drm_format.code
+        )
+    })?;
+    trace!(
+        "Importing {width}x{height} dmabuf with DRM format {}:0x{:016x} and {} plane(s)",
+        drm_format.code,
+        u64::from(drm_format.modifier),
+        planes.len(),
+    );
+
+    let vk_image =
+        unsafe { create_import_image(&dev, width, height, wgpu_format, drm_format, &planes) }?;
+    let vk_memory = match unsafe { import_memory(&dev, vk_image, fd) } {
+        Ok(vk_memory) => vk_memory,
+        Err(err) => {
+            unsafe { dev.vk_device.destroy_image(vk_image, None) };
+            return Err(err);
+        }
+    };
+    if let Err(err) = unsafe { dev.vk_device.bind_image_memory(vk_image, vk_memory, 0) } {
+        unsafe {
+            dev.vk_device.destroy_image(vk_image, None);
+            dev.vk_device.free_memory(vk_memory, None);
+        }
+        return Err(err.into());
+    }
+
+    // TODO: we don't do a queue family ownership transfer from
+    // `VK_QUEUE_FAMILY_FOREIGN_EXT`, and wgpu will transition the image out of
+    // `UNDEFINED` on first use. Mesa drivers preserve the contents of dmabuf
+    // images anyway, but this is technically not guaranteed.
+    let texture_params = TextureParams {
+        label: IMPORT_LABEL,
+        hal: import_hal_usage(),
+        wgpu: import_wgpu_usage(),
+    };
+    let wgpu_texture = vk_texture_to_wgpu(
+        &dev,
+        vk_image,
+        vk_memory,
+        width,
+        height,
+        wgpu_format,
+        texture_params,
+    );
+    Ok(ImportedDmabufTexture {
+        wgpu_texture,
+        drm_format,
+    })
+}
+
+unsafe fn create_import_image(
+    dev: &Devices,
+    width: u32,
+    height: u32,
+    wgpu_format: wgpu::TextureFormat,
+    drm_format: DrmFormat,
+    planes: &[DmabufImportPlane],
+) -> Result<vk::Image, BevyError> {
+    let vk_format = dev.hal_adapter.texture_format_as_raw(wgpu_format);
+
+    // the producer already chose a modifier, so make sure we can actually use it
+    let drm_modifier_infos = unsafe { get_drm_modifier_infos(dev.hal_adapter, wgpu_format) };
+    let Some(drm_modifier_info) = drm_modifier_infos
+        .iter()
+        .find(|info| info.modifier == drm_format.modifier)
+    else {
+        return Err(format!(
+            "DRM modifier {:?} is not supported by this device for {wgpu_format:?}",
+            drm_format.modifier
+        )
+        .into());
+    };
+    if drm_modifier_info.plane_count as usize != planes.len() {
+        return Err(format!(
+            "DRM modifier {:?} requires {} plane(s), but {} were given",
+            drm_format.modifier,
+            drm_modifier_info.plane_count,
+            planes.len()
+        )
+        .into());
+    }
+
+    let plane_layouts = planes
+        .iter()
+        .map(|plane| vk::SubresourceLayout {
+            offset: u64::from(plane.offset),
+            row_pitch: u64::from(plane.stride),
+            // must be 0 for explicit modifier layouts
+            size: 0,
+            array_pitch: 0,
+            depth_pitch: 0,
+        })
+        .collect::<ArrayVec<_, MAX_PLANES_U>>();
+    let mut with_drm_modifier = vk::ImageDrmFormatModifierExplicitCreateInfoEXT {
+        drm_format_modifier: drm_format.modifier.into(),
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "there are no more than `MAX_PLANES` planes"
+        )]
+        drm_format_modifier_plane_count: plane_layouts.len() as u32,
+        p_plane_layouts: plane_layouts.as_ptr(),
+        ..default()
+    };
+
+    let mut with_external_memory = vk::ExternalMemoryImageCreateInfo {
+        handle_types: MEMORY_HANDLE_TYPE,
+        ..default()
+    };
+
+    let params = vk::ImageCreateInfo {
+        image_type: VK_DIM,
+        format: vk_format,
+        extent: vk::Extent3D {
+            width,
+            height,
+            depth: 1,
+        },
+        mip_levels: MIP_LEVELS,
+        array_layers: 1,
+        samples: VK_SAMPLES,
+        tiling: VK_TILING,
+        usage: import_vk_usage(),
+        sharing_mode: vk::SharingMode::EXCLUSIVE,
+        initial_layout: vk::ImageLayout::UNDEFINED,
+        ..default()
+    }
+    .push_next(&mut with_drm_modifier)
+    .push_next(&mut with_external_memory);
+    Ok(unsafe { dev.vk_device.create_image(&params, None) }?)
+}
+
+unsafe fn import_memory(
+    dev: &Devices,
+    vk_image: vk::Image,
+    fd: OwnedFd,
+) -> Result<vk::DeviceMemory, BevyError> {
+    let mut dedicated_requirements = vk::MemoryDedicatedRequirements::default();
+    let memory_requirements = {
+        let image_memory_requirements = vk::ImageMemoryRequirementsInfo2 {
+            image: vk_image,
+            ..default()
+        };
+        let mut out = vk::MemoryRequirements2::default().push_next(&mut dedicated_requirements);
+        unsafe {
+            dev.vk_device
+                .get_image_memory_requirements2(&image_memory_requirements, &mut out);
+        }
+        out.memory_requirements
+    };
+
+    // the memory types we can use are limited by both the image and the fd
+    let fd_memory_type_bits = {
+        let mut out = vk::MemoryFdPropertiesKHR::default();
+        unsafe {
+            ash::khr::external_memory_fd::Device::new(dev.vk_instance, dev.vk_device)
+                .get_memory_fd_properties(MEMORY_HANDLE_TYPE, fd.as_raw_fd(), &mut out)
+        }?;
+        out.memory_type_bits
+    };
+    let memory_type_bits = memory_requirements.memory_type_bits & fd_memory_type_bits;
+    if memory_type_bits == 0 {
+        return Err("no compatible memory type found for imported dmabuf".into());
+    }
+    let memory_type_index = memory_type_bits.trailing_zeros();
+
+    let mut with_dedicated = vk::MemoryDedicatedAllocateInfo {
+        image: vk_image,
+        ..default()
+    };
+    let mut with_import = vk::ImportMemoryFdInfoKHR {
+        handle_type: MEMORY_HANDLE_TYPE,
+        fd: fd.as_raw_fd(),
+        ..default()
+    };
+
+    let mut params = vk::MemoryAllocateInfo {
+        allocation_size: memory_requirements.size,
+        memory_type_index,
+        ..default()
+    }
+    .push_next(&mut with_import);
+    if dedicated_requirements.requires_dedicated_allocation == vk::TRUE
+        || dedicated_requirements.prefers_dedicated_allocation == vk::TRUE
+    {
+        params = params.push_next(&mut with_dedicated);
+    }
+    let vk_memory = unsafe { dev.vk_device.allocate_memory(&params, None) }?;
+    // <https://registry.khronos.org/vulkan/specs/latest/man/html/VkImportMemoryFdInfoKHR.html>
+    //
+    //     Importing memory from a file descriptor transfers ownership of the
+    //     file descriptor from the application to the Vulkan implementation.
+    //
+    // on failure, ownership stays with us, and `fd` is closed on drop
+    _ = fd.into_raw_fd();
+    Ok(vk_memory)
+}
+
+pub(super) fn format_to_fourcc(format: wgpu::TextureFormat) -> Option<DrmFourcc> {
     // <https://registry.khronos.org/vulkan/specs/latest/man/html/VK_EXT_image_drm_format_modifier.html#_format_translation>
     use {DrmFourcc as Cc, wgpu::TextureFormat as Tf};
     match format {
+        // fourccs don't say how values are encoded, so sRGB and linear formats
+        // share a fourcc, and the texture is tagged with a color state instead;
+        // see `ViewportColorState::for_texture_format`
         Tf::Rgba8Unorm | Tf::Rgba8UnormSrgb => Some(Cc::Abgr8888),
+        Tf::Bgra8Unorm | Tf::Bgra8UnormSrgb => Some(Cc::Argb8888),
+        Tf::Rgba16Float => Some(Cc::Abgr16161616f),
+        _ => None, // TODO
+    }
+}
+
+/// DRM fourccs which [`ImportedDmabufTexture`] can import.
+pub(super) const IMPORTABLE_FOURCCS: [DrmFourcc; 6] = [
+    DrmFourcc::Abgr8888,
+    DrmFourcc::Xbgr8888,
+    DrmFourcc::Argb8888,
+    DrmFourcc::Xrgb8888,
+    DrmFourcc::Abgr16161616f,
+    DrmFourcc::Xbgr16161616f,
+];
+
+/// Gets the DRM formats which [`ImportedDmabufTexture::new`] can import on
+/// `wgpu_adapter`, or nothing if it isn't a Vulkan adapter.
+pub(super) fn importable_drm_formats(wgpu_adapter: &wgpu::Adapter) -> Vec<DrmFormat> {
+    // SAFETY: `hal_adapter` is not manually destroyed by us
+    let Some(hal_adapter) = (unsafe { wgpu_adapter.as_hal::<wgpu_hal::vulkan::Api>() }) else {
+        return Vec::new();
+    };
+    IMPORTABLE_FOURCCS
+        .into_iter()
+        .filter_map(|code| Some((code, fourcc_to_format(code)?)))
+        .flat_map(|(code, wgpu_format)| {
+            // SAFETY: `hal_adapter` is a valid Vulkan adapter
+            let infos = unsafe { get_drm_modifier_infos(&hal_adapter, wgpu_format) };
+            infos.into_iter().map(move |info| DrmFormat {
+                code,
+                modifier: info.modifier,
+            })
+        })
+        .collect()
+}
+
+fn fourcc_to_format(fourcc: DrmFourcc) -> Option<wgpu::TextureFormat> {
+    use {DrmFourcc as Cc, wgpu::TextureFormat as Tf};
+    match fourcc {
+        Cc::Abgr8888 | Cc::Xbgr8888 => Some(Tf::Rgba8Unorm),
+        Cc::Argb8888 | Cc::Xrgb8888 => Some(Tf::Bgra8Unorm),
+        Cc::Abgr16161616f | Cc::Xbgr16161616f => Some(Tf::Rgba16Float),
         _ => None, // TODO
     }
 }
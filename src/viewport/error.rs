@@ -0,0 +1,128 @@
+use {
+    alloc::sync::Arc,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    core::{
+        fmt::Display,
+        sync::atomic::{self, AtomicBool},
+    },
+    log::error,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let (tx, rx) = async_channel::unbounded();
+    app.add_event::<ViewportError>()
+        .insert_resource(ViewportErrorChannel { tx, rx })
+        .add_systems(PreUpdate, forward_errors);
+}
+
+/// Emitted when a viewport fails and can no longer render.
+///
+/// When a viewport fails, it is marked as broken (see
+/// [`GtkViewport::is_broken`]), and its GTK widget displays a fallback widget
+/// instead of the Bevy content. The rest of the app keeps running, and it is
+/// up to you to decide how to respond, e.g. by showing an error to the user or
+/// creating a new viewport.
+///
+/// [`GtkViewport::is_broken`]: crate::GtkViewport::is_broken
+#[derive(Debug, Clone, Event)]
+pub struct ViewportError {
+    /// Entity of the viewport which failed.
+    ///
+    /// See [`GtkViewport::entity`](crate::GtkViewport::entity).
+    pub viewport: Entity,
+    /// What the viewport was doing when it failed.
+    pub kind: ViewportErrorKind,
+    /// Description of the error.
+    pub message: String,
+}
+
+/// What a viewport was doing when it failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViewportErrorKind {
+    /// Creating the main world [`Image`](bevy_image::Image).
+    CreateImage,
+    /// Creating a [`DmabufTexture`](crate::DmabufTexture) in the render world.
+    CreateDmabuf,
+    /// Importing a dmabuf which was made outside of Bevy, e.g. a frame of a
+    /// GStreamer video sink.
+    ///
+    /// For video sinks, [`ViewportError::viewport`] is the sink's own private
+    /// entity, not a viewport.
+    ImportDmabuf,
+    /// Building a [`gdk::Texture`] from a rendered frame on the GTK side.
+    BuildGdkTexture,
+    /// Exchanging frames with another process, when rendering in a separate
+    /// process from GTK.
+    ///
+    /// See [`RemoteViewport`](crate::RemoteViewport).
+    Remote,
+}
+
+#[derive(Debug, Resource)]
+pub(super) struct ViewportErrorChannel {
+    pub tx: async_channel::Sender<ViewportError>,
+    rx: async_channel::Receiver<ViewportError>,
+}
+
+/// Shared between all parts of a single viewport, to mark it as broken and
+/// report the error back to the main world, or to mark it as destroyed from the
+/// Bevy side.
+#[derive(Debug, Clone)]
+pub(super) struct ViewportHealth {
+    viewport: Entity,
+    broken: Arc<AtomicBool>,
+    destroyed: Arc<AtomicBool>,
+    tx_error: async_channel::Sender<ViewportError>,
+}
+
+impl ViewportHealth {
+    pub fn new(viewport: Entity, tx_error: async_channel::Sender<ViewportError>) -> Self {
+        Self {
+            viewport,
+            broken: Arc::new(AtomicBool::new(false)),
+            destroyed: Arc::new(AtomicBool::new(false)),
+            tx_error,
+        }
+    }
+
+    pub fn viewport(&self) -> Entity {
+        self.viewport
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.broken.load(atomic::Ordering::SeqCst)
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.destroyed.load(atomic::Ordering::SeqCst)
+    }
+
+    /// Marks this viewport as destroyed, so that the GTK side stops displaying
+    /// it.
+    pub fn destroy(&self) {
+        self.destroyed.store(true, atomic::Ordering::SeqCst);
+    }
+
+    /// Marks this viewport as broken, and reports the error if this is the
+    /// first time it has failed.
+    pub fn fail(&self, kind: ViewportErrorKind, err: impl Display) {
+        if self.broken.swap(true, atomic::Ordering::SeqCst) {
+            return;
+        }
+
+        let message = err.to_string();
+        error!("Viewport {} failed ({kind:?}): {message}", self.viewport);
+        _ = self.tx_error.try_send(ViewportError {
+            viewport: self.viewport,
+            kind,
+            message,
+        });
+    }
+}
+
+fn forward_errors(channel: Res<ViewportErrorChannel>, mut errors: EventWriter<ViewportError>) {
+    while let Ok(error) = channel.rx.try_recv() {
+        errors.write(error);
+    }
+}
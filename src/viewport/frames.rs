@@ -0,0 +1,149 @@
+use {
+    super::{Swapchain, ViewportColorState, ViewportFrame},
+    alloc::collections::VecDeque,
+    bevy_ecs::error::BevyError,
+    core::time::Duration,
+    std::sync::{Condvar, Mutex, MutexGuard, PoisonError},
+};
+
+/// Frames which the render world has handed over to the GTK side, but which
+/// GTK hasn't taken yet.
+///
+/// How many frames may be queued depends on the viewport's
+/// [`ViewportLatency`](super::ViewportLatency).
+#[derive(Debug, Default)]
+pub(super) struct FrameQueue {
+    frames: Mutex<VecDeque<ViewportFrame>>,
+    /// Notified whenever GTK takes a frame.
+    taken: Condvar,
+}
+
+impl FrameQueue {
+    /// Queues a frame for GTK to take.
+    ///
+    /// If more than `capacity` frames are queued, the oldest ones are dropped.
+    pub fn push(&self, frame: ViewportFrame, capacity: usize) {
+        let mut frames = self.lock();
+        frames.push_back(frame);
+        while frames.len() > capacity.max(1) {
+            frames.pop_front();
+        }
+    }
+
+    /// Takes the oldest queued frame.
+    pub fn pop(&self) -> Option<ViewportFrame> {
+        let frame = self.lock().pop_front();
+        if frame.is_some() {
+            self.taken.notify_all();
+        }
+        frame
+    }
+
+    /// Drops all queued frames.
+    pub fn clear(&self) {
+        self.lock().clear();
+        self.taken.notify_all();
+    }
+
+    /// Blocks until GTK has taken every queued frame, or until `timeout` has
+    /// passed.
+    ///
+    /// Returns whether all frames were taken.
+    pub fn wait_until_empty(&self, timeout: Duration) -> bool {
+        let (frames, _) = self
+            .taken
+            .wait_timeout_while(self.lock(), timeout, |frames| !frames.is_empty())
+            .unwrap_or_else(PoisonError::into_inner);
+        frames.is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<ViewportFrame>> {
+        // the queue is valid even if someone panicked while holding the lock
+        self.frames.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// GDK textures which the GTK side has built for the frames it's taken.
+///
+/// When a viewport renders into several dmabufs in turn, we receive the same
+/// dmabufs again and again, so we keep the textures that we built for each of
+/// them, instead of importing every frame into GTK from scratch.
+#[derive(Debug, Default)]
+pub(super) struct FrameTextures {
+    /// Maximum number of frames which we keep textures for.
+    ///
+    /// We always keep the textures of the current frame, even if this is zero.
+    capacity: usize,
+    /// Color state that the textures are tagged with, overriding the one
+    /// which matches the format of each frame.
+    color_state: Option<ViewportColorState>,
+    /// Textures of each frame, with the current frame last, keyed by the
+    /// texture of the dmabuf that they were built from.
+    ///
+    /// Frames which were copied through the CPU are never received again, so
+    /// they have no key.
+    built: VecDeque<(Option<wgpu::Texture>, Swapchain)>,
+}
+
+impl FrameTextures {
+    pub const fn new(capacity: usize, color_state: Option<ViewportColorState>) -> Self {
+        Self {
+            capacity,
+            color_state,
+            built: VecDeque::new(),
+        }
+    }
+
+    /// Makes `frame` the current frame, building textures for it if we
+    /// haven't seen it before.
+    pub fn take_frame(&mut self, frame: &ViewportFrame) -> Result<(), BevyError> {
+        let key = frame.dmabuf_texture();
+        let existing = key.and_then(|key| {
+            self.built
+                .iter()
+                .position(|(built_key, _)| built_key.as_ref() == Some(key))
+        });
+        let entry = if let Some(index) = existing {
+            self.built.remove(index).expect("index should be in bounds")
+        } else {
+            // "wait.. why do we build 2 gdk textures for the same dmabuf?"
+            //
+            // GTK doesn't redraw the picture unless you manually change the
+            // paintable inside it. I couldn't find a way to force it to redraw.
+            // So instead, we have 2 paintables with the same underlying content
+            // (same dmabuf), and switch between them.
+            let color_state = self
+                .color_state
+                .unwrap_or_else(|| ViewportColorState::for_texture_format(frame.format()))
+                .to_gdk();
+            let texture_a = frame.build_gdk_texture(&color_state)?;
+            let texture_b = frame.build_gdk_texture(&color_state)?;
+            if key.is_none() {
+                self.built.retain(|(built_key, _)| built_key.is_some());
+            }
+            (
+                key.cloned(),
+                Swapchain {
+                    texture_a,
+                    texture_b,
+                },
+            )
+        };
+
+        self.built.push_back(entry);
+        while self.built.len() > self.capacity.max(1) {
+            self.built.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Gets the textures of the current frame.
+    pub fn current(&mut self) -> Option<&mut Swapchain> {
+        self.built.back_mut().map(|(_, swapchain)| swapchain)
+    }
+
+    /// Drops all textures.
+    pub fn clear(&mut self) {
+        self.built.clear();
+    }
+}
@@ -0,0 +1,86 @@
+use {
+    super::RenderViewport,
+    bevy_ecs::prelude::*,
+    bevy_math::UVec2,
+    bevy_render::{
+        graph::CameraDriverLabel,
+        render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel},
+        render_resource::{Texture, TextureView},
+        renderer::RenderContext,
+    },
+    wgpu::TextureFormat,
+};
+
+pub(super) fn add_driver_node(world: &mut World) {
+    let node = ViewportDriverNode {
+        viewports: world.query(),
+    };
+    let mut render_graph = world.resource_mut::<RenderGraph>();
+    render_graph.add_node(ViewportDriverLabel, node);
+    render_graph.add_node_edge(CameraDriverLabel, ViewportDriverLabel);
+}
+
+/// Texture which a viewport's content is rendered into, on the render world
+/// entity of a viewport.
+///
+/// When a viewport has a [`ViewportConfig::render_graph`], its sub-graph is run
+/// with the viewport's render world entity as the view entity, so nodes in
+/// that graph can get this component from [`RenderGraphContext::view_entity`]
+/// and write straight into the texture which is shared with GTK.
+///
+/// [`ViewportConfig::render_graph`]: crate::ViewportConfig::render_graph
+#[derive(Debug, Clone, Component)]
+pub struct ViewportRenderTarget {
+    /// Texture backed by the viewport's dmabuf, or a plain texture if the
+    /// render device can't share dmabufs with GTK.
+    ///
+    /// This can be used as a render attachment, or as the source or
+    /// destination of a copy. It can't be bound as a storage texture, so
+    /// compute shaders should write to their own texture, and copy or draw it
+    /// into this one.
+    pub texture: Texture,
+    /// Default view of [`ViewportRenderTarget::texture`].
+    pub view: TextureView,
+    /// Format of [`ViewportRenderTarget::texture`].
+    pub format: TextureFormat,
+    /// Size of [`ViewportRenderTarget::texture`] in physical pixels.
+    pub size: UVec2,
+}
+
+/// Render graph node which runs the [`ViewportConfig::render_graph`] of each
+/// viewport.
+///
+/// This runs after [`CameraDriverLabel`], so a viewport's graph can draw on top
+/// of what cameras have rendered into it.
+///
+/// [`ViewportConfig::render_graph`]: crate::ViewportConfig::render_graph
+#[derive(Debug, Clone, PartialEq, Eq, Hash, RenderLabel)]
+pub struct ViewportDriverLabel;
+
+struct ViewportDriverNode {
+    viewports: QueryState<(Entity, &'static RenderViewport)>,
+}
+
+impl Node for ViewportDriverNode {
+    fn update(&mut self, world: &mut World) {
+        self.viewports.update_archetypes(world);
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        for (entity, viewport) in self.viewports.iter_manual(world) {
+            let Some(render_graph) = viewport.render_graph else {
+                continue;
+            };
+            if viewport.health.is_broken() || viewport.back_buffer.is_none() {
+                continue;
+            }
+            graph.run_sub_graph(render_graph, vec![], Some(entity))?;
+        }
+        Ok(())
+    }
+}
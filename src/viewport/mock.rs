@@ -0,0 +1,170 @@
+//! Fakes of the GTK side of a viewport, so that viewport systems can run
+//! without a display.
+
+use {
+    super::{AtomicSize, FrameQueue, RenderViewport, ViewportFrame, ViewportHealth, WidgetFactory},
+    alloc::sync::Arc,
+    atomic_float::AtomicF64,
+    bevy_ecs::prelude::*,
+    bevy_render::{render_resource::Texture, renderer::RenderDevice},
+    core::sync::atomic::{self, AtomicBool, AtomicU64},
+    wgpu::TextureFormat,
+};
+
+/// Makes viewports render into normal textures instead of dmabufs.
+///
+/// Insert this into the render app, and viewports hand over the textures that
+/// they render into as frames, instead of allocating dmabufs or reading frames
+/// back to the CPU. This lets the render world systems of a viewport run on
+/// any render device, e.g. a software rasterizer in CI, which can't share
+/// dmabufs.
+///
+/// The frames can't be displayed by GTK, so only use this with a
+/// [`MockWidget`].
+#[derive(Debug, Clone, Copy, Default, Resource)]
+pub struct MockDmabufs;
+
+/// GTK side of a viewport, without a GTK widget.
+///
+/// Make one with [`WidgetFactory::make_mock`]. Instead of GTK laying out a
+/// widget and taking frames from the render world, you set the size of the
+/// widget and take frames yourself. Like a widget, the viewport lives for as
+/// long as this lives: dropping it despawns the viewport on the next update.
+///
+/// # Examples
+///
+/// ```ignore
+/// let (viewport, widget_factory) = viewports.create();
+/// let mock = widget_factory.make_mock();
+/// mock.set_size(640, 480);
+///
+/// app.update();
+/// let frame = mock.take_frame().expect("should have rendered a frame");
+/// assert_eq!(frame.size(), Some((640, 480)));
+/// ```
+#[derive(Debug)]
+pub struct MockWidget {
+    health: ViewportHealth,
+    frames: Arc<FrameQueue>,
+    widget_size: Arc<AtomicSize>,
+    frame_count: Arc<AtomicU64>,
+    rx_frame_ready: async_channel::Receiver<()>,
+    widget_scale_factor: Arc<AtomicF64>,
+    window_resizing: Arc<AtomicBool>,
+    _widget_alive: Arc<()>,
+}
+
+impl WidgetFactory {
+    /// Makes a [`MockWidget`] instead of a widget, for running viewports
+    /// without a display.
+    ///
+    /// Unlike [`WidgetFactory::make`], this does not need to be called on the
+    /// GTK thread.
+    #[must_use]
+    pub fn make_mock(self) -> MockWidget {
+        MockWidget {
+            health: self.health,
+            frames: self.frames,
+            widget_size: self.widget_size,
+            frame_count: self.frame_count,
+            rx_frame_ready: self.rx_frame_ready,
+            widget_scale_factor: self.widget_scale_factor,
+            window_resizing: self.window_resizing,
+            _widget_alive: self.widget_alive,
+        }
+    }
+}
+
+impl MockWidget {
+    /// Entity of the viewport which this is the GTK side of.
+    ///
+    /// See [`GtkViewport::entity`](super::GtkViewport::entity).
+    #[must_use]
+    pub fn entity(&self) -> Entity {
+        self.health.viewport()
+    }
+
+    /// Whether the viewport has failed and can no longer render.
+    #[must_use]
+    pub fn is_broken(&self) -> bool {
+        self.health.is_broken()
+    }
+
+    /// Sets the size of the widget in physical pixels, as GTK would after
+    /// laying it out.
+    pub fn set_size(&self, width: u32, height: u32) {
+        self.widget_size.store(width, height);
+    }
+
+    /// Sets the scale factor of the widget, as GTK would when it's moved to
+    /// another monitor.
+    pub fn set_scale_factor(&self, scale_factor: f64) {
+        self.widget_scale_factor
+            .store(scale_factor, atomic::Ordering::SeqCst);
+    }
+
+    /// Sets whether the window that the widget is in is being resized
+    /// interactively.
+    pub fn set_resizing(&self, resizing: bool) {
+        self.window_resizing
+            .store(resizing, atomic::Ordering::SeqCst);
+    }
+
+    /// Number of frames presented to this widget so far.
+    #[must_use]
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count.load(atomic::Ordering::SeqCst)
+    }
+
+    /// Whether a frame has been presented since the last call.
+    pub fn frame_ready(&self) -> bool {
+        self.rx_frame_ready.try_recv().is_ok()
+    }
+
+    /// Takes the oldest frame which the render world has handed over, like
+    /// GTK does when it draws the widget.
+    pub fn take_frame(&self) -> Option<MockFrame> {
+        self.frames.pop().map(MockFrame)
+    }
+}
+
+/// Frame taken by a [`MockWidget`].
+#[derive(Debug)]
+pub struct MockFrame(ViewportFrame);
+
+impl MockFrame {
+    /// Texture that Bevy rendered this frame into, if it was not copied
+    /// through the CPU.
+    #[must_use]
+    pub fn texture(&self) -> Option<&wgpu::Texture> {
+        self.0.dmabuf_texture()
+    }
+
+    /// Whether this frame is a dmabuf which GTK could import.
+    #[must_use]
+    pub fn is_dmabuf(&self) -> bool {
+        matches!(self.0, ViewportFrame::Dmabuf(_))
+    }
+
+    /// Size of this frame in pixels, if it was not copied through the CPU.
+    #[must_use]
+    pub fn size(&self) -> Option<(u32, u32)> {
+        self.texture()
+            .map(|texture| (texture.width(), texture.height()))
+    }
+}
+
+/// Creates a texture for a viewport to render into, and hand over as frames
+/// in place of a dmabuf.
+pub(super) fn create_texture(
+    viewport: &mut RenderViewport,
+    render_device: &RenderDevice,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+) -> Texture {
+    let texture = super::readback::create_texture(render_device, width, height, format);
+    viewport.readback = None;
+    viewport.mock_frame = Some(texture.clone());
+    texture
+}
@@ -5,8 +5,9 @@
 //!   camera render into that viewport
 //!   - you can also get a [`Handle<Image>`] to its image directly, if you need
 //!     that
-//! - you get a [`ViewportWidgetFactory`] which you can use to make a
-//!   [`gtk::GraphicsOffload`] widget for your app
+//! - you get a [`WidgetFactory`] which you can use to make a
+//!   [`gtk::GraphicsOffload`] widget for your app, or a [`BevyPaintable`] which
+//!   any GTK widget can display
 //! - a private [`ViewportPrivate`] entity is spawned which is copied into the
 //!   render world, and drives rendering logic
 //!
@@ -21,11 +22,23 @@
 //! destroyed, everything else goes with it. We then manually propagate this
 //! cleanup to the Bevy world.
 //!
+//! Removing the widget from its parent doesn't destroy it, as long as something
+//! else still holds a reference to it. This means the widget can be moved to a
+//! different parent, or a different window (see
+//! [`GtkWindows::move_content`](crate::GtkWindows::move_content)), and the
+//! viewport keeps rendering with the same camera and dmabufs. Its size and
+//! scale factor are picked up again once it's shown in its new parent.
+//!
 //! The widget is responsible for:
 //! - reading its own width and height, and sending that to the Bevy app
 //! - receiving [`DmabufTexture`]s from the app, making [`gdk::Texture`]s out of
 //!   them, and rendering them to the GTK app
 //!
+//! If the render device doesn't support the Vulkan extensions needed to share
+//! dmabufs, we instead render into a plain texture, read each frame back to
+//! the CPU, and give GTK a [`gdk::MemoryTexture`] copy of it. This is much
+//! slower, but means the app still works on any driver.
+//!
 //! GTK land effectively acts as our front buffer, and Bevy as our back buffer;
 //! swapping buffers is implicit, by sending the rendered Bevy back buffer to
 //! GTK. Bevy deals with dmabufs and wgpu textures, and GTK deals with dmabufs
@@ -44,72 +57,189 @@
 //! different sizes.
 
 use {
-    alloc::sync::Arc,
-    atomic_float::AtomicF64,
-    atomicbox::AtomicOptionBox,
+    crate::{GtkCapabilities, GtkCommands, GtkContext, GtkLifecycle, MakeWidget},
+    alloc::{borrow::Cow, rc::Rc, sync::Arc},
+    atomic_float::{AtomicF32, AtomicF64},
     bevy_app::prelude::*,
-    bevy_asset::{Assets, Handle, RenderAssetUsages},
+    bevy_asset::{AssetId, Assets, Handle, RenderAssetUsages},
     bevy_camera::{Camera, CameraUpdateSystems, ImageRenderTarget, RenderTarget},
-    bevy_ecs::{prelude::*, query::QueryItem, system::SystemParam},
+    bevy_ecs::{error::BevyError, prelude::*, system::SystemParam},
     bevy_image::Image,
-    bevy_math::FloatOrd,
+    bevy_math::{FloatOrd, URect, UVec2},
+    bevy_platform::collections::HashMap,
     bevy_render::{
-        Render, RenderApp, RenderSystems,
-        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        Extract, ExtractSchedule, Render, RenderApp, RenderSystems,
+        extract_resource::ExtractResourcePlugin,
         render_asset::RenderAssets,
+        render_graph::InternedRenderSubGraph,
         render_resource::{Texture, TextureView},
-        renderer::{RenderAdapter, RenderDevice},
-        sync_world::SyncToRenderWorld,
+        renderer::{
+            RenderAdapter, RenderDevice, RenderQueue, raw_vulkan_init::AdditionalVulkanFeatures,
+        },
+        sync_world::{MainEntity, RenderEntity, SyncToRenderWorld},
         texture::{DefaultImageSampler, GpuImage},
     },
     core::{
         cell::{Cell, RefCell},
         mem,
-        sync::atomic::{self, AtomicU32},
+        num::NonZeroU8,
+        sync::atomic::{self, AtomicBool, AtomicU8, AtomicU64},
+        time::Duration,
     },
     gdk::prelude::*,
     glib::clone,
     gtk::prelude::*,
     log::{debug, trace},
+    std::time::Instant,
     wgpu::{Extent3d, TextureDimension, TextureFormat, TextureUsages, TextureViewDescriptor},
 };
 
+mod accessibility;
+mod adapter;
+mod allocation;
+mod auto;
+mod canvas;
+mod capture;
+mod close;
+mod context_menu;
+mod depth;
+mod device_lost;
+mod diagnostics;
 mod dmabuf;
-pub use dmabuf::*;
+mod error;
+mod frames;
+mod graph;
+#[cfg(feature = "test-utils")]
+mod mock;
+mod paintable;
+mod print;
+mod readback;
+mod redraw;
+mod remote;
+mod render_data;
+mod snapshot;
+mod transition;
+mod validation;
+#[cfg(feature = "gstreamer")]
+mod video;
+mod visible;
+mod widget;
+#[cfg(feature = "test-utils")]
+pub use mock::{MockDmabufs, MockFrame, MockWidget};
+#[cfg(feature = "gstreamer")]
+pub use video::*;
+use {
+    accessibility::{AccessibilityBridge, AccessibilityWidget},
+    capture::Recorder,
+    device_lost::RenderDeviceState,
+    diagnostics::ViewportCounters,
+    error::{ViewportErrorChannel, ViewportHealth},
+    frames::{FrameQueue, FrameTextures},
+    readback::{CpuFrame, Readback},
+    visible::VisibleRect,
+};
+pub use {
+    accessibility::{AccessibleNode, AccessibleNodeActivated},
+    adapter::GtkAdapterSelection,
+    auto::GtkWindowViewport,
+    canvas::{CanvasWidgetFactory, GtkCanvas},
+    capture::{
+        CapturedFrame, FrameEncoder, MakeFrameEncoder, RecordingEncoder, StartRecording,
+        StopRecording,
+    },
+    close::ViewportWillClose,
+    context_menu::{ContextMenuActivated, GtkMenu, GtkMenuItem, ShowContextMenu},
+    depth::{ViewportDepthTexture, ViewportDepthTextures},
+    device_lost::RenderDeviceLost,
+    diagnostics::GtkViewportDiagnosticsPlugin,
+    dmabuf::*,
+    error::{ViewportError, ViewportErrorKind},
+    graph::{ViewportDriverLabel, ViewportRenderTarget},
+    paintable::BevyPaintable,
+    print::{PrintImage, PrintScale},
+    remote::{REMOTE_FD_ENV, RemoteConnection, RemoteViewport},
+    render_data::GtkRenderData,
+    snapshot::viewport_frame_texture,
+    transition::ViewportTransition,
+    validation::GtkVulkanValidation,
+    widget::BevyGtkViewport,
+};
 
 pub(super) fn init_plugin(app: &mut App) {
+    validation::init_plugin(app);
     dmabuf::init_plugin(app);
 }
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(ExtractComponentPlugin::<RenderViewport>::default())
-        .add_systems(
-            PostStartup,
-            (sync_viewport_and_camera, update_images)
+    if app.get_sub_app(RenderApp).is_none() {
+        debug!("Bevy has no `RenderApp`, so viewports are disabled");
+        return;
+    }
+
+    #[cfg(feature = "gstreamer")]
+    video::plugin(app);
+
+    // register the type so that `gtk::Builder` can find it by name
+    BevyGtkViewport::static_type();
+
+    app.add_plugins((
+        error::plugin,
+        device_lost::plugin,
+        capture::plugin,
+        close::plugin,
+        context_menu::plugin,
+        accessibility::plugin,
+        print::plugin,
+        render_data::plugin,
+        transition::plugin,
+        canvas::plugin,
+        auto::plugin,
+        ExtractResourcePlugin::<GtkLifecycle>::default(),
+        ExtractResourcePlugin::<GtkCapabilities>::default(),
+    ))
+    .add_systems(
+        PostStartup,
+        (
+            sync_viewport_and_camera,
+            update_images,
+            visible::sync_sub_views,
+        )
+            .chain()
+            .before(CameraUpdateSystems),
+    )
+    .add_systems(
+        PostUpdate,
+        (
+            (
+                sync_viewport_and_camera,
+                update_images,
+                visible::sync_sub_views,
+            )
                 .chain()
                 .before(CameraUpdateSystems),
-        )
+            despawn_destroyed_viewports,
+            drop_unbound_factories,
+        ),
+    );
+
+    let render_app = app
+        .get_sub_app_mut(RenderApp)
+        .expect("checked that `RenderApp` exists above");
+    graph::add_driver_node(render_app.world_mut());
+    render_app
+        .init_resource::<ViewportDepthTextures>()
+        .add_systems(ExtractSchedule, extract_viewports)
         .add_systems(
-            PostUpdate,
+            Render,
             (
-                (sync_viewport_and_camera, update_images)
+                // I tested; this exact scheduling is correct.
+                (release_despawned_viewports, set_target_images)
                     .chain()
-                    .before(CameraUpdateSystems),
-                despawn_destroyed_viewports,
+                    .after(RenderSystems::ExtractCommands),
+                present_frames.after(RenderSystems::Render),
+                capture::capture_frames.after(RenderSystems::Render),
             ),
         );
-
-    let render_app = app
-        .get_sub_app_mut(RenderApp)
-        .expect("`GtkPlugin` with `render` feature requires `RenderApp`");
-    render_app.add_systems(
-        Render,
-        (
-            // I tested; this exact scheduling is correct.
-            set_target_images.after(RenderSystems::ExtractCommands),
-            present_frames.after(RenderSystems::Render),
-        ),
-    );
 }
 
 /// Represents a [`gtk::Widget`] which renders Bevy content.
@@ -121,13 +251,39 @@ pub(super) fn plugin(app: &mut App) {
 /// Note that this component does not keep the viewport alive and does not drive
 /// rendering logic; only camera logic. The actual GTK viewport and underlying
 /// rendering logic lives for as long as the GTK widget lives.
-#[derive(Debug, Component)]
+#[derive(Debug, Clone, Component)]
 pub struct GtkViewport {
     image_handle: Handle<Image>,
     widget_scale_factor: Arc<AtomicF64>,
+    render_scale: Arc<AtomicF32>,
+    resolution: Option<(u32, u32)>,
+    visible_rect: Arc<VisibleRect>,
+    memory: Arc<AtomicU8>,
+    health: ViewportHealth,
 }
 
 impl GtkViewport {
+    /// Entity of the private viewport state, which lives for as long as the
+    /// GTK widget lives.
+    ///
+    /// This is the entity referenced by viewport events like
+    /// [`ViewportError`], and in this crate's logs. The GTK side of the
+    /// viewport exposes the same entity through [`WidgetFactory::entity`],
+    /// [`BevyPaintable::entity`] and [`BevyGtkViewport::entity`], so you can
+    /// correlate diagnostics from both sides.
+    #[must_use]
+    pub fn entity(&self) -> Entity {
+        self.health.viewport()
+    }
+
+    /// Whether this viewport has failed and can no longer render.
+    ///
+    /// See [`ViewportError`].
+    #[must_use]
+    pub fn is_broken(&self) -> bool {
+        self.health.is_broken()
+    }
+
     /// [`Handle`] to the [`Image`] used as a [`Camera::target`] for rendering.
     ///
     /// If you have more advanced needs you can use the image handle directly,
@@ -143,36 +299,173 @@ impl GtkViewport {
     ///
     /// This takes fractional scaling into account, and the resulting render
     /// target output is already properly scaled by this factor.
+    ///
+    /// If the [`Window`] which the widget is in has a
+    /// [`WindowResolution::scale_factor_override`], this is the overridden
+    /// factor instead, and GTK scales the rendered frames to fit the widget.
+    /// Use this to test HiDPI layouts, or to supersample the viewport.
+    ///
+    /// [`Window`]: bevy_window::Window
+    /// [`WindowResolution::scale_factor_override`]: bevy_window::WindowResolution::scale_factor_override
     #[must_use]
     pub fn widget_scale_factor(&self) -> f64 {
         self.widget_scale_factor.load(atomic::Ordering::SeqCst)
     }
+
+    /// Fraction of the widget's size that Bevy renders at.
+    ///
+    /// See [`ViewportConfig::render_scale`].
+    #[must_use]
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale.load(atomic::Ordering::SeqCst)
+    }
+
+    /// Sets the fraction of the widget's size that Bevy renders at.
+    ///
+    /// This can be changed every frame, e.g. to lower the resolution while the
+    /// frame rate drops, and raise it again once it recovers. Each change
+    /// allocates new images, subject to [`ViewportConfig::resize_debounce`].
+    ///
+    /// See [`ViewportConfig::render_scale`].
+    pub fn set_render_scale(&self, render_scale: f32) {
+        self.render_scale
+            .store(clamp_render_scale(render_scale), atomic::Ordering::SeqCst);
+    }
+
+    /// Part of the widget which is visible on screen, in physical pixels
+    /// relative to the widget's top-left corner.
+    ///
+    /// This is smaller than the widget when it's partly scrolled out of a
+    /// [`gtk::ScrolledWindow`], or clipped by another container. Returns
+    /// [`None`] if none of the widget is visible, or it's displayed through a
+    /// [`BevyPaintable`].
+    ///
+    /// See [`ViewportConfig::render_visible_only`].
+    #[must_use]
+    pub fn visible_rect(&self) -> Option<URect> {
+        self.visible_rect.load()
+    }
+
+    /// Where the images that this viewport renders into are allocated.
+    ///
+    /// Returns [`None`] if the render world hasn't allocated any images for
+    /// this viewport yet.
+    #[must_use]
+    pub fn memory(&self) -> Option<ViewportMemory> {
+        ViewportMemory::from_u8(self.memory.load(atomic::Ordering::SeqCst))
+    }
 }
 
 #[derive(Debug, Component)]
 #[require(SyncToRenderWorld)]
 struct ViewportPrivate {
     image_handle: Handle<Image>,
-    next_dmabuf: Arc<AtomicOptionBox<DmabufTexture>>,
-    widget_size: Arc<(AtomicU32, AtomicU32)>,
+    health: ViewportHealth,
+    frames: Arc<FrameQueue>,
+    widget_size: Arc<AtomicSize>,
+    /// Fraction of [`ViewportPrivate::widget_size`] that the image is.
+    render_scale: Arc<AtomicF32>,
+    /// Size that the image always is, regardless of the widget's size.
+    resolution: Option<(u32, u32)>,
+    visible_rect: Arc<VisibleRect>,
+    render_visible_only: bool,
+    /// Size that the image should be, which the render world reads.
+    ///
+    /// This is [`ViewportPrivate::widget_size`] scaled by
+    /// [`ViewportPrivate::render_scale`], and lags behind it while a resize is
+    /// being debounced.
+    image_size: Arc<AtomicSize>,
+    frame_count: Arc<AtomicU64>,
+    tx_frame_ready: async_channel::Sender<()>,
+    recorder: Recorder,
+    accessibility: AccessibilityBridge,
     /// Marks if the GTK-side widget is still alive.
     widget_alive: Arc<()>,
+    /// Whether the window that the widget is in is being resized
+    /// interactively.
+    window_resizing: Arc<AtomicBool>,
+    /// Value of [`ViewportPrivate::image_size`] that we last stored.
     old_widget_size: (u32, u32),
+    resize_debounce: Duration,
+    hold_size_while_resizing: bool,
+    depth_format: Option<TextureFormat>,
+    render_graph: Option<InternedRenderSubGraph>,
+    latency: ViewportLatency,
+    dmabuf_memory: DmabufMemoryPreference,
+    memory: Arc<AtomicU8>,
+    /// Widget size that we're waiting to settle, and when we first saw it.
+    pending_resize: Option<((u32, u32), Instant)>,
+}
+
+/// Width and height in physical pixels, packed into a single atomic, so that
+/// readers never see the width of one size with the height of another.
+#[derive(Debug, Default)]
+struct AtomicSize(AtomicU64);
+
+impl AtomicSize {
+    fn load(&self) -> (u32, u32) {
+        let packed = self.0.load(atomic::Ordering::SeqCst);
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "the width and height are the high and low 32 bits"
+        )]
+        ((packed >> 32) as u32, packed as u32)
+    }
+
+    fn store(&self, width: u32, height: u32) {
+        let packed = (u64::from(width) << 32) | u64::from(height);
+        self.0.store(packed, atomic::Ordering::SeqCst);
+    }
 }
 
 #[derive(Debug, Component)]
 struct RenderViewport {
     image_handle: Handle<Image>,
-    next_dmabuf: Arc<AtomicOptionBox<DmabufTexture>>,
-    widget_size: Arc<(AtomicU32, AtomicU32)>,
+    health: ViewportHealth,
+    frames: Arc<FrameQueue>,
+    image_size: Arc<AtomicSize>,
+    /// Number of frames rendered into this viewport so far.
+    ///
+    /// The GTK side uses this to detect when a new frame has been rendered,
+    /// since Bevy renders into the same dmabuf frame after frame.
+    frame_count: Arc<AtomicU64>,
+    /// Notifies the GTK side that a frame has been presented.
+    ///
+    /// [`BevyPaintable`] has no frame clock to tick on, so it waits on this
+    /// instead of polling [`RenderViewport::frame_count`].
+    tx_frame_ready: async_channel::Sender<()>,
+    /// Sends frames to the encoder thread, if this viewport is being recorded.
+    recorder: Recorder,
     /// Texture and view that this viewport will render into.
     back_buffer: Option<(Texture, TextureView)>,
-    /// Value of [`RenderViewport::widget_size`] from the previous frame.
+    depth_format: Option<TextureFormat>,
+    /// Depth texture with the same size as [`RenderViewport::back_buffer`].
+    depth_buffer: Option<ViewportDepthTexture>,
+    /// Sub-graph to run for this viewport, in addition to any cameras.
+    render_graph: Option<InternedRenderSubGraph>,
+    latency: ViewportLatency,
+    dmabuf_memory: DmabufMemoryPreference,
+    /// Where the images are allocated, which [`GtkViewport::memory`] reports.
+    memory: Arc<AtomicU8>,
+    /// Value of [`RenderViewport::image_size`] from the previous frame.
     ///
     /// If this is different to the current size, we will create a new texture
     /// with the new size and render into that.
     old_widget_size: (u32, u32),
-    /// Texture which will next be stored in [`RenderViewport::next_dmabuf`].
+    /// Copies frames to the GTK side through the CPU, if the render device
+    /// can't share dmabufs.
+    readback: Option<Readback>,
+    /// Dmabufs which this viewport renders into in turn, if its latency
+    /// policy queues up frames for GTK.
+    ///
+    /// Frames in the queue must not be overwritten before GTK displays them,
+    /// so each frame is rendered into the next dmabuf in this ring.
+    dmabuf_ring: Vec<RingBuffer>,
+    /// Index of the dmabuf in [`RenderViewport::dmabuf_ring`] which is the
+    /// current [`RenderViewport::back_buffer`].
+    ring_index: usize,
+    /// Texture which will next be pushed to [`RenderViewport::frames`], if
+    /// this viewport renders into a single dmabuf.
     ///
     /// When we need to create a new texture because the size has changed, we
     /// do the following:
@@ -180,20 +473,724 @@ struct RenderViewport {
     ///   - create a new [`DmabufTexture`]
     ///   - set that texture as the [`RenderViewport::back_buffer`]
     ///   - set that texture as the queued dmabuf
-    ///   - do *not* put it in `next_dmabuf` yet, since we've just made it and
+    ///   - do *not* push it to `frames` yet, since we've just made it and
     ///     it has no rendered content
     /// - after rendering
-    ///   - the dmabuf now has drawn content, so take the dmabuf and put it into
-    ///     `next_dmabuf`
+    ///   - the dmabuf now has drawn content, so take the dmabuf and push it to
+    ///     `frames`
     queued_dmabuf: Option<DmabufTexture>,
+    /// Texture which is pushed to [`RenderViewport::frames`] in place of a
+    /// dmabuf, if the render world has [`MockDmabufs`].
+    #[cfg(feature = "test-utils")]
+    mock_frame: Option<Texture>,
+}
+
+/// Dmabuf in a [`RenderViewport::dmabuf_ring`], and the Bevy texture and view
+/// of it.
+#[derive(Debug)]
+struct RingBuffer {
+    dmabuf: DmabufTexture,
+    texture: Texture,
+    view: TextureView,
 }
 
 // creation logic
 
+/// Configuration for a viewport created with [`GtkViewports::create_with`].
+#[derive(Debug, Clone)]
+pub struct ViewportConfig {
+    /// How the GTK widget presents frames rendered by Bevy.
+    pub present_mode: ViewportPresentMode,
+    /// How many frames may be queued up between Bevy and GTK.
+    pub latency: ViewportLatency,
+    /// Whether pointer input over the viewport passes through its window, to
+    /// whatever is behind the window on the desktop.
+    ///
+    /// This is useful for overlays, like streaming HUDs, where only the GTK
+    /// widgets around the viewport should be interactive.
+    ///
+    /// See [`set_input_passthrough`](crate::set_input_passthrough).
+    pub input_passthrough: bool,
+    /// How the widget's logical size is converted into the size of the Bevy
+    /// image, in physical pixels.
+    pub size_rounding: ViewportSizeRounding,
+    /// How the widget finds out its own size.
+    pub size_detection: ViewportSizeDetection,
+    /// How long the widget's size must stay the same before the viewport's
+    /// image is resized.
+    ///
+    /// Resizing the image means allocating a new dmabuf, so during an
+    /// interactive resize, when the size changes on every frame, this avoids
+    /// hammering the allocator. Until the image is resized, GTK stretches the
+    /// last frame to fit the widget.
+    ///
+    /// The first size is always applied immediately. By default, this is zero,
+    /// so every size change is applied immediately.
+    pub resize_debounce: Duration,
+    /// Whether the image keeps its size while the window that the viewport is
+    /// in is being resized interactively, e.g. by dragging its edge.
+    ///
+    /// During a live resize, rendering full frames at every intermediate size
+    /// makes the resize lag behind the pointer. With this enabled, Bevy keeps
+    /// rendering at the size from before the resize, GTK scales those frames
+    /// to fit the widget, and the image snaps to the final size once the
+    /// window's surface has stopped changing size for
+    /// [`ViewportConfig::INTERACTIVE_RESIZE_SETTLE`].
+    ///
+    /// Size changes which come from the window's state, like maximizing or
+    /// tiling it, aren't interactive, and are applied immediately.
+    ///
+    /// This only applies to widgets made with [`WidgetFactory::make`], since a
+    /// [`BevyPaintable`] doesn't know which window it's in.
+    pub hold_size_while_resizing: bool,
+    /// Format of a depth texture to allocate alongside the viewport's image.
+    ///
+    /// Bevy's cameras already have their own depth textures, so this is only
+    /// needed by custom render graph nodes which want a depth texture tied to
+    /// the viewport, like outline or gizmo passes. See
+    /// [`ViewportDepthTextures`].
+    ///
+    /// By default, this is [`None`], and no depth texture is allocated.
+    pub depth_format: Option<TextureFormat>,
+    /// Render sub-graph which is run for this viewport every frame.
+    ///
+    /// This lets you fill a viewport without a [`Camera`], e.g. by copying the
+    /// output of a compute shader into the viewport's texture. The graph is
+    /// run with the viewport's render world entity as the view entity, which
+    /// has a [`ViewportRenderTarget`]. If a camera also renders into this
+    /// viewport, the graph runs after the camera.
+    ///
+    /// Register the sub-graph on the [`RenderGraph`] yourself, e.g. with
+    /// [`RenderGraphExt::add_render_sub_graph`].
+    ///
+    /// [`RenderGraph`]: bevy_render::render_graph::RenderGraph
+    /// [`RenderGraphExt::add_render_sub_graph`]: bevy_render::render_graph::RenderGraphExt::add_render_sub_graph
+    pub render_graph: Option<InternedRenderSubGraph>,
+    /// Which kind of memory the viewport's dmabufs are allocated in.
+    ///
+    /// Use [`GtkViewport::memory`] to check where they actually ended up.
+    pub dmabuf_memory: DmabufMemoryPreference,
+    /// Fraction of the widget's size that Bevy renders at.
+    ///
+    /// Below `1.0`, Bevy renders into a smaller image, and GTK upscales it to
+    /// fill the widget, which trades sharpness for GPU time on weak hardware.
+    /// Above `1.0`, the viewport is supersampled. The camera's scale factor is
+    /// scaled by the same amount, so UI laid out in logical pixels keeps its
+    /// size.
+    ///
+    /// Change this at runtime with [`GtkViewport::set_render_scale`]. By
+    /// default, this is `1.0`.
+    pub render_scale: f32,
+    /// Whether Bevy only renders the part of the widget which is visible on
+    /// screen.
+    ///
+    /// A viewport inside of a [`gtk::ScrolledWindow`] is allocated its full
+    /// size, even if only a small part of it is scrolled into view. With
+    /// this enabled, the image is only as big as the
+    /// [visible part](GtkViewport::visible_rect) of the widget, the camera
+    /// renders the matching part of its full view through
+    /// [`Camera::sub_camera_view`], and GTK draws the image over that part of
+    /// the widget. This makes huge scrollable canvases affordable, and avoids
+    /// GTK having to clip the offloaded image.
+    ///
+    /// While scrolling, the rendered region trails the visible one by a frame
+    /// or two, so the edges of the image may briefly show the widget's
+    /// background.
+    ///
+    /// This only applies to widgets made with [`WidgetFactory::make`], and
+    /// replaces any [`Camera::sub_camera_view`] of cameras rendering into
+    /// this viewport.
+    pub render_visible_only: bool,
+    /// Size in physical pixels that the viewport's image always is,
+    /// regardless of the widget's size.
+    ///
+    /// This is for fixed-resolution content, like pixel art games, which GTK
+    /// then fits into the widget according to
+    /// [`ViewportConfig::content_fit`]. The camera's scale factor is `1.0`,
+    /// so UI is laid out in the image's pixels.
+    ///
+    /// This takes precedence over [`ViewportConfig::render_scale`] and
+    /// [`ViewportConfig::render_visible_only`]. By default, this is [`None`],
+    /// and the image follows the size of the widget.
+    pub resolution: Option<(u32, u32)>,
+    /// How the image is fitted into the widget when their sizes differ.
+    ///
+    /// The sizes differ when the viewport has a fixed
+    /// [`ViewportConfig::resolution`] or [`ViewportConfig::render_scale`], and
+    /// briefly while the image catches up with a resize.
+    ///
+    /// This only applies to widgets made with [`WidgetFactory::make`]. With
+    /// [`ViewportConfig::render_visible_only`], the image always fills the
+    /// visible part of the widget.
+    pub content_fit: ViewportContentFit,
+    /// Color state that GTK interprets the viewport's frames in.
+    ///
+    /// GTK converts frames from this color state into the color state of the
+    /// monitor, so wide-gamut and HDR monitors show them accurately. This
+    /// only tags the frames, and doesn't change how Bevy encodes them.
+    ///
+    /// By default, this is [`None`], and the color state matches the encoding
+    /// of the format that the frames are in (see
+    /// [`ViewportColorState::for_texture_format`]). Override this if you fill
+    /// the viewport in another color state yourself, e.g. through
+    /// [`ViewportConfig::render_graph`], or if frames come out too dark or
+    /// washed out on your setup.
+    pub color_state: Option<ViewportColorState>,
+}
+
+impl Default for ViewportConfig {
+    fn default() -> Self {
+        Self {
+            present_mode: ViewportPresentMode::default(),
+            latency: ViewportLatency::default(),
+            input_passthrough: false,
+            size_rounding: ViewportSizeRounding::default(),
+            size_detection: ViewportSizeDetection::default(),
+            resize_debounce: Duration::ZERO,
+            hold_size_while_resizing: false,
+            depth_format: None,
+            render_graph: None,
+            dmabuf_memory: DmabufMemoryPreference::default(),
+            render_scale: 1.0,
+            render_visible_only: false,
+            resolution: None,
+            content_fit: ViewportContentFit::default(),
+            color_state: None,
+        }
+    }
+}
+
+/// How a viewport's logical size is converted into physical pixels.
+///
+/// At fractional scales (like 125% or 150%), a widget's logical size
+/// multiplied by the scale factor is usually not a whole number, so it has to
+/// be rounded somehow. If the image ends up a different size to the area that
+/// GTK actually draws it in, GTK will scale it, which makes the output blurry
+/// or leaves a 1px gap at the edge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ViewportSizeRounding {
+    /// Snaps the widget's edges to the surface's device pixel grid, and uses
+    /// the distance between the snapped edges.
+    ///
+    /// This matches how GTK and the compositor place the widget on screen, so
+    /// the image maps 1:1 onto device pixels.
+    #[default]
+    SurfacePixels,
+    /// Rounds the scaled size down.
+    Floor,
+    /// Rounds the scaled size to the nearest pixel.
+    Round,
+    /// Rounds the scaled size up.
+    Ceil,
+}
+
+/// How a viewport widget made with [`WidgetFactory::make`] finds out its own
+/// size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ViewportSizeDetection {
+    /// Places zero-width and zero-height drawing areas next to the picture,
+    /// and measures them whenever they're drawn.
+    ///
+    /// This only uses stock widgets, but adds extra widgets and draw callbacks
+    /// to the tree, and misses size changes when GTK doesn't redraw the
+    /// listeners, e.g. in some cases inside a [`gtk::ScrolledWindow`].
+    #[default]
+    Listeners,
+    /// Wraps the picture in a custom widget, which reports its size whenever
+    /// GTK allocates it.
+    ///
+    /// This doesn't depend on anything being drawn, and keeps the widget tree
+    /// shallower.
+    Allocation,
+}
+
+/// How the image of a viewport is fitted into its widget.
+///
+/// See [`ViewportConfig::content_fit`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ViewportContentFit {
+    /// Stretches the image to fill the widget, ignoring its aspect ratio.
+    Fill,
+    /// Scales the image to fit inside of the widget, keeping its aspect
+    /// ratio, and letterboxes the rest of the widget.
+    #[default]
+    Contain,
+    /// Scales the image to cover the whole widget, keeping its aspect ratio,
+    /// and crops the parts that stick out.
+    Cover,
+    /// Like [`ViewportContentFit::Contain`], but never scales the image up
+    /// beyond its own size.
+    ScaleDown,
+    /// Scales the image up by the largest whole number which fits, so that
+    /// every pixel of the image covers the same number of physical pixels,
+    /// and letterboxes the rest of the widget.
+    ///
+    /// If the image doesn't fit at all, this falls back to
+    /// [`ViewportContentFit::Contain`].
+    Integer,
+}
+
+impl ViewportContentFit {
+    const fn to_gtk(self) -> gtk::ContentFit {
+        match self {
+            Self::Fill | Self::Integer => gtk::ContentFit::Fill,
+            Self::Contain => gtk::ContentFit::Contain,
+            Self::Cover => gtk::ContentFit::Cover,
+            Self::ScaleDown => gtk::ContentFit::ScaleDown,
+        }
+    }
+}
+
+/// Color state of the frames of a viewport.
+///
+/// See [`ViewportConfig::color_state`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ViewportColorState {
+    /// sRGB primaries and transfer function.
+    #[default]
+    Srgb,
+    /// sRGB primaries with a linear transfer function.
+    SrgbLinear,
+    /// BT.2020 primaries with the PQ transfer function, for HDR content.
+    Rec2100Pq,
+    /// BT.2020 primaries with a linear transfer function.
+    Rec2100Linear,
+}
+
+impl ViewportColorState {
+    /// Gets the color state that a texture of `format` holds, after Bevy has
+    /// rendered into it.
+    ///
+    /// Fourccs only describe the memory layout of a dmabuf, so e.g.
+    /// [`TextureFormat::Rgba8Unorm`] and [`TextureFormat::Rgba8UnormSrgb`]
+    /// share a fourcc, and GTK can't tell them apart. But the GPU encodes
+    /// values written to an sRGB format with the sRGB transfer function,
+    /// whereas other formats hold exactly what the shader wrote, which for
+    /// Bevy is linear. Tagging a linear texture as sRGB makes it look too
+    /// dark, and tagging an sRGB texture as linear makes it look washed out.
+    #[must_use]
+    pub fn for_texture_format(format: TextureFormat) -> Self {
+        if format.is_srgb() {
+            Self::Srgb
+        } else {
+            Self::SrgbLinear
+        }
+    }
+
+    /// Gets the GDK color state with the same primaries and transfer
+    /// function.
+    #[must_use]
+    pub fn to_gdk(self) -> gdk::ColorState {
+        match self {
+            Self::Srgb => gdk::ColorState::srgb(),
+            Self::SrgbLinear => gdk::ColorState::srgb_linear(),
+            Self::Rec2100Pq => gdk::ColorState::rec2100_pq(),
+            Self::Rec2100Linear => gdk::ColorState::rec2100_linear(),
+        }
+    }
+}
+
+impl ViewportSizeRounding {
+    /// Converts a span of `length` logical pixels, which starts `offset`
+    /// logical pixels from the surface origin, into physical pixels.
+    #[expect(
+        clippy::cast_sign_loss,
+        clippy::cast_possible_truncation,
+        reason = "value is clamped to be non-negative, and widget sizes are relatively small"
+    )]
+    fn to_physical(self, offset: f64, length: f64, scale: f64) -> u32 {
+        let physical = match self {
+            Self::SurfacePixels => ((offset + length) * scale).round() - (offset * scale).round(),
+            Self::Floor => (length * scale).floor(),
+            Self::Round => (length * scale).round(),
+            Self::Ceil => (length * scale).ceil(),
+        };
+        physical.max(0.0) as u32
+    }
+}
+
+/// Gets the scale factor that `widget` should be rendered at.
+///
+/// This is the scale of the widget's surface, unless the Bevy window which it's
+/// in has a [`WindowResolution::scale_factor_override`].
+///
+/// [`WindowResolution::scale_factor_override`]: bevy_window::WindowResolution::scale_factor_override
+fn widget_scale(widget: &gtk::Widget) -> Option<f64> {
+    let scale_factor_override = widget
+        .root()
+        .and_then(|root| root.downcast::<gtk::Window>().ok())
+        .and_then(|gtk_window| crate::window::scale_factor_override(&gtk_window));
+    scale_factor_override.or_else(|| {
+        widget
+            .native()
+            .and_then(|native| native.surface())
+            .map(|surface| surface.scale())
+    })
+}
+
+/// Centers `picture` inside of `widget`, at the largest whole multiple of the
+/// size of its paintable which fits.
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "widget sizes are relatively small"
+)]
+fn place_integer_scaled(picture: &gtk::Picture, widget: &gtk::Widget, scale: f64) {
+    let Some(paintable) = picture.paintable() else {
+        return;
+    };
+    let (image_width, image_height) = (
+        f64::from(paintable.intrinsic_width()),
+        f64::from(paintable.intrinsic_height()),
+    );
+    if image_width <= 0.0 || image_height <= 0.0 {
+        return;
+    }
+
+    let (width, height) = (f64::from(widget.width()), f64::from(widget.height()));
+    let factor = (width * scale / image_width)
+        .min(height * scale / image_height)
+        .floor();
+    let (margin_x, margin_y, content_fit) = if factor >= 1.0 {
+        (
+            (width - image_width * factor / scale).max(0.0) / 2.0,
+            (height - image_height * factor / scale).max(0.0) / 2.0,
+            gtk::ContentFit::Fill,
+        )
+    } else {
+        (0.0, 0.0, gtk::ContentFit::Contain)
+    };
+    picture.set_content_fit(content_fit);
+    picture.set_margin_start(margin_x.floor() as i32);
+    picture.set_margin_end(margin_x.ceil() as i32);
+    picture.set_margin_top(margin_y.floor() as i32);
+    picture.set_margin_bottom(margin_y.ceil() as i32);
+}
+
+/// Physical size of the picture, as measured by the listeners next to it.
+fn listener_size(
+    width_listener: &gtk::DrawingArea,
+    height_listener: &gtk::DrawingArea,
+    size_rounding: ViewportSizeRounding,
+) -> Option<(u32, u32)> {
+    let scale = widget_scale(width_listener.upcast_ref())?;
+    // the width listener spans the same columns as the picture,
+    // and the height listener spans the same rows
+    let (offset_x, _) = surface_offset(width_listener.upcast_ref());
+    let (_, offset_y) = surface_offset(height_listener.upcast_ref());
+    Some((
+        size_rounding.to_physical(offset_x, f64::from(width_listener.width()), scale),
+        size_rounding.to_physical(offset_y, f64::from(height_listener.height()), scale),
+    ))
+}
+
+/// Gets the offset of `widget`'s origin from its surface's origin, in logical
+/// pixels.
+fn surface_offset(widget: &gtk::Widget) -> (f64, f64) {
+    let Some(native) = widget.native() else {
+        return (0.0, 0.0);
+    };
+    let Some(point) = widget.compute_point(&native, &gtk::graphene::Point::zero()) else {
+        return (0.0, 0.0);
+    };
+    let (transform_x, transform_y) = native.surface_transform();
+    (
+        f64::from(point.x()) + transform_x,
+        f64::from(point.y()) + transform_y,
+    )
+}
+
+/// Wraps `content` in a container which measures its size with listener
+/// widgets.
+///
+/// See [`ViewportSizeDetection::Listeners`].
+fn listener_container(
+    content: &gtk::Widget,
+    widget_size: &Arc<AtomicSize>,
+    widget_scale_factor: &Arc<AtomicF64>,
+    size_rounding: ViewportSizeRounding,
+) -> gtk::Widget {
+    // Use a trick to detect when the picture is resized.
+    // <https://stackoverflow.com/questions/70488187/get-calculated-size-of-widget-in-gtk-4-0>
+    // +-----------------------+
+    // |          WL           |  WL: width_listener  (height 0)
+    // |-----------------------|  HL: height_listener (width 0)
+    // |   |                   |
+    // | H |     picture       |
+    // | L |                   |
+    // |   |                   |
+    // +-----------------------+
+
+    let width_listener = gtk::DrawingArea::builder().hexpand(true).build();
+    let height_listener = gtk::DrawingArea::builder().vexpand(true).build();
+
+    // both listeners are allocated in the same layout pass, so
+    // whichever one draws first stores both dimensions, and Bevy never
+    // sees the new width with the old height
+    width_listener.set_draw_func(clone!(
+        #[strong]
+        widget_size,
+        #[weak]
+        height_listener,
+        move |width_listener, _, _, _| {
+            if let Some((width, height)) =
+                listener_size(width_listener, &height_listener, size_rounding)
+            {
+                widget_size.store(width, height);
+            }
+        },
+    ));
+    height_listener.set_draw_func(clone!(
+        #[strong]
+        widget_size,
+        #[weak]
+        width_listener,
+        move |height_listener, _, _, _| {
+            if let Some((width, height)) =
+                listener_size(&width_listener, height_listener, size_rounding)
+            {
+                widget_size.store(width, height);
+            }
+        },
+    ));
+
+    let frame_content_h = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+    frame_content_h.append(&height_listener);
+    frame_content_h.append(content);
+
+    let frame_content_v = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    frame_content_v.append(&width_listener);
+    frame_content_v.append(&frame_content_h);
+
+    // the listeners only measure the picture once they're drawn, and
+    // the scale factor is only updated when it changes, so Bevy would
+    // render its first frames at the wrong size and scale. instead, we
+    // push both as soon as GTK has laid out the widget
+    frame_content_v.connect_realize(clone!(
+        #[strong]
+        widget_size,
+        #[strong]
+        widget_scale_factor,
+        #[weak]
+        width_listener,
+        #[weak]
+        height_listener,
+        move |container| {
+            let Some(frame_clock) = container.frame_clock() else {
+                return;
+            };
+            let handler = Rc::new(Cell::new(None::<glib::SignalHandlerId>));
+            // GTK allocates widgets in its own `layout` handler, which
+            // was connected before ours
+            let id = frame_clock.connect_layout(clone!(
+                #[strong]
+                handler,
+                #[strong]
+                widget_size,
+                #[strong]
+                widget_scale_factor,
+                #[weak]
+                width_listener,
+                #[weak]
+                height_listener,
+                move |frame_clock| {
+                    if let Some(scale) = widget_scale(width_listener.upcast_ref()) {
+                        widget_scale_factor.store(scale, atomic::Ordering::SeqCst);
+                    }
+                    if let Some((width, height)) =
+                        listener_size(&width_listener, &height_listener, size_rounding)
+                    {
+                        widget_size.store(width, height);
+                    }
+                    if let Some(handler) = handler.take() {
+                        frame_clock.disconnect(handler);
+                    }
+                },
+            ));
+            handler.set(Some(id));
+        },
+    ));
+
+    frame_content_v.upcast()
+}
+
+impl ViewportConfig {
+    /// How long a window's surface must keep the same size before an
+    /// interactive resize is considered to have ended.
+    ///
+    /// See [`ViewportConfig::hold_size_while_resizing`].
+    pub const INTERACTIVE_RESIZE_SETTLE: Duration = Duration::from_millis(150);
+}
+
+/// Detects when the window that a widget is in is being resized interactively,
+/// by watching the size and state of its surface on every tick.
+///
+/// GDK doesn't tell us whether the compositor is in the middle of an
+/// interactive resize, so we treat any surface size change that isn't caused
+/// by a change of the toplevel state as one.
+#[derive(Debug, Default)]
+struct InteractiveResize {
+    last_size: Cell<Option<(i32, i32)>>,
+    last_state: Cell<Option<gdk::ToplevelState>>,
+    last_change: Cell<Option<Instant>>,
+}
+
+impl InteractiveResize {
+    /// Updates the tracked surface of `widget`, and returns whether its window
+    /// is being resized.
+    fn update(&self, widget: &gtk::Widget) -> bool {
+        let Some(surface) = widget.native().and_then(|native| native.surface()) else {
+            self.last_size.set(None);
+            self.last_change.set(None);
+            return false;
+        };
+        let size = (surface.width(), surface.height());
+        let state = surface
+            .downcast_ref::<gdk::Toplevel>()
+            .map(ToplevelExt::state);
+
+        let now = Instant::now();
+        let resized = self
+            .last_size
+            .replace(Some(size))
+            .is_some_and(|last| last != size);
+        let state_changed = self.last_state.replace(state) != state;
+        if state_changed {
+            self.last_change.set(None);
+        } else if resized {
+            self.last_change.set(Some(now));
+        }
+        self.last_change.get().is_some_and(|since| {
+            now.duration_since(since) < ViewportConfig::INTERACTIVE_RESIZE_SETTLE
+        })
+    }
+}
+
+/// How a viewport's GTK widget presents frames rendered by Bevy.
+///
+/// Bevy renders into the same dmabuf frame after frame, so GTK has to be told
+/// explicitly when to redraw the viewport.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ViewportPresentMode {
+    /// On every tick of the GTK frame clock, the widget's paintable is set
+    /// again, regardless of whether Bevy rendered a new frame.
+    ///
+    /// This always shows the latest content, but keeps the GPU and compositor
+    /// busy even when the scene is static.
+    #[default]
+    EveryTick,
+    /// The widget's paintable is only set again when Bevy has rendered a new
+    /// frame since the last tick.
+    ///
+    /// This avoids needless compositor work when Bevy renders at a lower rate
+    /// than GTK's refresh rate, or not at all.
+    OnNewFrame,
+    /// The widget keeps the same paintable, and instead asks GTK to redraw it
+    /// on every tick of the GTK frame clock, at GTK's own rate.
+    ///
+    /// Like [`ViewportPresentMode::EveryTick`], this always shows the latest
+    /// content of the dmabuf, but GTK doesn't have to measure and lay out the
+    /// widget again on every tick.
+    Redraw,
+}
+
+/// How many frames a viewport may queue up between Bevy and GTK.
+///
+/// This trades latency for smoothness. Interactive tools want the viewport to
+/// react to input as soon as possible, while video-like content wants every
+/// frame to be shown, evenly paced.
+///
+/// Frames which are copied through the CPU are never overwritten, so only the
+/// queueing behavior applies to them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ViewportLatency {
+    /// Bevy renders into a single dmabuf, and GTK displays whatever is in it
+    /// when it draws.
+    ///
+    /// This has the lowest latency, and Bevy never waits for GTK, but frames
+    /// which Bevy renders in between two GTK frames are never shown, and GTK
+    /// may show a frame which Bevy is still rendering.
+    #[default]
+    LatestFrame,
+    /// Bevy renders each frame into the next dmabuf in a ring, and queues up
+    /// to this many finished frames, which GTK displays in order, one per tick
+    /// of its frame clock.
+    ///
+    /// If the queue is full, the oldest frame is dropped, so Bevy never waits
+    /// for GTK. This adds up to this many frames of latency, and allocates
+    /// this many extra dmabufs.
+    Mailbox(NonZeroU8),
+    /// Bevy doesn't start rendering a frame until GTK has taken the previous
+    /// one, so every frame is shown.
+    ///
+    /// This blocks Bevy's render thread, so Bevy runs at the rate that GTK
+    /// displays the viewport. If GTK doesn't take the frame within
+    /// [`ViewportLatency::BLOCKING_TIMEOUT`], e.g. because the widget is
+    /// hidden, Bevy renders the next frame anyway.
+    ///
+    /// Use this with pipelined rendering, which is enabled by default.
+    /// Otherwise Bevy renders on the GTK thread, so GTK can't take frames
+    /// while Bevy waits, and every frame waits for the full timeout.
+    Blocking,
+}
+
+impl ViewportLatency {
+    /// How long [`ViewportLatency::Blocking`] waits for GTK to take a frame.
+    pub const BLOCKING_TIMEOUT: Duration = Duration::from_millis(100);
+
+    /// Maximum number of finished frames which are queued for GTK.
+    fn queue_capacity(self) -> usize {
+        match self {
+            Self::LatestFrame | Self::Blocking => 1,
+            Self::Mailbox(frames) => usize::from(frames.get()),
+        }
+    }
+
+    /// Number of dmabufs which Bevy renders into in turn.
+    ///
+    /// On top of the queued frames, GTK may still be displaying one dmabuf,
+    /// and Bevy is rendering into another.
+    fn ring_size(self) -> usize {
+        match self {
+            Self::LatestFrame => 1,
+            Self::Mailbox(_) | Self::Blocking => self.queue_capacity() + 2,
+        }
+    }
+}
+
+/// Where the images of a viewport are allocated, as reported by
+/// [`GtkViewport::memory`].
+///
+/// See [`ViewportConfig::dmabuf_memory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ViewportMemory {
+    /// Dmabufs in memory which is local to the GPU.
+    DeviceLocal = 1,
+    /// Dmabufs in memory which is visible to the CPU, but not local to the
+    /// GPU, e.g. because no device-local memory could be exported.
+    HostVisible = 2,
+    /// Textures which are copied to GTK through the CPU, since dmabufs can't
+    /// be shared with GTK.
+    Readback = 3,
+}
+
+impl ViewportMemory {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::DeviceLocal),
+            2 => Some(Self::HostVisible),
+            3 => Some(Self::Readback),
+            _ => None,
+        }
+    }
+}
+
 /// Allows creating a [`GtkViewport`].
 #[derive(SystemParam)]
 pub struct GtkViewports<'w, 's> {
     images: ResMut<'w, Assets<Image>>,
+    errors: Res<'w, ViewportErrorChannel>,
+    gtk_commands: GtkCommands<'w>,
     commands: Commands<'w, 's>,
 }
 
@@ -208,49 +1205,209 @@ impl GtkViewports<'_, '_> {
     ///
     /// [`GtkWindowContent`]: crate::GtkWindowContent
     pub fn create(&mut self) -> (GtkViewport, WidgetFactory) {
+        self.create_with(ViewportConfig::default())
+    }
+
+    /// Creates a viewport with the given configuration.
+    ///
+    /// See [`GtkViewports::create`].
+    pub fn create_with(&mut self, config: ViewportConfig) -> (GtkViewport, WidgetFactory) {
         let image_handle = self.images.reserve_handle();
-        let next_dmabuf = Arc::new(AtomicOptionBox::none());
-        let widget_size = Arc::new((AtomicU32::new(0), AtomicU32::new(0)));
+        let frames = Arc::new(FrameQueue::default());
+        let widget_size = Arc::new(AtomicSize::default());
+        let image_size = Arc::new(AtomicSize::default());
+        let frame_count = Arc::new(AtomicU64::new(0));
+        let (tx_frame_ready, rx_frame_ready) = async_channel::bounded(1);
+        let (accessibility_bridge, accessibility_widget) = accessibility::bridge();
         let widget_scale_factor = Arc::new(AtomicF64::new(1.0));
+        let render_scale = Arc::new(AtomicF32::new(clamp_render_scale(config.render_scale)));
+        let visible_rect = Arc::new(VisibleRect::default());
         let widget_alive = Arc::new(());
+        let window_resizing = Arc::new(AtomicBool::new(false));
+        let memory = Arc::new(AtomicU8::new(0));
+        let entity = self.commands.spawn_empty().id();
+        let health = ViewportHealth::new(entity, self.errors.tx.clone());
 
-        self.commands.spawn(ViewportPrivate {
+        self.commands.entity(entity).insert(ViewportPrivate {
             image_handle: image_handle.clone(),
-            next_dmabuf: next_dmabuf.clone(),
+            health: health.clone(),
+            frames: frames.clone(),
             widget_size: widget_size.clone(),
+            render_scale: render_scale.clone(),
+            resolution: config.resolution,
+            visible_rect: visible_rect.clone(),
+            render_visible_only: config.render_visible_only && config.resolution.is_none(),
+            image_size,
+            frame_count: frame_count.clone(),
+            tx_frame_ready,
+            recorder: Recorder::default(),
+            accessibility: accessibility_bridge,
             widget_alive: widget_alive.clone(),
+            window_resizing: window_resizing.clone(),
             old_widget_size: (u32::MAX, u32::MAX),
+            resize_debounce: config.resize_debounce,
+            hold_size_while_resizing: config.hold_size_while_resizing,
+            depth_format: config.depth_format,
+            render_graph: config.render_graph,
+            latency: config.latency,
+            dmabuf_memory: config.dmabuf_memory,
+            memory: memory.clone(),
+            pending_resize: None,
         });
 
         (
             GtkViewport {
                 image_handle,
                 widget_scale_factor: widget_scale_factor.clone(),
+                render_scale,
+                resolution: config.resolution,
+                visible_rect: visible_rect.clone(),
+                memory,
+                health: health.clone(),
             },
             WidgetFactory {
-                next_dmabuf,
+                config,
+                health,
+                frames,
                 widget_size,
+                frame_count,
+                rx_frame_ready,
                 widget_scale_factor,
                 widget_alive,
+                window_resizing,
+                visible_rect,
+                loading_placeholder: LoadingPlaceholder::Spinner,
+                error_placeholder: None,
+                accessibility: accessibility_widget,
             },
         )
     }
-}
 
-impl ExtractComponent for RenderViewport {
-    type QueryData = &'static ViewportPrivate;
-    type QueryFilter = Added<ViewportPrivate>;
-    type Out = Self;
+    /// Destroys a viewport from the Bevy side.
+    ///
+    /// This despawns the viewport's private entity, which tears down its
+    /// render world state and frees its dmabufs, and removes its image. The
+    /// GTK widget stays in the widget tree, but stops displaying Bevy content
+    /// and only shows its black background; a [`BevyGtkViewport`] removes the
+    /// viewport's widget entirely. Remove the widget from your UI yourself if
+    /// you need to, e.g. when closing a document tab.
+    ///
+    /// Cameras which still have `viewport` as a component are left alone, but
+    /// won't render anywhere; you should remove the [`GtkViewport`] component
+    /// or despawn the camera.
+    pub fn destroy(&mut self, viewport: &GtkViewport) {
+        let entity = viewport.entity();
+        debug!("Destroying viewport {entity} from the Bevy side");
+        viewport.health.destroy();
+        self.images.remove(&viewport.image_handle);
+        if let Ok(mut entity) = self.commands.get_entity(entity) {
+            entity.despawn();
+        }
+    }
+
+    /// Creates a viewport which is displayed in the [`BevyGtkViewport`] widget
+    /// with the given ID.
+    ///
+    /// This is useful when your UI is defined in a [`gtk::Builder`] UI
+    /// definition, where you can't insert a [`WidgetFactory`] directly.
+    pub fn create_for_widget(&mut self, id: impl Into<Cow<'static, str>>) -> GtkViewport {
+        self.create_for_widget_with(id, ViewportConfig::default())
+    }
 
-    fn extract_component(viewport: QueryItem<Self::QueryData>) -> Option<Self::Out> {
-        Some(Self {
+    /// Creates a viewport with the given configuration, which is displayed in
+    /// the [`BevyGtkViewport`] widget with the given ID.
+    ///
+    /// See [`GtkViewports::create_for_widget`].
+    pub fn create_for_widget_with(
+        &mut self,
+        id: impl Into<Cow<'static, str>>,
+        config: ViewportConfig,
+    ) -> GtkViewport {
+        let id = id.into();
+        let (viewport, widget_factory) = self.create_with(config);
+        self.gtk_commands
+            .queue(move |_: &mut GtkContext| widget::bind_factory(id, widget_factory));
+        viewport
+    }
+}
+
+impl RenderViewport {
+    fn new(viewport: &ViewportPrivate) -> Self {
+        Self {
             image_handle: viewport.image_handle.clone(),
-            widget_size: viewport.widget_size.clone(),
-            next_dmabuf: viewport.next_dmabuf.clone(),
+            health: viewport.health.clone(),
+            image_size: viewport.image_size.clone(),
+            frames: viewport.frames.clone(),
+            frame_count: viewport.frame_count.clone(),
+            tx_frame_ready: viewport.tx_frame_ready.clone(),
+            recorder: viewport.recorder.clone(),
             back_buffer: None,
+            depth_format: viewport.depth_format,
+            depth_buffer: None,
+            render_graph: viewport.render_graph,
+            latency: viewport.latency,
+            dmabuf_memory: viewport.dmabuf_memory,
+            memory: viewport.memory.clone(),
             old_widget_size: (u32::MAX, u32::MAX),
+            readback: None,
+            dmabuf_ring: Vec::new(),
+            ring_index: 0,
             queued_dmabuf: None,
-        })
+            #[cfg(feature = "test-utils")]
+            mock_frame: None,
+        }
+    }
+
+    /// Applies changes to the main world viewport.
+    fn update(&mut self, viewport: &ViewportPrivate) {
+        let reallocate = self.image_handle != viewport.image_handle
+            || self.depth_format != viewport.depth_format
+            || self.latency != viewport.latency
+            || self.dmabuf_memory != viewport.dmabuf_memory;
+
+        self.image_handle = viewport.image_handle.clone();
+        self.health = viewport.health.clone();
+        self.image_size = viewport.image_size.clone();
+        self.frames = viewport.frames.clone();
+        self.frame_count = viewport.frame_count.clone();
+        self.tx_frame_ready = viewport.tx_frame_ready.clone();
+        self.recorder = viewport.recorder.clone();
+        self.depth_format = viewport.depth_format;
+        self.render_graph = viewport.render_graph;
+        self.latency = viewport.latency;
+        self.dmabuf_memory = viewport.dmabuf_memory;
+        self.memory = viewport.memory.clone();
+
+        if reallocate {
+            // `set_target_images` creates new textures when the size changes
+            self.old_widget_size = (u32::MAX, u32::MAX);
+        }
+    }
+}
+
+/// Creates, updates and removes [`RenderViewport`]s to match the
+/// [`ViewportPrivate`]s in the main world.
+fn extract_viewports(
+    main_viewports: Extract<Query<(RenderEntity, Ref<ViewportPrivate>)>>,
+    mut render_viewports: Query<(Entity, &MainEntity, &mut RenderViewport)>,
+    mut commands: Commands,
+) {
+    for (entity, main_entity, _) in &render_viewports {
+        if !main_viewports.contains(main_entity.id()) {
+            commands.entity(entity).remove::<RenderViewport>();
+        }
+    }
+
+    for (entity, viewport) in &main_viewports {
+        if let Ok((_, _, mut render_viewport)) = render_viewports.get_mut(entity) {
+            if viewport.is_changed() {
+                render_viewport.update(&viewport);
+            }
+        } else {
+            commands
+                .entity(entity)
+                .insert(RenderViewport::new(&viewport));
+        }
     }
 }
 
@@ -260,46 +1417,106 @@ const TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
 
 fn sync_viewport_and_camera(mut viewports: Query<(&GtkViewport, &mut Camera)>) {
     for (viewport, mut camera) in &mut viewports {
+        #[expect(clippy::cast_possible_truncation, reason = "しょうがないね")]
+        let scale_factor = if viewport.resolution.is_some() {
+            1.0
+        } else {
+            viewport.widget_scale_factor() as f32 * viewport.render_scale()
+        };
         camera.target = RenderTarget::Image(ImageRenderTarget {
             handle: viewport.image_handle.clone(),
-            #[expect(clippy::cast_possible_truncation, reason = "しょうがないね")]
-            scale_factor: FloatOrd(viewport.widget_scale_factor() as f32),
+            scale_factor: FloatOrd(scale_factor),
         });
     }
 }
 
 fn update_images(mut viewports: Query<&mut ViewportPrivate>, mut images: ResMut<Assets<Image>>) {
     for mut viewport in &mut viewports {
-        let (new_width, new_height) = (
-            viewport.widget_size.0.load(atomic::Ordering::SeqCst),
-            viewport.widget_size.1.load(atomic::Ordering::SeqCst),
+        if viewport.health.is_broken() {
+            continue;
+        }
+
+        let (mut widget_width, mut widget_height) = viewport.widget_size.load();
+        if viewport.render_visible_only {
+            if let Some(rect) = viewport.visible_rect.load() {
+                widget_width = rect.width().min(widget_width);
+                widget_height = rect.height().min(widget_height);
+            }
+        }
+        let render_scale = viewport.render_scale.load(atomic::Ordering::SeqCst);
+        let (new_width, new_height) = viewport.resolution.map_or_else(
+            || {
+                (
+                    scale_length(widget_width, render_scale),
+                    scale_length(widget_height, render_scale),
+                )
+            },
+            |(width, height)| (width.max(1), height.max(1)),
         );
         let (old_width, old_height) = viewport.old_widget_size;
-        if new_width != old_width || new_height != old_height {
-            trace!(
-                "Old/new widget size: {old_width}x{old_height} / {new_width}x{new_height}, \
-                 creating new main world image"
-            );
-            viewport.old_widget_size = (new_width, new_height);
+        if new_width == old_width && new_height == old_height {
+            viewport.pending_resize = None;
+            continue;
+        }
 
-            let (tex_width, tex_height) = texture_size(new_width, new_height);
-            let mut image = Image::new_uninit(
-                Extent3d {
-                    width: tex_width,
-                    height: tex_height,
-                    depth_or_array_layers: 1,
-                },
-                TextureDimension::D2,
-                TEXTURE_FORMAT,
-                RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
-            );
-            image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
-                | TextureUsages::COPY_DST
-                | TextureUsages::RENDER_ATTACHMENT;
-            images
-                .insert(&viewport.image_handle, image)
-                .expect("should be able to insert image asset");
+        let first_size = viewport.old_widget_size == (u32::MAX, u32::MAX);
+        // GTK scales the last frame to fit until the resize ends
+        if !first_size
+            && viewport.hold_size_while_resizing
+            && viewport.window_resizing.load(atomic::Ordering::SeqCst)
+        {
+            continue;
+        }
+        if !first_size && !viewport.resize_debounce.is_zero() {
+            let now = Instant::now();
+            match viewport.pending_resize {
+                Some((size, since)) if size == (new_width, new_height) => {
+                    if now.duration_since(since) < viewport.resize_debounce {
+                        continue;
+                    }
+                }
+                _ => {
+                    viewport.pending_resize = Some(((new_width, new_height), now));
+                    continue;
+                }
+            }
         }
+        viewport.pending_resize = None;
+
+        trace!(
+            "Old/new image size: {old_width}x{old_height} / {new_width}x{new_height} (widget \
+             {widget_width}x{widget_height} at {render_scale}x), creating new main world image"
+        );
+        viewport.old_widget_size = (new_width, new_height);
+        viewport.image_size.store(new_width, new_height);
+
+        let (tex_width, tex_height) = texture_size(new_width, new_height);
+        let mut image = Image::new_uninit(
+            Extent3d {
+                width: tex_width,
+                height: tex_height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            TEXTURE_FORMAT,
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        );
+        image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+            | TextureUsages::COPY_DST
+            | TextureUsages::RENDER_ATTACHMENT;
+        if let Err(err) = images.insert(&viewport.image_handle, image) {
+            viewport.health.fail(ViewportErrorKind::CreateImage, err);
+        }
+    }
+}
+
+fn set_memory(viewport: &RenderViewport, memory: ViewportMemory) {
+    let old = viewport.memory.swap(memory as u8, atomic::Ordering::SeqCst);
+    if ViewportMemory::from_u8(old) != Some(memory) {
+        debug!(
+            "Viewport {} images are now in {memory:?} memory",
+            viewport.health.viewport()
+        );
     }
 }
 
@@ -307,44 +1524,198 @@ fn texture_size(width: u32, height: u32) -> (u32, u32) {
     (width.max(1), height.max(1))
 }
 
+/// Smallest [`ViewportConfig::render_scale`] that we render at, so that the
+/// image never collapses to nothing.
+const MIN_RENDER_SCALE: f32 = 0.05;
+
+fn clamp_render_scale(render_scale: f32) -> f32 {
+    if render_scale.is_finite() {
+        render_scale.max(MIN_RENDER_SCALE)
+    } else {
+        1.0
+    }
+}
+
+#[expect(
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    reason = "render scale is positive, and widget sizes are relatively small"
+)]
+fn scale_length(length: u32, render_scale: f32) -> u32 {
+    if length == 0 || (render_scale - 1.0).abs() <= f32::EPSILON {
+        return length;
+    }
+    ((length as f32 * render_scale).round() as u32).max(1)
+}
+
 // frame-to-frame rendering logic, in the render world
 
 fn set_target_images(
-    mut viewports: Query<&mut RenderViewport>,
+    mut viewports: Query<(Entity, &mut RenderViewport)>,
     render_adapter: Res<RenderAdapter>,
     render_device: Res<RenderDevice>,
     default_image_sampler: Res<DefaultImageSampler>,
     mut gpu_images: ResMut<RenderAssets<GpuImage>>,
+    mut depth_textures: ResMut<ViewportDepthTextures>,
+    render_data: Option<Res<GtkRenderData>>,
+    vulkan_features: Option<Res<AdditionalVulkanFeatures>>,
+    lifecycle: Option<Res<GtkLifecycle>>,
+    capabilities: Option<Res<GtkCapabilities>>,
+    counters: Option<Res<ViewportCounters>>,
+    device_state: Res<RenderDeviceState>,
+    #[cfg(feature = "test-utils")] mock_dmabufs: Option<Res<MockDmabufs>>,
+    mut commands: Commands,
 ) {
-    for mut viewport in &mut viewports {
-        let (new_width, new_height) = (
-            viewport.widget_size.0.load(atomic::Ordering::SeqCst),
-            viewport.widget_size.1.load(atomic::Ordering::SeqCst),
-        );
+    // both Bevy and GTK have to be able to share dmabufs,
+    // otherwise we present through the CPU
+    let dmabuf_supported = dmabuf::dmabuf_supported(vulkan_features.as_deref())
+        && capabilities.is_none_or(|capabilities| capabilities.dmabuf_import);
+    let linear_dmabufs = dmabuf::linear_dmabufs(vulkan_features.as_deref());
+    let suspended = lifecycle.is_some_and(|lifecycle| lifecycle.is_suspended());
+    // nothing can be allocated on a lost device, so we wait for a new one
+    let device_lost = device_state.is_lost();
+    // only allocate dmabufs which GTK can import
+    let modifiers = match (render_data, dmabuf::format_to_fourcc(TEXTURE_FORMAT)) {
+        (Some(render_data), Some(fourcc)) => render_data.modifiers_for(fourcc).collect(),
+        _ => Vec::new(),
+    };
+
+    // rebuilt every frame, so that despawned viewports are removed
+    depth_textures.0.clear();
+    for (entity, mut viewport) in &mut viewports {
+        if viewport.health.is_broken() {
+            continue;
+        }
+
+        if suspended || device_lost {
+            if viewport.back_buffer.is_some() {
+                let reason = if device_lost {
+                    "Render device lost"
+                } else {
+                    "App suspended"
+                };
+                debug!("{reason}, releasing textures of viewport {entity}");
+                release_textures(&mut viewport);
+                gpu_images.remove(&viewport.image_handle);
+                commands.entity(entity).remove::<ViewportRenderTarget>();
+            }
+            continue;
+        }
+
+        if viewport.latency == ViewportLatency::Blocking
+            && viewport.back_buffer.is_some()
+            && !viewport
+                .frames
+                .wait_until_empty(ViewportLatency::BLOCKING_TIMEOUT)
+        {
+            trace!("Timed out waiting for GTK to take the last frame of viewport {entity}");
+        }
+
+        let (new_width, new_height) = viewport.image_size.load();
 
         let (old_width, old_height) = viewport.old_widget_size;
         if new_width != old_width || new_height != old_height {
             trace!(
-                "Old/new widget size: {old_width}x{old_height} / {new_width}x{new_height}, \
+                "Old/new image size: {old_width}x{old_height} / {new_width}x{new_height}, \
                  creating new dmabuf"
             );
             viewport.old_widget_size = (new_width, new_height);
 
             let (tex_width, tex_height) = texture_size(new_width, new_height);
 
-            let dmabuf = DmabufTexture::new(
-                &render_adapter,
-                render_device.wgpu_device(),
-                tex_width,
-                tex_height,
-                TEXTURE_FORMAT,
-            )
-            .expect("failed to create dmabuf texture");
+            #[cfg(feature = "test-utils")]
+            let mock_texture = mock_dmabufs.is_some().then(|| {
+                set_memory(&viewport, ViewportMemory::DeviceLocal);
+                mock::create_texture(
+                    &mut viewport,
+                    &render_device,
+                    tex_width,
+                    tex_height,
+                    TEXTURE_FORMAT,
+                )
+            });
+            #[cfg(not(feature = "test-utils"))]
+            let mock_texture = None;
+
+            let texture = if let Some(texture) = mock_texture {
+                texture
+            } else if dmabuf_supported {
+                let dmabufs = (0..viewport.latency.ring_size())
+                    .map(|_| {
+                        DmabufTexture::new_with_params(
+                            &render_adapter,
+                            render_device.wgpu_device(),
+                            tex_width,
+                            tex_height,
+                            TEXTURE_FORMAT,
+                            DmabufParams {
+                                modifiers: &modifiers,
+                                memory: viewport.dmabuf_memory,
+                                queue_families: &[],
+                                linear: linear_dmabufs,
+                            },
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>();
+                let mut dmabufs = match dmabufs {
+                    Ok(dmabufs) => dmabufs,
+                    Err(err) => {
+                        viewport.health.fail(ViewportErrorKind::CreateDmabuf, err);
+                        release_textures(&mut viewport);
+                        continue;
+                    }
+                };
+                if let Some(counters) = &counters {
+                    counters.add_dmabuf_allocations(dmabufs.len());
+                }
+                let memory = if dmabufs.iter().all(DmabufTexture::is_device_local) {
+                    ViewportMemory::DeviceLocal
+                } else {
+                    ViewportMemory::HostVisible
+                };
+                set_memory(&viewport, memory);
+
+                if dmabufs.len() == 1 {
+                    let dmabuf = dmabufs.remove(0);
+                    let texture = Texture::from(dmabuf.wgpu_texture().clone());
+                    viewport.queued_dmabuf = Some(dmabuf);
+                    texture
+                } else {
+                    viewport.dmabuf_ring = dmabufs
+                        .into_iter()
+                        .map(|dmabuf| {
+                            let texture = Texture::from(dmabuf.wgpu_texture().clone());
+                            let view = texture.create_view(&TextureViewDescriptor::default());
+                            RingBuffer {
+                                dmabuf,
+                                texture,
+                                view,
+                            }
+                        })
+                        .collect();
+                    viewport.ring_index = 0;
+                    viewport.dmabuf_ring[0].texture.clone()
+                }
+            } else {
+                viewport.readback.get_or_insert_default();
+                set_memory(&viewport, ViewportMemory::Readback);
+                readback::create_texture(&render_device, tex_width, tex_height, TEXTURE_FORMAT)
+            };
+
+            viewport.depth_buffer = viewport.depth_format.map(|format| {
+                ViewportDepthTexture::new(&render_device, tex_width, tex_height, format)
+            });
 
-            let texture = Texture::from(dmabuf.wgpu_texture().clone());
             let texture_view = texture.create_view(&TextureViewDescriptor::default());
-            viewport.back_buffer = Some((texture, texture_view));
-            viewport.queued_dmabuf = Some(dmabuf);
+            set_back_buffer(&mut viewport, entity, texture, texture_view, &mut commands);
+        } else if !viewport.dmabuf_ring.is_empty() {
+            // render into the next dmabuf, while GTK may still display the
+            // previous ones
+            viewport.ring_index = (viewport.ring_index + 1) % viewport.dmabuf_ring.len();
+            let buffer = &viewport.dmabuf_ring[viewport.ring_index];
+            let (texture, texture_view) = (buffer.texture.clone(), buffer.view.clone());
+            set_back_buffer(&mut viewport, entity, texture, texture_view, &mut commands);
         }
 
         if let Some((texture, texture_view)) = &viewport.back_buffer {
@@ -358,194 +1729,585 @@ fn set_target_images(
             };
             gpu_images.insert(&viewport.image_handle, gpu_image);
         }
+        if let Some(depth_buffer) = &viewport.depth_buffer {
+            depth_textures
+                .0
+                .insert(viewport.image_handle.id(), depth_buffer.clone());
+        }
+    }
+}
+
+/// Makes a viewport render into `texture` from now on.
+fn set_back_buffer(
+    viewport: &mut RenderViewport,
+    entity: Entity,
+    texture: Texture,
+    texture_view: TextureView,
+    commands: &mut Commands,
+) {
+    commands.entity(entity).insert(ViewportRenderTarget {
+        texture: texture.clone(),
+        view: texture_view.clone(),
+        format: texture.format(),
+        size: UVec2::new(texture.width(), texture.height()),
+    });
+    viewport.back_buffer = Some((texture, texture_view));
+}
+
+/// Drops all textures of a viewport, and makes sure that they are created again
+/// on the next frame that the viewport renders.
+fn release_textures(viewport: &mut RenderViewport) {
+    viewport.back_buffer = None;
+    viewport.depth_buffer = None;
+    viewport.dmabuf_ring.clear();
+    viewport.queued_dmabuf = None;
+    #[cfg(feature = "test-utils")]
+    {
+        viewport.mock_frame = None;
     }
+    viewport.frames.clear();
+    // the size can't match this, so new textures are made
+    viewport.old_widget_size = (u32::MAX, u32::MAX);
 }
 
-fn present_frames(mut viewports: Query<&mut RenderViewport>) {
+fn present_frames(
+    mut viewports: Query<&mut RenderViewport>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    counters: Option<Res<ViewportCounters>>,
+) {
+    let start = Instant::now();
     for mut viewport in &mut viewports {
+        let capacity = viewport.latency.queue_capacity();
         if let Some(dmabuf) = viewport.queued_dmabuf.take() {
             viewport
-                .next_dmabuf
-                .store(Some(Box::new(dmabuf)), atomic::Ordering::SeqCst);
+                .frames
+                .push(ViewportFrame::Dmabuf(dmabuf), capacity);
+        }
+        if let Some(buffer) = viewport.dmabuf_ring.get(viewport.ring_index) {
+            viewport
+                .frames
+                .push(ViewportFrame::Dmabuf(buffer.dmabuf.clone()), capacity);
+        }
+        #[cfg(feature = "test-utils")]
+        if let Some(texture) = &viewport.mock_frame {
+            viewport
+                .frames
+                .push(ViewportFrame::Mock(texture.clone()), capacity);
+        }
+        let Some((texture, _)) = &viewport.back_buffer else {
+            continue;
+        };
+
+        let frame_count = viewport.frame_count.clone();
+        let tx_frame_ready = viewport.tx_frame_ready.clone();
+        let on_presented = move || {
+            frame_count.fetch_add(1, atomic::Ordering::SeqCst);
+            _ = tx_frame_ready.try_send(());
+        };
+        match &viewport.readback {
+            // the frame is only presented once it's been copied to the CPU
+            Some(readback) => readback.read_back(
+                texture,
+                &render_device,
+                &render_queue,
+                viewport.frames.clone(),
+                capacity,
+                on_presented,
+            ),
+            None => on_presented(),
         }
     }
+    if let Some(counters) = &counters {
+        counters.add_present_time(start.elapsed());
+    }
 }
 
 // destroy logic
 
 fn despawn_destroyed_viewports(
     viewports: Query<(Entity, &ViewportPrivate)>,
+    mut images: ResMut<Assets<Image>>,
     mut commands: Commands,
 ) {
     for (entity, viewport) in &viewports {
         if Arc::strong_count(&viewport.widget_alive) == 1 {
             debug!("Despawned viewport {entity} because its GTK widget was dropped");
+            // cameras may still hold a handle to the image, which would keep
+            // it, and the texture in the render world, alive
+            images.remove(&viewport.image_handle);
             commands.entity(entity).despawn();
         }
     }
 }
 
+/// Drops the [`WidgetFactory`]s of despawned viewports which were made with
+/// [`GtkViewports::create_for_widget`], but never found their widget.
+///
+/// Otherwise, they would stay around until a widget with their ID is realized,
+/// and be bound to a viewport which no longer exists.
+fn drop_unbound_factories(
+    mut despawned_viewports: RemovedComponents<ViewportPrivate>,
+    mut gtk_commands: GtkCommands,
+) {
+    let despawned_viewports = despawned_viewports.read().collect::<Vec<_>>();
+    if despawned_viewports.is_empty() {
+        return;
+    }
+    gtk_commands.queue(move |_: &mut GtkContext| {
+        widget::drop_unbound_factories(&despawned_viewports);
+    });
+}
+
+/// Releases the GPU image of viewports whose main world entity has been
+/// despawned.
+///
+/// Despawning the main world entity also despawns its [`RenderViewport`],
+/// which frees its dmabufs, but [`set_target_images`] replaced the viewport
+/// image's [`GpuImage`] with our own texture. That entry would only be
+/// removed once the image asset is, so we remove it ourselves.
+fn release_despawned_viewports(
+    new_viewports: Query<(Entity, &RenderViewport), Added<RenderViewport>>,
+    mut despawned_viewports: RemovedComponents<RenderViewport>,
+    mut gpu_images: ResMut<RenderAssets<GpuImage>>,
+    mut image_ids: Local<HashMap<Entity, AssetId<Image>>>,
+) {
+    for (entity, viewport) in &new_viewports {
+        image_ids.insert(entity, viewport.image_handle.id());
+    }
+    for entity in despawned_viewports.read() {
+        let Some(image_id) = image_ids.remove(&entity) else {
+            continue;
+        };
+        if gpu_images.remove(image_id).is_some() {
+            trace!("Released GPU image of despawned viewport render entity {entity}");
+        }
+    }
+}
+
 // GTK-side logic
 
-#[derive(Debug)]
+#[derive(derive_more::Debug)]
 pub struct WidgetFactory {
-    next_dmabuf: Arc<AtomicOptionBox<DmabufTexture>>,
-    widget_size: Arc<(AtomicU32, AtomicU32)>,
+    config: ViewportConfig,
+    health: ViewportHealth,
+    frames: Arc<FrameQueue>,
+    widget_size: Arc<AtomicSize>,
+    frame_count: Arc<AtomicU64>,
+    rx_frame_ready: async_channel::Receiver<()>,
     widget_scale_factor: Arc<AtomicF64>,
     widget_alive: Arc<()>,
+    window_resizing: Arc<AtomicBool>,
+    visible_rect: Arc<VisibleRect>,
+    loading_placeholder: LoadingPlaceholder,
+    #[debug(skip)]
+    error_placeholder: Option<Box<dyn MakeWidget>>,
+    accessibility: AccessibilityWidget,
+}
+
+/// What a viewport widget displays until Bevy has presented its first frame.
+///
+/// The first frame only arrives after Bevy's render pipeline has warmed up,
+/// which can take a noticeable amount of time.
+#[derive(derive_more::Debug, Default)]
+pub enum LoadingPlaceholder {
+    /// Displays nothing but the black background of the viewport.
+    None,
+    /// Displays a spinner in the center of the viewport.
+    #[default]
+    Spinner,
+    /// Displays a custom widget.
+    Custom(#[debug(skip)] Box<dyn MakeWidget>),
+}
+
+impl LoadingPlaceholder {
+    fn make(self) -> Option<gtk::Widget> {
+        match self {
+            Self::None => None,
+            Self::Spinner => Some(
+                gtk::Spinner::builder()
+                    .spinning(true)
+                    .halign(gtk::Align::Center)
+                    .valign(gtk::Align::Center)
+                    .width_request(32)
+                    .height_request(32)
+                    .build()
+                    .upcast(),
+            ),
+            Self::Custom(make_widget) => Some(make_widget.make()),
+        }
+    }
 }
 
 impl WidgetFactory {
+    /// Entity of the viewport which this factory makes a widget for.
+    ///
+    /// This is the same as [`GtkViewport::entity`], so you can use it to
+    /// correlate the GTK widget with the Bevy side of the viewport.
     #[must_use]
-    #[expect(
-        clippy::cast_sign_loss,
-        reason = "GTK should never give us a negative width"
-    )]
-    #[expect(
-        clippy::cast_possible_truncation,
-        reason = "widget widths are relatively small"
-    )]
-    pub fn make(self) -> gtk::Widget {
-        #[derive(Debug)]
-        struct Swapchain {
-            // these aren't `front` and `back` buffers,
-            // because their role constantly swaps
-            texture_a: gdk::Texture,
-            texture_b: gdk::Texture,
+    pub fn entity(&self) -> Entity {
+        self.health.viewport()
+    }
+
+    /// Sets what the widget displays until the first frame is presented.
+    ///
+    /// By default, this is [`LoadingPlaceholder::Spinner`].
+    #[must_use]
+    pub fn with_loading_placeholder(self, loading_placeholder: LoadingPlaceholder) -> Self {
+        Self {
+            loading_placeholder,
+            ..self
+        }
+    }
+
+    /// Sets what the widget displays if the viewport fails.
+    ///
+    /// By default, this is a label with a short error description.
+    ///
+    /// See [`ViewportError`].
+    #[must_use]
+    pub fn with_error_placeholder(self, error_placeholder: impl MakeWidget) -> Self {
+        Self {
+            error_placeholder: Some(Box::new(error_placeholder)),
+            ..self
         }
+    }
 
+    /// Makes a [`BevyPaintable`] instead of a widget, which can be displayed
+    /// by any GTK widget which accepts a [`gdk::Paintable`].
+    ///
+    /// The size that the paintable is drawn at drives the size of the Bevy
+    /// image. Unlike [`WidgetFactory::make`], the paintable does not use a
+    /// [`gtk::GraphicsOffload`], and ignores the present mode and
+    /// placeholders.
+    #[must_use]
+    pub fn make_paintable(self) -> BevyPaintable {
+        BevyPaintable::new(paintable::PaintableState {
+            health: self.health,
+            frames: self.frames,
+            frame_textures_capacity: self.config.latency.ring_size(),
+            color_state: self.config.color_state,
+            widget_size: self.widget_size,
+            widget_scale_factor: self.widget_scale_factor,
+            size_rounding: self.config.size_rounding,
+            widget_alive: self.widget_alive,
+            rx_frame_ready: self.rx_frame_ready,
+        })
+    }
+
+    #[must_use]
+    pub fn make(self) -> gtk::Widget {
         let Self {
-            next_dmabuf,
+            config,
+            health,
+            frames,
             widget_size,
+            frame_count,
+            rx_frame_ready: _,
             widget_scale_factor,
             widget_alive,
+            window_resizing,
+            visible_rect,
+            loading_placeholder,
+            error_placeholder,
+            accessibility,
         } = self;
+        let entity = health.viewport();
 
         let picture = gtk::Picture::new();
+        let render_visible_only = config.render_visible_only && config.resolution.is_none();
+        // placed over the visible part of the widget, which is updated on
+        // every tick
+        let visible_bin = render_visible_only.then(|| visible::VisibleBin::new(&picture));
+        if render_visible_only {
+            picture.set_content_fit(gtk::ContentFit::Fill);
+        } else {
+            picture.set_content_fit(config.content_fit.to_gtk());
+        }
         let offload = gtk::GraphicsOffload::builder()
             .black_background(true)
-            .child(&picture)
+            .child(visible_bin.as_ref().map_or_else(
+                || picture.upcast_ref::<gtk::Widget>(),
+                |bin| bin.upcast_ref(),
+            ))
             .hexpand(true)
             .vexpand(true)
             .build();
 
-        let get_scale = |widget: &gtk::Widget| {
-            widget
-                .native()
-                .and_then(|native| native.surface())
-                .map(|surface| surface.scale())
-        };
+        // shows the loading placeholder until the first frame arrives,
+        // then switches to the offload for the rest of the widget's lifetime
+        let stack = gtk::Stack::builder()
+            .transition_type(gtk::StackTransitionType::Crossfade)
+            .hexpand(true)
+            .vexpand(true)
+            .build();
+        stack.add_child(&offload);
+        let mut loading = false;
+        if let Some(placeholder) = loading_placeholder.make() {
+            stack.add_child(&placeholder);
+            stack.set_visible_child(&placeholder);
+            loading = true;
+        }
 
+        let size_rounding = config.size_rounding;
         offload.connect_scale_factor_notify(clone!(
             #[strong]
             widget_size,
+            #[strong]
+            widget_scale_factor,
             move |widget| {
-                let Some(scale) = get_scale(widget.upcast_ref()) else {
+                let Some(scale) = widget_scale(widget.upcast_ref()) else {
                     return;
                 };
                 widget_scale_factor.store(scale, atomic::Ordering::SeqCst);
 
-                #[expect(
-                    clippy::cast_sign_loss,
-                    clippy::cast_possible_truncation,
-                    reason = "GTK should never give us a negative width"
-                )]
+                let (offset_x, offset_y) = surface_offset(widget.upcast_ref());
                 let (width, height) = (
-                    (f64::from(widget.width()) * scale) as u32,
-                    (f64::from(widget.height()) * scale) as u32,
+                    size_rounding.to_physical(offset_x, f64::from(widget.width()), scale),
+                    size_rounding.to_physical(offset_y, f64::from(widget.height()), scale),
                 );
-                widget_size.0.store(width, atomic::Ordering::SeqCst);
-                widget_size.1.store(height, atomic::Ordering::SeqCst);
+                widget_size.store(width, height);
             },
         ));
 
-        let container = {
-            // Use a trick to detect when the picture is resized.
-            // <https://stackoverflow.com/questions/70488187/get-calculated-size-of-widget-in-gtk-4-0>
-            // +-----------------------+
-            // |          WL           |  WL: width_listener  (height 0)
-            // |-----------------------|  HL: height_listener (width 0)
-            // |   |                   |
-            // | H |     picture       |
-            // | L |                   |
-            // |   |                   |
-            // +-----------------------+
-
-            let width_listener = gtk::DrawingArea::builder().hexpand(true).build();
-
-            width_listener.set_draw_func(clone!(
-                #[strong]
-                widget_size,
-                move |widget, _, width, _| {
-                    let Some(scale) = get_scale(widget.upcast_ref()) else {
-                        return;
-                    };
+        let content = transition::wrap(entity, stack.upcast_ref(), &picture);
+        let container: gtk::Widget = match config.size_detection {
+            ViewportSizeDetection::Allocation => allocation::AllocationBin::new(
+                &content,
+                clone!(
+                    #[strong]
+                    widget_size,
+                    #[strong]
+                    widget_scale_factor,
+                    move |widget, width, height| {
+                        let Some(scale) = widget_scale(widget.upcast_ref()) else {
+                            return;
+                        };
+                        widget_scale_factor.store(scale, atomic::Ordering::SeqCst);
 
-                    let width = (f64::from(width) * scale) as u32;
-                    widget_size.0.store(width, atomic::Ordering::SeqCst);
-                },
-            ));
+                        let (offset_x, offset_y) = surface_offset(widget.upcast_ref());
+                        widget_size.store(
+                            size_rounding.to_physical(offset_x, f64::from(width), scale),
+                            size_rounding.to_physical(offset_y, f64::from(height), scale),
+                        );
+                    }
+                ),
+            )
+            .upcast(),
+            ViewportSizeDetection::Listeners => {
+                listener_container(&content, &widget_size, &widget_scale_factor, size_rounding)
+            }
+        };
 
-            let height_listener = gtk::DrawingArea::builder().vexpand(true).build();
-            height_listener.set_draw_func(clone!(
-                #[strong]
-                widget_size,
-                move |widget, _, _, height| {
-                    let Some(scale) = get_scale(widget.upcast_ref()) else {
-                        return;
-                    };
+        let frame_textures = RefCell::new(FrameTextures::new(
+            config.latency.ring_size(),
+            config.color_state,
+        ));
+        // the offload isn't mapped while the placeholder is shown,
+        // so we tick on the container instead
+        let loading = Cell::new(loading);
+        let last_frame_count = Cell::new(0);
+        let interactive_resize = InteractiveResize::default();
+        let error_placeholder = RefCell::new(Some(error_placeholder));
+        let redraw_paintable = (config.present_mode == ViewportPresentMode::Redraw).then(|| {
+            let paintable = redraw::RedrawPaintable::new();
+            picture.set_paintable(Some(&paintable));
+            paintable
+        });
+        container.add_tick_callback(clone!(
+            #[weak]
+            stack,
+            #[weak]
+            offload,
+            #[strong]
+            picture,
+            #[upgrade_or]
+            glib::ControlFlow::Break,
+            move |_, _| {
+                if health.is_destroyed() {
+                    trace!(
+                        "Viewport {} was destroyed, clearing widget",
+                        health.viewport()
+                    );
+                    frame_textures.borrow_mut().clear();
+                    picture.set_paintable(None::<&gdk::Paintable>);
+                    stack.set_visible_child(&offload);
+                    return glib::ControlFlow::Break;
+                }
 
-                    let height = (f64::from(height) * scale) as u32;
-                    widget_size.1.store(height, atomic::Ordering::SeqCst);
-                },
-            ));
+                // GTK doesn't tell us when the window's scale factor override
+                // changes, so we check for it ourselves
+                if widget_scale(offload.upcast_ref()).is_some_and(|scale| {
+                    (scale - widget_scale_factor.load(atomic::Ordering::SeqCst)).abs()
+                        > f64::EPSILON
+                }) {
+                    offload.notify("scale-factor");
+                }
 
-            let frame_content_h = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-            frame_content_h.append(&height_listener);
-            frame_content_h.append(&offload);
+                let visible_bounds = visible::visible_bounds(offload.upcast_ref());
+                let scale = widget_scale_factor.load(atomic::Ordering::SeqCst);
+                visible_rect.store(
+                    visible_bounds
+                        .as_ref()
+                        .map(|bounds| visible::to_physical(bounds, scale)),
+                );
+                if let Some(visible_bin) = &visible_bin {
+                    if let Some(bounds) = &visible_bounds {
+                        visible_bin.set_visible_bounds(bounds);
+                    }
+                } else if config.content_fit == ViewportContentFit::Integer {
+                    place_integer_scaled(&picture, offload.upcast_ref(), scale);
+                }
 
-            let frame_content_v = gtk::Box::new(gtk::Orientation::Vertical, 0);
-            frame_content_v.append(&width_listener);
-            frame_content_v.append(&frame_content_h);
+                if config.hold_size_while_resizing {
+                    let resizing = interactive_resize.update(offload.upcast_ref());
+                    if window_resizing.swap(resizing, atomic::Ordering::SeqCst) != resizing {
+                        trace!(
+                            "Window of viewport {} resizing interactively: {resizing}",
+                            health.viewport()
+                        );
+                    }
+                }
 
-            frame_content_v
-        };
+                if health.is_broken() {
+                    if let Some(error_placeholder) = error_placeholder.take() {
+                        let error_placeholder = error_placeholder.map_or_else(
+                            || {
+                                gtk::Label::builder()
+                                    .label("Failed to render viewport")
+                                    .css_classes(["dim-label"])
+                                    .build()
+                                    .upcast()
+                            },
+                            |make_widget| make_widget.make(),
+                        );
+                        stack.add_child(&error_placeholder);
+                        stack.set_visible_child(&error_placeholder);
+                        frame_textures.borrow_mut().clear();
+                        picture.set_paintable(None::<&gdk::Paintable>);
+                    }
+                    return glib::ControlFlow::Continue;
+                }
 
-        let swapchain = RefCell::new(None::<Swapchain>);
-        offload.add_tick_callback(move |_, _| {
-            if let Some(dmabuf) = next_dmabuf.take(atomic::Ordering::SeqCst) {
-                trace!("Downloading new dmabufs from GTK");
-                // "wait.. why do we build 2 gdk textures for the same dmabuf?"
-                //
-                // GTK doesn't redraw the picture unless you manually change the
-                // paintable inside it. I couldn't find a way to force it to redraw.
-                // So instead, we have 2 paintables with the same underlying content
-                // (same dmabuf), and switch between them.
-                let (texture_a, texture_b) = (
-                    dmabuf
-                        .build_gdk_texture()
-                        .expect("failed to build dmabuf texture"),
-                    dmabuf
-                        .build_gdk_texture()
-                        .expect("failed to build dmabuf texture"),
-                );
-                swapchain.replace(Some(Swapchain {
-                    texture_a,
-                    texture_b,
-                }));
-            }
+                let frame_count = frame_count.load(atomic::Ordering::SeqCst);
+                let new_frame = last_frame_count.replace(frame_count) != frame_count;
 
-            if let Some(swapchain) = &mut *swapchain.borrow_mut() {
-                picture.set_paintable(Some(&swapchain.texture_a));
-                mem::swap(&mut swapchain.texture_a, &mut swapchain.texture_b);
+                // at most one frame per tick, so queued frames are shown in order
+                let new_frame_texture = frames.pop();
+                let new_swapchain = new_frame_texture.is_some();
+                if let Some(frame) = new_frame_texture {
+                    trace!("Taking new frame of viewport {}", health.viewport());
+                    if let Err(err) = frame_textures.borrow_mut().take_frame(&frame) {
+                        health.fail(ViewportErrorKind::BuildGdkTexture, err);
+                        return glib::ControlFlow::Continue;
+                    }
+
+                    if loading.replace(false) {
+                        trace!(
+                            "Received first frame of viewport {}, hiding loading placeholder",
+                            health.viewport()
+                        );
+                        stack.set_visible_child(&offload);
+                    }
+                }
+
+                if let Some(swapchain) = frame_textures.borrow_mut().current() {
+                    let swap = match config.present_mode {
+                        ViewportPresentMode::EveryTick => true,
+                        ViewportPresentMode::OnNewFrame => new_frame || new_swapchain,
+                        ViewportPresentMode::Redraw => {
+                            if let Some(redraw_paintable) = &redraw_paintable {
+                                if new_swapchain {
+                                    redraw_paintable.set_textures((
+                                        swapchain.texture_a.clone(),
+                                        swapchain.texture_b.clone(),
+                                    ));
+                                } else {
+                                    redraw_paintable.redraw();
+                                }
+                            }
+                            false
+                        }
+                    };
+                    if swap {
+                        picture.set_paintable(Some(&swapchain.texture_a));
+                        mem::swap(&mut swapchain.texture_a, &mut swapchain.texture_b);
+                    }
+                }
+
+                glib::ControlFlow::Continue
             }
+        ));
 
-            glib::ControlFlow::Continue
-        });
+        if config.input_passthrough {
+            crate::set_input_passthrough(&offload, true);
+        }
 
         let widget_alive = Cell::new(widget_alive);
         offload.connect_destroy(move |_| drop(widget_alive.take()));
 
-        container.upcast()
+        let widget = accessibility.wrap(container.upcast_ref());
+        close::mark_widget(&widget, entity);
+        snapshot::mark_picture(&widget, &picture);
+        widget
+    }
+}
+
+/// Frame which the render world hands over to the GTK side.
+#[derive(Debug)]
+enum ViewportFrame {
+    /// Dmabuf which Bevy keeps rendering into, frame after frame.
+    Dmabuf(DmabufTexture),
+    /// Copy of a single frame, if the render device can't share dmabufs.
+    Cpu(CpuFrame),
+    /// Normal texture which Bevy keeps rendering into, if the render world
+    /// has [`MockDmabufs`].
+    #[cfg(feature = "test-utils")]
+    Mock(Texture),
+}
+
+impl ViewportFrame {
+    /// Texture of the dmabuf which this frame was rendered into, if Bevy may
+    /// render into it again.
+    fn dmabuf_texture(&self) -> Option<&wgpu::Texture> {
+        match self {
+            Self::Dmabuf(dmabuf) => Some(dmabuf.wgpu_texture()),
+            Self::Cpu(_) => None,
+            #[cfg(feature = "test-utils")]
+            Self::Mock(texture) => Some(&**texture),
+        }
+    }
+
+    /// Format of the texture which this frame was rendered into.
+    fn format(&self) -> TextureFormat {
+        match self {
+            Self::Dmabuf(dmabuf) => dmabuf.wgpu_texture().format(),
+            Self::Cpu(frame) => frame.format(),
+            #[cfg(feature = "test-utils")]
+            Self::Mock(texture) => texture.format(),
+        }
+    }
+
+    fn build_gdk_texture(&self, color_state: &gdk::ColorState) -> Result<gdk::Texture, BevyError> {
+        match self {
+            Self::Dmabuf(dmabuf) => dmabuf.build_gdk_texture_with_color_state(color_state),
+            Self::Cpu(frame) => frame.build_gdk_texture(color_state),
+            #[cfg(feature = "test-utils")]
+            Self::Mock(_) => Err("mock frames cannot be displayed by GTK".into()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Swapchain {
+    // these aren't `front` and `back` buffers,
+    // because their role constantly swaps
+    texture_a: gdk::Texture,
+    texture_b: gdk::Texture,
+}
+
+impl MakeWidget for WidgetFactory {
+    fn make(self: Box<Self>) -> gtk::Widget {
+        Self::make(*self)
     }
 }
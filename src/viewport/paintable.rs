@@ -0,0 +1,227 @@
+use {
+    super::{
+        AtomicSize, FrameQueue, FrameTextures, ViewportColorState, ViewportErrorKind,
+        ViewportHealth, ViewportSizeRounding,
+    },
+    alloc::sync::Arc,
+    atomic_float::AtomicF64,
+    bevy_ecs::entity::Entity,
+    core::{mem, sync::atomic},
+    gdk::{prelude::*, subclass::prelude::*},
+    log::trace,
+};
+
+glib::wrapper! {
+    /// [`gdk::Paintable`] which displays the content of a Bevy viewport.
+    ///
+    /// Make one with [`WidgetFactory::make_paintable`], and display it in any
+    /// widget which accepts a paintable, like [`gtk::Picture`],
+    /// [`gtk::Image`], or a button's icon. The viewport lives for as long as
+    /// this paintable lives.
+    ///
+    /// The size that GTK draws this paintable at is used as the viewport size.
+    /// Paintables don't know what scale factor they're drawn at, so if you are
+    /// displaying this on a scaled surface, use
+    /// [`BevyPaintable::set_scale_factor`] to render at the native resolution.
+    ///
+    /// [`WidgetFactory::make_paintable`]: crate::WidgetFactory::make_paintable
+    pub struct BevyPaintable(ObjectSubclass<imp::BevyPaintable>)
+        @implements gdk::Paintable;
+}
+
+#[derive(Debug)]
+pub(super) struct PaintableState {
+    pub health: ViewportHealth,
+    pub frames: Arc<FrameQueue>,
+    /// Maximum number of frames which we keep GDK textures for.
+    pub frame_textures_capacity: usize,
+    pub color_state: Option<ViewportColorState>,
+    pub widget_size: Arc<AtomicSize>,
+    pub widget_scale_factor: Arc<AtomicF64>,
+    pub size_rounding: ViewportSizeRounding,
+    /// Marks if the paintable is still alive.
+    #[expect(dead_code, reason = "only held to keep the viewport alive")]
+    pub widget_alive: Arc<()>,
+    pub rx_frame_ready: async_channel::Receiver<()>,
+}
+
+impl BevyPaintable {
+    pub(super) fn new(state: PaintableState) -> Self {
+        let rx_frame_ready = state.rx_frame_ready.clone();
+        let paintable = glib::Object::new::<Self>();
+        paintable.imp().frame_textures.replace(FrameTextures::new(
+            state.frame_textures_capacity,
+            state.color_state,
+        ));
+        _ = paintable.imp().state.set(state);
+
+        // once the paintable is dropped, the viewport is despawned, which drops
+        // the sender and ends this task
+        let weak = paintable.downgrade();
+        glib::spawn_future_local(async move {
+            while rx_frame_ready.recv().await.is_ok() {
+                let Some(paintable) = weak.upgrade() else {
+                    break;
+                };
+                paintable.imp().on_frame_ready();
+            }
+            if let Some(paintable) = weak.upgrade() {
+                paintable.imp().on_destroyed();
+            }
+        });
+
+        paintable
+    }
+
+    /// Entity of the viewport which this paintable displays.
+    ///
+    /// See [`GtkViewport::entity`](crate::GtkViewport::entity).
+    #[must_use]
+    pub fn entity(&self) -> Entity {
+        self.imp().state().health.viewport()
+    }
+
+    /// Sets the scale factor of the surface which this paintable is drawn on.
+    ///
+    /// Defaults to 1.0.
+    pub fn set_scale_factor(&self, scale_factor: f64) {
+        self.imp()
+            .state()
+            .widget_scale_factor
+            .store(scale_factor, atomic::Ordering::SeqCst);
+        self.invalidate_size();
+    }
+}
+
+mod imp {
+    use {
+        super::*,
+        core::cell::{OnceCell, RefCell},
+    };
+
+    #[derive(Debug, Default)]
+    pub struct BevyPaintable {
+        pub(super) state: OnceCell<PaintableState>,
+        pub(super) frame_textures: RefCell<FrameTextures>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for BevyPaintable {
+        const NAME: &'static str = "BevyGtkPaintable";
+        type Type = super::BevyPaintable;
+        type Interfaces = (gdk::Paintable,);
+    }
+
+    impl ObjectImpl for BevyPaintable {}
+
+    impl BevyPaintable {
+        pub(super) fn state(&self) -> &PaintableState {
+            self.state
+                .get()
+                .expect("paintable state should be set on construction")
+        }
+
+        /// Stops displaying the viewport after its Bevy side is gone, e.g.
+        /// because it was destroyed with [`GtkViewports::destroy`].
+        ///
+        /// [`GtkViewports::destroy`]: crate::GtkViewports::destroy
+        pub(super) fn on_destroyed(&self) {
+            trace!(
+                "Viewport {} of paintable was destroyed",
+                self.state().health.viewport()
+            );
+            self.frame_textures.borrow_mut().clear();
+            self.obj().invalidate_size();
+            self.obj().invalidate_contents();
+        }
+
+        pub(super) fn on_frame_ready(&self) {
+            let state = self.state();
+            if state.health.is_broken() {
+                return;
+            }
+
+            let old_size = self.image_size();
+            let mut frame_textures = self.frame_textures.borrow_mut();
+            if let Some(frame) = state.frames.pop() {
+                trace!(
+                    "Paintable received new frame of viewport {}",
+                    state.health.viewport()
+                );
+                if let Err(err) = frame_textures.take_frame(&frame) {
+                    state.health.fail(ViewportErrorKind::BuildGdkTexture, err);
+                    frame_textures.clear();
+                }
+            } else if let Some(swapchain) = frame_textures.current() {
+                mem::swap(&mut swapchain.texture_a, &mut swapchain.texture_b);
+            }
+            drop(frame_textures);
+
+            if self.image_size() != old_size {
+                self.obj().invalidate_size();
+            }
+            self.obj().invalidate_contents();
+        }
+
+        /// Gets the size of the current frame in physical pixels, or [`None`]
+        /// if there is no frame yet.
+        fn image_size(&self) -> Option<(i32, i32)> {
+            self.frame_textures
+                .borrow_mut()
+                .current()
+                .map(|swapchain| (swapchain.texture_a.width(), swapchain.texture_a.height()))
+        }
+
+        /// Converts a length of the current frame to logical pixels.
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "image sizes are relatively small"
+        )]
+        fn to_logical(&self, length: i32) -> i32 {
+            let scale = self
+                .state()
+                .widget_scale_factor
+                .load(atomic::Ordering::SeqCst);
+            (f64::from(length) / scale).round() as i32
+        }
+    }
+
+    impl PaintableImpl for BevyPaintable {
+        fn flags(&self) -> gdk::PaintableFlags {
+            gdk::PaintableFlags::empty()
+        }
+
+        // frames are rendered at the size that we're drawn at, so the
+        // intrinsic size is the size that we were last drawn at
+        fn intrinsic_width(&self) -> i32 {
+            self.image_size()
+                .map_or(0, |(width, _)| self.to_logical(width))
+        }
+
+        fn intrinsic_height(&self) -> i32 {
+            self.image_size()
+                .map_or(0, |(_, height)| self.to_logical(height))
+        }
+
+        fn intrinsic_aspect_ratio(&self) -> f64 {
+            self.image_size()
+                .filter(|&(_, height)| height > 0)
+                .map_or(0.0, |(width, height)| f64::from(width) / f64::from(height))
+        }
+
+        fn snapshot(&self, snapshot: &gdk::Snapshot, width: f64, height: f64) {
+            let state = self.state();
+            let scale = state.widget_scale_factor.load(atomic::Ordering::SeqCst);
+            // paintables don't know where on the surface they're drawn
+            let rounding = state.size_rounding;
+            state.widget_size.store(
+                rounding.to_physical(0.0, width, scale),
+                rounding.to_physical(0.0, height, scale),
+            );
+
+            if let Some(swapchain) = self.frame_textures.borrow_mut().current() {
+                swapchain.texture_a.snapshot(snapshot, width, height);
+            }
+        }
+    }
+}
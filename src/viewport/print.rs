@@ -0,0 +1,230 @@
+use {
+    crate::{GtkCommands, GtkContext},
+    bevy_app::prelude::*,
+    bevy_asset::Handle,
+    bevy_ecs::{error::BevyError, prelude::*},
+    bevy_image::Image,
+    bevy_render::view::screenshot::{Screenshot, ScreenshotCaptured},
+    gdk::prelude::*,
+    gtk::{cairo, prelude::*},
+    log::{debug, warn},
+    wgpu::TextureFormat,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<PrintImage>()
+        .add_systems(PostUpdate, capture_print_images);
+}
+
+/// Prints the contents of a Bevy image through GTK's print dialog.
+///
+/// The image is read back from the GPU on the next frame, so this can print
+/// anything which Bevy renders into, including a viewport via
+/// [`GtkViewport::image_handle`](crate::GtkViewport::image_handle). The print
+/// dialog is shown once the image has been read back.
+///
+/// # Examples
+///
+/// ```ignore
+/// fn print_viewport(viewport: Single<&GtkViewport>, mut print: EventWriter<PrintImage>) {
+///     print.write(PrintImage::new(viewport.image_handle().clone()));
+/// }
+/// ```
+#[derive(Debug, Clone, Event)]
+pub struct PrintImage {
+    /// Image to print.
+    pub image: Handle<Image>,
+    /// Window which the print dialog is shown for.
+    ///
+    /// If [`None`], the primary window is used.
+    pub window: Option<Entity>,
+    /// Name of the print job, which may be shown in the system's print queue.
+    pub job_name: String,
+    /// How the image is laid out on the page.
+    pub scale: PrintScale,
+}
+
+impl PrintImage {
+    /// Creates an event which prints `image`, fit to a single page.
+    #[must_use]
+    pub fn new(image: Handle<Image>) -> Self {
+        Self {
+            image,
+            window: None,
+            job_name: String::new(),
+            scale: PrintScale::default(),
+        }
+    }
+
+    /// Sets [`PrintImage::scale`].
+    #[must_use]
+    pub fn with_scale(self, scale: PrintScale) -> Self {
+        Self { scale, ..self }
+    }
+}
+
+/// How an image is laid out on the printed page.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PrintScale {
+    /// Scales the image to fit on a single page, keeping its aspect ratio.
+    #[default]
+    FitToPage,
+    /// Prints each pixel of the image at this many dots per inch.
+    ///
+    /// If the image doesn't fit on a single page at this size, it is split
+    /// across as many pages as needed, from left to right, then top to bottom.
+    Dpi(f64),
+}
+
+/// Points per inch, which is the unit we lay out pages in.
+const POINTS_PER_INCH: f64 = 72.0;
+
+fn capture_print_images(mut events: EventReader<PrintImage>, mut commands: Commands) {
+    for event in events.read() {
+        let event = event.clone();
+        commands
+            .spawn(Screenshot::image(event.image.clone()))
+            .observe(
+                move |captured: On<ScreenshotCaptured>, mut gtk_commands: GtkCommands| {
+                    let image = captured.image.clone();
+                    let event = event.clone();
+                    gtk_commands.queue(move |ctx: &mut GtkContext| {
+                        let surface = match image_to_surface(&image) {
+                            Ok(surface) => surface,
+                            Err(err) => {
+                                warn!("Failed to print image: {err}");
+                                return;
+                            }
+                        };
+                        let gtk_window = match event.window {
+                            Some(window) => ctx.windows.get(window),
+                            None => ctx.windows.primary(),
+                        }
+                        .map(|proxy| proxy.gtk_window.clone());
+                        run_print_operation(&event, surface, gtk_window.as_ref());
+                    });
+                },
+            );
+    }
+}
+
+fn image_to_surface(image: &Image) -> Result<cairo::ImageSurface, BevyError> {
+    let data = image.data.as_ref().ok_or("image has no data")?;
+    let format = match image.texture_descriptor.format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => gdk::MemoryFormat::R8g8b8a8,
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => gdk::MemoryFormat::B8g8r8a8,
+        TextureFormat::Rgba16Float => gdk::MemoryFormat::R16g16b16a16Float,
+        TextureFormat::Rgba32Float => gdk::MemoryFormat::R32g32b32a32Float,
+        format => return Err(format!("cannot print images of format {format:?}").into()),
+    };
+    let width = image.width();
+    let height = image.height();
+    let stride = data.len() / height.max(1) as usize;
+    let texture = gdk::MemoryTexture::new(
+        i32::try_from(width)?,
+        i32::try_from(height)?,
+        format,
+        &glib::Bytes::from(data),
+        stride,
+    );
+
+    // cairo's ARGB32 is premultiplied BGRA on little-endian
+    let mut downloader = gdk::TextureDownloader::new(&texture);
+    downloader.set_format(gdk::MemoryFormat::B8g8r8a8Premultiplied);
+    let (bytes, stride) = downloader.download_bytes();
+    let surface = cairo::ImageSurface::create_for_data(
+        bytes.to_vec(),
+        cairo::Format::ARgb32,
+        i32::try_from(width)?,
+        i32::try_from(height)?,
+        i32::try_from(stride)?,
+    )?;
+    Ok(surface)
+}
+
+fn run_print_operation(
+    event: &PrintImage,
+    surface: cairo::ImageSurface,
+    gtk_window: Option<&gtk::ApplicationWindow>,
+) {
+    let operation = gtk::PrintOperation::new();
+    operation.set_unit(gtk::Unit::Points);
+    operation.set_allow_async(true);
+    if !event.job_name.is_empty() {
+        operation.set_job_name(&event.job_name);
+    }
+
+    let scale = event.scale;
+    let image_width = f64::from(surface.width());
+    let image_height = f64::from(surface.height());
+    operation.connect_begin_print(move |operation, context| {
+        let (columns, rows) = match scale {
+            PrintScale::FitToPage => (1, 1),
+            PrintScale::Dpi(dpi) => {
+                let points_per_pixel = POINTS_PER_INCH / dpi;
+                (
+                    pages_needed(image_width * points_per_pixel, context.width()),
+                    pages_needed(image_height * points_per_pixel, context.height()),
+                )
+            }
+        };
+        operation.set_n_pages(columns * rows);
+    });
+
+    operation.connect_draw_page(move |_, context, page| {
+        let cr = context.cairo_context();
+        let (page_width, page_height) = (context.width(), context.height());
+        match scale {
+            PrintScale::FitToPage => {
+                let fit = (page_width / image_width).min(page_height / image_height);
+                // center the image on the page
+                cr.translate(
+                    image_width.mul_add(-fit, page_width) / 2.0,
+                    image_height.mul_add(-fit, page_height) / 2.0,
+                );
+                cr.scale(fit, fit);
+            }
+            PrintScale::Dpi(dpi) => {
+                let points_per_pixel = POINTS_PER_INCH / dpi;
+                let columns = pages_needed(image_width * points_per_pixel, page_width);
+                let (row, column) = (page / columns, page % columns);
+                cr.rectangle(0.0, 0.0, page_width, page_height);
+                cr.clip();
+                cr.translate(
+                    -f64::from(column) * page_width,
+                    -f64::from(row) * page_height,
+                );
+                cr.scale(points_per_pixel, points_per_pixel);
+            }
+        }
+        if let Err(err) = cr
+            .set_source_surface(&surface, 0.0, 0.0)
+            .and_then(|()| cr.paint())
+        {
+            warn!("Failed to draw page {page} of print: {err}");
+        }
+    });
+
+    operation.connect_done(|operation, result| {
+        if result == gtk::PrintOperationResult::Error {
+            let err = operation.error();
+            warn!("Failed to print image: {err:?}");
+        } else {
+            debug!("Print operation finished with {result:?}");
+        }
+    });
+
+    if let Err(err) = operation.run(gtk::PrintOperationAction::PrintDialog, gtk_window) {
+        warn!("Failed to start print operation: {err}");
+    }
+}
+
+/// Number of pages needed to fit `length` points, when each page is
+/// `page_length` points long.
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "page counts are always small"
+)]
+fn pages_needed(length: f64, page_length: f64) -> i32 {
+    (length / page_length).ceil().max(1.0) as i32
+}
@@ -0,0 +1,169 @@
+use {
+    super::{FrameQueue, ViewportFrame},
+    alloc::sync::Arc,
+    bevy_ecs::error::BevyError,
+    bevy_render::{
+        render_resource::Texture,
+        renderer::{RenderDevice, RenderQueue},
+    },
+    core::sync::atomic::{self, AtomicBool},
+    gdk::prelude::*,
+    log::warn,
+    wgpu::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages},
+};
+
+/// Frame which was read back to the CPU, because the render device can't share
+/// dmabufs with GTK.
+#[derive(Debug)]
+pub(super) struct CpuFrame {
+    width: u32,
+    height: u32,
+    stride: usize,
+    format: TextureFormat,
+    data: glib::Bytes,
+}
+
+impl CpuFrame {
+    pub const fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Builds a [`gdk::Texture`] which holds a copy of this frame.
+    pub fn build_gdk_texture(
+        &self,
+        color_state: &gdk::ColorState,
+    ) -> Result<gdk::Texture, BevyError> {
+        let format = match self.format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+                gdk::MemoryFormat::R8g8b8a8
+            }
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+                gdk::MemoryFormat::B8g8r8a8
+            }
+            format => return Err(format!("cannot present frames of format {format:?}").into()),
+        };
+        let texture = gdk::MemoryTextureBuilder::new()
+            .set_width(i32::try_from(self.width)?)
+            .set_height(i32::try_from(self.height)?)
+            .set_format(format)
+            .set_bytes(Some(&self.data))
+            .set_stride(self.stride)
+            .set_color_state(color_state)
+            .build();
+        Ok(texture)
+    }
+}
+
+/// Creates a texture for a viewport to render into, which isn't shared with
+/// GTK.
+pub(super) fn create_texture(
+    render_device: &RenderDevice,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+) -> Texture {
+    render_device.create_texture(&TextureDescriptor {
+        label: Some("bevy_gtk viewport readback"),
+        size: Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format,
+        usage: TextureUsages::RENDER_ATTACHMENT
+            | TextureUsages::TEXTURE_BINDING
+            | TextureUsages::COPY_SRC
+            | TextureUsages::COPY_DST,
+        view_formats: &[],
+    })
+}
+
+/// Presents a viewport by copying each frame through the CPU.
+///
+/// This is much slower than sharing a dmabuf, but works on any render device.
+#[derive(Debug, Default)]
+pub(super) struct Readback {
+    /// Whether a frame is still being read back.
+    ///
+    /// If the GPU is slower than we are, we skip frames instead of queueing up
+    /// more and more copies.
+    in_flight: Arc<AtomicBool>,
+}
+
+impl Readback {
+    /// Copies the contents of `texture` to the CPU, and once that's done,
+    /// pushes the frame to `frames` and calls `on_presented`.
+    pub fn read_back(
+        &self,
+        texture: &Texture,
+        render_device: &RenderDevice,
+        render_queue: &RenderQueue,
+        frames: Arc<FrameQueue>,
+        capacity: usize,
+        on_presented: impl FnOnce() + Send + 'static,
+    ) {
+        if self.in_flight.swap(true, atomic::Ordering::SeqCst) {
+            return;
+        }
+
+        let (width, height) = (texture.width(), texture.height());
+        let format = texture.format();
+        let Some(pixel_size) = format.block_copy_size(None) else {
+            self.in_flight.store(false, atomic::Ordering::SeqCst);
+            return;
+        };
+        let bytes_per_row =
+            (width * pixel_size).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let device = render_device.wgpu_device();
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("bevy_gtk viewport readback"),
+            size: u64::from(bytes_per_row) * u64::from(height),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("bevy_gtk viewport readback"),
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            texture.size(),
+        );
+        render_queue.submit([encoder.finish()]);
+
+        let in_flight = self.in_flight.clone();
+        buffer
+            .clone()
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                in_flight.store(false, atomic::Ordering::SeqCst);
+                if let Err(err) = result {
+                    warn!("Failed to read back viewport frame: {err}");
+                    return;
+                }
+
+                let data = glib::Bytes::from_owned(buffer.slice(..).get_mapped_range().to_vec());
+                buffer.unmap();
+                let frame = CpuFrame {
+                    width,
+                    height,
+                    stride: bytes_per_row as usize,
+                    format,
+                    data,
+                };
+                frames.push(ViewportFrame::Cpu(frame), capacity);
+                on_presented();
+            });
+    }
+}
@@ -0,0 +1,104 @@
+use {
+    core::{cell::RefCell, mem},
+    gdk::{prelude::*, subclass::prelude::*},
+};
+
+glib::wrapper! {
+    /// [`gdk::Paintable`] which keeps drawing the current frame of a viewport,
+    /// for [`ViewportPresentMode::Redraw`](super::ViewportPresentMode::Redraw).
+    ///
+    /// GSK caches what it has drawn for each [`gdk::Texture`], so drawing the
+    /// same texture again would show a stale frame, even though Bevy has
+    /// rendered into its dmabuf since. Like the picture's paintable in the
+    /// other present modes, this alternates between the two textures of a
+    /// swapchain, which both wrap the same dmabuf.
+    pub(super) struct RedrawPaintable(ObjectSubclass<imp::RedrawPaintable>)
+        @implements gdk::Paintable;
+}
+
+impl RedrawPaintable {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Draws `textures` from now on, which must show the same frame.
+    pub fn set_textures(&self, textures: (gdk::Texture, gdk::Texture)) {
+        let old_size = self.imp().size();
+        self.imp().textures.replace(Some(textures));
+        if self.imp().size() != old_size {
+            self.invalidate_size();
+        }
+        self.invalidate_contents();
+    }
+
+    /// Draws the current frame again, reading the latest content of its
+    /// dmabuf.
+    pub fn redraw(&self) {
+        if let Some((texture_a, texture_b)) = &mut *self.imp().textures.borrow_mut() {
+            mem::swap(texture_a, texture_b);
+        }
+        self.invalidate_contents();
+    }
+
+    /// Gets the texture which is currently drawn.
+    pub fn texture(&self) -> Option<gdk::Texture> {
+        self.imp()
+            .textures
+            .borrow()
+            .as_ref()
+            .map(|(texture, _)| texture.clone())
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct RedrawPaintable {
+        pub(super) textures: RefCell<Option<(gdk::Texture, gdk::Texture)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RedrawPaintable {
+        const NAME: &'static str = "BevyGtkRedrawPaintable";
+        type Type = super::RedrawPaintable;
+        type Interfaces = (gdk::Paintable,);
+    }
+
+    impl ObjectImpl for RedrawPaintable {}
+
+    impl RedrawPaintable {
+        pub(super) fn size(&self) -> Option<(i32, i32)> {
+            self.textures
+                .borrow()
+                .as_ref()
+                .map(|(texture, _)| (texture.width(), texture.height()))
+        }
+    }
+
+    impl PaintableImpl for RedrawPaintable {
+        fn flags(&self) -> gdk::PaintableFlags {
+            gdk::PaintableFlags::empty()
+        }
+
+        fn intrinsic_width(&self) -> i32 {
+            self.size().map_or(0, |(width, _)| width)
+        }
+
+        fn intrinsic_height(&self) -> i32 {
+            self.size().map_or(0, |(_, height)| height)
+        }
+
+        fn intrinsic_aspect_ratio(&self) -> f64 {
+            self.size()
+                .filter(|&(_, height)| height > 0)
+                .map_or(0.0, |(width, height)| f64::from(width) / f64::from(height))
+        }
+
+        fn snapshot(&self, snapshot: &gdk::Snapshot, width: f64, height: f64) {
+            if let Some((texture, _)) = &*self.textures.borrow() {
+                texture.snapshot(snapshot, width, height);
+            }
+        }
+    }
+}
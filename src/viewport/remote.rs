@@ -0,0 +1,559 @@
+//! Runs the Bevy renderer in a separate process from the GTK UI.
+//!
+//! The parent process shows a [`RemoteViewport`] widget, and spawns a child
+//! process which runs a Bevy app with a normal [`GtkViewport`]. Instead of
+//! making a widget, the child calls [`WidgetFactory::serve_remote`], which
+//! sends every frame to the parent as a dmabuf over a Unix socket. The parent
+//! sends the widget's size back, so the child renders at the right size.
+//!
+//! If the child crashes, the parent's widget shows an error placeholder, and
+//! the rest of the UI keeps running.
+//!
+//! [`GtkViewport`]: crate::GtkViewport
+
+use {
+    super::{
+        Swapchain, ViewportErrorKind, ViewportFrame, ViewportSizeRounding, WidgetFactory,
+        surface_offset, widget_scale,
+    },
+    crate::{DmabufImport, DmabufImportPlane, DmabufTexture},
+    alloc::collections::VecDeque,
+    arrayvec::ArrayVec,
+    bevy_ecs::error::BevyError,
+    core::{cell::Cell, iter, mem, sync::atomic},
+    drm_fourcc::{DrmFormat, DrmFourcc},
+    gio::prelude::*,
+    glib::clone,
+    gtk::prelude::*,
+    log::{debug, trace, warn},
+    std::{
+        ffi::OsStr,
+        os::{
+            fd::{AsRawFd as _, BorrowedFd, FromRawFd as _, OwnedFd},
+            unix::net::UnixStream,
+        },
+    },
+};
+
+/// Environment variable which holds the file descriptor of the socket which
+/// connects a child process to its [`RemoteViewport`].
+pub const REMOTE_FD_ENV: &str = "BEVY_GTK_REMOTE_FD";
+
+/// Maximum number of dmabufs which the parent keeps textures for.
+///
+/// The child tracks the same number of buffers, so that it sends a buffer
+/// again once the parent has forgotten it.
+const MAX_REMOTE_BUFFERS: usize = 8;
+
+/// Child-to-parent message: a new buffer, followed by its fd.
+const TAG_NEW_BUFFER: u8 = 0;
+/// Child-to-parent message: present a buffer which was already sent.
+const TAG_PRESENT: u8 = 1;
+
+/// Length of a child-to-parent [`FrameHeader`].
+const FRAME_HEADER_LEN: usize = 64;
+/// Length of a parent-to-child size message.
+const SIZE_LEN: usize = 16;
+
+/// Connection from a child process to the [`RemoteViewport`] which spawned it.
+#[derive(Debug, Clone)]
+pub struct RemoteConnection(gio::UnixConnection);
+
+impl RemoteConnection {
+    /// Connects to the parent process through the socket given in
+    /// [`REMOTE_FD_ENV`].
+    ///
+    /// Returns [`None`] if this process wasn't spawned by a
+    /// [`RemoteViewport`]. Only call this once, since the connection takes
+    /// ownership of the socket.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the socket can't be opened.
+    pub fn from_env() -> Result<Option<Self>, BevyError> {
+        let Some(fd) = std::env::var_os(REMOTE_FD_ENV) else {
+            return Ok(None);
+        };
+        let fd = fd
+            .to_str()
+            .and_then(|fd| fd.parse::<i32>().ok())
+            .ok_or_else(|| format!("`{REMOTE_FD_ENV}` is not a file descriptor: {fd:?}"))?;
+        // SAFETY: the parent passed this fd to us, and nothing else in this
+        // process owns it, as long as this is only called once
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        Ok(Some(Self::from_fd(fd)?))
+    }
+
+    /// Wraps a connected Unix socket.
+    ///
+    /// # Errors
+    ///
+    /// Errors if `fd` is not a Unix socket.
+    pub fn from_fd(fd: OwnedFd) -> Result<Self, BevyError> {
+        let socket = gio::Socket::from_fd(fd)?;
+        let connection = socket
+            .connection_factory_create_connection()
+            .downcast::<gio::UnixConnection>()
+            .map_err(|_| "socket is not a Unix socket")?;
+        Ok(Self(connection))
+    }
+}
+
+impl WidgetFactory {
+    /// Sends this viewport's frames to the [`RemoteViewport`] on the other end
+    /// of `connection`, instead of making a widget in this process.
+    ///
+    /// This requires the render device to share dmabufs with GTK. If it can't,
+    /// the viewport fails with [`ViewportErrorKind::Remote`], since copying
+    /// frames through the CPU is not supported across processes.
+    ///
+    /// Once the connection closes, the viewport is treated like a viewport
+    /// whose widget was destroyed.
+    pub fn serve_remote(self, connection: RemoteConnection) {
+        let Self {
+            health,
+            frames,
+            widget_size,
+            rx_frame_ready,
+            widget_scale_factor,
+            widget_alive,
+            ..
+        } = self;
+        let connection = connection.0;
+
+        glib::spawn_future_local(clone!(
+            #[strong]
+            connection,
+            async move {
+                let input = connection.input_stream();
+                let mut buf = [0; SIZE_LEN];
+                loop {
+                    match input.read_all_future(buf, glib::Priority::DEFAULT).await {
+                        Ok((read_buf, SIZE_LEN, None)) => buf = read_buf,
+                        Ok(_) => break,
+                        Err((_, err)) => {
+                            debug!("Failed to read from remote viewport: {err}");
+                            break;
+                        }
+                    }
+                    let (width, height, scale) = decode_size(&buf);
+                    trace!("Remote viewport resized to {width}x{height} @ {scale}x");
+                    widget_size.store(width, height);
+                    widget_scale_factor.store(scale, atomic::Ordering::SeqCst);
+                }
+                debug!("Remote viewport disconnected");
+                drop(widget_alive);
+            }
+        ));
+
+        glib::spawn_future_local(async move {
+            let mut sent = VecDeque::<(wgpu::Texture, u64)>::new();
+            let mut next_id = 0;
+            while rx_frame_ready.recv().await.is_ok() {
+                if health.is_destroyed() || health.is_broken() {
+                    break;
+                }
+                // the parent only ever shows the latest frame
+                let Some(frame) = iter::from_fn(|| frames.pop()).last() else {
+                    continue;
+                };
+                let ViewportFrame::Dmabuf(dmabuf) = frame else {
+                    health.fail(
+                        ViewportErrorKind::Remote,
+                        "render device can't share dmabufs, which remote viewports need",
+                    );
+                    break;
+                };
+
+                let existing = sent
+                    .iter()
+                    .find(|(texture, _)| texture == dmabuf.wgpu_texture())
+                    .map(|(_, id)| *id);
+                let result = if let Some(id) = existing {
+                    send_present(&connection, id).await
+                } else {
+                    let id = next_id;
+                    next_id += 1;
+                    sent.push_back((dmabuf.wgpu_texture().clone(), id));
+                    while sent.len() > MAX_REMOTE_BUFFERS {
+                        sent.pop_front();
+                    }
+                    send_new_buffer(&connection, id, &dmabuf).await
+                };
+                if let Err(err) = result {
+                    health.fail(ViewportErrorKind::Remote, err);
+                    break;
+                }
+            }
+        });
+    }
+}
+
+async fn send_new_buffer(
+    connection: &gio::UnixConnection,
+    id: u64,
+    dmabuf: &DmabufTexture,
+) -> Result<(), BevyError> {
+    let export = dmabuf.export()?;
+    let header = FrameHeader {
+        id,
+        layout: Some(BufferLayout {
+            width: export.width,
+            height: export.height,
+            drm_format: export.drm_format,
+            planes: export.planes.clone(),
+        }),
+    };
+    connection
+        .output_stream()
+        .write_all_future(header.encode(), glib::Priority::DEFAULT)
+        .await
+        .map_err(|(_, err)| err)?;
+    // sends a single byte along with the fd, right after the header
+    connection.send_fd(export.fd.as_raw_fd(), gio::Cancellable::NONE)?;
+    Ok(())
+}
+
+async fn send_present(connection: &gio::UnixConnection, id: u64) -> Result<(), BevyError> {
+    let header = FrameHeader { id, layout: None };
+    connection
+        .output_stream()
+        .write_all_future(header.encode(), glib::Priority::DEFAULT)
+        .await
+        .map_err(|(_, err)| err)?;
+    Ok(())
+}
+
+/// Widget which shows a viewport rendered by a child process.
+///
+/// Use [`RemoteViewport::spawn`] to start the child process, which must call
+/// [`RemoteConnection::from_env`] and [`WidgetFactory::serve_remote`] for one
+/// of its viewports.
+///
+/// Frames are shared as dmabufs, so they're never copied through the CPU, in
+/// the same way as a [`WidgetFactory::make`] widget in the same process.
+#[derive(Debug)]
+pub struct RemoteViewport {
+    subprocess: gio::Subprocess,
+    connection: gio::UnixConnection,
+}
+
+impl RemoteViewport {
+    /// Spawns the child process given by `argv`, with a socket to this
+    /// viewport passed through [`REMOTE_FD_ENV`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if creating the socket or spawning the process fails.
+    pub fn spawn(argv: &[&OsStr]) -> Result<Self, BevyError> {
+        let (parent, child) = UnixStream::pair()?;
+        let child = OwnedFd::from(child);
+        let child_raw_fd = child.as_raw_fd();
+
+        let launcher = gio::SubprocessLauncher::new(gio::SubprocessFlags::NONE);
+        launcher.setenv(REMOTE_FD_ENV, child_raw_fd.to_string(), true);
+        // SAFETY: `take_fd` only reads the number of the target fd, and
+        // `child_raw_fd` stays open, since the launcher now owns it
+        launcher.take_fd(child, unsafe { BorrowedFd::borrow_raw(child_raw_fd) });
+        let subprocess = launcher.spawn(argv)?;
+        debug!("Spawned remote renderer {argv:?}");
+
+        let RemoteConnection(connection) = RemoteConnection::from_fd(OwnedFd::from(parent))?;
+        Ok(Self {
+            subprocess,
+            connection,
+        })
+    }
+
+    /// Gets the child process.
+    #[must_use]
+    pub fn subprocess(&self) -> &gio::Subprocess {
+        &self.subprocess
+    }
+
+    /// Makes the widget which shows the child's frames.
+    ///
+    /// Until the first frame arrives, the widget shows a spinner. If the
+    /// child exits or the connection breaks, it shows a label saying that the
+    /// renderer stopped. Destroying the widget closes the connection, but
+    /// doesn't kill the child.
+    #[must_use]
+    pub fn make(self) -> gtk::Widget {
+        let Self {
+            subprocess,
+            connection,
+        } = self;
+
+        let picture = gtk::Picture::new();
+        let offload = gtk::GraphicsOffload::builder()
+            .black_background(true)
+            .child(&picture)
+            .hexpand(true)
+            .vexpand(true)
+            .build();
+        let spinner = gtk::Spinner::builder()
+            .spinning(true)
+            .halign(gtk::Align::Center)
+            .valign(gtk::Align::Center)
+            .width_request(32)
+            .height_request(32)
+            .build();
+        let stopped = gtk::Label::builder()
+            .label("Renderer stopped")
+            .css_classes(["dim-label"])
+            .build();
+        let stack = gtk::Stack::builder()
+            .transition_type(gtk::StackTransitionType::Crossfade)
+            .hexpand(true)
+            .vexpand(true)
+            .build();
+        stack.add_child(&offload);
+        stack.add_child(&stopped);
+        stack.add_child(&spinner);
+        stack.set_visible_child(&spinner);
+
+        let last_size = Cell::new(None);
+        stack.add_tick_callback(clone!(
+            #[strong]
+            connection,
+            move |stack, _| {
+                let Some(scale) = widget_scale(stack.upcast_ref()) else {
+                    return glib::ControlFlow::Continue;
+                };
+                let (offset_x, offset_y) = surface_offset(stack.upcast_ref());
+                let rounding = ViewportSizeRounding::default();
+                let size = (
+                    rounding.to_physical(offset_x, f64::from(stack.width()), scale),
+                    rounding.to_physical(offset_y, f64::from(stack.height()), scale),
+                    scale,
+                );
+                if last_size.replace(Some(size)) == Some(size) {
+                    return glib::ControlFlow::Continue;
+                }
+
+                let output = connection.output_stream();
+                if let Err(err) = output.write_all(&encode_size(size), gio::Cancellable::NONE) {
+                    debug!("Failed to send size to remote renderer: {err}");
+                    return glib::ControlFlow::Break;
+                }
+                glib::ControlFlow::Continue
+            }
+        ));
+
+        glib::spawn_future_local(clone!(
+            #[weak]
+            picture,
+            #[weak]
+            stack,
+            #[weak]
+            offload,
+            #[weak]
+            stopped,
+            #[strong]
+            connection,
+            async move {
+                if let Err(err) = receive_frames(&connection, &picture, &stack, &offload).await {
+                    warn!("Lost connection to remote renderer: {err}");
+                }
+                picture.set_paintable(None::<&gdk::Paintable>);
+                stack.set_visible_child(&stopped);
+            }
+        ));
+
+        glib::spawn_future_local(async move {
+            match subprocess.wait_future().await {
+                Ok(()) if subprocess.is_successful() => {
+                    debug!("Remote renderer exited");
+                }
+                Ok(()) => warn!(
+                    "Remote renderer exited with status {}",
+                    subprocess.exit_status()
+                ),
+                Err(err) => warn!("Failed to wait for remote renderer: {err}"),
+            }
+        });
+
+        stack.connect_destroy(move |_| {
+            _ = connection.close(gio::Cancellable::NONE);
+        });
+
+        stack.upcast()
+    }
+}
+
+async fn receive_frames(
+    connection: &gio::UnixConnection,
+    picture: &gtk::Picture,
+    stack: &gtk::Stack,
+    offload: &gtk::GraphicsOffload,
+) -> Result<(), BevyError> {
+    let input = connection.input_stream();
+    let mut buffers = VecDeque::<(u64, Swapchain)>::new();
+    let mut buf = [0; FRAME_HEADER_LEN];
+    loop {
+        let (read_buf, read, err) = input
+            .read_all_future(buf, glib::Priority::DEFAULT)
+            .await
+            .map_err(|(_, err)| err)?;
+        if let Some(err) = err {
+            return Err(err.into());
+        }
+        if read < FRAME_HEADER_LEN {
+            // the child closed the connection
+            return Ok(());
+        }
+        buf = read_buf;
+        let header = FrameHeader::decode(&buf)?;
+
+        if let Some(layout) = header.layout {
+            let fd = connection.receive_fd(gio::Cancellable::NONE)?;
+            // SAFETY: `receive_fd` gives us a new fd, which nothing else owns
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+            let import = DmabufImport {
+                fd,
+                width: layout.width,
+                height: layout.height,
+                drm_format: layout.drm_format,
+                planes: layout.planes,
+            };
+            // two textures for the same dmabuf, see `FrameTextures`
+            let swapchain = Swapchain {
+                texture_a: import.build_gdk_texture()?,
+                texture_b: import.build_gdk_texture()?,
+            };
+            buffers.retain(|(id, _)| *id != header.id);
+            buffers.push_back((header.id, swapchain));
+            while buffers.len() > MAX_REMOTE_BUFFERS {
+                buffers.pop_front();
+            }
+        }
+
+        let Some((_, swapchain)) = buffers.iter_mut().find(|(id, _)| *id == header.id) else {
+            return Err(format!("remote renderer presented unknown buffer {}", header.id).into());
+        };
+        picture.set_paintable(Some(&swapchain.texture_a));
+        mem::swap(&mut swapchain.texture_a, &mut swapchain.texture_b);
+        if stack.visible_child().as_ref() != Some(offload.upcast_ref()) {
+            trace!("Received first frame from remote renderer");
+            stack.set_visible_child(offload);
+        }
+    }
+}
+
+/// Child-to-parent message describing a frame.
+///
+/// All fields are little-endian:
+///
+/// | offset | field                                   |
+/// |--------|-----------------------------------------|
+/// | 0      | tag (`u8`)                              |
+/// | 1      | number of planes (`u8`)                 |
+/// | 4      | width (`u32`)                           |
+/// | 8      | height (`u32`)                          |
+/// | 12     | DRM fourcc (`u32`)                      |
+/// | 16     | buffer ID (`u64`)                       |
+/// | 24     | DRM modifier (`u64`)                    |
+/// | 32     | 4 × plane offset and stride (`u32`s)    |
+///
+/// Everything but the tag and buffer ID is zero for [`TAG_PRESENT`].
+#[derive(Debug)]
+struct FrameHeader {
+    id: u64,
+    /// Layout of the buffer, if this is a new buffer.
+    layout: Option<BufferLayout>,
+}
+
+#[derive(Debug)]
+struct BufferLayout {
+    width: u32,
+    height: u32,
+    drm_format: DrmFormat,
+    planes: ArrayVec<DmabufImportPlane, 4>,
+}
+
+impl FrameHeader {
+    fn encode(&self) -> [u8; FRAME_HEADER_LEN] {
+        let mut buf = [0; FRAME_HEADER_LEN];
+        buf[16..24].copy_from_slice(&self.id.to_le_bytes());
+        let Some(layout) = &self.layout else {
+            buf[0] = TAG_PRESENT;
+            return buf;
+        };
+
+        buf[0] = TAG_NEW_BUFFER;
+        buf[1] = u8::try_from(layout.planes.len()).expect("there should be at most 4 planes");
+        buf[4..8].copy_from_slice(&layout.width.to_le_bytes());
+        buf[8..12].copy_from_slice(&layout.height.to_le_bytes());
+        buf[12..16].copy_from_slice(&(layout.drm_format.code as u32).to_le_bytes());
+        buf[24..32].copy_from_slice(&u64::from(layout.drm_format.modifier).to_le_bytes());
+        for (index, plane) in layout.planes.iter().enumerate() {
+            let start = 32 + index * 8;
+            buf[start..start + 4].copy_from_slice(&plane.offset.to_le_bytes());
+            buf[start + 4..start + 8].copy_from_slice(&plane.stride.to_le_bytes());
+        }
+        buf
+    }
+
+    fn decode(buf: &[u8; FRAME_HEADER_LEN]) -> Result<Self, BevyError> {
+        let u32_at = |start: usize| {
+            u32::from_le_bytes(
+                buf[start..start + 4]
+                    .try_into()
+                    .expect("slice should be 4 bytes"),
+            )
+        };
+        let u64_at = |start: usize| {
+            u64::from_le_bytes(
+                buf[start..start + 8]
+                    .try_into()
+                    .expect("slice should be 8 bytes"),
+            )
+        };
+
+        let id = u64_at(16);
+        match buf[0] {
+            TAG_PRESENT => Ok(Self { id, layout: None }),
+            TAG_NEW_BUFFER => {
+                let n_planes = usize::from(buf[1]);
+                if !(1..=4).contains(&n_planes) {
+                    return Err(format!("invalid number of planes {n_planes}").into());
+                }
+                let code = DrmFourcc::try_from(u32_at(12))
+                    .map_err(|err| format!("invalid DRM fourcc: {err:?}"))?;
+                Ok(Self {
+                    id,
+                    layout: Some(BufferLayout {
+                        width: u32_at(4),
+                        height: u32_at(8),
+                        drm_format: DrmFormat {
+                            code,
+                            modifier: u64_at(24).into(),
+                        },
+                        planes: (0..n_planes)
+                            .map(|index| DmabufImportPlane {
+                                offset: u32_at(32 + index * 8),
+                                stride: u32_at(32 + index * 8 + 4),
+                            })
+                            .collect(),
+                    }),
+                })
+            }
+            tag => Err(format!("invalid remote frame tag {tag}").into()),
+        }
+    }
+}
+
+fn encode_size((width, height, scale): (u32, u32, f64)) -> [u8; SIZE_LEN] {
+    let mut buf = [0; SIZE_LEN];
+    buf[0..4].copy_from_slice(&width.to_le_bytes());
+    buf[4..8].copy_from_slice(&height.to_le_bytes());
+    buf[8..16].copy_from_slice(&scale.to_le_bytes());
+    buf
+}
+
+fn decode_size(buf: &[u8; SIZE_LEN]) -> (u32, u32, f64) {
+    let width = u32::from_le_bytes(buf[0..4].try_into().expect("slice should be 4 bytes"));
+    let height = u32::from_le_bytes(buf[4..8].try_into().expect("slice should be 4 bytes"));
+    let scale = f64::from_le_bytes(buf[8..16].try_into().expect("slice should be 8 bytes"));
+    (width, height, scale)
+}
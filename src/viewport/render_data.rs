@@ -0,0 +1,139 @@
+use {
+    super::dmabuf::format_to_fourcc,
+    crate::GtkApplication,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_render::extract_resource::{ExtractResource, ExtractResourcePlugin},
+    drm_fourcc::{DrmFormat, DrmFourcc, DrmModifier},
+    gdk::prelude::*,
+    gio::prelude::*,
+    glib::clone,
+    log::debug,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(ExtractResourcePlugin::<GtkRenderData>::default())
+        .add_systems(PreStartup, setup_render_data_forwarding)
+        .add_systems(PreUpdate, forward_render_data);
+}
+
+/// What the GTK display is able to render, as reported by GDK.
+///
+/// This is inserted as a resource at startup, and is available in both the
+/// main and render world. It is refreshed when the display's capabilities may
+/// have changed, e.g. when a monitor is plugged in; use [`Res::is_changed`] to
+/// react to that.
+///
+/// Viewports only allocate dmabufs with modifiers which are listed here, so
+/// that GTK can import them without falling back to a copy.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Resource)]
+pub struct GtkRenderData {
+    dmabuf_formats: Vec<DrmFormat>,
+}
+
+impl GtkRenderData {
+    fn from_display(display: &gdk::Display) -> Self {
+        let formats = display.dmabuf_formats();
+        let dmabuf_formats = (0..formats.n_formats())
+            .filter_map(|index| {
+                let (fourcc, modifier) = formats.format(index);
+                Some(DrmFormat {
+                    code: DrmFourcc::try_from(fourcc).ok()?,
+                    modifier: DrmModifier::from(modifier),
+                })
+            })
+            .collect();
+        Self { dmabuf_formats }
+    }
+
+    /// Dmabuf formats which GTK can import, from most to least preferred.
+    #[must_use]
+    pub fn dmabuf_formats(&self) -> &[DrmFormat] {
+        &self.dmabuf_formats
+    }
+
+    /// Whether GTK can import dmabufs with this fourcc and modifier.
+    #[must_use]
+    pub fn supports(&self, fourcc: DrmFourcc, modifier: DrmModifier) -> bool {
+        self.dmabuf_formats
+            .iter()
+            .any(|format| format.code == fourcc && format.modifier == modifier)
+    }
+
+    /// Modifiers which GTK can import for this fourcc, from most to least
+    /// preferred.
+    pub fn modifiers_for(&self, fourcc: DrmFourcc) -> impl Iterator<Item = DrmModifier> + '_ {
+        self.dmabuf_formats
+            .iter()
+            .filter(move |format| format.code == fourcc)
+            .map(|format| format.modifier)
+    }
+
+    /// Gets GTK's most preferred dmabuf format for textures of this format.
+    ///
+    /// Returns [`None`] if the format can't be shared as a dmabuf, or GTK
+    /// doesn't support any modifiers for it.
+    #[must_use]
+    pub fn preferred_format_for(&self, format: wgpu::TextureFormat) -> Option<DrmFormat> {
+        let fourcc = format_to_fourcc(format)?;
+        self.dmabuf_formats
+            .iter()
+            .find(|format| format.code == fourcc)
+            .copied()
+    }
+}
+
+impl ExtractResource for GtkRenderData {
+    type Source = Self;
+
+    fn extract_resource(source: &Self::Source) -> Self {
+        source.clone()
+    }
+}
+
+#[derive(Debug, Resource)]
+struct RxRenderData(async_channel::Receiver<GtkRenderData>);
+
+// `NonSend` keeps this on the GTK thread
+fn setup_render_data_forwarding(_: NonSend<GtkApplication>, mut commands: Commands) {
+    let (tx_render_data, rx_render_data) = async_channel::unbounded();
+    commands.insert_resource(RxRenderData(rx_render_data));
+
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+    commands.insert_resource(GtkRenderData::from_display(&display));
+
+    let send_render_data = move |display: &gdk::Display| {
+        _ = tx_render_data.try_send(GtkRenderData::from_display(display));
+    };
+    display.connect_dmabuf_formats_notify(send_render_data.clone());
+    // a hotplugged monitor may be driven by a different GPU
+    display.monitors().connect_items_changed(clone!(
+        #[weak]
+        display,
+        move |_, _, _, _| send_render_data(&display)
+    ));
+}
+
+fn forward_render_data(
+    rx_render_data: Res<RxRenderData>,
+    render_data: Option<ResMut<GtkRenderData>>,
+    mut commands: Commands,
+) {
+    let mut new = None;
+    while let Ok(render_data) = rx_render_data.0.try_recv() {
+        new = Some(render_data);
+    }
+    let Some(new) = new else {
+        return;
+    };
+    match render_data {
+        Some(mut render_data) => {
+            if render_data.set_if_neq(new) {
+                debug!("GTK render data changed");
+            }
+        }
+        None => commands.insert_resource(new),
+    }
+}
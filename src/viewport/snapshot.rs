@@ -0,0 +1,70 @@
+use {gdk::prelude::*, gtk::prelude::*};
+
+/// Key of the picture showing frames on the widget made by
+/// [`WidgetFactory::make`](crate::WidgetFactory::make).
+const PICTURE_KEY: &str = "bevy-gtk-viewport-picture";
+
+/// Marks `picture` as the picture which shows the frames of `widget`, so that
+/// we can take snapshots of them.
+pub(super) fn mark_picture(widget: &gtk::Widget, picture: &gtk::Picture) {
+    // SAFETY: this key is only ever used to store a `gtk::Picture`
+    unsafe {
+        widget.set_data(PICTURE_KEY, picture.clone());
+    }
+}
+
+/// Gets the picture which shows the frames of the viewport widget `widget`.
+pub(super) fn picture(widget: &gtk::Widget) -> Option<gtk::Picture> {
+    // SAFETY: this key is only ever used to store a `gtk::Picture`
+    let picture = unsafe { widget.data::<gtk::Picture>(PICTURE_KEY) }?;
+    // SAFETY: the data is valid for as long as the widget is
+    Some(unsafe { picture.as_ref() }.clone())
+}
+
+/// Copies the frame which a viewport widget is currently showing into a new
+/// [`gdk::Texture`].
+///
+/// `widget` must be a widget made by
+/// [`WidgetFactory::make`](crate::WidgetFactory::make). Use this for
+/// thumbnails, drag icons, or transitions between views, without asking
+/// Bevy for a screenshot. The texture is a copy, so it keeps showing this
+/// frame after Bevy renders the next one.
+///
+/// Returns [`None`] if `widget` isn't a viewport widget, or it hasn't shown a
+/// frame yet.
+///
+/// Must be called on the GTK thread.
+#[must_use]
+pub fn viewport_frame_texture(widget: &impl IsA<gtk::Widget>) -> Option<gdk::Texture> {
+    // SAFETY: this key is only ever used to store a `gtk::Picture`
+    let picture = unsafe { widget.as_ref().data::<gtk::Picture>(PICTURE_KEY) }?;
+    // SAFETY: the data is valid for as long as the widget is
+    copy_frame(unsafe { picture.as_ref() })
+}
+
+/// Copies the frame which `picture` is currently showing.
+pub(super) fn copy_frame(picture: &gtk::Picture) -> Option<gdk::Texture> {
+    let paintable = picture.paintable()?;
+    let texture = match paintable.downcast::<super::redraw::RedrawPaintable>() {
+        Ok(paintable) => paintable.texture()?,
+        Err(paintable) => paintable.downcast::<gdk::Texture>().ok()?,
+    };
+
+    // Bevy renders into the same dmabufs frame after frame,
+    // so the frame would change under the caller
+    if !texture.is::<gdk::DmabufTexture>() {
+        return Some(texture);
+    }
+    let downloader = gdk::TextureDownloader::new(&texture);
+    let (bytes, stride) = downloader.download_bytes();
+    Some(
+        gdk::MemoryTexture::new(
+            texture.width(),
+            texture.height(),
+            downloader.format(),
+            &bytes,
+            stride,
+        )
+        .upcast(),
+    )
+}
@@ -0,0 +1,194 @@
+use {
+    crate::{GtkApplication, GtkSystems},
+    alloc::rc::Rc,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_platform::collections::HashMap,
+    core::{
+        cell::{Cell, RefCell},
+        time::Duration,
+    },
+    gtk::{graphene, prelude::*},
+    log::debug,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<ViewportTransition>()
+        .add_systems(Last, start_transitions.after(GtkSystems::SyncWindows));
+}
+
+/// Send this event to crossfade a viewport from the frame that it's showing
+/// now, to whatever Bevy renders into it next.
+///
+/// Scene transitions, like switching which camera renders into a viewport,
+/// otherwise pop in instantly. Send this in the same update as you make the
+/// change: the GTK side copies the frame that it's currently showing, lays it
+/// over the viewport, and fades it out over [`ViewportTransition::duration`],
+/// while Bevy keeps rendering underneath it. Nothing is blended on the GPU.
+///
+/// Starting a transition while another one is running on the same viewport
+/// restarts it from the current frame.
+///
+/// This only applies to widgets made with
+/// [`WidgetFactory::make`](crate::WidgetFactory::make), not to a
+/// [`BevyPaintable`](crate::BevyPaintable).
+///
+/// # Examples
+///
+/// ```ignore
+/// fn switch_camera(
+///     viewport: Single<(Entity, &GtkViewport), With<MenuCamera>>,
+///     game_camera: Single<Entity, With<GameCamera>>,
+///     mut transitions: EventWriter<ViewportTransition>,
+///     mut commands: Commands,
+/// ) {
+///     let (menu_camera, viewport) = *viewport;
+///     transitions.write(ViewportTransition::crossfade(
+///         viewport.entity(),
+///         Duration::from_millis(300),
+///     ));
+///     commands.entity(menu_camera).remove::<GtkViewport>();
+///     // ...and give the game camera a viewport component
+/// }
+/// ```
+#[derive(Debug, Clone, Event)]
+pub struct ViewportTransition {
+    /// Entity of the viewport.
+    ///
+    /// See [`GtkViewport::entity`](crate::GtkViewport::entity).
+    pub viewport: Entity,
+    /// How long the old frame takes to fade out.
+    pub duration: Duration,
+}
+
+impl ViewportTransition {
+    /// Creates a crossfade of `viewport` which lasts for `duration`.
+    #[must_use]
+    pub const fn crossfade(viewport: Entity, duration: Duration) -> Self {
+        Self { viewport, duration }
+    }
+}
+
+thread_local! {
+    /// Overlays of the viewport widgets which are alive.
+    static OVERLAYS: RefCell<HashMap<Entity, TransitionOverlay>> =
+        RefCell::new(HashMap::default());
+}
+
+#[derive(Debug)]
+struct TransitionOverlay {
+    /// Picture which shows the frames of the viewport.
+    picture: glib::WeakRef<gtk::Picture>,
+    /// Picture laid over [`TransitionOverlay::picture`], which shows the
+    /// frame being faded out.
+    cover: glib::WeakRef<gtk::Picture>,
+    /// Tick callback of the transition which is running.
+    tick: Rc<RefCell<Option<gtk::TickCallbackId>>>,
+}
+
+/// Wraps `child` in an overlay which can show transitions over `picture`.
+pub(super) fn wrap(entity: Entity, child: &gtk::Widget, picture: &gtk::Picture) -> gtk::Widget {
+    let cover = gtk::Picture::builder()
+        .content_fit(gtk::ContentFit::Fill)
+        .can_target(false)
+        .visible(false)
+        .build();
+    let overlay = gtk::Overlay::builder()
+        .child(child)
+        .hexpand(true)
+        .vexpand(true)
+        .build();
+    overlay.add_overlay(&cover);
+
+    OVERLAYS.with_borrow_mut(|overlays| {
+        overlays.retain(|_, overlay| overlay.picture.upgrade().is_some());
+        overlays.insert(
+            entity,
+            TransitionOverlay {
+                picture: picture.downgrade(),
+                cover: cover.downgrade(),
+                tick: Rc::default(),
+            },
+        );
+    });
+    overlay.upcast()
+}
+
+fn start_transitions(
+    mut transitions: EventReader<ViewportTransition>,
+    _gtk_app: NonSend<GtkApplication>,
+) {
+    for transition in transitions.read() {
+        let entity = transition.viewport;
+        OVERLAYS.with_borrow(|overlays| {
+            let Some(overlay) = overlays.get(&entity) else {
+                debug!("Viewport {entity} has no widget to transition");
+                return;
+            };
+            let (Some(picture), Some(cover)) = (overlay.picture.upgrade(), overlay.cover.upgrade())
+            else {
+                return;
+            };
+            // nothing to fade out from
+            let Some(frame) = super::snapshot::copy_frame(&picture) else {
+                return;
+            };
+            if let Some(tick) = overlay.tick.take() {
+                tick.remove();
+            }
+
+            debug!(
+                "Starting {:?} transition of viewport {entity}",
+                transition.duration
+            );
+            cover.set_paintable(Some(&frame));
+            // the picture may only cover part of the viewport, e.g. its
+            // visible part
+            if let Some((overlay, bounds)) = cover.parent().and_then(|overlay| {
+                let bounds = picture.compute_bounds(&overlay)?;
+                Some((overlay, bounds))
+            }) {
+                place_cover(&cover, &overlay, &bounds);
+            }
+            cover.set_opacity(1.0);
+            cover.set_visible(true);
+            let duration_us = transition.duration.as_secs_f64() * 1_000_000.0;
+            let started_at = Cell::new(None::<i64>);
+            let tick = overlay.tick.clone();
+            overlay
+                .tick
+                .replace(Some(cover.add_tick_callback(move |cover, frame_clock| {
+                    let now = frame_clock.frame_time();
+                    let start = started_at.get().unwrap_or(now);
+                    started_at.set(Some(start));
+                    #[expect(clippy::cast_precision_loss, reason = "durations are short")]
+                    let t = ((now - start) as f64 / duration_us).clamp(0.0, 1.0);
+                    if t.is_nan() || t >= 1.0 {
+                        cover.set_visible(false);
+                        cover.set_paintable(None::<&gdk::Paintable>);
+                        tick.take();
+                        return glib::ControlFlow::Break;
+                    }
+                    // ease out, so the new frame comes in quickly
+                    cover.set_opacity(1.0 - t * (2.0 - t));
+                    glib::ControlFlow::Continue
+                })));
+        });
+    }
+}
+
+/// Places `cover` over `bounds` of `overlay`, which contains it.
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "widget sizes are relatively small"
+)]
+fn place_cover(cover: &gtk::Picture, overlay: &gtk::Widget, bounds: &graphene::Rect) {
+    let left = bounds.x().floor() as i32;
+    let top = bounds.y().floor() as i32;
+    let right = (bounds.x() + bounds.width()).ceil() as i32;
+    let bottom = (bounds.y() + bounds.height()).ceil() as i32;
+    cover.set_margin_start(left.max(0));
+    cover.set_margin_top(top.max(0));
+    cover.set_margin_end((overlay.width() - right).max(0));
+    cover.set_margin_bottom((overlay.height() - bottom).max(0));
+}
@@ -0,0 +1,81 @@
+use {
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    log::{debug, info},
+    std::env,
+};
+
+/// Environment variable which wgpu reads to enable the Vulkan validation
+/// layer.
+const VALIDATION_VAR: &str = "WGPU_VALIDATION";
+/// Environment variable which wgpu reads to enable GPU-assisted validation.
+const GPU_BASED_VALIDATION_VAR: &str = "WGPU_GPU_BASED_VALIDATION";
+/// Environment variable which wgpu reads to pass debug labels to the driver.
+const DEBUG_VAR: &str = "WGPU_DEBUG";
+
+/// Vulkan validation and debugging options for Bevy's render device.
+///
+/// Viewports hook into how Bevy creates its Vulkan instance and device, but
+/// the instance is still created with the flags in Bevy's
+/// [`WgpuSettings::instance_flags`], which are read from wgpu's environment
+/// variables. Insert this as a resource before adding [`GtkInitPlugin`] to
+/// set those variables from code instead, e.g. from a `--validate` CLI flag:
+///
+/// ```ignore
+/// App::new()
+///     .insert_resource(GtkVulkanValidation {
+///         validation: Some(true),
+///         ..default()
+///     })
+///     .add_plugins((GtkInitPlugin, DefaultPlugins, GtkPlugin::new(APP_ID)));
+/// ```
+///
+/// [`None`] leaves an option at wgpu's default, which enables validation in
+/// debug builds only. A variable which is already set in the environment
+/// always takes priority.
+///
+/// wgpu API traces can't be enabled this way, since Bevy always creates its
+/// device with tracing turned off.
+///
+/// [`WgpuSettings::instance_flags`]: bevy_render::settings::WgpuSettings::instance_flags
+/// [`GtkInitPlugin`]: crate::GtkInitPlugin
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Resource)]
+pub struct GtkVulkanValidation {
+    /// Whether to enable the `VK_LAYER_KHRONOS_validation` layer.
+    ///
+    /// If the layer supports `VK_EXT_validation_features`, wgpu also enables
+    /// synchronization validation, which catches missing barriers between
+    /// Bevy's rendering and the dmabufs that GTK reads from.
+    pub validation: Option<bool>,
+    /// Whether to enable GPU-assisted validation, which checks shader
+    /// accesses at runtime. This is very slow.
+    pub gpu_based_validation: Option<bool>,
+    /// Whether to pass debug labels to the driver, so that they show up in
+    /// validation messages and graphics debuggers, even in release builds.
+    pub debug_labels: Option<bool>,
+}
+
+pub(super) fn init_plugin(app: &mut App) {
+    let Some(validation) = app.world().get_resource::<GtkVulkanValidation>().copied() else {
+        return;
+    };
+
+    for (var, enabled) in [
+        (VALIDATION_VAR, validation.validation),
+        (GPU_BASED_VALIDATION_VAR, validation.gpu_based_validation),
+        (DEBUG_VAR, validation.debug_labels),
+    ] {
+        let Some(enabled) = enabled else {
+            continue;
+        };
+        if env::var_os(var).is_some() {
+            debug!("`{var}` is already set, not overriding it");
+            continue;
+        }
+
+        info!("Setting `{var}={}`", u8::from(enabled));
+        // SAFETY: this runs while `GtkInitPlugin` is built, before Bevy's
+        // plugins spawn any threads
+        unsafe { env::set_var(var, if enabled { "1" } else { "0" }) };
+    }
+}
@@ -0,0 +1,470 @@
+use {
+    super::{
+        AtomicSize, DmabufImport, DmabufImportPlane, ImportedDmabufTexture, ViewportErrorKind,
+        dmabuf,
+        error::{ViewportErrorChannel, ViewportHealth},
+    },
+    alloc::{collections::VecDeque, sync::Arc},
+    arrayvec::ArrayVec,
+    bevy_app::prelude::*,
+    bevy_asset::{Assets, Handle, RenderAssetUsages},
+    bevy_ecs::{error::BevyError, prelude::*, query::QueryItem, system::SystemParam},
+    bevy_image::Image,
+    bevy_render::{
+        Render, RenderApp, RenderSystems,
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_asset::RenderAssets,
+        render_resource::{Texture, TextureView},
+        renderer::{
+            RenderAdapter, RenderDevice, RenderQueue, raw_vulkan_init::AdditionalVulkanFeatures,
+        },
+        sync_world::SyncToRenderWorld,
+        texture::{DefaultImageSampler, GpuImage},
+    },
+    drm_fourcc::{DrmFormat, DrmFourcc, DrmModifier},
+    gst::prelude::*,
+    log::{debug, trace, warn},
+    std::{
+        fs::File,
+        os::{
+            fd::{BorrowedFd, OwnedFd},
+            unix::fs::MetadataExt,
+        },
+    },
+    wgpu::{Extent3d, TextureDimension, TextureFormat, TextureUsages, TextureViewDescriptor},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    if let Err(err) = gst::init() {
+        warn!("Failed to initialize GStreamer, video sinks will not work: {err}");
+    }
+
+    app.add_plugins(ExtractComponentPlugin::<RenderVideoSink>::default())
+        .add_systems(PostUpdate, (update_images, despawn_dropped_sinks));
+
+    let render_app = app
+        .get_sub_app_mut(RenderApp)
+        .expect("`viewport::plugin` checks that `RenderApp` exists");
+    render_app.add_systems(
+        Render,
+        (
+            import_frames.after(RenderSystems::ExtractCommands),
+            present_frames.after(RenderSystems::Render),
+        ),
+    );
+}
+
+/// Creates GStreamer video sinks which feed decoded frames into a Bevy
+/// [`Image`].
+///
+/// Frames are passed from GStreamer to Bevy as dmabufs, and imported with
+/// [`ImportedDmabufTexture`], so the video never leaves the GPU. Your pipeline
+/// must be able to produce `video/x-raw(memory:DMABuf)` buffers in the
+/// `DMA_DRM` format, e.g. by using a VA-API decoder. The sink only accepts
+/// RGB formats, with modifiers that the render device can import, so
+/// decoders which output YUV need a converter like `vapostproc` in front of
+/// the sink. Buffers are imported once, and reused when their buffer pool
+/// hands them out again.
+///
+/// If the render device can't import dmabufs, or a frame fails to import, the
+/// sink fails: a [`ViewportError`] with [`ViewportErrorKind::ImportDmabuf`] is
+/// emitted, and the sink stops accepting frames, which stops the pipeline with
+/// a flow error.
+///
+/// # Examples
+///
+/// ```ignore
+/// fn setup(mut video_sinks: GstVideoSinks, mut materials: ResMut<Assets<StandardMaterial>>) {
+///     let (image, sink) = video_sinks.create();
+///     let pipeline = gst::parse::launch("filesrc location=video.mp4 ! decodebin ! vapostproc")
+///         .unwrap();
+///     // link the last element of `pipeline` to `sink`, and start playing
+///     materials.add(StandardMaterial {
+///         base_color_texture: Some(image),
+///         ..default()
+///     });
+/// }
+/// ```
+///
+/// [`ViewportError`]: crate::ViewportError
+#[derive(SystemParam)]
+pub struct GstVideoSinks<'w, 's> {
+    images: ResMut<'w, Assets<Image>>,
+    errors: Res<'w, ViewportErrorChannel>,
+    render_adapter: Option<Res<'w, RenderAdapter>>,
+    caps: Local<'s, Option<gst::Caps>>,
+    commands: Commands<'w, 's>,
+}
+
+impl GstVideoSinks<'_, '_> {
+    /// Creates a video sink, exposing the Bevy [`Image`] which the video
+    /// frames are written to, and the [`gst_app::AppSink`] to add to your
+    /// pipeline.
+    ///
+    /// The image is updated every time the sink receives a new frame, and
+    /// lives for as long as the sink lives.
+    pub fn create(&mut self) -> (Handle<Image>, gst_app::AppSink) {
+        let image_handle = self.images.reserve_handle();
+        let (tx_frame, rx_frame) = async_channel::bounded(1);
+        let frame_size = Arc::new(AtomicSize::default());
+        let sink_alive = Arc::new(());
+        let entity = self.commands.spawn_empty().id();
+        let health = ViewportHealth::new(entity, self.errors.tx.clone());
+
+        self.commands.entity(entity).insert(VideoSinkPrivate {
+            image_handle: image_handle.clone(),
+            health: health.clone(),
+            rx_frame,
+            frame_size: frame_size.clone(),
+            sink_alive: sink_alive.clone(),
+            old_frame_size: (0, 0),
+        });
+
+        let caps = self
+            .caps
+            .get_or_insert_with(|| importable_caps(self.render_adapter.as_deref()))
+            .clone();
+        let sink = gst_app::AppSink::builder()
+            .caps(&caps)
+            .max_buffers(1)
+            .drop(true)
+            .build();
+        sink.set_callbacks(
+            gst_app::AppSinkCallbacks::builder()
+                .new_sample(move |sink| {
+                    // keeps the Bevy-side entity alive for as long as the sink
+                    let _ = &sink_alive;
+                    if health.is_broken() {
+                        return Err(gst::FlowError::NotSupported);
+                    }
+                    let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                    match sample_to_import(&sample) {
+                        Ok((import, key)) => {
+                            _ = tx_frame.force_send(VideoFrame {
+                                import,
+                                key,
+                                sample,
+                            });
+                            Ok(gst::FlowSuccess::Ok)
+                        }
+                        Err(err) => {
+                            health.fail(
+                                ViewportErrorKind::ImportDmabuf,
+                                format!("failed to read video frame as a dmabuf: {err}"),
+                            );
+                            Err(gst::FlowError::NotSupported)
+                        }
+                    }
+                })
+                .build(),
+        );
+
+        (image_handle, sink)
+    }
+}
+
+/// Creates caps for dmabufs in the DRM formats which the render device can
+/// import, so that upstream elements convert frames into one of them.
+fn importable_caps(render_adapter: Option<&RenderAdapter>) -> gst::Caps {
+    let mut drm_formats = render_adapter
+        .map(|render_adapter| dmabuf::importable_drm_formats(render_adapter))
+        .unwrap_or_default();
+    if drm_formats.is_empty() {
+        // we can't tell which modifiers the device supports, so ask for
+        // linear buffers, which are the most likely to import
+        drm_formats = dmabuf::IMPORTABLE_FOURCCS
+            .into_iter()
+            .map(|code| DrmFormat {
+                code,
+                modifier: DrmModifier::Linear,
+            })
+            .collect();
+    }
+    let drm_formats = drm_formats
+        .into_iter()
+        .map(|drm_format| {
+            gst_video::dma_drm_fourcc_to_string(drm_format.code as u32, drm_format.modifier.into())
+        })
+        .collect::<Vec<_>>();
+    gst::Caps::builder("video/x-raw")
+        .features([gst_allocators::CAPS_FEATURE_MEMORY_DMABUF])
+        .field("format", "DMA_DRM")
+        .field("drm-format", gst::List::new(drm_formats))
+        .build()
+}
+
+/// Most imported buffers which a sink keeps around for reuse.
+///
+/// GStreamer buffer pools hand out the same few dmabufs over and over, so
+/// this covers a whole pool in practice.
+const MAX_CACHED_IMPORTS: usize = 8;
+
+/// Identifies an imported dmabuf, along with the layout it was imported with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ImportKey {
+    /// Device and inode of the dmabuf, which stay the same across fds.
+    dev: u64,
+    ino: u64,
+    width: u32,
+    height: u32,
+    drm_format: DrmFormat,
+    planes: ArrayVec<DmabufImportPlane, 4>,
+}
+
+#[derive(Debug)]
+struct VideoFrame {
+    import: DmabufImport,
+    key: ImportKey,
+    /// Keeps the GStreamer buffer alive, so that the buffer pool doesn't reuse
+    /// the dmabuf while we're still sampling from it.
+    sample: gst::Sample,
+}
+
+#[derive(Debug, Component)]
+#[require(SyncToRenderWorld)]
+struct VideoSinkPrivate {
+    image_handle: Handle<Image>,
+    health: ViewportHealth,
+    rx_frame: async_channel::Receiver<VideoFrame>,
+    /// Size of the last frame imported in the render world.
+    frame_size: Arc<AtomicSize>,
+    /// Marks if the GStreamer-side sink is still alive.
+    sink_alive: Arc<()>,
+    old_frame_size: (u32, u32),
+}
+
+#[derive(Debug, Component)]
+struct RenderVideoSink {
+    image_handle: Handle<Image>,
+    health: ViewportHealth,
+    rx_frame: async_channel::Receiver<VideoFrame>,
+    frame_size: Arc<AtomicSize>,
+    /// Textures of the dmabufs which we've imported, oldest first.
+    imports: VecDeque<(ImportKey, Texture, TextureView)>,
+    /// Texture of the last imported frame, along with its sample to keep it
+    /// alive while the texture is in use.
+    current: Option<(Texture, TextureView, gst::Sample)>,
+    /// Sample of the frame before [`RenderVideoSink::current`].
+    ///
+    /// GPU work which was submitted before the current frame was imported may
+    /// still be sampling from it, so it's only released once that work is
+    /// done.
+    previous: Option<gst::Sample>,
+}
+
+impl ExtractComponent for RenderVideoSink {
+    type QueryData = &'static VideoSinkPrivate;
+    type QueryFilter = Added<VideoSinkPrivate>;
+    type Out = Self;
+
+    fn extract_component(sink: QueryItem<Self::QueryData>) -> Option<Self::Out> {
+        Some(Self {
+            image_handle: sink.image_handle.clone(),
+            health: sink.health.clone(),
+            rx_frame: sink.rx_frame.clone(),
+            frame_size: sink.frame_size.clone(),
+            imports: VecDeque::new(),
+            current: None,
+            previous: None,
+        })
+    }
+}
+
+fn sample_to_import(sample: &gst::Sample) -> Result<(DmabufImport, ImportKey), BevyError> {
+    let caps = sample.caps().ok_or("sample has no caps")?;
+    let info = gst_video::VideoInfoDmaDrm::from_caps(caps)?;
+    let buffer = sample.buffer().ok_or("sample has no buffer")?;
+    if buffer.n_memory() != 1 {
+        return Err(format!(
+            "buffer has {} memories, only single-memory buffers are supported",
+            buffer.n_memory()
+        )
+        .into());
+    }
+    let memory = buffer.peek_memory(0);
+    let dmabuf = memory
+        .downcast_memory_ref::<gst_allocators::DmaBufMemory>()
+        .ok_or("buffer memory is not a dmabuf")?;
+    // DMA_DRM buffers must carry a video meta describing their plane layout
+    let meta = buffer
+        .meta::<gst_video::VideoMeta>()
+        .ok_or("buffer has no video meta")?;
+
+    // SAFETY: `dmabuf` is alive for the duration of this function, and we
+    // immediately duplicate its fd so that we have our own copy
+    let fd = unsafe { BorrowedFd::borrow_raw(dmabuf.fd()) }.try_clone_to_owned()?;
+    let file = File::from(fd);
+    let metadata = file.metadata()?;
+    let fd = OwnedFd::from(file);
+    let code = DrmFourcc::try_from(info.fourcc())
+        .map_err(|_| format!("unrecognized DRM fourcc 0x{:08x}", info.fourcc()))?;
+
+    let planes = meta
+        .offset()
+        .iter()
+        .zip(meta.stride())
+        .map(|(&offset, &stride)| {
+            Ok(DmabufImportPlane {
+                offset: u32::try_from(memory.offset() + offset)
+                    .map_err(|_| "plane offset too large")?,
+                stride: u32::try_from(stride).map_err(|_| "plane stride is invalid")?,
+            })
+        })
+        .collect::<Result<ArrayVec<_, 4>, BevyError>>()?;
+
+    let import = DmabufImport {
+        fd,
+        width: meta.width(),
+        height: meta.height(),
+        drm_format: DrmFormat {
+            code,
+            modifier: DrmModifier::from(info.modifier()),
+        },
+        planes,
+    };
+    let key = ImportKey {
+        dev: metadata.dev(),
+        ino: metadata.ino(),
+        width: import.width,
+        height: import.height,
+        drm_format: import.drm_format,
+        planes: import.planes.clone(),
+    };
+    Ok((import, key))
+}
+
+// frame-to-frame logic, in the main world
+
+fn update_images(mut sinks: Query<&mut VideoSinkPrivate>, mut images: ResMut<Assets<Image>>) {
+    for mut sink in &mut sinks {
+        let (new_width, new_height) = sink.frame_size.load();
+        if (new_width, new_height) == sink.old_frame_size {
+            continue;
+        }
+        trace!(
+            "Video frame size changed to {new_width}x{new_height}, creating new main world image"
+        );
+        sink.old_frame_size = (new_width, new_height);
+
+        // like viewports, this image is only here for compatibility with
+        // `bevy_render` - the render world replaces its GPU image with the
+        // imported frame
+        let mut image = Image::new_uninit(
+            Extent3d {
+                width: new_width.max(1),
+                height: new_height.max(1),
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            TextureFormat::Rgba8Unorm,
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        );
+        image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST;
+        if let Err(err) = images.insert(&sink.image_handle, image) {
+            warn!("Failed to create video sink image: {err}");
+        }
+    }
+}
+
+fn despawn_dropped_sinks(sinks: Query<(Entity, &VideoSinkPrivate)>, mut commands: Commands) {
+    for (entity, sink) in &sinks {
+        if Arc::strong_count(&sink.sink_alive) == 1 {
+            debug!("Despawned video sink {entity} because its GStreamer sink was dropped");
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// frame-to-frame logic, in the render world
+
+fn import_frames(
+    mut sinks: Query<&mut RenderVideoSink>,
+    render_adapter: Res<RenderAdapter>,
+    render_device: Res<RenderDevice>,
+    default_image_sampler: Res<DefaultImageSampler>,
+    mut gpu_images: ResMut<RenderAssets<GpuImage>>,
+    vulkan_features: Option<Res<AdditionalVulkanFeatures>>,
+) {
+    let dmabuf_supported = dmabuf::dmabuf_supported(vulkan_features.as_deref());
+    for mut sink in &mut sinks {
+        if sink.health.is_broken() {
+            continue;
+        }
+
+        if let Ok(VideoFrame {
+            import,
+            key,
+            sample,
+        }) = sink.rx_frame.try_recv()
+        {
+            // without the dmabuf extensions, their functions aren't loaded
+            if !dmabuf_supported {
+                sink.health.fail(
+                    ViewportErrorKind::ImportDmabuf,
+                    "render device doesn't support importing dmabufs",
+                );
+                continue;
+            }
+
+            let cached = sink
+                .imports
+                .iter()
+                .find(|(other, _, _)| *other == key)
+                .map(|(_, texture, texture_view)| (texture.clone(), texture_view.clone()));
+            // the fd in `import` is closed if we don't import it
+            let (texture, texture_view) = if let Some(cached) = cached {
+                cached
+            } else {
+                let imported = match ImportedDmabufTexture::new(
+                    &render_adapter,
+                    render_device.wgpu_device(),
+                    import,
+                ) {
+                    Ok(imported) => imported,
+                    Err(err) => {
+                        sink.health.fail(
+                            ViewportErrorKind::ImportDmabuf,
+                            format!("failed to import video frame: {err}"),
+                        );
+                        continue;
+                    }
+                };
+                trace!("Imported new video frame dmabuf {key:?}");
+                let texture = Texture::from(imported.wgpu_texture().clone());
+                let texture_view = texture.create_view(&TextureViewDescriptor::default());
+                if sink.imports.len() >= MAX_CACHED_IMPORTS {
+                    sink.imports.pop_front();
+                }
+                sink.imports
+                    .push_back((key, texture.clone(), texture_view.clone()));
+                (texture, texture_view)
+            };
+
+            sink.frame_size.store(texture.width(), texture.height());
+            let previous = sink.current.replace((texture, texture_view, sample));
+            sink.previous = previous.map(|(_, _, sample)| sample);
+        }
+
+        if let Some((texture, texture_view, _)) = &sink.current {
+            let gpu_image = GpuImage {
+                texture: texture.clone(),
+                texture_view: texture_view.clone(),
+                texture_format: texture.format(),
+                sampler: (**default_image_sampler).clone(),
+                size: texture.size(),
+                mip_level_count: 1,
+            };
+            gpu_images.insert(&sink.image_handle, gpu_image);
+        }
+    }
+}
+
+fn present_frames(mut sinks: Query<&mut RenderVideoSink>, render_queue: Res<RenderQueue>) {
+    for mut sink in &mut sinks {
+        if let Some(previous) = sink.previous.take() {
+            // this frame's work no longer samples from the previous frame,
+            // but work from earlier frames may still be running
+            render_queue.on_submitted_work_done(move || drop(previous));
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use {
+    super::{GtkViewport, ViewportPrivate},
+    bevy_camera::{Camera, SubCameraView},
+    bevy_ecs::prelude::*,
+    bevy_math::{URect, UVec2, Vec2},
+    core::sync::atomic,
+    gtk::{graphene, prelude::*, subclass::prelude::*},
+    std::sync::{Mutex, PoisonError},
+};
+
+/// Part of a viewport widget which is visible on screen, in physical pixels
+/// relative to the widget's origin.
+///
+/// [`None`] if the widget isn't visible at all, e.g. because it's scrolled out
+/// of view or unmapped.
+#[derive(Debug, Default)]
+pub(super) struct VisibleRect(Mutex<Option<URect>>);
+
+impl VisibleRect {
+    pub fn load(&self) -> Option<URect> {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn store(&self, rect: Option<URect>) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = rect;
+    }
+}
+
+/// Gets the part of `widget` which isn't clipped away by its ancestors, in
+/// logical pixels relative to the widget's origin.
+///
+/// Ancestors with [`gtk::Overflow::Hidden`] clip their children, which
+/// includes [`gtk::ScrolledWindow`] and [`gtk::Viewport`]. The window itself
+/// clips everything to its bounds.
+pub(super) fn visible_bounds(widget: &gtk::Widget) -> Option<graphene::Rect> {
+    if !widget.is_mapped() {
+        return None;
+    }
+    #[expect(clippy::cast_precision_loss, reason = "widget sizes are small")]
+    let mut bounds = graphene::Rect::new(0.0, 0.0, widget.width() as f32, widget.height() as f32);
+    let mut ancestor = widget.parent();
+    while let Some(widget_ancestor) = ancestor {
+        let clips = widget_ancestor.overflow() == gtk::Overflow::Hidden
+            || widget_ancestor.is::<gtk::ScrolledWindow>()
+            || widget_ancestor.is::<gtk::Native>();
+        if clips {
+            let clip = widget_ancestor.compute_bounds(widget)?;
+            bounds = bounds.intersection(&clip)?;
+        }
+        ancestor = widget_ancestor.parent();
+    }
+    (bounds.width() > 0.0 && bounds.height() > 0.0).then_some(bounds)
+}
+
+/// Converts logical `bounds` relative to a widget into physical pixels.
+#[expect(
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation,
+    reason = "bounds are clamped to be non-negative, and widget sizes are relatively small"
+)]
+pub(super) fn to_physical(bounds: &graphene::Rect, scale: f64) -> URect {
+    let min_x = (f64::from(bounds.x()) * scale).floor().max(0.0) as u32;
+    let min_y = (f64::from(bounds.y()) * scale).floor().max(0.0) as u32;
+    let max_x = (f64::from(bounds.x() + bounds.width()) * scale)
+        .ceil()
+        .max(0.0) as u32;
+    let max_y = (f64::from(bounds.y() + bounds.height()) * scale)
+        .ceil()
+        .max(0.0) as u32;
+    URect::new(min_x, min_y, max_x, max_y)
+}
+
+/// Makes cameras of viewports which only render their visible region render
+/// the part of the full view which that region covers.
+pub(super) fn sync_sub_views(
+    viewports: Query<&ViewportPrivate>,
+    mut cameras: Query<(&GtkViewport, &mut Camera)>,
+) {
+    for (gtk_viewport, mut camera) in &mut cameras {
+        let Ok(viewport) = viewports.get(gtk_viewport.entity()) else {
+            continue;
+        };
+        if !viewport.render_visible_only {
+            continue;
+        }
+        let (image_width, image_height) = viewport.old_widget_size;
+        if (image_width, image_height) == (u32::MAX, u32::MAX) {
+            continue;
+        }
+
+        let render_scale = viewport.render_scale.load(atomic::Ordering::SeqCst);
+        let (widget_width, widget_height) = viewport.widget_size.load();
+        let full_size = UVec2::new(
+            super::scale_length(widget_width, render_scale),
+            super::scale_length(widget_height, render_scale),
+        )
+        .max(UVec2::ONE);
+        let size = UVec2::new(image_width, image_height)
+            .max(UVec2::ONE)
+            .min(full_size);
+        let offset = viewport
+            .visible_rect
+            .load()
+            .map_or(Vec2::ZERO, |rect| rect.min.as_vec2() * render_scale);
+        // the image may lag behind the visible region while it's resized,
+        // so keep the sub view inside of the full view
+        let offset = offset.min((full_size - size).as_vec2()).max(Vec2::ZERO);
+
+        let sub_view = Some(SubCameraView {
+            full_size,
+            offset,
+            size,
+        });
+        if camera.sub_camera_view != sub_view {
+            camera.sub_camera_view = sub_view;
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Widget with a single child, which is placed over the part of this
+    /// widget that is visible on screen.
+    ///
+    /// See [`ViewportConfig::render_visible_only`](super::ViewportConfig::render_visible_only).
+    pub(super) struct VisibleBin(ObjectSubclass<imp::VisibleBin>)
+        @extends gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl VisibleBin {
+    pub fn new(child: &impl IsA<gtk::Widget>) -> Self {
+        let widget = glib::Object::builder::<Self>()
+            .property("hexpand", true)
+            .property("vexpand", true)
+            .build();
+        child.set_parent(&widget);
+        widget.imp().child.replace(Some(child.clone().upcast()));
+        widget
+    }
+
+    /// Places the child over `bounds`, in logical pixels relative to this
+    /// widget, as returned by [`visible_bounds`].
+    ///
+    /// This only reallocates the child if its placement actually changes.
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "widget sizes are relatively small"
+    )]
+    pub fn set_visible_bounds(&self, bounds: &graphene::Rect) {
+        let new = (
+            bounds.x().floor() as i32,
+            bounds.y().floor() as i32,
+            (bounds.x() + bounds.width()).ceil() as i32,
+            (bounds.y() + bounds.height()).ceil() as i32,
+        );
+        if self.imp().bounds.replace(Some(new)) != Some(new) {
+            self.queue_allocate();
+        }
+    }
+}
+
+mod imp {
+    use {
+        super::*,
+        core::cell::{Cell, RefCell},
+        gtk::{gsk, subclass::prelude::*},
+    };
+
+    #[derive(Debug, Default)]
+    pub struct VisibleBin {
+        pub(super) child: RefCell<Option<gtk::Widget>>,
+        /// Visible part of the widget, as `(left, top, right, bottom)`.
+        pub(super) bounds: Cell<Option<(i32, i32, i32, i32)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for VisibleBin {
+        const NAME: &'static str = "BevyGtkVisibleBin";
+        type Type = super::VisibleBin;
+        type ParentType = gtk::Widget;
+    }
+
+    impl ObjectImpl for VisibleBin {
+        fn dispose(&self) {
+            if let Some(child) = self.child.take() {
+                child.unparent();
+            }
+        }
+    }
+
+    impl WidgetImpl for VisibleBin {
+        fn measure(&self, orientation: gtk::Orientation, for_size: i32) -> (i32, i32, i32, i32) {
+            self.child
+                .borrow()
+                .as_ref()
+                .map_or((0, 0, -1, -1), |child| child.measure(orientation, for_size))
+        }
+
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "widget sizes are relatively small"
+        )]
+        fn size_allocate(&self, width: i32, height: i32, baseline: i32) {
+            let Some(child) = &*self.child.borrow() else {
+                return;
+            };
+            let Some((left, top, right, bottom)) = self.bounds.get() else {
+                child.allocate(width, height, baseline, None);
+                return;
+            };
+            let (left, top) = (left.clamp(0, width), top.clamp(0, height));
+            let (right, bottom) = (right.clamp(left, width), bottom.clamp(top, height));
+            // translating the child is cheaper than changing its margins,
+            // which would also make GTK measure it again
+            let transform =
+                gsk::Transform::new().translate(&graphene::Point::new(left as f32, top as f32));
+            child.allocate(right - left, bottom - top, -1, Some(transform));
+        }
+    }
+}
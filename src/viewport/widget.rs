@@ -0,0 +1,251 @@
+use {
+    super::WidgetFactory,
+    alloc::borrow::Cow,
+    bevy_ecs::entity::Entity,
+    bevy_platform::collections::HashMap,
+    core::cell::RefCell,
+    gtk::{prelude::*, subclass::prelude::*},
+    log::debug,
+};
+
+glib::wrapper! {
+    /// [`gtk::Widget`] which displays a Bevy viewport, and can be placed in
+    /// [`gtk::Builder`] UI definitions.
+    ///
+    /// Place this widget in your UI file with an `id`:
+    ///
+    /// ```xml
+    /// <object class="BevyGtkViewport" id="game_view"/>
+    /// ```
+    ///
+    /// or in Blueprint:
+    ///
+    /// ```blp
+    /// $BevyGtkViewport game_view {}
+    /// ```
+    ///
+    /// Then, on the Bevy side, use [`GtkViewports::create_for_widget`] with the
+    /// same ID. Once both the widget and the Bevy viewport exist, the viewport
+    /// is made with [`WidgetFactory::make`] and placed inside this widget. It
+    /// doesn't matter which one is created first.
+    ///
+    /// The type is registered when [`GtkPlugin`] is built, so it is available
+    /// to any builder created after that point.
+    ///
+    /// Once bound, the read-only `viewport-entity` property holds the
+    /// [entity](crate::GtkViewport::entity) of the viewport, formatted the
+    /// same way as in Bevy's logs, so that you can correlate diagnostics from
+    /// GTK tools like the inspector with the Bevy side.
+    ///
+    /// [`GtkViewports::create_for_widget`]: crate::GtkViewports::create_for_widget
+    /// [`GtkPlugin`]: crate::GtkPlugin
+    pub struct BevyGtkViewport(ObjectSubclass<imp::BevyGtkViewport>)
+        @extends gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+thread_local! {
+    /// Factories created on the Bevy side which haven't found a widget yet.
+    static UNBOUND_FACTORIES: RefCell<HashMap<Cow<'static, str>, WidgetFactory>> =
+        RefCell::new(HashMap::default());
+    /// Widgets which have been realized, but haven't found a factory yet.
+    static UNBOUND_WIDGETS: RefCell<Vec<glib::WeakRef<BevyGtkViewport>>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+impl BevyGtkViewport {
+    /// Creates an empty viewport widget, which will be bound to a viewport
+    /// created with [`GtkViewports::create_for_widget`] with the same ID.
+    ///
+    /// [`GtkViewports::create_for_widget`]: crate::GtkViewports::create_for_widget
+    #[must_use]
+    pub fn new(id: &str) -> Self {
+        let widget = glib::Object::new::<Self>();
+        widget.imp().id.replace(Some(id.into()));
+        widget
+    }
+
+    /// Gets the ID that this widget is bound by.
+    ///
+    /// This is the ID passed to [`BevyGtkViewport::new`], or the ID set in the
+    /// UI definition.
+    #[must_use]
+    pub fn id(&self) -> Option<glib::GString> {
+        self.imp()
+            .id
+            .borrow()
+            .clone()
+            .or_else(|| self.buildable_id())
+    }
+
+    /// Whether a Bevy viewport has been placed in this widget yet.
+    #[must_use]
+    pub fn is_bound(&self) -> bool {
+        self.imp().child.borrow().is_some()
+    }
+
+    /// Entity of the viewport placed in this widget, if it has been bound.
+    ///
+    /// See [`GtkViewport::entity`](crate::GtkViewport::entity).
+    #[must_use]
+    pub fn entity(&self) -> Option<Entity> {
+        self.imp().entity.get()
+    }
+
+    /// Copies the frame which this widget is currently showing into a new
+    /// [`gdk::Texture`], if it has been bound and has shown a frame.
+    ///
+    /// See [`viewport_frame_texture`](crate::viewport_frame_texture).
+    #[must_use]
+    pub fn frame_texture(&self) -> Option<gdk::Texture> {
+        self.imp()
+            .child
+            .borrow()
+            .as_ref()
+            .and_then(super::viewport_frame_texture)
+    }
+
+    fn bind(&self, factory: WidgetFactory) {
+        let entity = factory.entity();
+        debug!("Bound viewport widget {:?} to viewport {entity}", self.id());
+        let health = factory.health.clone();
+        let child = factory.make();
+        child.set_parent(self);
+        self.imp().child.replace(Some(child));
+        self.imp().entity.set(Some(entity));
+        self.notify("viewport-entity");
+
+        // the viewport may be destroyed from the Bevy side,
+        // after which there's nothing left to display
+        self.add_tick_callback(move |widget, _| {
+            if !health.is_destroyed() {
+                return glib::ControlFlow::Continue;
+            }
+            debug!(
+                "Unbound viewport widget {:?} from destroyed viewport {entity}",
+                widget.id()
+            );
+            if let Some(child) = widget.imp().child.take() {
+                child.unparent();
+            }
+            widget.imp().entity.set(None);
+            widget.notify("viewport-entity");
+            glib::ControlFlow::Break
+        });
+    }
+
+    fn try_bind(&self) {
+        if self.is_bound() {
+            return;
+        }
+        let Some(id) = self.id() else {
+            return;
+        };
+
+        let factory = UNBOUND_FACTORIES.with_borrow_mut(|factories| factories.remove(id.as_str()));
+        if let Some(factory) = factory {
+            self.bind(factory);
+        } else {
+            UNBOUND_WIDGETS.with_borrow_mut(|widgets| widgets.push(self.downgrade()));
+        }
+    }
+}
+
+/// Binds `factory` to the widget with ID `id`, or stores it until that widget
+/// is realized.
+///
+/// Must be called on the GTK thread.
+pub(super) fn bind_factory(id: Cow<'static, str>, factory: WidgetFactory) {
+    let widget = UNBOUND_WIDGETS.with_borrow_mut(|widgets| {
+        widgets.retain(|widget| widget.upgrade().is_some_and(|widget| !widget.is_bound()));
+        widgets
+            .iter()
+            .filter_map(glib::WeakRef::upgrade)
+            .find(|widget| widget.id().as_deref() == Some(&*id))
+    });
+
+    if let Some(widget) = widget {
+        widget.bind(factory);
+    } else {
+        UNBOUND_FACTORIES.with_borrow_mut(|factories| {
+            factories.insert(id, factory);
+        });
+    }
+}
+
+/// Drops the factories of `viewports` which are still waiting for their
+/// widget, since those viewports have been despawned.
+///
+/// Must be called on the GTK thread.
+pub(super) fn drop_unbound_factories(viewports: &[Entity]) {
+    UNBOUND_FACTORIES.with_borrow_mut(|factories| {
+        factories.retain(|id, factory| {
+            let despawned = viewports.contains(&factory.entity());
+            if despawned {
+                debug!("Dropped factory for widget {id:?}, since its viewport was despawned");
+            }
+            !despawned
+        });
+    });
+}
+
+mod imp {
+    use {super::*, core::cell::Cell, std::sync::OnceLock};
+
+    #[derive(Debug, Default)]
+    pub struct BevyGtkViewport {
+        pub(super) id: RefCell<Option<glib::GString>>,
+        pub(super) child: RefCell<Option<gtk::Widget>>,
+        pub(super) entity: Cell<Option<Entity>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for BevyGtkViewport {
+        const NAME: &'static str = "BevyGtkViewport";
+        type Type = super::BevyGtkViewport;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+        }
+    }
+
+    impl ObjectImpl for BevyGtkViewport {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: OnceLock<Vec<glib::ParamSpec>> = OnceLock::new();
+            PROPERTIES.get_or_init(|| {
+                vec![
+                    glib::ParamSpecString::builder("viewport-entity")
+                        .read_only()
+                        .build(),
+                ]
+            })
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "viewport-entity" => self
+                    .entity
+                    .get()
+                    .map(|entity| entity.to_string())
+                    .to_value(),
+                name => unreachable!("unknown property `{name}`"),
+            }
+        }
+
+        fn dispose(&self) {
+            if let Some(child) = self.child.take() {
+                child.unparent();
+            }
+        }
+    }
+
+    impl WidgetImpl for BevyGtkViewport {
+        fn realize(&self) {
+            self.parent_realize();
+            // the buildable ID is only set after construction,
+            // so we wait until we're realized to look for our factory
+            self.obj().try_bind();
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use {
+    crate::{GtkCommands, GtkContext},
+    alloc::borrow::Cow,
+    bevy_ecs::system::SystemParam,
+    bevy_platform::collections::HashMap,
+    core::cell::RefCell,
+    gtk::prelude::*,
+};
+
+thread_local! {
+    /// Widgets registered with [`register_widget`], by key.
+    static WIDGETS: RefCell<HashMap<Cow<'static, str>, glib::WeakRef<gtk::Widget>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Registers `widget` under `key`, so that Bevy systems can find it later
+/// through [`GtkWidgets`] or [`GtkContext::widget`].
+///
+/// Call this when building widgets in a [`MakeWidget`] closure. Only a weak
+/// reference to the widget is kept, so this does not keep the widget alive.
+/// If a widget is already registered under `key`, it's replaced.
+///
+/// Must be called on the GTK thread.
+///
+/// [`MakeWidget`]: crate::MakeWidget
+pub fn register_widget(key: impl Into<Cow<'static, str>>, widget: &impl IsA<gtk::Widget>) {
+    let widget = widget.upcast_ref::<gtk::Widget>();
+    WIDGETS.with_borrow_mut(|widgets| {
+        widgets.retain(|_, widget| widget.upgrade().is_some());
+        widgets.insert(key.into(), widget.downgrade());
+    });
+}
+
+/// Removes the widget registered under `key`, if any.
+///
+/// Must be called on the GTK thread.
+pub fn unregister_widget(key: &str) {
+    WIDGETS.with_borrow_mut(|widgets| widgets.remove(key));
+}
+
+impl GtkContext<'_> {
+    /// Gets the widget registered under `key` with [`register_widget`].
+    ///
+    /// Returns [`None`] if no widget is registered under `key`, it has been
+    /// destroyed, or it is not a `W`.
+    #[must_use]
+    pub fn widget<W: IsA<gtk::Widget>>(&self, key: &str) -> Option<W> {
+        WIDGETS
+            .with_borrow(|widgets| widgets.get(key).and_then(glib::WeakRef::upgrade))
+            .and_then(|widget| widget.downcast::<W>().ok())
+    }
+}
+
+/// Queues changes to widgets which were registered with [`register_widget`].
+///
+/// This is a shorthand for queueing a [`GtkCommand`] which looks up the
+/// widget with [`GtkContext::widget`].
+///
+/// # Examples
+///
+/// ```ignore
+/// fn setup_window(mut commands: Commands) {
+///     commands.spawn((
+///         Window::default(),
+///         GtkWindowContent::from(|| {
+///             let label = gtk::Label::new(None);
+///             register_widget("score", &label);
+///             label
+///         }),
+///     ));
+/// }
+///
+/// fn show_score(score: Res<Score>, mut widgets: GtkWidgets) {
+///     let text = format!("Score: {}", score.0);
+///     widgets.with("score", move |label: &gtk::Label| label.set_text(&text));
+/// }
+/// ```
+///
+/// [`GtkCommand`]: crate::GtkCommand
+#[derive(SystemParam)]
+pub struct GtkWidgets<'w> {
+    gtk_commands: GtkCommands<'w>,
+}
+
+impl GtkWidgets<'_> {
+    /// Queues `f` to run on the GTK thread with the widget registered under
+    /// `key`.
+    ///
+    /// If there is no such widget of type `W` by the time the command runs,
+    /// `f` is not called.
+    pub fn with<W: IsA<gtk::Widget>>(
+        &mut self,
+        key: impl Into<Cow<'static, str>>,
+        f: impl FnOnce(&W) + Send + 'static,
+    ) {
+        let key = key.into();
+        self.gtk_commands.queue(move |ctx: &mut GtkContext| {
+            if let Some(widget) = ctx.widget::<W>(&key) {
+                f(&widget);
+            }
+        });
+    }
+}
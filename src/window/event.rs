@@ -1,10 +1,10 @@
-#[cfg(feature = "adwaita")]
-use bevy_window::{WindowTheme, WindowThemeChanged};
 use {
     crate::GtkWindows,
     bevy_app::prelude::*,
     bevy_ecs::prelude::*,
-    bevy_window::{WindowEvent, WindowScaleFactorChanged, prelude::*},
+    bevy_window::{
+        WindowEvent, WindowScaleFactorChanged, WindowTheme, WindowThemeChanged, prelude::*,
+    },
     glib::clone,
     gtk::prelude::*,
 };
@@ -65,18 +65,28 @@ fn setup_event_forwarding(
             }
         ));
 
-        adw::StyleManager::default().connect_dark_notify(clone!(
-            #[strong]
-            tx_event,
-            move |style_manager| {
-                let theme = if style_manager.is_dark() {
-                    WindowTheme::Dark
-                } else {
-                    WindowTheme::Light
-                };
-                send_event(&tx_event, WindowThemeChanged { window, theme }.into());
-            }
-        ));
+        let send_theme = move |dark: bool| {
+            let theme = if dark {
+                WindowTheme::Dark
+            } else {
+                WindowTheme::Light
+            };
+            send_event(&tx_event, WindowThemeChanged { window, theme }.into());
+        };
+        if_adw!(
+            gtk_windows.use_adw(),
+            {
+                adw::StyleManager::default()
+                    .connect_dark_notify(move |style_manager| send_theme(style_manager.is_dark()));
+            },
+            {
+                if let Some(settings) = gtk::Settings::default() {
+                    settings.connect_gtk_application_prefer_dark_theme_notify(move |settings| {
+                        send_theme(settings.is_gtk_application_prefer_dark_theme());
+                    });
+                }
+            },
+        );
     }
 }
 
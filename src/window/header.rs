@@ -0,0 +1,177 @@
+use {
+    super::{GtkWindows, MakeWidget},
+    bevy_ecs::prelude::*,
+    bevy_window::Window,
+    core::mem,
+    gtk::prelude::*,
+};
+
+/// Widgets to add to the header bar of a window, next to the window controls,
+/// e.g. toolbar buttons, menu buttons, or a search entry.
+///
+/// Like [`GtkWindowContent`], the widgets are made once, on the GTK thread,
+/// and this component is removed afterwards. They're kept when the title bar
+/// is rebuilt because the [`Window`]'s title bar settings changed.
+///
+/// The title widget replaces the window title, and is only shown if the title
+/// bar is opaque and [`Window::titlebar_show_title`] is enabled. This has no
+/// effect on windows created through [`GtkAdoptedWindow`], since we don't
+/// manage their widget tree.
+///
+/// # Examples
+///
+/// ```ignore
+/// commands.spawn((
+///     Window::default(),
+///     GtkWindowContent::from(|| viewport_widget.make()),
+///     GtkHeaderBarContent::new()
+///         .with_start(|| gtk::Button::from_icon_name("document-open-symbolic"))
+///         .with_end(|| gtk::MenuButton::builder().icon_name("open-menu-symbolic").build())
+///         .with_title(gtk::SearchEntry::new),
+/// ));
+/// ```
+///
+/// [`GtkWindowContent`]: super::GtkWindowContent
+/// [`GtkAdoptedWindow`]: super::GtkAdoptedWindow
+#[derive(Default, Component)]
+pub struct GtkHeaderBarContent {
+    /// Widgets packed at the start of the header bar, in order.
+    pub start: Vec<Box<dyn MakeWidget>>,
+    /// Widgets packed at the end of the header bar, in order from the end.
+    pub end: Vec<Box<dyn MakeWidget>>,
+    /// Widget which replaces the window title.
+    pub title: Option<Box<dyn MakeWidget>>,
+}
+
+impl GtkHeaderBarContent {
+    /// Creates an empty header bar content.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a widget to [`GtkHeaderBarContent::start`].
+    #[must_use]
+    pub fn with_start(mut self, widget: impl MakeWidget) -> Self {
+        self.start.push(Box::new(widget));
+        self
+    }
+
+    /// Adds a widget to [`GtkHeaderBarContent::end`].
+    #[must_use]
+    pub fn with_end(mut self, widget: impl MakeWidget) -> Self {
+        self.end.push(Box::new(widget));
+        self
+    }
+
+    /// Sets [`GtkHeaderBarContent::title`].
+    #[must_use]
+    pub fn with_title(self, widget: impl MakeWidget) -> Self {
+        Self {
+            title: Some(Box::new(widget)),
+            ..self
+        }
+    }
+}
+
+/// Widgets made from a [`GtkHeaderBarContent`].
+#[derive(Debug)]
+pub(super) struct HeaderWidgets {
+    pub start: gtk::Box,
+    pub end: gtk::Box,
+    pub title: Option<gtk::Widget>,
+}
+
+impl HeaderWidgets {
+    fn make(content: GtkHeaderBarContent) -> Self {
+        let start = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        for make_widget in content.start {
+            start.append(&make_widget.make());
+        }
+        let end = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        for make_widget in content.end {
+            end.prepend(&make_widget.make());
+        }
+        Self {
+            start,
+            end,
+            title: content.title.map(|make_widget| make_widget.make()),
+        }
+    }
+
+    /// Removes the widgets from the title bar they're currently in, so that
+    /// they can be added to a new one.
+    pub fn detach(&self) {
+        for widget in [
+            self.start.upcast_ref::<gtk::Widget>(),
+            self.end.upcast_ref(),
+        ]
+        .into_iter()
+        .chain(self.title.as_ref())
+        {
+            detach(widget);
+        }
+    }
+
+    /// Builds a GTK header bar holding these widgets, for windows which don't
+    /// use Adwaita.
+    pub fn gtk_header_bar(&self, config: &Window) -> gtk::HeaderBar {
+        let header = gtk::HeaderBar::new();
+        header.pack_start(&self.start);
+        header.pack_end(&self.end);
+        if config.titlebar_show_title {
+            header.set_title_widget(self.title.as_ref());
+        } else {
+            header.set_title_widget(Some(&gtk::Label::new(None)));
+        }
+        header.set_show_title_buttons(config.titlebar_show_buttons);
+        header
+    }
+}
+
+fn detach(widget: &gtk::Widget) {
+    #[cfg(feature = "adwaita")]
+    if let Some(header) = widget
+        .ancestor(adw::HeaderBar::static_type())
+        .and_downcast::<adw::HeaderBar>()
+    {
+        header.remove(widget);
+        return;
+    }
+    if let Some(header) = widget
+        .ancestor(gtk::HeaderBar::static_type())
+        .and_downcast::<gtk::HeaderBar>()
+    {
+        header.remove(widget);
+        return;
+    }
+    if let Some(parent) = widget.parent().and_downcast::<gtk::Box>() {
+        parent.remove(widget);
+    }
+}
+
+pub(super) fn sync_new_header_content(
+    mut commands: Commands,
+    mut changed_windows: Query<
+        (Entity, &Window, &mut GtkHeaderBarContent),
+        Changed<GtkHeaderBarContent>,
+    >,
+    mut gtk_windows: NonSendMut<GtkWindows>,
+) {
+    let gtk_windows = &mut *gtk_windows;
+    for (entity, bevy_window, mut content) in &mut changed_windows {
+        let Some(proxy) = gtk_windows.entity_to_proxy.get_mut(&entity) else {
+            continue;
+        };
+        commands.entity(entity).remove::<GtkHeaderBarContent>();
+        if proxy.adopted {
+            continue;
+        }
+
+        if let Some(old) = &proxy.header_widgets {
+            old.detach();
+        }
+        proxy.header_widgets = Some(HeaderWidgets::make(mem::take(&mut *content)));
+        super::rebuild_widgets(gtk_windows.use_adw, bevy_window, proxy);
+    }
+}
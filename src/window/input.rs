@@ -0,0 +1,213 @@
+use {
+    super::GtkWindows,
+    crate::GtkSystems,
+    alloc::rc::Rc,
+    bevy_app::prelude::*,
+    bevy_ecs::prelude::*,
+    bevy_window::CursorOptions,
+    core::{
+        cell::{Cell, RefCell},
+        ptr,
+    },
+    glib::{clone, translate::ToGlibPtr},
+    gtk::{cairo, prelude::*},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Last,
+        sync_input_regions
+            .after(super::sync_window_config)
+            .in_set(GtkSystems::SyncWindows),
+    );
+}
+
+thread_local! {
+    /// Widgets which let pointer input pass through to whatever is behind
+    /// their window.
+    static PASSTHROUGH_WIDGETS: RefCell<Vec<glib::WeakRef<gtk::Widget>>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+/// Makes pointer input over `widget` pass through its window, to whatever is
+/// behind the window on the desktop.
+///
+/// This cuts the widget's bounds out of its window's input region, which is
+/// kept up to date as the widget moves and resizes. The rest of the window
+/// still receives input as normal. To make an entire window click-through,
+/// set [`CursorOptions::hit_test`] to `false` instead.
+///
+/// Must be called on the GTK thread.
+pub fn set_input_passthrough(widget: &impl IsA<gtk::Widget>, passthrough: bool) {
+    let widget = widget.upcast_ref::<gtk::Widget>();
+    PASSTHROUGH_WIDGETS.with_borrow_mut(|widgets| {
+        widgets.retain(|other| other.upgrade().is_some_and(|other| other != *widget));
+        if passthrough {
+            widgets.push(widget.downgrade());
+        }
+    });
+}
+
+/// Input region of a window's surface, in surface coordinates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InputRegion {
+    /// Size of the surface, if the surface accepts input outside of `holes`.
+    full: Option<(i32, i32)>,
+    /// Rectangles which input passes through, as `(x, y, width, height)`.
+    holes: Vec<(i32, i32, i32, i32)>,
+}
+
+impl InputRegion {
+    fn to_cairo(&self) -> cairo::Region {
+        let Some((width, height)) = self.full else {
+            return cairo::Region::create();
+        };
+        let region =
+            cairo::Region::create_rectangle(&cairo::RectangleInt::new(0, 0, width, height));
+        for &(x, y, width, height) in &self.holes {
+            _ = region.subtract_rectangle(&cairo::RectangleInt::new(x, y, width, height));
+        }
+        region
+    }
+}
+
+fn sync_input_regions(gtk_windows: NonSend<GtkWindows>, cursor_options: Query<&CursorOptions>) {
+    for (entity, proxy) in &gtk_windows.entity_to_proxy {
+        let hit_test = cursor_options
+            .get(*entity)
+            .ok()
+            .is_none_or(|cursor_options| cursor_options.hit_test);
+        proxy.input.hit_test.set(hit_test);
+        if let Some(surface) = proxy.gtk_window.surface() {
+            proxy.input.update(&proxy.gtk_window, &surface);
+        }
+    }
+}
+
+/// Input region state of a window, shared with the handlers which re-apply
+/// the region after GTK draws the window.
+#[derive(Debug)]
+pub(super) struct InputState {
+    hit_test: Cell<bool>,
+    /// Region we last set on the surface, or [`None`] if GTK manages it.
+    region: RefCell<Option<InputRegion>>,
+}
+
+impl InputState {
+    pub(super) fn new(gtk_window: &gtk::ApplicationWindow) -> Rc<Self> {
+        let state = Rc::new(Self {
+            hit_test: Cell::new(true),
+            region: RefCell::new(None),
+        });
+        if let Some(surface) = gtk_window.surface() {
+            state.watch_frames(gtk_window, &surface);
+        }
+        gtk_window.connect_realize(clone!(
+            #[weak]
+            state,
+            move |gtk_window| {
+                if let Some(surface) = gtk_window.surface() {
+                    state.watch_frames(gtk_window, &surface);
+                }
+            }
+        ));
+        state
+    }
+
+    /// GTK sets the input region of client-side decorated windows to their
+    /// shadow and resize edges whenever it allocates them, e.g. after a
+    /// resize, which drops our region. We put ours back after every frame,
+    /// once GTK is done allocating.
+    fn watch_frames(self: &Rc<Self>, gtk_window: &gtk::ApplicationWindow, surface: &gdk::Surface) {
+        surface.frame_clock().connect_after_paint(clone!(
+            #[weak(rename_to = state)]
+            self,
+            #[weak]
+            gtk_window,
+            #[weak]
+            surface,
+            move |_| {
+                if state.region.borrow().is_some() {
+                    state.update(&gtk_window, &surface);
+                }
+            }
+        ));
+    }
+
+    fn update(&self, gtk_window: &gtk::ApplicationWindow, surface: &gdk::Surface) {
+        let new = input_region(gtk_window, surface, self.hit_test.get());
+        let mut region = self.region.borrow_mut();
+        match (&new, &*region) {
+            // GDK ignores regions which are the same as the current one
+            (Some(new), _) => surface.set_input_region(&new.to_cairo()),
+            // we've changed the region before, so hand it back to GTK
+            (None, Some(_)) => reset_input_region(gtk_window, surface),
+            (None, None) => {}
+        }
+        *region = new;
+    }
+}
+
+fn reset_input_region(gtk_window: &gtk::ApplicationWindow, surface: &gdk::Surface) {
+    // SAFETY: `surface` is a valid surface, and GDK documents a null region as
+    // making the surface accept input everywhere
+    unsafe {
+        gdk::ffi::gdk_surface_set_input_region(surface.to_glib_none().0, ptr::null_mut());
+    }
+    // GTK only sets the region of client-side decorated windows when it
+    // allocates them
+    gtk_window.queue_resize();
+}
+
+/// Computes the input region for a window, or [`None`] if the window should
+/// accept input everywhere.
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "widget bounds are relatively small"
+)]
+fn input_region(
+    gtk_window: &gtk::ApplicationWindow,
+    surface: &gdk::Surface,
+    hit_test: bool,
+) -> Option<InputRegion> {
+    if !hit_test {
+        return Some(InputRegion {
+            full: None,
+            holes: Vec::new(),
+        });
+    }
+
+    let window_widget = gtk_window.upcast_ref::<gtk::Widget>();
+    let (offset_x, offset_y) = gtk_window.surface_transform();
+    let holes = PASSTHROUGH_WIDGETS.with_borrow_mut(|widgets| {
+        widgets.retain(|widget| widget.upgrade().is_some());
+        widgets
+            .iter()
+            .filter_map(glib::WeakRef::upgrade)
+            .filter(|widget| widget.is_mapped())
+            .filter(|widget| {
+                widget
+                    .root()
+                    .is_some_and(|root| root.upcast_ref::<gtk::Widget>() == window_widget)
+            })
+            .filter_map(|widget| widget.compute_bounds(window_widget))
+            .map(|bounds| {
+                (
+                    (f64::from(bounds.x()) + offset_x) as i32,
+                    (f64::from(bounds.y()) + offset_y) as i32,
+                    bounds.width().ceil() as i32,
+                    bounds.height().ceil() as i32,
+                )
+            })
+            .collect::<Vec<_>>()
+    });
+
+    if holes.is_empty() {
+        None
+    } else {
+        Some(InputRegion {
+            full: Some((surface.width(), surface.height())),
+            holes,
+        })
+    }
+}
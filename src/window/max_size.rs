@@ -0,0 +1,138 @@
+use {
+    core::cell::{Cell, RefCell},
+    gtk::{prelude::*, subclass::prelude::*},
+};
+
+glib::wrapper! {
+    /// Holds the widget tree of a window, and stops it from growing past a
+    /// maximum size.
+    ///
+    /// GTK 4 has no API to limit the size of a toplevel, so instead of
+    /// limiting the window, we limit what's inside of it. When the window is
+    /// larger than the maximum size, the child is centered in it at the
+    /// maximum size. The window's natural size is also clamped, so a window
+    /// which isn't resizable is exactly as large as the maximum.
+    pub(super) struct MaxSizeBin(ObjectSubclass<imp::MaxSizeBin>)
+        @extends gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl MaxSizeBin {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn child(&self) -> Option<gtk::Widget> {
+        self.imp().child.borrow().clone()
+    }
+
+    pub fn set_child(&self, child: Option<&gtk::Widget>) {
+        if self.imp().child.borrow().as_ref() == child {
+            return;
+        }
+        if let Some(old) = self.imp().child.take() {
+            old.unparent();
+        }
+        if let Some(child) = child {
+            child.set_parent(self);
+        }
+        self.imp().child.replace(child.cloned());
+    }
+
+    /// Sets the maximum size of the child in logical pixels, or [`None`] if
+    /// it's unbounded along that axis.
+    pub fn set_max_size(&self, width: Option<i32>, height: Option<i32>) {
+        let (width, height) = (width.unwrap_or(i32::MAX), height.unwrap_or(i32::MAX));
+        let imp = self.imp();
+        if imp.max_width.get() == width && imp.max_height.get() == height {
+            return;
+        }
+        imp.max_width.set(width);
+        imp.max_height.set(height);
+        self.queue_resize();
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug)]
+    pub struct MaxSizeBin {
+        pub(super) child: RefCell<Option<gtk::Widget>>,
+        pub(super) max_width: Cell<i32>,
+        pub(super) max_height: Cell<i32>,
+    }
+
+    impl Default for MaxSizeBin {
+        fn default() -> Self {
+            Self {
+                child: RefCell::default(),
+                max_width: Cell::new(i32::MAX),
+                max_height: Cell::new(i32::MAX),
+            }
+        }
+    }
+
+    impl MaxSizeBin {
+        fn max(&self, orientation: gtk::Orientation) -> i32 {
+            match orientation {
+                gtk::Orientation::Vertical => self.max_height.get(),
+                _ => self.max_width.get(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MaxSizeBin {
+        const NAME: &'static str = "BevyGtkMaxSizeBin";
+        type Type = super::MaxSizeBin;
+        type ParentType = gtk::Widget;
+    }
+
+    impl ObjectImpl for MaxSizeBin {
+        fn dispose(&self) {
+            if let Some(child) = self.child.take() {
+                child.unparent();
+            }
+        }
+    }
+
+    impl WidgetImpl for MaxSizeBin {
+        fn measure(&self, orientation: gtk::Orientation, for_size: i32) -> (i32, i32, i32, i32) {
+            let Some(child) = self.child.borrow().clone() else {
+                return (0, 0, -1, -1);
+            };
+            let opposite = match orientation {
+                gtk::Orientation::Vertical => gtk::Orientation::Horizontal,
+                _ => gtk::Orientation::Vertical,
+            };
+            let for_size = if for_size < 0 {
+                for_size
+            } else {
+                for_size.min(self.max(opposite))
+            };
+            let (min, nat, _, _) = child.measure(orientation, for_size);
+            // never ask for less than the child needs, even if that's over the max
+            (min, nat.min(self.max(orientation)).max(min), -1, -1)
+        }
+
+        fn size_allocate(&self, width: i32, height: i32, _baseline: i32) {
+            let Some(child) = self.child.borrow().clone() else {
+                return;
+            };
+            let (min_width, ..) = child.measure(gtk::Orientation::Horizontal, -1);
+            let child_width = width.min(self.max_width.get().max(min_width));
+            let (min_height, ..) = child.measure(gtk::Orientation::Vertical, child_width);
+            let child_height = height.min(self.max_height.get().max(min_height));
+            child.size_allocate(
+                &gtk::Allocation::new(
+                    (width - child_width) / 2,
+                    (height - child_height) / 2,
+                    child_width,
+                    child_height,
+                ),
+                -1,
+            );
+        }
+    }
+}
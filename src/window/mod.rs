@@ -1,37 +1,63 @@
 use {
-    crate::GtkApplication,
+    crate::{GtkApplication, GtkSystems, GtkWindowHooks},
+    alloc::{rc::Rc, sync::Arc},
     bevy_app::prelude::*,
     bevy_ecs::prelude::*,
     bevy_platform::collections::{HashMap, hash_map::Entry},
     bevy_window::{
-        ClosingWindow, Window, WindowCloseRequested, WindowClosed, WindowClosing, WindowCreated,
-        WindowMode,
+        ClosingWindow, MonitorSelection, PrimaryWindow, Window, WindowCloseRequested, WindowClosed,
+        WindowClosing, WindowCreated, WindowMode, WindowResized, WindowTheme,
     },
     core::mem,
+    glib::clone,
     gtk::prelude::*,
-    log::info,
+    log::{debug, info},
 };
 
 mod event;
+mod header;
+mod input;
+mod max_size;
+mod parent;
+mod role;
+mod slots;
+
+pub use {
+    header::GtkHeaderBarContent,
+    input::set_input_passthrough,
+    parent::{GtkModalWindow, GtkWindowParent},
+    role::GtkWindowRole,
+    slots::GtkWindowSlot,
+};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(event::plugin).add_systems(
+    app.add_plugins((event::plugin, input::plugin)).add_systems(
         Last,
         (
             create_gtk_windows,
             despawn,
             sync_new_content,
+            sync_retained_content,
+            header::sync_new_header_content,
             sync_window_config,
+            role::sync_window_roles,
+            parent::sync_window_parents,
             sync_gtk_to_bevy,
         )
-            .chain(),
+            .chain()
+            .in_set(GtkSystems::SyncWindows),
     );
 }
 
+/// Tracks the GTK windows which back Bevy [`Window`] entities.
+///
+/// This is a non-send resource, since GTK objects can only be accessed from the
+/// GTK thread.
 #[derive(Debug)]
 pub struct GtkWindows {
     use_adw: bool,
     entity_to_proxy: HashMap<Entity, WindowProxy>,
+    primary: Option<Entity>,
 }
 
 impl GtkWindows {
@@ -40,6 +66,7 @@ impl GtkWindows {
         Self {
             use_adw,
             entity_to_proxy: HashMap::new(),
+            primary: None,
         }
     }
 
@@ -62,22 +89,260 @@ impl GtkWindows {
     pub fn get_mut(&mut self, entity: Entity) -> Option<&mut WindowProxy> {
         self.entity_to_proxy.get_mut(&entity)
     }
+
+    /// Gets the entity of the [`PrimaryWindow`], if it has a GTK window.
+    #[must_use]
+    pub fn primary_entity(&self) -> Option<Entity> {
+        self.primary
+    }
+
+    /// Gets the proxy of the [`PrimaryWindow`], if it has a GTK window.
+    #[must_use]
+    pub fn primary(&self) -> Option<&WindowProxy> {
+        self.primary.and_then(|entity| self.get(entity))
+    }
+
+    /// Mutable version of [`GtkWindows::primary`].
+    #[must_use]
+    pub fn primary_mut(&mut self) -> Option<&mut WindowProxy> {
+        self.primary
+            .and_then(|entity| self.entity_to_proxy.get_mut(&entity))
+    }
+
+    /// Iterates over all window entities and their proxies.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &WindowProxy)> {
+        self.entity_to_proxy
+            .iter()
+            .map(|(entity, proxy)| (*entity, proxy))
+    }
+
+    /// Mutable version of [`GtkWindows::iter`].
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut WindowProxy)> {
+        self.entity_to_proxy
+            .iter_mut()
+            .map(|(entity, proxy)| (*entity, proxy))
+    }
+
+    /// Moves the content of window `from` into window `to`, replacing the
+    /// content of `to`.
+    ///
+    /// Viewports inside the content are reparented rather than destroyed, so
+    /// they keep rendering from the same cameras, into the same images.
+    /// `from` is left with an empty placeholder.
+    ///
+    /// Returns `false` if either window doesn't have a GTK window, they are
+    /// the same window, or `from` has no content to move.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// fn tear_off(mut gtk_commands: GtkCommands, old: Entity, new: Entity) {
+    ///     gtk_commands.queue(move |ctx: &mut GtkContext| {
+    ///         ctx.windows.move_content(old, new);
+    ///     });
+    /// }
+    /// ```
+    pub fn move_content(&mut self, from: Entity, to: Entity) -> bool {
+        if from == to || !self.entity_to_proxy.contains_key(&to) {
+            return false;
+        }
+        let Some(from_proxy) = self.entity_to_proxy.get_mut(&from) else {
+            return false;
+        };
+        let Some(content) = from_proxy.take_content() else {
+            return false;
+        };
+        if let Some(to_proxy) = self.entity_to_proxy.get_mut(&to) {
+            debug!("Moving content of window {from} to {to}");
+            to_proxy.set_content(content);
+        }
+        true
+    }
+
+    /// Gets the entity of the Bevy window backed by this GTK window.
+    #[must_use]
+    pub fn entity(&self, gtk_window: &impl IsA<gtk::Window>) -> Option<Entity> {
+        let gtk_window = gtk_window.upcast_ref::<gtk::Window>();
+        self.entity_to_proxy
+            .iter()
+            .find(|(_, proxy)| proxy.gtk_window.upcast_ref::<gtk::Window>() == gtk_window)
+            .map(|(entity, _)| *entity)
+    }
+
+    /// Gets the entity of the Bevy window which this widget is inside of.
+    ///
+    /// This is useful in GTK callbacks, to attribute an event back to the
+    /// Bevy window it was emitted from.
+    #[must_use]
+    pub fn entity_of_widget(&self, widget: &impl IsA<gtk::Widget>) -> Option<Entity> {
+        let root = widget.root()?;
+        let gtk_window = root.downcast_ref::<gtk::Window>()?;
+        self.entity(gtk_window)
+    }
 }
 
+/// GTK-side state of a single Bevy [`Window`].
 #[derive(Debug)]
 pub struct WindowProxy {
     pub gtk_window: gtk::ApplicationWindow,
     content: gtk::Widget,
+    /// Whether [`WindowProxy::gtk_window`] was created by the user via
+    /// [`GtkAdoptedWindow`], in which case we don't manage its widget tree.
+    adopted: bool,
     cache: Option<Window>,
+    /// Input region of the window's surface.
+    input: Rc<input::InputState>,
+    /// Widgets which the user added to the title bar.
+    header_widgets: Option<header::HeaderWidgets>,
+    /// Whether a [`GtkRetainedWindowContent`] has made the content of this
+    /// window.
+    retained_content_made: bool,
+    /// Layout of the [`GtkWindowSlot`]s, once a slot other than the main one
+    /// has been used.
+    ///
+    /// While this exists, [`WindowProxy::content`] is the root of the layout.
+    slots: Option<slots::WindowSlots>,
+    /// Root of the widget tree that we manage, which applies the maximum size
+    /// from [`Window::resize_constraints`].
+    max_size: max_size::MaxSizeBin,
     rx_close_request: async_channel::Receiver<()>,
+    rx_state_change: async_channel::Receiver<WindowStateChange>,
+}
+
+/// Change to a GTK window's state, made from the GTK side, which we reflect
+/// back into Bevy.
+#[derive(Debug)]
+enum WindowStateChange {
+    Title(String),
+    Fullscreened(bool),
+    Maximized(bool),
+    /// The surface was laid out at a new size, in logical pixels, or moved to
+    /// a monitor with another scale.
+    Resized {
+        width: i32,
+        height: i32,
+        scale: f64,
+    },
+}
+
+/// State of a GTK window which can't be represented in [`Window`].
+///
+/// This is automatically inserted into windows, and is kept in sync with the
+/// GTK window's state. Writing to this component has no effect; use
+/// [`Window::set_maximized`] to request a state change instead.
+#[derive(Debug, Clone, Default, Component)]
+pub struct GtkWindowState {
+    /// Whether the window is maximized.
+    pub maximized: bool,
+    /// Whether the window is fullscreened.
+    pub fullscreened: bool,
 }
 
 impl WindowProxy {
+    /// Whether this window was created by the user via [`GtkAdoptedWindow`].
+    #[must_use]
+    pub fn is_adopted(&self) -> bool {
+        self.adopted
+    }
+
+    /// Gets the main content of this window.
+    ///
+    /// This is the widget in [`GtkWindowSlot::Main`], or a placeholder if the
+    /// window has no content yet.
+    #[must_use]
+    pub fn content(&self) -> &gtk::Widget {
+        self.slots
+            .as_ref()
+            .and_then(|slots| slots.get(GtkWindowSlot::Main))
+            .unwrap_or(&self.content)
+    }
+
+    /// Replaces the main content of this window.
+    ///
+    /// This is the same as setting [`GtkWindowSlot::Main`] with
+    /// [`WindowProxy::set_slot`], and leaves the other slots untouched.
     pub fn set_content(&mut self, content: impl IsA<gtk::Widget>) {
         let new: gtk::Widget = content.into();
+        if let Some(slots) = &mut self.slots {
+            slots.set(GtkWindowSlot::Main, Some(new));
+            return;
+        }
         let old = mem::replace(&mut self.content, new.clone());
         replace_content(&old, Some(&new));
     }
+
+    /// Gets the widget in `slot` of this window.
+    #[must_use]
+    pub fn slot(&self, slot: GtkWindowSlot) -> Option<&gtk::Widget> {
+        if slot == GtkWindowSlot::Main {
+            return Some(self.content());
+        }
+        self.slots.as_ref().and_then(|slots| slots.get(slot))
+    }
+
+    /// Places `widget` in `slot` of this window, or empties the slot if
+    /// [`None`], and returns the widget which was there before.
+    ///
+    /// Emptying [`GtkWindowSlot::Main`] leaves a placeholder in its place.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// fn add_status_bar(window: Query<Entity, With<PrimaryWindow>>, mut gtk: GtkCommands) {
+    ///     let window = window.single().unwrap();
+    ///     gtk.queue(move |ctx: &mut GtkContext| {
+    ///         if let Some(proxy) = ctx.windows.get_mut(window) {
+    ///             proxy.set_slot(GtkWindowSlot::Bottom, Some(gtk::Label::new(Some("Ready"))));
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn set_slot(
+        &mut self,
+        slot: GtkWindowSlot,
+        widget: Option<impl IsA<gtk::Widget>>,
+    ) -> Option<gtk::Widget> {
+        let widget = widget.map(Into::into);
+        if slot == GtkWindowSlot::Main {
+            let new = widget.unwrap_or_else(|| gtk::Label::new(None).upcast());
+            let old = self.content().clone();
+            self.set_content(new);
+            return Some(old);
+        }
+
+        let slots = if let Some(slots) = &mut self.slots {
+            slots
+        } else {
+            // move the main content into a new slot layout
+            let mut slots = slots::WindowSlots::new();
+            let main = mem::replace(&mut self.content, slots.root.clone().upcast());
+            replace_content(&main, Some(&self.content));
+            slots.set(GtkWindowSlot::Main, Some(main));
+            self.slots.insert(slots)
+        };
+        slots.set(slot, widget)
+    }
+
+    /// Detaches the content of this window and returns it, leaving an empty
+    /// placeholder in its place.
+    ///
+    /// The returned widget, and any viewports inside of it, stay alive for as
+    /// long as you hold on to it. Use this to dock the content into another
+    /// window with [`WindowProxy::set_content`] without recreating its
+    /// viewports, e.g. when tearing off a tab.
+    ///
+    /// Returns [`None`] if the window uses [slots](GtkWindowSlot) and has no
+    /// [`GtkWindowSlot::Main`] widget.
+    pub fn take_content(&mut self) -> Option<gtk::Widget> {
+        let placeholder = gtk::Label::new(None).upcast::<gtk::Widget>();
+        if let Some(slots) = &mut self.slots {
+            // the content is the slot container, which has to stay in place
+            return slots.set(GtkWindowSlot::Main, Some(placeholder));
+        }
+        let old = mem::replace(&mut self.content, placeholder.clone());
+        replace_content(&old, Some(&placeholder));
+        Some(old)
+    }
 }
 
 #[derive(Component)]
@@ -103,14 +368,114 @@ where
     }
 }
 
+/// Content of a window which, unlike [`GtkWindowContent`], is kept after it's
+/// been made, so that it can be made again.
+///
+/// The factory is run on the GTK thread whenever a new GTK window is created
+/// for this entity, so a window which is recreated, e.g. because it's adopted
+/// again, gets fresh content. Use [`GtkRetainedWindowContent::remake`] to
+/// replace the current content with a newly made one, and
+/// [`GtkRetainedWindowContent::is_installed`] to check whether the content is
+/// in a GTK window yet.
+///
+/// Since the factory may run more than once, it must make the content from
+/// scratch every time. A viewport's `WidgetFactory` can only be made once, so
+/// put viewports in this content as `BevyGtkViewport`s instead.
+///
+/// # Examples
+///
+/// ```ignore
+/// commands.spawn((
+///     Window::default(),
+///     GtkRetainedWindowContent::new(|| gtk::Label::new(Some("Hello world!"))),
+/// ));
+/// ```
+#[derive(Clone, Component)]
+pub struct GtkRetainedWindowContent {
+    make: Arc<dyn Fn() -> gtk::Widget + Send + Sync>,
+    installed: bool,
+    remake: bool,
+}
+
+impl GtkRetainedWindowContent {
+    /// Creates content which is made by calling `make` on the GTK thread.
+    #[must_use]
+    pub fn new<W: IsA<gtk::Widget>>(make: impl Fn() -> W + Send + Sync + 'static) -> Self {
+        Self {
+            make: Arc::new(move || make().upcast()),
+            installed: false,
+            remake: false,
+        }
+    }
+
+    /// Whether the content has been made and set as the content of this
+    /// entity's GTK window.
+    ///
+    /// This is `false` until the GTK window is created, and while the entity
+    /// has no GTK window.
+    #[must_use]
+    pub const fn is_installed(&self) -> bool {
+        self.installed
+    }
+
+    /// Makes the content again at the next [`GtkSystems::SyncWindows`], and
+    /// replaces the current content of the window with it.
+    pub fn remake(&mut self) {
+        self.remake = true;
+    }
+}
+
+/// Uses an existing [`gtk::ApplicationWindow`] as the backing window for a
+/// Bevy [`Window`], instead of letting this crate create one.
+///
+/// This must be inserted at the same time as the [`Window`] is spawned. The
+/// factory is run on the GTK thread when the window is created, and the
+/// component is removed afterwards.
+///
+/// The widget tree of an adopted window is left untouched: titlebar settings
+/// on [`Window`] are ignored, and [`GtkWindowContent`] replaces the window's
+/// existing child.
+#[derive(Component)]
+pub struct GtkAdoptedWindow(pub Option<Box<dyn MakeWindow>>);
+
+impl<T: MakeWindow> From<T> for GtkAdoptedWindow {
+    fn from(value: T) -> Self {
+        Self(Some(Box::new(value)))
+    }
+}
+
+pub trait MakeWindow: Send + Sync + 'static {
+    fn make(self: Box<Self>, app: &gtk::Application) -> gtk::ApplicationWindow;
+}
+
+impl<W, F> MakeWindow for F
+where
+    W: IsA<gtk::ApplicationWindow>,
+    F: FnOnce(&gtk::Application) -> W + Send + Sync + 'static,
+{
+    fn make(self: Box<Self>, app: &gtk::Application) -> gtk::ApplicationWindow {
+        (self)(app).upcast()
+    }
+}
+
 pub fn create_gtk_windows(
-    new_windows: Query<(Entity, &mut Window), Added<Window>>,
+    mut commands: Commands,
+    mut new_windows: Query<
+        (
+            Entity,
+            &Window,
+            Has<PrimaryWindow>,
+            Option<&mut GtkAdoptedWindow>,
+        ),
+        Added<Window>,
+    >,
     mut gtk_windows: NonSendMut<GtkWindows>,
     gtk_app: NonSend<GtkApplication>,
+    mut window_hooks: Option<NonSendMut<GtkWindowHooks>>,
     mut window_created_events: EventWriter<WindowCreated>,
 ) {
     let gtk_windows = &mut *gtk_windows;
-    for (entity, bevy_window) in &new_windows {
+    for (entity, bevy_window, is_primary, adopted_window) in &mut new_windows {
         let Entry::Vacant(entry) = gtk_windows.entity_to_proxy.entry(entity) else {
             continue;
         };
@@ -121,11 +486,30 @@ pub fn create_gtk_windows(
             entity
         );
 
-        let gtk_window = if_adw!(
-            gtk_windows.use_adw,
-            adw::ApplicationWindow::new(&**gtk_app).upcast::<gtk::ApplicationWindow>(),
-            gtk::ApplicationWindow::new(&**gtk_app),
-        );
+        let make_window = adopted_window.and_then(|mut adopted| adopted.0.take());
+        let adopted = make_window.is_some();
+        let gtk_window = if let Some(make_window) = make_window {
+            commands.entity(entity).remove::<GtkAdoptedWindow>();
+            let gtk_window = make_window.make(&gtk_app);
+            if gtk_window.application().is_none() {
+                gtk_window.set_application(Some(&**gtk_app));
+            }
+            gtk_window
+        } else {
+            if_adw!(
+                gtk_windows.use_adw,
+                adw::ApplicationWindow::new(&**gtk_app).upcast::<gtk::ApplicationWindow>(),
+                gtk::ApplicationWindow::new(&**gtk_app),
+            )
+        };
+        if let Some(window_hooks) = &mut window_hooks {
+            window_hooks.run(entity, &gtk_window);
+        }
+        let content = if adopted {
+            adopted_content(&gtk_window)
+        } else {
+            gtk::Label::new(None).upcast()
+        };
 
         // I think it's fine to drop some close requests if it gets spammed?
         let (tx_close_request, rx_close_request) = async_channel::bounded(8);
@@ -134,20 +518,106 @@ pub fn create_gtk_windows(
             glib::Propagation::Stop
         });
 
+        // changes made on the GTK side, e.g. by the user or other GTK code,
+        // must be reflected back into Bevy, otherwise Bevy would overwrite them
+        let (tx_state_change, rx_state_change) = async_channel::unbounded();
+        gtk_window.connect_title_notify(clone!(
+            #[strong]
+            tx_state_change,
+            move |gtk_window| {
+                let title = gtk_window.title().map(String::from).unwrap_or_default();
+                _ = tx_state_change.try_send(WindowStateChange::Title(title));
+            }
+        ));
+        gtk_window.connect_fullscreened_notify(clone!(
+            #[strong]
+            tx_state_change,
+            move |gtk_window| {
+                _ = tx_state_change
+                    .try_send(WindowStateChange::Fullscreened(gtk_window.is_fullscreen()));
+            }
+        ));
+        gtk_window.connect_maximized_notify(clone!(
+            #[strong]
+            tx_state_change,
+            move |gtk_window| {
+                _ = tx_state_change
+                    .try_send(WindowStateChange::Maximized(gtk_window.is_maximized()));
+            }
+        ));
+        // the surface only exists once the window is realized
+        gtk_window.connect_realize(move |gtk_window| {
+            let Some(surface) = gtk_window.surface() else {
+                return;
+            };
+            let send_size = clone!(
+                #[strong]
+                tx_state_change,
+                move |surface: &gdk::Surface| {
+                    _ = tx_state_change.try_send(WindowStateChange::Resized {
+                        width: surface.width(),
+                        height: surface.height(),
+                        scale: surface.scale(),
+                    });
+                }
+            );
+            send_size(&surface);
+            surface.connect_layout(clone!(
+                #[strong]
+                send_size,
+                move |surface, _, _| send_size(surface)
+            ));
+            surface.connect_scale_notify(move |surface| send_size(surface));
+        });
+        commands.entity(entity).insert(GtkWindowState::default());
+
         let mut proxy = WindowProxy {
             gtk_window,
-            content: gtk::Label::new(None).upcast(),
+            content,
+            adopted,
             cache: None,
+            input: input::InputState::new(&gtk_window),
+            header_widgets: None,
+            retained_content_made: false,
+            slots: None,
+            max_size: max_size::MaxSizeBin::new(),
             rx_close_request,
+            rx_state_change,
         };
         sync_one(gtk_windows.use_adw, bevy_window, &mut proxy);
-        proxy.gtk_window.present();
+        if bevy_window.visible {
+            proxy.gtk_window.present();
+        }
 
         entry.insert(proxy);
+        if is_primary {
+            gtk_windows.primary = Some(entity);
+        }
         window_created_events.write(WindowCreated { window: entity });
     }
 }
 
+/// Gets the current content widget of an adopted window, or sets a placeholder
+/// as its content if it has none.
+fn adopted_content(gtk_window: &gtk::ApplicationWindow) -> gtk::Widget {
+    #[cfg(feature = "adwaita")]
+    if let Some(adw_window) = gtk_window.downcast_ref::<adw::ApplicationWindow>() {
+        use adw::prelude::*;
+
+        return adw_window.content().unwrap_or_else(|| {
+            let placeholder = gtk::Label::new(None).upcast::<gtk::Widget>();
+            adw_window.set_content(Some(&placeholder));
+            placeholder
+        });
+    }
+
+    gtk_window.child().unwrap_or_else(|| {
+        let placeholder = gtk::Label::new(None).upcast::<gtk::Widget>();
+        gtk_window.set_child(Some(&placeholder));
+        placeholder
+    })
+}
+
 pub fn sync_new_content(
     mut commands: Commands,
     mut changed_windows: Query<(Entity, Option<&mut GtkWindowContent>), Changed<GtkWindowContent>>,
@@ -168,17 +638,50 @@ pub fn sync_new_content(
     }
 }
 
+pub fn sync_retained_content(
+    mut contents: Query<(Entity, &mut GtkRetainedWindowContent)>,
+    mut gtk_windows: NonSendMut<GtkWindows>,
+) {
+    for (entity, mut content) in &mut contents {
+        let Some(proxy) = gtk_windows.entity_to_proxy.get_mut(&entity) else {
+            if content.installed {
+                content.installed = false;
+            }
+            continue;
+        };
+        if proxy.retained_content_made && !content.remake && !content.is_added() {
+            continue;
+        }
+
+        debug!("Making retained content of window {entity}");
+        proxy.set_content((content.make)());
+        proxy.retained_content_made = true;
+        content.installed = true;
+        content.remake = false;
+    }
+}
+
 pub fn sync_window_config(
-    mut changed_windows: Query<(Entity, &Window), Changed<Window>>,
+    mut changed_windows: Query<(Entity, &mut Window), Changed<Window>>,
     mut gtk_windows: NonSendMut<GtkWindows>,
 ) {
-    for (entity, bevy_window) in &mut changed_windows {
+    for (entity, mut bevy_window) in &mut changed_windows {
         let gtk_windows = &mut *gtk_windows;
         let Some(proxy) = gtk_windows.entity_to_proxy.get_mut(&entity) else {
             continue;
         };
 
-        sync_one(gtk_windows.use_adw, bevy_window, proxy);
+        sync_one(gtk_windows.use_adw, &bevy_window, proxy);
+
+        let internal = &mut bevy_window.bypass_change_detection().internal;
+        match internal.take_maximize_request() {
+            Some(true) => proxy.gtk_window.maximize(),
+            Some(false) => proxy.gtk_window.unmaximize(),
+            None => {}
+        }
+        if internal.take_minimize_request() == Some(true) {
+            proxy.gtk_window.minimize();
+        }
     }
 }
 
@@ -198,61 +701,156 @@ fn sync_one(use_adw: bool, new: &Window, proxy: &mut WindowProxy) {
         }
     }
 
+    // windows are first shown once they're created
+    if cache.is_some_and(|c| c.visible != new.visible) {
+        if new.visible {
+            gtk_window.present();
+        } else {
+            gtk_window.set_visible(false);
+        }
+    }
+
     if cache.is_none_or(|c| c.title != new.title) {
         gtk_window.set_title(Some(&new.title));
     }
 
+    // GTK identifies every window to the window manager by the application ID,
+    // so the closest we can get is the widget name, which CSS and tools like
+    // the GTK inspector can select the window by
+    if cache.is_none_or(|c| c.name != new.name) {
+        if let Some(name) = &new.name {
+            gtk_window.set_widget_name(name);
+        }
+    }
+
     // `set_default_width/height` MUST be called before `set_width/height_request`,
     // or the window size will be wrong on startup
-    if cache.is_none_or(|c| c.resolution != new.resolution) {
-        gtk_window.set_default_width(new.resolution.width() as i32);
-        gtk_window.set_default_height(new.resolution.height() as i32);
+    if cache.is_none_or(|c| c.resolution.physical_size() != new.resolution.physical_size()) {
+        // the resolution is in physical pixels, but GTK sizes are logical
+        let scale = surface_scale(gtk_window.upcast_ref());
+        let to_logical = |physical: u32| (f64::from(physical) / scale).round() as i32;
+        gtk_window.set_default_width(to_logical(new.resolution.physical_width()));
+        gtk_window.set_default_height(to_logical(new.resolution.physical_height()));
+    }
+
+    if cache.is_none_or(|c| {
+        c.resolution.scale_factor_override() != new.resolution.scale_factor_override()
+    }) {
+        let scale_factor_override = new.resolution.scale_factor_override().map(f64::from);
+        // SAFETY: this key is only ever used to store an `f64`
+        unsafe {
+            match scale_factor_override {
+                Some(scale) => gtk_window.set_data(SCALE_FACTOR_OVERRIDE_KEY, scale),
+                None => _ = gtk_window.steal_data::<f64>(SCALE_FACTOR_OVERRIDE_KEY),
+            }
+        }
     }
 
     if cache.is_none_or(|c| c.resize_constraints != new.resize_constraints) {
-        gtk_window.set_width_request(new.resize_constraints.min_width as i32);
-        gtk_window.set_height_request(new.resize_constraints.min_height as i32);
+        let constraints = new.resize_constraints.check_constraints();
+        gtk_window.set_width_request(constraints.min_width as i32);
+        gtk_window.set_height_request(constraints.min_height as i32);
+        let max = |max: f32| max.is_finite().then_some(max as i32);
+        proxy
+            .max_size
+            .set_max_size(max(constraints.max_width), max(constraints.max_height));
     }
 
-    if cache.is_none_or(|c| c.resizable != new.resizable) {
-        gtk_window.set_resizable(new.resizable);
+    if cache.is_none_or(|c| {
+        c.resizable != new.resizable || c.resize_constraints != new.resize_constraints
+    }) {
+        // if the min and max sizes are the same, the user can't resize the
+        // window anyway, so stop the window manager from offering to
+        let constraints = new.resize_constraints.check_constraints();
+        let fixed_size = constraints.min_width >= constraints.max_width
+            && constraints.min_height >= constraints.max_height;
+        gtk_window.set_resizable(new.resizable && !fixed_size);
     }
 
     // TODO: IME
 
-    #[cfg(feature = "adwaita")]
+    // the theme is app-wide, not per-window
     if cache.is_none_or(|c| c.window_theme != new.window_theme) {
-        use bevy_window::WindowTheme;
-
-        adw::StyleManager::default().set_color_scheme(match new.window_theme {
-            None => adw::ColorScheme::Default,
-            Some(WindowTheme::Light) => adw::ColorScheme::ForceLight,
-            Some(WindowTheme::Dark) => adw::ColorScheme::ForceDark,
-        });
-    }
-
-    let rebuild_widgets = cache.is_none_or(|c| {
-        c.titlebar_shown != new.titlebar_shown
-            || c.titlebar_transparent != new.titlebar_transparent
-            || c.titlebar_show_title != new.titlebar_show_title
-            || c.titlebar_show_buttons != new.titlebar_show_buttons
-    });
-    if rebuild_widgets {
         if_adw!(
             use_adw,
-            if let Some(adw_window) = proxy.gtk_window.downcast_ref::<adw::ApplicationWindow>() {
-                use adw::prelude::*;
-
-                let content_root = adw_content_root(new, &proxy.content);
-                adw_window.set_content(Some(&content_root));
+            adw::StyleManager::default().set_color_scheme(match new.window_theme {
+                None => adw::ColorScheme::Default,
+                Some(WindowTheme::Light) => adw::ColorScheme::ForceLight,
+                Some(WindowTheme::Dark) => adw::ColorScheme::ForceDark,
+            }),
+            if let Some(settings) = gtk::Settings::default() {
+                match new.window_theme {
+                    None => settings.reset_property("gtk-application-prefer-dark-theme"),
+                    Some(theme) => {
+                        settings.set_gtk_application_prefer_dark_theme(theme == WindowTheme::Dark)
+                    }
+                }
             },
-            proxy.gtk_window.set_child(Some(&proxy.content)),
         );
     }
 
+    let rebuild_widgets = !proxy.adopted
+        && cache.is_none_or(|c| {
+            c.titlebar_shown != new.titlebar_shown
+                || c.titlebar_transparent != new.titlebar_transparent
+                || c.titlebar_show_title != new.titlebar_show_title
+                || c.titlebar_show_buttons != new.titlebar_show_buttons
+        });
+    if rebuild_widgets {
+        rebuild_widgets(use_adw, new, proxy);
+    }
+
     proxy.cache = Some(new.clone());
 }
 
+/// Rebuilds the widget tree around the window's content, e.g. the title bar.
+fn rebuild_widgets(use_adw: bool, config: &Window, proxy: &WindowProxy) {
+    if let Some(header_widgets) = &proxy.header_widgets {
+        header_widgets.detach();
+    }
+    if_adw!(
+        use_adw,
+        if let Some(adw_window) = proxy.gtk_window.downcast_ref::<adw::ApplicationWindow>() {
+            use adw::prelude::*;
+
+            let content_root =
+                adw_content_root(config, &proxy.content, proxy.header_widgets.as_ref());
+            proxy.max_size.set_child(Some(&content_root));
+            if adw_window.content().as_ref() != Some(proxy.max_size.upcast_ref()) {
+                adw_window.set_content(Some(&proxy.max_size));
+            }
+        },
+        {
+            proxy.max_size.set_child(Some(&proxy.content));
+            if proxy.gtk_window.child().as_ref() != Some(proxy.max_size.upcast_ref()) {
+                proxy.gtk_window.set_child(Some(&proxy.max_size));
+            }
+            if let Some(header_widgets) = &proxy.header_widgets {
+                let header = config
+                    .titlebar_shown
+                    .then(|| header_widgets.gtk_header_bar(config));
+                proxy.gtk_window.set_titlebar(header.as_ref());
+            }
+        },
+    );
+}
+
+/// Key of the data on a GTK window which stores the
+/// [`WindowResolution::scale_factor_override`] of its Bevy window.
+///
+/// [`WindowResolution::scale_factor_override`]: bevy_window::WindowResolution::scale_factor_override
+const SCALE_FACTOR_OVERRIDE_KEY: &str = "bevy-gtk-scale-factor-override";
+
+/// Gets the scale factor which the Bevy window backed by `gtk_window` should be
+/// rendered at instead of the surface scale, if the app has overridden it.
+pub(crate) fn scale_factor_override(gtk_window: &gtk::Window) -> Option<f64> {
+    // SAFETY: this key is only ever used to store an `f64`
+    unsafe { gtk_window.data::<f64>(SCALE_FACTOR_OVERRIDE_KEY) }.map(|scale| {
+        // SAFETY: the data is alive as long as `gtk_window` is, and isn't mutated
+        unsafe { *scale.as_ref() }
+    })
+}
+
 fn replace_content(old: &gtk::Widget, new: Option<&gtk::Widget>) {
     let parent = match (old.parent(), new) {
         (Some(parent), _) => parent,
@@ -281,12 +879,20 @@ fn replace_content(old: &gtk::Widget, new: Option<&gtk::Widget>) {
         parent.set_child(new);
         return;
     }
+    if let Some(parent) = parent.downcast_ref::<max_size::MaxSizeBin>() {
+        parent.set_child(new);
+        return;
+    }
 
     unreachable!("invalid parent widget {parent:?}");
 }
 
 #[cfg(feature = "adwaita")]
-fn adw_content_root(config: &Window, content: &gtk::Widget) -> gtk::Widget {
+fn adw_content_root(
+    config: &Window,
+    content: &gtk::Widget,
+    header_widgets: Option<&header::HeaderWidgets>,
+) -> gtk::Widget {
     // ensure `proxy.content` has no parent before we add it to a new parent
     replace_content(content, None);
 
@@ -304,7 +910,13 @@ fn adw_content_root(config: &Window, content: &gtk::Widget) -> gtk::Widget {
                     .valign(gtk::Align::Start)
                     .build();
                 header_box.append(&gtk::WindowControls::new(gtk::PackType::Start));
+                if let Some(header_widgets) = header_widgets {
+                    header_box.append(&header_widgets.start);
+                }
                 header_box.append(&gtk::Box::builder().hexpand(true).build());
+                if let Some(header_widgets) = header_widgets {
+                    header_box.append(&header_widgets.end);
+                }
                 header_box.append(&gtk::WindowControls::new(gtk::PackType::End));
 
                 let overlay = gtk::Overlay::new();
@@ -316,6 +928,13 @@ fn adw_content_root(config: &Window, content: &gtk::Widget) -> gtk::Widget {
             }
         } else {
             let header = adw::HeaderBar::new();
+            if let Some(header_widgets) = header_widgets {
+                header.pack_start(&header_widgets.start);
+                header.pack_end(&header_widgets.end);
+                if config.titlebar_show_title {
+                    header.set_title_widget(header_widgets.title.as_ref());
+                }
+            }
             if !config.titlebar_show_title {
                 header.set_title_widget(Some(&gtk::Label::new(None)));
             }
@@ -334,15 +953,103 @@ fn adw_content_root(config: &Window, content: &gtk::Widget) -> gtk::Widget {
     }
 }
 
+/// Gets the scale of the surface that `gtk_window` is shown on.
+///
+/// Before the window is realized, it has no surface, so we guess the scale
+/// of the display's first monitor, which is where most compositors open new
+/// windows.
+fn surface_scale(gtk_window: &gtk::Window) -> f64 {
+    gtk_window
+        .surface()
+        .map(|surface| surface.scale())
+        .or_else(|| {
+            WidgetExt::display(gtk_window)
+                .monitors()
+                .item(0)
+                .and_downcast::<gdk::Monitor>()
+                .map(|monitor| monitor.scale())
+        })
+        .filter(|scale| *scale > 0.0)
+        .unwrap_or(1.0)
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "window sizes are small and positive"
+)]
 pub fn sync_gtk_to_bevy(
-    gtk_windows: NonSend<GtkWindows>,
+    mut gtk_windows: NonSendMut<GtkWindows>,
+    mut windows: Query<(&mut Window, &mut GtkWindowState)>,
     mut close_requested: EventWriter<WindowCloseRequested>,
+    mut resized: EventWriter<WindowResized>,
 ) {
-    for (entity, proxy) in &gtk_windows.entity_to_proxy {
+    for (entity, proxy) in &mut gtk_windows.entity_to_proxy {
         if let Ok(()) | Err(async_channel::TryRecvError::Closed) = proxy.rx_close_request.try_recv()
         {
             close_requested.write(WindowCloseRequested { window: *entity });
         }
+
+        let Ok((mut bevy_window, mut state)) = windows.get_mut(*entity) else {
+            continue;
+        };
+        while let Ok(change) = proxy.rx_state_change.try_recv() {
+            // we update the cache as well, so that `sync_one` doesn't see
+            // these changes as coming from Bevy
+            let cache = proxy.cache.as_mut();
+            match change {
+                WindowStateChange::Title(title) => {
+                    if let Some(cache) = cache {
+                        cache.title.clone_from(&title);
+                    }
+                    if bevy_window.title != title {
+                        bevy_window.title = title;
+                    }
+                }
+                WindowStateChange::Fullscreened(fullscreened) => {
+                    state.fullscreened = fullscreened;
+                    let mode = match (fullscreened, bevy_window.mode) {
+                        (false, _) => WindowMode::Windowed,
+                        (true, WindowMode::Windowed) => {
+                            WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+                        }
+                        (true, mode) => mode,
+                    };
+                    if let Some(cache) = cache {
+                        cache.mode = mode;
+                    }
+                    if bevy_window.mode != mode {
+                        bevy_window.mode = mode;
+                    }
+                }
+                WindowStateChange::Maximized(maximized) => {
+                    state.maximized = maximized;
+                }
+                WindowStateChange::Resized {
+                    width,
+                    height,
+                    scale,
+                } => {
+                    let mut resolution = bevy_window.resolution.clone();
+                    resolution.set_scale_factor(scale as f32);
+                    resolution.set_physical_resolution(
+                        (f64::from(width) * scale).round() as u32,
+                        (f64::from(height) * scale).round() as u32,
+                    );
+                    if let Some(cache) = cache {
+                        cache.resolution = resolution.clone();
+                    }
+                    if bevy_window.resolution != resolution {
+                        bevy_window.resolution = resolution;
+                        resized.write(WindowResized {
+                            window: *entity,
+                            width: bevy_window.width(),
+                            height: bevy_window.height(),
+                        });
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -361,6 +1068,9 @@ pub fn despawn(
         if let Some(proxy) = gtk_windows.entity_to_proxy.remove(&window) {
             proxy.gtk_window.destroy();
         }
+        if gtk_windows.primary == Some(window) {
+            gtk_windows.primary = None;
+        }
         closed_events.write(WindowClosed { window });
     }
 }
@@ -0,0 +1,81 @@
+use {super::GtkWindows, bevy_ecs::prelude::*, gtk::prelude::*};
+
+/// Makes this window a transient child of another Bevy window.
+///
+/// The window manager keeps a transient window stacked above its parent,
+/// minimizes and restores it along with the parent, and may center it over
+/// the parent. Use this for secondary windows which belong to a main window,
+/// like tool palettes and dialogs. Add [`GtkModalWindow`] as well to block
+/// input to the parent while this window is open.
+///
+/// If the parent entity has no window, e.g. because it was despawned, this
+/// window has no parent until it does. Removing this component detaches the
+/// window from its parent.
+///
+/// # Examples
+///
+/// ```ignore
+/// let main_window = commands.spawn(Window::default()).id();
+/// commands.spawn((
+///     Window {
+///         title: "Preferences".into(),
+///         ..default()
+///     },
+///     GtkWindowParent(main_window),
+///     GtkModalWindow,
+/// ));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct GtkWindowParent(pub Entity);
+
+/// Makes this window modal, so that other windows of the app can't be
+/// interacted with while it's open.
+///
+/// With a [`GtkWindowParent`], this usually only blocks the parent, but the
+/// window manager decides what's blocked. Removing this component makes the
+/// window non-modal again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Component)]
+pub struct GtkModalWindow;
+
+pub(super) fn sync_window_parents(
+    windows: Query<
+        (Entity, Option<&GtkWindowParent>, Has<GtkModalWindow>),
+        Or<(With<GtkWindowParent>, With<GtkModalWindow>)>,
+    >,
+    mut removed_parents: RemovedComponents<GtkWindowParent>,
+    mut removed_modals: RemovedComponents<GtkModalWindow>,
+    gtk_windows: NonSend<GtkWindows>,
+) {
+    for entity in removed_parents.read() {
+        if let Some(proxy) = gtk_windows.get(entity) {
+            proxy.gtk_window.set_transient_for(None::<&gtk::Window>);
+        }
+    }
+    for entity in removed_modals.read() {
+        if let Some(proxy) = gtk_windows.get(entity) {
+            proxy.gtk_window.set_modal(false);
+        }
+    }
+
+    // the parent's GTK window may be created after the child's, or destroyed
+    // before it, so we check these every frame instead of only on change
+    for (entity, parent, modal) in &windows {
+        let Some(proxy) = gtk_windows.get(entity) else {
+            continue;
+        };
+        let gtk_window = &proxy.gtk_window;
+
+        if let Some(GtkWindowParent(parent)) = parent {
+            let parent = gtk_windows
+                .get(*parent)
+                .filter(|_| *parent != entity)
+                .map(|parent| parent.gtk_window.upcast_ref::<gtk::Window>().clone());
+            if gtk_window.transient_for() != parent {
+                gtk_window.set_transient_for(parent.as_ref());
+            }
+        }
+        if gtk_window.is_modal() != modal {
+            gtk_window.set_modal(modal);
+        }
+    }
+}
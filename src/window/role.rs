@@ -0,0 +1,50 @@
+use {
+    super::GtkWindows,
+    bevy_ecs::prelude::*,
+    gtk::{accessible::Property, prelude::*},
+};
+
+/// Describes what a window is for to assistive technologies, e.g.
+/// `"Inspector"` or `"Level editor"`.
+///
+/// Screen readers announce this in place of the generic "window" role, which
+/// helps users tell apart the windows of an app that has several. The window
+/// title is still announced as the window's name.
+///
+/// Removing this component restores the generic role.
+///
+/// # Examples
+///
+/// ```ignore
+/// commands.spawn((
+///     Window {
+///         title: "Untitled level".into(),
+///         ..default()
+///     },
+///     GtkWindowRole("Level editor".into()),
+/// ));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Component)]
+pub struct GtkWindowRole(pub String);
+
+pub(super) fn sync_window_roles(
+    roles: Query<(Entity, &GtkWindowRole), Changed<GtkWindowRole>>,
+    mut removed: RemovedComponents<GtkWindowRole>,
+    gtk_windows: NonSend<GtkWindows>,
+) {
+    for entity in removed.read() {
+        if let Some(proxy) = gtk_windows.get(entity) {
+            proxy
+                .gtk_window
+                .reset_property(gtk::AccessibleProperty::RoleDescription);
+        }
+    }
+
+    for (entity, role) in &roles {
+        if let Some(proxy) = gtk_windows.get(entity) {
+            proxy
+                .gtk_window
+                .update_property(&[Property::RoleDescription(&role.0)]);
+        }
+    }
+}
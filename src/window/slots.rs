@@ -0,0 +1,97 @@
+use {bevy_platform::collections::HashMap, gtk::prelude::*};
+
+/// Region of a window which a widget can be placed into with
+/// [`WindowProxy::set_slot`](super::WindowProxy::set_slot).
+///
+/// The slots are laid out like this:
+///
+/// ```text
+/// +-------------------------+
+/// |           Top           |
+/// +-------+---------+-------+
+/// | Start |  Main   |  End  |
+/// +-------+---------+-------+
+/// |         Bottom          |
+/// +-------------------------+
+/// ```
+///
+/// This lets different plugins contribute parts of the same window, e.g. one
+/// plugin adds a sidebar in [`GtkWindowSlot::Start`] and another adds a
+/// status bar in [`GtkWindowSlot::Bottom`], without replacing each other's
+/// widgets. [`GtkWindowSlot::Main`] is the window's content, as set by
+/// [`GtkWindowContent`](super::GtkWindowContent).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GtkWindowSlot {
+    /// Main content of the window, which fills the space between the other
+    /// slots.
+    Main,
+    /// Above all other slots, e.g. a toolbar.
+    Top,
+    /// Below all other slots, e.g. a status bar.
+    Bottom,
+    /// Before the main content, e.g. a sidebar.
+    Start,
+    /// After the main content, e.g. an inspector panel.
+    End,
+}
+
+/// Layout which holds the widgets in each [`GtkWindowSlot`].
+#[derive(Debug)]
+pub(super) struct WindowSlots {
+    /// Holds [`GtkWindowSlot::Top`], the middle row, and
+    /// [`GtkWindowSlot::Bottom`].
+    pub root: gtk::Box,
+    /// Holds [`GtkWindowSlot::Start`], [`GtkWindowSlot::Main`], and
+    /// [`GtkWindowSlot::End`].
+    middle: gtk::Box,
+    widgets: HashMap<GtkWindowSlot, gtk::Widget>,
+}
+
+impl WindowSlots {
+    /// Creates the layout, with every slot empty.
+    pub fn new() -> Self {
+        let middle = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .vexpand(true)
+            .build();
+        let root = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        root.append(&middle);
+        Self {
+            root,
+            middle,
+            widgets: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, slot: GtkWindowSlot) -> Option<&gtk::Widget> {
+        self.widgets.get(&slot)
+    }
+
+    /// Places `widget` in `slot`, returning the widget which was there
+    /// before.
+    ///
+    /// `widget` must not have a parent.
+    pub fn set(&mut self, slot: GtkWindowSlot, widget: Option<gtk::Widget>) -> Option<gtk::Widget> {
+        let old = self.widgets.remove(&slot);
+        if let Some(old) = &old {
+            if let Some(parent) = old.parent().and_downcast::<gtk::Box>() {
+                parent.remove(old);
+            }
+        }
+        let Some(widget) = widget else {
+            return old;
+        };
+
+        match slot {
+            GtkWindowSlot::Top => self.root.prepend(&widget),
+            GtkWindowSlot::Bottom => self.root.append(&widget),
+            GtkWindowSlot::Start => self.middle.prepend(&widget),
+            GtkWindowSlot::End => self.middle.append(&widget),
+            GtkWindowSlot::Main => self
+                .middle
+                .insert_child_after(&widget, self.widgets.get(&GtkWindowSlot::Start)),
+        }
+        self.widgets.insert(slot, widget);
+        old
+    }
+}
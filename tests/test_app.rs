@@ -0,0 +1,33 @@
+//! Runs [`test_app`] until it exits, and checks that its primary window was
+//! created and shown by GTK.
+//!
+//! GTK can only be initialized once per process, so this runs without the
+//! default test harness. See [`test_app`] for how to provide a display.
+
+use {
+    bevy::{prelude::*, window::PrimaryWindow},
+    bevy_gtk::{GtkSystems, GtkWindows, gtk::prelude::*, test_app},
+    core::sync::atomic::{AtomicBool, Ordering},
+};
+
+static WINDOW_SHOWN: AtomicBool = AtomicBool::new(false);
+
+fn main() {
+    let mut app = test_app();
+    app.add_systems(Last, check_window.after(GtkSystems::SyncWindows));
+    assert_eq!(app.run(), AppExit::Success);
+    assert!(
+        WINDOW_SHOWN.load(Ordering::SeqCst),
+        "primary window should have a visible GTK window"
+    );
+}
+
+fn check_window(windows: Query<Entity, With<PrimaryWindow>>, gtk_windows: NonSend<GtkWindows>) {
+    let shown = windows
+        .iter()
+        .filter_map(|entity| gtk_windows.get(entity))
+        .any(|proxy| proxy.gtk_window.is_visible());
+    if shown {
+        WINDOW_SHOWN.store(true, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,96 @@
+//! Drives a viewport through a [`MockWidget`], from the widget reporting its
+//! size, to the render world handing over frames, to the viewport being
+//! despawned once its widget is dropped.
+//!
+//! GTK can only be initialized once per process, so this runs without the
+//! default test harness. It needs a display, like
+//! [`test_app`](bevy_gtk::test_app) does, and a Vulkan device, which can be a
+//! software rasterizer like lavapipe.
+
+use {
+    bevy::{
+        app::PluginsState,
+        ecs::system::RunSystemOnce,
+        prelude::*,
+        render::{RenderApp, pipelined_rendering::PipelinedRenderingPlugin},
+        winit::WinitPlugin,
+    },
+    bevy_gtk::{
+        GtkInitPlugin, GtkPlugin, GtkViewports, MockDmabufs, MockWidget, TEST_APP_ID, gdk, gtk,
+    },
+};
+
+fn main() {
+    if std::env::var_os("GDK_BACKEND").is_none() {
+        gdk::set_allowed_backends("broadway,*");
+    }
+    gtk::init().expect("should initialize GTK");
+
+    let mut app = App::new();
+    app.add_plugins((
+        GtkInitPlugin,
+        DefaultPlugins
+            .build()
+            .disable::<WinitPlugin>()
+            // render in the same update, so that frames come out right away
+            .disable::<PipelinedRenderingPlugin>()
+            .set(WindowPlugin {
+                primary_window: None,
+                ..default()
+            }),
+        GtkPlugin::new(TEST_APP_ID).non_unique(),
+    ));
+    app.sub_app_mut(RenderApp).insert_resource(MockDmabufs);
+    while app.plugins_state() == PluginsState::Adding {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    let mock = app
+        .world_mut()
+        .run_system_once(create_viewport)
+        .expect("should create a viewport");
+    let entity = mock.entity();
+
+    // nothing is rendered until GTK lays out the widget
+    update(&mut app, 3);
+    assert!(mock.take_frame().is_none());
+    assert_eq!(mock.frame_count(), 0);
+
+    mock.set_size(64, 48);
+    update(&mut app, 3);
+    assert!(mock.frame_ready());
+    assert!(mock.frame_count() > 0);
+    let frame = mock.take_frame().expect("should hand over a frame");
+    assert!(!frame.is_dmabuf());
+    assert_eq!(frame.size(), Some((64, 48)));
+
+    mock.set_size(32, 24);
+    update(&mut app, 3);
+    let frame = mock
+        .take_frame()
+        .expect("should hand over a frame after resizing");
+    assert_eq!(frame.size(), Some((32, 24)));
+
+    assert!(!mock.is_broken());
+
+    drop(mock);
+    update(&mut app, 2);
+    assert!(
+        app.world().get_entity(entity).is_err(),
+        "viewport should be despawned once its widget is dropped"
+    );
+}
+
+fn create_viewport(mut viewports: GtkViewports, mut commands: Commands) -> MockWidget {
+    let (viewport, widget_factory) = viewports.create();
+    commands.spawn((Camera3d::default(), viewport));
+    widget_factory.make_mock()
+}
+
+fn update(app: &mut App, times: usize) {
+    for _ in 0..times {
+        app.update();
+    }
+}